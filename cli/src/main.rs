@@ -18,6 +18,8 @@ struct Args {
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum Format {
+    #[value(alias = "heic", alias = "avif")]
+    Heif,
     Mp4,
     Webp,
 }
@@ -43,6 +45,7 @@ fn main() -> Result<(), anyhow::Error> {
     let file = File::open(args.file).context("Error opening file")?;
 
     match format {
+        Format::Heif => heifsan::sanitize(file).map(drop).context("Error parsing heif file")?,
         Format::Mp4 => mp4san::sanitize(file).map(drop).context("Error parsing mp4 file")?,
         Format::Webp => webpsan::sanitize(file).context("Error parsing webp file")?,
     }
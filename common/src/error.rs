@@ -23,6 +23,10 @@ pub enum Error<E: ReportableError> {
     /// The input could not be parsed as a media file.
     #[error("Parse error: {0}")]
     Parse(#[from] Report<E>),
+
+    /// The operation was cancelled before it completed.
+    #[error("Cancelled")]
+    Cancelled,
 }
 
 /// A report with additional debugging info for an error.
@@ -8,8 +8,11 @@ pub mod macros;
 pub mod async_skip;
 pub mod error;
 pub mod parse;
+mod read_at;
 mod skip;
 pub mod sync;
+#[cfg(feature = "tokio")]
+mod tokio_skip;
 pub mod util;
 
 use std::io;
@@ -62,8 +65,35 @@ pub trait AsyncSkip {
     fn poll_stream_len(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>>;
 }
 
+/// An input supporting positional reads at arbitrary offsets, such as a file read with `pread`, a memory map, or a blob
+/// store supporting range reads.
+///
+/// Unlike [`Read`](io::Read), reads take `&self` and don't depend on a cursor, so they may be served concurrently, and
+/// an input can be read starting from any offset without first reading or skipping the data before it.
+pub trait ReadAt {
+    /// Read bytes starting at `offset` in the input into `buf`, returning the number of bytes read.
+    ///
+    /// Fewer bytes than requested may be read. Zero bytes are read only if `buf` is empty or `offset` is at or past the
+    /// end of the input.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// Returns the length of the input, in bytes.
+    fn stream_len(&self) -> io::Result<u64>;
+}
+
+/// An adapter implementing [`Read`](io::Read) + [`Skip`] for types implementing [`ReadAt`].
+///
+/// The adapter keeps its own cursor, which is skipped forward without reading any data.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReadAtAdapter<T> {
+    inner: T,
+    position: u64,
+}
+
 /// An adapter implementing [`Skip`]/[`AsyncSkip`] for all types implementing [`Seek`]/[`AsyncSeek`].
 #[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
 pub struct SeekSkipAdapter<T: ?Sized>(pub T);
 
 pub use async_skip::AsyncSkipExt;
+#[cfg(feature = "tokio")]
+pub use tokio_skip::TokioSkipAdapter;
@@ -0,0 +1,169 @@
+//! [`ReadAt`] implementations for byte buffers, files, and smart pointers, and the [`Read`] + [`Skip`] implementation
+//! of [`ReadAtAdapter`].
+
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{ReadAt, ReadAtAdapter, Skip};
+
+//
+// ReadAt impls
+//
+
+macro_rules! deref_read_at {
+    () => {
+        fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+            (**self).read_at(offset, buf)
+        }
+
+        fn stream_len(&self) -> io::Result<u64> {
+            (**self).stream_len()
+        }
+    };
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    deref_read_at!();
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for Box<T> {
+    deref_read_at!();
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for Rc<T> {
+    deref_read_at!();
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for Arc<T> {
+    deref_read_at!();
+}
+
+impl ReadAt for [u8] {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(data) = usize::try_from(offset).ok().and_then(|offset| self.get(offset..)) else {
+            return Ok(0);
+        };
+        let read_len = data.len().min(buf.len());
+        buf[..read_len].copy_from_slice(&data[..read_len]);
+        Ok(read_len)
+    }
+
+    fn stream_len(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+}
+
+impl ReadAt for Vec<u8> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self[..].read_at(offset, buf)
+    }
+
+    fn stream_len(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+}
+
+#[cfg(any(unix, windows))]
+impl ReadAt for File {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        #[cfg(unix)]
+        return std::os::unix::fs::FileExt::read_at(self, buf, offset);
+
+        // NB: This also moves the file's cursor, which is unused by the ReadAt API.
+        #[cfg(windows)]
+        return std::os::windows::fs::FileExt::seek_read(self, buf, offset);
+    }
+
+    fn stream_len(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+//
+// ReadAtAdapter impls
+//
+
+impl<T> ReadAtAdapter<T> {
+    /// Construct a new adapter wrapping `inner`, with its cursor at the start of the input.
+    pub fn new(inner: T) -> Self {
+        Self { inner, position: 0 }
+    }
+
+    /// Returns a reference to the wrapped value.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped value.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Unwraps this adapter, returning the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: ReadAt> Read for ReadAtAdapter<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read_len = self.inner.read_at(self.position, buf)?;
+        self.position += read_len as u64;
+        Ok(read_len)
+    }
+}
+
+impl<T: ReadAt> Skip for ReadAtAdapter<T> {
+    fn skip(&mut self, amount: u64) -> io::Result<()> {
+        self.position = self
+            .position
+            .checked_add(amount)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "seek past u64::MAX"))?;
+        Ok(())
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+
+    fn stream_len(&mut self) -> io::Result<u64> {
+        self.inner.stream_len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn slice_read_at() {
+        let data: &[u8] = b"abcdefgh";
+        let mut buf = [0; 4];
+        assert_eq!(data.read_at(2, &mut buf).unwrap(), 4);
+        assert_eq!(&buf, b"cdef");
+        assert_eq!(data.read_at(6, &mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"gh");
+        assert_eq!(data.read_at(8, &mut buf).unwrap(), 0);
+        assert_eq!(data.read_at(u64::MAX, &mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn adapter_read_skip() {
+        let mut adapter = ReadAtAdapter::new(b"abcdefgh".to_vec());
+        let mut buf = [0; 2];
+        adapter.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ab");
+        adapter.skip(3).unwrap();
+        assert_eq!(adapter.stream_position().unwrap(), 5);
+        assert_eq!(adapter.stream_len().unwrap(), 8);
+        adapter.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"fg");
+
+        adapter.skip(10).unwrap();
+        assert_eq!(adapter.read(&mut buf).unwrap(), 0);
+        assert_eq!(adapter.skip(u64::MAX).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
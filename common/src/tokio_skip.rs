@@ -0,0 +1,252 @@
+//! Adapters implementing [`AsyncRead`] + [`AsyncSkip`] for [`tokio`] IO types.
+
+use std::io;
+use std::io::SeekFrom;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use futures_util::AsyncRead;
+use tokio::io::ReadBuf;
+
+use crate::AsyncSkip;
+
+//
+// public types
+//
+
+/// An adapter implementing [`AsyncRead`] + [`AsyncSkip`] for types implementing [`tokio::io::AsyncRead`] +
+/// [`tokio::io::AsyncSeek`], such as [`tokio::fs::File`] or [`tokio::io::BufReader`].
+///
+/// [`tokio::fs::File`]: https://docs.rs/tokio/latest/tokio/fs/struct.File.html
+/// [`tokio::io::BufReader`]: https://docs.rs/tokio/latest/tokio/io/struct.BufReader.html
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioSkipAdapter<T> {
+    inner: T,
+    seek: Option<SeekFrom>,
+    skip_pos: Option<u64>,
+    stream_len: StreamLenState,
+}
+
+//
+// private types
+//
+
+/// The state of a [`AsyncSkip::poll_stream_len`] call, which requires multiple seeks.
+#[derive(Clone, Copy, Debug, Default)]
+enum StreamLenState {
+    #[default]
+    Start,
+    SeekEnd {
+        stream_pos: u64,
+    },
+    Restore {
+        stream_pos: u64,
+        len: u64,
+    },
+}
+
+//
+// TokioSkipAdapter impls
+//
+
+impl<T> TokioSkipAdapter<T> {
+    /// Construct a new adapter wrapping `inner`.
+    pub fn new(inner: T) -> Self {
+        Self { inner, seek: None, skip_pos: None, stream_len: StreamLenState::Start }
+    }
+
+    /// Returns a reference to the wrapped value.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped value.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Unwraps this adapter, returning the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: tokio::io::AsyncSeek + Unpin> TokioSkipAdapter<T> {
+    /// Poll seeking to `pos`, which may be polled again with the same `pos` until it completes.
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<io::Result<u64>> {
+        if self.seek != Some(pos) {
+            // Complete any seek left in progress before starting a new one.
+            if self.seek.is_some() {
+                let _ = ready!(Pin::new(&mut self.inner).poll_complete(cx));
+                self.seek = None;
+            }
+            Pin::new(&mut self.inner).start_seek(pos)?;
+            self.seek = Some(pos);
+        }
+        let result = ready!(Pin::new(&mut self.inner).poll_complete(cx));
+        self.seek = None;
+        Poll::Ready(result)
+    }
+
+    /// Poll skipping `amount` bytes too large for a relative seek, by seeking to an absolute position instead.
+    fn poll_skip_from_start(&mut self, cx: &mut Context<'_>, amount: u64) -> Poll<io::Result<()>> {
+        let skip_pos = match self.skip_pos {
+            Some(skip_pos) => skip_pos,
+            None => {
+                let stream_pos = ready!(self.poll_seek(cx, SeekFrom::Current(0)))?;
+                let skip_pos = stream_pos
+                    .checked_add(amount)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "seek past u64::MAX"))?;
+                self.skip_pos = Some(skip_pos);
+                skip_pos
+            }
+        };
+        let result = ready!(self.poll_seek(cx, SeekFrom::Start(skip_pos)));
+        self.skip_pos = None;
+        Poll::Ready(result.map(drop))
+    }
+
+    fn poll_stream_len_state(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        loop {
+            match self.stream_len {
+                StreamLenState::Start => {
+                    let stream_pos = ready!(self.poll_seek(cx, SeekFrom::Current(0)))?;
+                    self.stream_len = StreamLenState::SeekEnd { stream_pos };
+                }
+                StreamLenState::SeekEnd { stream_pos } => {
+                    let len = ready!(self.poll_seek(cx, SeekFrom::End(0)))?;
+                    if stream_pos == len {
+                        return Ok(len).into();
+                    }
+                    self.stream_len = StreamLenState::Restore { stream_pos, len };
+                }
+                StreamLenState::Restore { stream_pos, len } => {
+                    ready!(self.poll_seek(cx, SeekFrom::Start(stream_pos)))?;
+                    return Ok(len).into();
+                }
+            }
+        }
+    }
+}
+
+impl<T: tokio::io::AsyncRead + Unpin> AsyncRead for TokioSkipAdapter<T> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut read_buf = ReadBuf::new(buf);
+        ready!(Pin::new(&mut self.inner).poll_read(cx, &mut read_buf))?;
+        Ok(read_buf.filled().len()).into()
+    }
+}
+
+impl<T: tokio::io::AsyncSeek + Unpin> AsyncSkip for TokioSkipAdapter<T> {
+    fn poll_skip(self: Pin<&mut Self>, cx: &mut Context<'_>, amount: u64) -> Poll<io::Result<()>> {
+        match amount.try_into() {
+            Ok(0) => (),
+            Ok(amount) => {
+                ready!(self.get_mut().poll_seek(cx, SeekFrom::Current(amount)))?;
+            }
+            Err(_) => return self.get_mut().poll_skip_from_start(cx, amount),
+        }
+        Ok(()).into()
+    }
+
+    fn poll_stream_position(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        self.get_mut().poll_seek(cx, SeekFrom::Current(0))
+    }
+
+    fn poll_stream_len(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let result = ready!(this.poll_stream_len_state(cx));
+        this.stream_len = StreamLenState::Start;
+        Poll::Ready(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures_util::{AsyncReadExt, FutureExt};
+
+    use crate::AsyncSkipExt;
+
+    use super::*;
+
+    /// A [`tokio::io::AsyncSeek`] wrapper which returns [`Poll::Pending`] once before completing each seek.
+    struct PendingSeek<T> {
+        inner: T,
+        pending: bool,
+    }
+
+    impl<T: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for PendingSeek<T> {
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.inner).poll_read(cx, buf)
+        }
+    }
+
+    impl<T: tokio::io::AsyncSeek + Unpin> tokio::io::AsyncSeek for PendingSeek<T> {
+        fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+            self.pending = true;
+            Pin::new(&mut self.inner).start_seek(position)
+        }
+
+        fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+            if self.pending {
+                self.pending = false;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Pin::new(&mut self.inner).poll_complete(cx)
+        }
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        loop {
+            if let Some(output) = (&mut future).now_or_never() {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn read_skip() {
+        let mut adapter = TokioSkipAdapter::new(io::Cursor::new(b"abcdefgh".to_vec()));
+        let mut buf = [0; 2];
+        block_on(adapter.read_exact(&mut buf)).unwrap();
+        assert_eq!(&buf, b"ab");
+        block_on(adapter.skip(3)).unwrap();
+        assert_eq!(block_on(adapter.stream_position()).unwrap(), 5);
+        assert_eq!(block_on(adapter.stream_len()).unwrap(), 8);
+        block_on(adapter.read_exact(&mut buf)).unwrap();
+        assert_eq!(&buf, b"fg");
+    }
+
+    #[test]
+    fn skip_past_i64_max() {
+        let inner = PendingSeek { inner: io::Cursor::new(b"abcdefgh".to_vec()), pending: false };
+        let mut adapter = TokioSkipAdapter::new(inner);
+        block_on(adapter.skip(2)).unwrap();
+        block_on(adapter.skip(i64::MAX as u64 + 1)).unwrap();
+        assert_eq!(block_on(adapter.stream_position()).unwrap(), i64::MAX as u64 + 3);
+    }
+
+    #[test]
+    fn skip_past_u64_max() {
+        let mut adapter = TokioSkipAdapter::new(io::Cursor::new(b"abcdefgh".to_vec()));
+        block_on(adapter.skip(2)).unwrap();
+        let err = block_on(adapter.skip(u64::MAX - 1)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(block_on(adapter.stream_position()).unwrap(), 2);
+    }
+
+    #[test]
+    fn pending_seeks() {
+        let inner = PendingSeek { inner: io::Cursor::new(b"abcdefgh".to_vec()), pending: false };
+        let mut adapter = TokioSkipAdapter::new(inner);
+        block_on(adapter.skip(3)).unwrap();
+        assert_eq!(block_on(adapter.stream_position()).unwrap(), 3);
+        assert_eq!(block_on(adapter.stream_len()).unwrap(), 8);
+        assert_eq!(block_on(adapter.stream_position()).unwrap(), 3);
+        let mut buf = [0; 2];
+        block_on(adapter.read_exact(&mut buf)).unwrap();
+        assert_eq!(&buf, b"de");
+    }
+}
@@ -0,0 +1,749 @@
+#![warn(missing_docs)]
+
+//! `heifsan` is a HEIF image format "sanitizer", supporting HEIF images such as HEIC and AVIF.
+//!
+//! HEIF images are ISO Base Media Files, and are parsed using the box parser of [`mp4san`]. The sanitizer currently
+//! always performs the following functions:
+//!
+//! - Check the validity of the image metadata (`meta`), including its item information (`iinf`), item properties
+//!   (`iprp`), and item references (`iref`).
+//! - Check that the data of each item, as located by the item location box (`iloc`), lies within the media data
+//!   (`mdat`) or the item data box (`idat`).
+//! - Return all metadata present in the input as a self-contained contiguous byte array, followed by the media data.
+//!
+//! "Self-contained and contiguous" means that the returned metadata can be concatenated with the media data to form a
+//! valid HEIF file. The original metadata only needs to be modified when the media data precedes it; otherwise, the
+//! returned [`SanitizedMetadata::metadata`] will be [`None`] to prevent needless data copying.
+//!
+//! # Unsupported HEIF features
+//!
+//! The sanitizer does not currently support:
+//!
+//! - Image sequences, which are stored in movie boxes (`moov`) as in MP4 files.
+//! - Multiple media data boxes (`mdat`).
+//! - Item data located in separate files, or constructed from the data of other items.
+//! - Any file which does not contain the [`mif1` compatible brand](COMPATIBLE_BRAND) in its file type header (`ftyp`).
+//!
+//! # Usage
+//!
+//! The main entry points to the sanitizer are [`sanitize`]/[`sanitize_async`], which take a [`Read`] + [`Skip`] input.
+//! The [`Skip`] trait represents a subset of the [`Seek`] trait; an input stream which can be skipped forward, but not
+//! necessarily seeked to arbitrary positions.
+//!
+//! ```no_run
+//! let input = std::fs::File::open("image.heic")?;
+//! let sanitized = heifsan::sanitize(input)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! The [`parse`] module also contains a less stable and undocumented API which can be used to parse individual HEIF
+//! box types.
+//!
+//! [`Seek`]: std::io::Seek
+
+#[macro_use]
+extern crate mediasan_common;
+
+pub mod parse;
+mod util;
+
+use std::collections::HashSet;
+use std::io::Read;
+
+use derive_builder::Builder;
+use futures_util::io::BufReader;
+use futures_util::{pin_mut, AsyncBufReadExt, AsyncRead};
+use mediasan_common::sync;
+use mediasan_common::util::IoResultExt;
+use mediasan_common::{AsyncSkipExt, ResultExt};
+
+use crate::parse::error::{MultipleBoxes, WhileParsingBox, WhileParsingItem};
+use crate::parse::{
+    BoxHeader, BoxType, ConstructionMethod, FourCC, FtypBox, IlocItem, MetaBox, Mp4Box, Mp4Value, ParseError,
+};
+
+//
+// public types
+//
+
+/// Error type returned by `heifsan`.
+pub type Error = mediasan_common::error::Error<ParseError>;
+
+#[derive(Builder, Clone)]
+#[builder(build_fn(name = "try_build"))]
+/// Configuration for the HEIF sanitizer.
+pub struct Config {
+    /// The maximum size of metadata to support.
+    ///
+    /// This is useful to set an upper bound on memory consumption in the parser.
+    ///
+    /// The default is 1 GiB.
+    #[builder(default = "1024 * 1024 * 1024")]
+    pub max_metadata_size: u64,
+}
+
+/// Sanitized metadata returned by the sanitizer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SanitizedMetadata {
+    /// The sanitized metadata from the given input, as a self-contained contiguous byte array.
+    ///
+    /// "Self-contained and contiguous" means that the metadata can be concatenated with the [media data](Self::data) to
+    /// form a valid HEIF file.
+    ///
+    /// If the original metadata did not need to be modified, this will be [`None`].
+    pub metadata: Option<Vec<u8>>,
+
+    /// A pointer to the span in the input containing the media data box (`mdat`).
+    ///
+    /// If the input contains no media data box, because the data of every item is located in the item data box
+    /// (`idat`), this is an empty span at the end of the input.
+    pub data: InputSpan,
+}
+
+pub use mediasan_common::{AsyncSkip, InputSpan, Report, SeekSkipAdapter, Skip};
+
+/// The HEIF "compatible brand" recognized by the sanitizer.
+///
+/// This compatible brand must be present in the input's file type header (`ftyp`) in order to be parsed by the
+/// sanitizer.
+pub const COMPATIBLE_BRAND: FourCC = FourCC { value: *b"mif1" };
+
+//
+// private types
+//
+
+/// The handler type of the meta box of HEIF images.
+const PICTURE_HANDLER: FourCC = FourCC { value: *b"pict" };
+
+const MAX_FTYP_SIZE: u64 = 1024;
+
+//
+// public functions
+//
+
+/// Sanitize a HEIF input.
+///
+/// The `input` must implement [`Read`] + [`Skip`], where [`Skip`] represents a subset of the [`Seek`] trait; an input
+/// stream which can be skipped forward, but not necessarily seeked to arbitrary positions.
+///
+/// See the [module-level documentation](self) for usage examples.
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs, an [`Error`] is returned.
+///
+/// [`Seek`]: std::io::Seek
+pub fn sanitize<R: Read + Skip + Unpin>(input: R) -> Result<SanitizedMetadata, Error> {
+    sync::sanitize(input, sanitize_async)
+}
+
+/// Sanitize a HEIF input, with the given [`Config`].
+///
+/// The `input` must implement [`Read`] + [`Skip`], where [`Skip`] represents a subset of the [`Seek`] trait; an input
+/// stream which can be skipped forward, but not necessarily seeked to arbitrary positions.
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs, an [`Error`] is returned.
+///
+/// [`Seek`]: std::io::Seek
+pub fn sanitize_with_config<R: Read + Skip + Unpin>(input: R, config: Config) -> Result<SanitizedMetadata, Error> {
+    sync::sanitize(input, |input| sanitize_async_with_config(input, config))
+}
+
+/// Sanitize a HEIF input asynchronously.
+///
+/// The `input` must implement [`AsyncRead`] + [`AsyncSkip`], where [`AsyncSkip`] represents a subset of the
+/// [`AsyncSeek`] trait; an input stream which can be skipped forward, but not necessarily seeked to arbitrary
+/// positions.
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs, an [`Error`] is returned.
+///
+/// [`AsyncSeek`]: futures_util::io::AsyncSeek
+pub async fn sanitize_async<R: AsyncRead + AsyncSkip>(input: R) -> Result<SanitizedMetadata, Error> {
+    sanitize_async_with_config(input, Config::default()).await
+}
+
+/// Sanitize a HEIF input asynchronously, with the given [`Config`].
+///
+/// The `input` must implement [`AsyncRead`] + [`AsyncSkip`], where [`AsyncSkip`] represents a subset of the
+/// [`AsyncSeek`] trait; an input stream which can be skipped forward, but not necessarily seeked to arbitrary
+/// positions.
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs, an [`Error`] is returned.
+///
+/// [`AsyncSeek`]: futures_util::io::AsyncSeek
+pub async fn sanitize_async_with_config<R: AsyncRead + AsyncSkip>(
+    input: R,
+    config: Config,
+) -> Result<SanitizedMetadata, Error> {
+    let reader = BufReader::with_capacity(BoxHeader::MAX_SIZE as usize, input);
+    pin_mut!(reader);
+
+    let input_len = reader.as_mut().stream_len().await?;
+
+    let mut ftyp: Option<Mp4Box<FtypBox>> = None;
+    let mut meta: Option<(u64, Mp4Box<MetaBox>)> = None;
+    let mut mdat: Option<(InputSpan, InputSpan)> = None;
+
+    while !reader.as_mut().fill_buf().await?.is_empty() {
+        let start_pos = reader.as_mut().stream_position().await?;
+
+        let header = BoxHeader::read(&mut reader)
+            .await
+            .map_eof(|_| Error::Parse(report_attach!(ParseError::TruncatedBox, "while parsing box header")))?;
+        let data_offset = start_pos + header.encoded_len();
+        let data_len = match header.box_data_size()? {
+            Some(data_len) => data_len,
+            None => input_len.saturating_sub(data_offset),
+        };
+        ensure_attach!(
+            data_offset
+                .checked_add(data_len)
+                .is_some_and(|data_end| data_end <= input_len),
+            ParseError::TruncatedBox,
+            WhileParsingBox(header.box_type()),
+        );
+        let box_size = header.encoded_len() + data_len;
+
+        match header.box_type() {
+            name @ (BoxType::FREE | BoxType::SKIP) => {
+                reader.as_mut().skip(data_len).await?;
+                log::info!("{name} @ 0x{start_pos:08x}: {box_size} bytes");
+            }
+
+            BoxType::FTYP => {
+                ensure_attach!(
+                    ftyp.is_none(),
+                    ParseError::InvalidBoxLayout,
+                    MultipleBoxes(BoxType::FTYP)
+                );
+                let mut read_ftyp: Mp4Box<FtypBox> = Mp4Box::read_data(reader.as_mut(), header, MAX_FTYP_SIZE).await?;
+                let ftyp_data = read_ftyp.data.parse()?;
+                let compatible_brand_count = ftyp_data.compatible_brands().len();
+                let FtypBox { major_brand, minor_version, .. } = ftyp_data;
+                log::info!("ftyp @ 0x{start_pos:08x}: {major_brand} version {minor_version}, {compatible_brand_count} compatible brands");
+
+                ensure_attach!(
+                    ftyp_data.compatible_brands().any(|b| b == COMPATIBLE_BRAND),
+                    ParseError::UnsupportedFormat(ftyp_data.major_brand)
+                );
+
+                ftyp = Some(read_ftyp);
+            }
+
+            _ if ftyp.is_none() => {
+                bail_attach!(ParseError::InvalidBoxLayout, "ftyp is not the first significant box");
+            }
+
+            BoxType::META => {
+                ensure_attach!(
+                    meta.is_none(),
+                    ParseError::InvalidBoxLayout,
+                    MultipleBoxes(BoxType::META)
+                );
+                let mut read_meta: Mp4Box<MetaBox> =
+                    Mp4Box::read_data(reader.as_mut(), header, config.max_metadata_size).await?;
+                let item_count = read_meta.data.parse()?.iinf_mut()?.entry_count();
+                log::info!("meta @ 0x{start_pos:08x}: {box_size} bytes, {item_count} items");
+
+                meta = Some((start_pos, read_meta));
+            }
+
+            BoxType::MDAT => {
+                ensure_attach!(
+                    mdat.is_none(),
+                    ParseError::UnsupportedBoxLayout,
+                    MultipleBoxes(BoxType::MDAT)
+                );
+                reader.as_mut().skip(data_len).await?;
+                log::info!("mdat @ 0x{start_pos:08x}: {box_size} bytes");
+
+                let mdat_span = InputSpan { offset: start_pos, len: box_size };
+                mdat = Some((mdat_span, InputSpan { offset: data_offset, len: data_len }));
+            }
+
+            name => {
+                log::info!("{name} @ 0x{start_pos:08x}: {box_size} bytes");
+                bail_attach!(ParseError::UnsupportedBox(name));
+            }
+        }
+    }
+
+    let Some(ftyp) = ftyp else {
+        bail_attach!(ParseError::MissingRequiredBox(BoxType::FTYP));
+    };
+    let Some((meta_offset, mut meta)) = meta else {
+        bail_attach!(ParseError::MissingRequiredBox(BoxType::META));
+    };
+
+    validate_items(meta.data.parse()?, mdat.map(|(_, mdat_data)| mdat_data), input_len)?;
+
+    // Return early if there's nothing to sanitize. Since the only thing the sanitizer does currently is to move the
+    // meta to before the mdat, return if it's already there.
+    let data = match mdat {
+        Some((mdat, _)) if mdat.offset < meta_offset => mdat,
+        Some((mdat, _)) => {
+            log::info!("metadata: nothing to sanitize");
+            return Ok(SanitizedMetadata { metadata: None, data: mdat });
+        }
+        None => {
+            log::info!("metadata: nothing to sanitize");
+            return Ok(SanitizedMetadata { metadata: None, data: InputSpan { offset: input_len, len: 0 } });
+        }
+    };
+
+    // Make sure none of the metadata boxes use BoxSize::UntilEof, as we want the caller to be able to concatenate media
+    // data to the end of the metadata.
+    let ftyp = Mp4Box::with_data(ftyp.data)?;
+    let mut meta = Mp4Box::with_data(meta.data)?;
+
+    // Displace the file offsets of each item the amount the mdat was displaced. The meta may grow when the displaced
+    // offsets no longer fit in the iloc's field sizes, displacing the mdat further, so this is repeated until the iloc
+    // stops changing. Field sizes are never narrowed, so this always terminates.
+    let original_iloc = meta.data.parse()?.iloc_mut()?.clone();
+    loop {
+        let metadata_len = ftyp.encoded_len() + meta.encoded_len();
+        let displacement = i64::try_from(metadata_len)
+            .ok()
+            .zip(i64::try_from(data.offset).ok())
+            .map(|(metadata_len, data_offset)| metadata_len - data_offset)
+            .ok_or_else(|| report_attach!(ParseError::InvalidInput, "mdat offset overflow"))?;
+
+        let mut iloc = original_iloc.clone();
+        iloc.displace_file_offsets(displacement)?;
+        let current_iloc = meta.data.parse()?.iloc_mut()?;
+        iloc.widen_fields(current_iloc);
+        if iloc == *current_iloc {
+            log::info!("metadata: displaced item offsets by {displacement}");
+            break;
+        }
+        *current_iloc = iloc;
+    }
+
+    let mut metadata = Vec::with_capacity((ftyp.encoded_len() + meta.encoded_len()) as usize);
+    ftyp.put_buf(&mut metadata);
+    meta.put_buf(&mut metadata);
+
+    Ok(SanitizedMetadata { metadata: Some(metadata), data })
+}
+
+//
+// Config impls
+//
+
+impl Config {
+    /// Construct a builder for `Config`.
+    ///
+    /// See the documentation for [`ConfigBuilder`].
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+//
+// ConfigBuilder impls
+//
+
+impl ConfigBuilder {
+    /// Build a new [`Config`].
+    pub fn build(&self) -> Config {
+        self.try_build().unwrap()
+    }
+}
+
+//
+// private functions
+//
+
+/// Validate the items described by `meta`, and that their data lies within `mdat_data` or the item data box (`idat`).
+fn validate_items(meta: &mut MetaBox, mdat_data: Option<InputSpan>, input_len: u64) -> Result<(), Error> {
+    let handler_type = meta.hdlr_mut()?.handler_type;
+    ensure_attach!(
+        handler_type == PICTURE_HANDLER,
+        ParseError::InvalidInput,
+        format!("unsupported handler type `{handler_type}`"),
+        WhileParsingBox(BoxType::HDLR),
+    );
+
+    let mut item_ids = HashSet::new();
+    for infe in meta.iinf_mut()?.infes() {
+        let item_id = infe?.item_id;
+        ensure_attach!(
+            item_ids.insert(item_id),
+            ParseError::InvalidInput,
+            "duplicate item ID",
+            WhileParsingItem(item_id),
+            WhileParsingBox(BoxType::IINF),
+        );
+    }
+    let ensure_item_exists = |item_id: u32, box_type: BoxType| -> Result<(), Error> {
+        ensure_attach!(
+            item_ids.contains(&item_id),
+            ParseError::InvalidInput,
+            "reference to missing item",
+            WhileParsingItem(item_id),
+            WhileParsingBox(box_type),
+        );
+        Ok(())
+    };
+
+    ensure_item_exists(meta.pitm_mut()?.item_id, BoxType::PITM)?;
+
+    let idat_data = InputSpan { offset: 0, len: meta.idat_mut()?.map(|idat| idat.data_len()).unwrap_or_default() };
+    let mut located_item_ids = HashSet::new();
+    for item in &meta.iloc_mut()?.items {
+        ensure_item_exists(item.item_id, BoxType::ILOC)?;
+        ensure_attach!(
+            located_item_ids.insert(item.item_id),
+            ParseError::InvalidInput,
+            "duplicate item location",
+            WhileParsingItem(item.item_id),
+            WhileParsingBox(BoxType::ILOC),
+        );
+        validate_item_extents(item, mdat_data, idat_data, input_len)
+            .attach_printable(WhileParsingItem(item.item_id))
+            .attach_printable(WhileParsingBox(BoxType::ILOC))?;
+    }
+
+    let property_count = meta.iprp_mut()?.ipco_mut()?.property_count();
+    for ipma in meta.iprp_mut()?.ipmas() {
+        for entry in &ipma?.entries {
+            ensure_item_exists(entry.item_id, BoxType::IPMA)?;
+            for association in &entry.associations {
+                let property_index = association.property_index;
+                ensure_attach!(
+                    usize::from(property_index) <= property_count,
+                    ParseError::InvalidInput,
+                    format!("property index {property_index} out of range"),
+                    WhileParsingItem(entry.item_id),
+                    WhileParsingBox(BoxType::IPMA),
+                );
+            }
+        }
+    }
+
+    if let Some(iref) = meta.iref_mut()? {
+        for reference in iref.references() {
+            ensure_item_exists(reference.from_item_id, BoxType::IREF)?;
+            for &to_item_id in &reference.to_item_ids {
+                ensure_item_exists(to_item_id, BoxType::IREF)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate that each extent of `item` lies within the container of its data.
+///
+/// Extents located in the file must lie within `mdat_data`. An extent of length `0` extends to the end of its
+/// container, which for the file is the end of the input.
+fn validate_item_extents(
+    item: &IlocItem,
+    mdat_data: Option<InputSpan>,
+    idat_data: InputSpan,
+    input_len: u64,
+) -> Result<(), Error> {
+    let (container, container_end) = match item.construction_method {
+        ConstructionMethod::File => {
+            ensure_attach!(
+                item.data_reference_index == 0,
+                ParseError::UnsupportedBoxLayout,
+                "item data in a separate file",
+            );
+            let Some(mdat_data) = mdat_data else {
+                bail_attach!(ParseError::MissingRequiredBox(BoxType::MDAT));
+            };
+            (mdat_data, input_len)
+        }
+        ConstructionMethod::Idat => (idat_data, idat_data.len),
+        ConstructionMethod::Item => {
+            bail_attach!(
+                ParseError::UnsupportedBoxLayout,
+                "item data constructed from other items"
+            );
+        }
+    };
+
+    for extent in &item.extents {
+        let extent_start = item.base_offset.checked_add(extent.offset);
+        let extent_end = match extent.length {
+            0 => extent_start.map(|_| container_end),
+            length => extent_start.and_then(|extent_start| extent_start.checked_add(length)),
+        };
+        let (Some(extent_start), Some(extent_end)) = (extent_start, extent_end) else {
+            bail_attach!(ParseError::InvalidInput, "extent offset overflow");
+        };
+        ensure_attach!(
+            container.offset <= extent_start && extent_end <= container.offset + container.len,
+            ParseError::InvalidInput,
+            format!("extent 0x{extent_start:08x}..0x{extent_end:08x} not within item data"),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+pub mod readme {}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+
+    use crate::parse::{IpmaEntry, IrefBox, ItemReference, PropertyAssociation};
+    use crate::util::test::*;
+
+    use super::*;
+
+    fn sanitize_ok(input: &[u8]) -> SanitizedMetadata {
+        init_logger();
+        sanitize(SeekSkipAdapter(std::io::Cursor::new(input))).unwrap()
+    }
+
+    fn sanitize_err(input: &[u8]) -> ParseError {
+        init_logger();
+        match sanitize(SeekSkipAdapter(std::io::Cursor::new(input))).unwrap_err() {
+            Error::Parse(err) => {
+                log::info!("parse error: {err:?}");
+                err.into_inner()
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn meta_before_mdat() {
+        let heif = test_heif().build();
+        let sanitized = sanitize_ok(&heif.data);
+        assert_eq!(sanitized, SanitizedMetadata { metadata: None, data: heif.mdat });
+        assert_eq!(item_data(&heif.data, TEST_ITEM_ID), TEST_ITEM_DATA);
+    }
+
+    #[test]
+    fn meta_after_mdat() {
+        let heif = test_heif()
+            .boxes(vec![BoxType::FTYP, BoxType::MDAT, BoxType::META])
+            .build();
+        assert_eq!(item_data(&heif.data, TEST_ITEM_ID), TEST_ITEM_DATA);
+        let sanitized = sanitize_ok(&heif.data);
+        assert_eq!(sanitized.data, heif.mdat);
+        let sanitized_data = sanitized_data(sanitized, &heif.data);
+        assert_eq!(item_data(&sanitized_data, TEST_ITEM_ID), TEST_ITEM_DATA);
+        sanitize_ok(&sanitized_data);
+    }
+
+    #[test]
+    fn meta_after_mdat_async() {
+        init_logger();
+        let heif = test_heif()
+            .boxes(vec![BoxType::FTYP, BoxType::MDAT, BoxType::META])
+            .build();
+        let sanitize = sanitize_async(futures_util::io::Cursor::new(&heif.data[..]));
+        let sanitized = futures_util::FutureExt::now_or_never(sanitize).unwrap().unwrap();
+        let sanitized_data = sanitized_data(sanitized, &heif.data);
+        assert_eq!(item_data(&sanitized_data, TEST_ITEM_ID), TEST_ITEM_DATA);
+    }
+
+    #[test]
+    fn meta_after_mdat_negative_displacement() {
+        let heif = test_heif()
+            .boxes(vec![BoxType::FTYP, BoxType::FREE, BoxType::MDAT, BoxType::META])
+            .free_len(1024)
+            .build();
+        let sanitized = sanitize_ok(&heif.data);
+        let sanitized_data = sanitized_data(sanitized, &heif.data);
+        assert!(sanitized_data.len() < heif.data.len());
+        assert_eq!(item_data(&sanitized_data, TEST_ITEM_ID), TEST_ITEM_DATA);
+    }
+
+    #[test]
+    fn meta_after_mdat_widened_base_offset() {
+        let heif = test_heif()
+            .boxes(vec![BoxType::FTYP, BoxType::MDAT, BoxType::META])
+            .base_offset(false)
+            .build();
+        let sanitized = sanitize_ok(&heif.data);
+        let sanitized_data = sanitized_data(sanitized, &heif.data);
+        assert_eq!(item_data(&sanitized_data, TEST_ITEM_ID), TEST_ITEM_DATA);
+    }
+
+    #[test]
+    fn idat_item_not_displaced() {
+        let heif = test_heif()
+            .boxes(vec![BoxType::FTYP, BoxType::MDAT, BoxType::META])
+            .idat_item(true)
+            .build();
+        assert_eq!(item_data(&heif.data, TEST_IDAT_ITEM_ID), TEST_IDAT_ITEM_DATA);
+        let sanitized = sanitize_ok(&heif.data);
+        let sanitized_data = sanitized_data(sanitized, &heif.data);
+        assert_eq!(item_data(&sanitized_data, TEST_ITEM_ID), TEST_ITEM_DATA);
+        assert_eq!(item_data(&sanitized_data, TEST_IDAT_ITEM_ID), TEST_IDAT_ITEM_DATA);
+    }
+
+    #[test]
+    fn no_mdat() {
+        let heif = test_heif()
+            .boxes(vec![BoxType::FTYP, BoxType::META])
+            .file_item(false)
+            .idat_item(true)
+            .build();
+        let sanitized = sanitize_ok(&heif.data);
+        let data = InputSpan { offset: heif.data.len() as u64, len: 0 };
+        assert_eq!(sanitized, SanitizedMetadata { metadata: None, data });
+    }
+
+    #[test]
+    fn file_item_no_mdat() {
+        let heif = test_heif().boxes(vec![BoxType::FTYP, BoxType::META]).build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::MissingRequiredBox(BoxType::MDAT));
+    }
+
+    #[test]
+    fn extent_outside_mdat() {
+        for extent_offset in [-1, 1] {
+            let heif = test_heif().extent_displacement(extent_offset).build();
+            assert_matches!(sanitize_err(&heif.data), ParseError::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn extent_until_end_of_input() {
+        let heif = test_heif().extent_len(0).build();
+        sanitize_ok(&heif.data);
+
+        let heif = test_heif()
+            .boxes(vec![BoxType::FTYP, BoxType::MDAT, BoxType::META])
+            .extent_len(0)
+            .build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::InvalidInput);
+    }
+
+    #[test]
+    fn idat_extent_outside_idat() {
+        let heif = test_heif()
+            .idat_item(true)
+            .idat_len(TEST_IDAT_ITEM_DATA.len() - 1)
+            .build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::InvalidInput);
+    }
+
+    #[test]
+    fn missing_primary_item() {
+        let heif = test_heif().primary_item_id(TEST_ITEM_ID + 100).build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::InvalidInput);
+    }
+
+    #[test]
+    fn missing_located_item() {
+        let heif = test_heif().file_item_id(TEST_ITEM_ID + 100).build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::InvalidInput);
+    }
+
+    #[test]
+    fn duplicate_item_location() {
+        let heif = test_heif().duplicate_iloc_item(true).build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::InvalidInput);
+    }
+
+    #[test]
+    fn property_index_out_of_range() {
+        let association = PropertyAssociation { essential: true, property_index: 2 };
+        let heif = test_heif()
+            .ipma_entries(vec![IpmaEntry {
+                item_id: TEST_ITEM_ID,
+                associations: vec![association],
+            }])
+            .build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::InvalidInput);
+    }
+
+    #[test]
+    fn iref_missing_item() {
+        let reference = ItemReference { reference_type: CDSC, from_item_id: TEST_ITEM_ID, to_item_ids: vec![100] };
+        let heif = test_heif().iref(IrefBox::new(vec![reference])).build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::InvalidInput);
+
+        let reference = ItemReference { reference_type: CDSC, from_item_id: TEST_ITEM_ID, to_item_ids: vec![] };
+        let heif = test_heif().iref(IrefBox::new(vec![reference])).build();
+        sanitize_ok(&heif.data);
+    }
+
+    #[test]
+    fn not_picture_handler() {
+        let heif = test_heif().handler_type(FourCC { value: *b"vide" }).build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::InvalidInput);
+    }
+
+    #[test]
+    fn not_heif() {
+        let heif = test_heif().compatible_brands(vec![ISOM]).build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::UnsupportedFormat(HEIC));
+    }
+
+    #[test]
+    fn ftyp_not_first() {
+        let heif = test_heif()
+            .boxes(vec![BoxType::MDAT, BoxType::FTYP, BoxType::META])
+            .build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::InvalidBoxLayout);
+    }
+
+    #[test]
+    fn no_meta() {
+        let heif = test_heif().boxes(vec![BoxType::FTYP, BoxType::MDAT]).build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::MissingRequiredBox(BoxType::META));
+    }
+
+    #[test]
+    fn multiple_meta() {
+        let heif = test_heif()
+            .boxes(vec![BoxType::FTYP, BoxType::META, BoxType::META, BoxType::MDAT])
+            .build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::InvalidBoxLayout);
+    }
+
+    #[test]
+    fn multiple_mdat() {
+        let heif = test_heif()
+            .boxes(vec![BoxType::FTYP, BoxType::META, BoxType::MDAT, BoxType::MDAT])
+            .build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::UnsupportedBoxLayout);
+    }
+
+    #[test]
+    fn unsupported_box() {
+        let heif = test_heif()
+            .boxes(vec![BoxType::FTYP, BoxType::META, BoxType::MOOV, BoxType::MDAT])
+            .build();
+        assert_matches!(sanitize_err(&heif.data), ParseError::UnsupportedBox(BoxType::MOOV));
+    }
+
+    #[test]
+    fn truncated() {
+        let heif = test_heif().build();
+        for len in [heif.data.len() - 1, heif.mdat.offset as usize + 4] {
+            assert_matches!(sanitize_err(&heif.data[..len]), ParseError::TruncatedBox);
+        }
+    }
+
+    #[test]
+    fn meta_too_large() {
+        init_logger();
+        let heif = test_heif().build();
+        let config = Config::builder().max_metadata_size(16).build();
+        let err = sanitize_with_config(SeekSkipAdapter(std::io::Cursor::new(&heif.data)), config).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => assert_matches!(err.into_inner(), ParseError::InvalidInput));
+    }
+}
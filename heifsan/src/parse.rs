@@ -0,0 +1,60 @@
+//! Unstable API for parsing individual HEIF box types.
+//!
+//! The boxes common to all ISO base media files, such as the file type box (`ftyp`), are parsed using the
+//! [`mp4san::parse`] API, parts of which are re-exported here.
+
+pub mod error;
+mod idat;
+mod iinf;
+mod iloc;
+mod infe;
+mod ipco;
+mod ipma;
+mod iprp;
+mod iref;
+mod meta;
+mod pitm;
+
+use bytes::{Buf, BufMut};
+use mediasan_common::Result;
+
+pub use error::ParseError;
+pub use idat::IdatBox;
+pub use iinf::IinfBox;
+pub use iloc::{ConstructionMethod, IlocBox, IlocExtent, IlocItem};
+pub use infe::InfeBox;
+pub use ipco::IpcoBox;
+pub use ipma::{IpmaBox, IpmaEntry, PropertyAssociation};
+pub use iprp::IprpBox;
+pub use iref::{IrefBox, ItemReference};
+pub use meta::MetaBox;
+pub use pitm::PitmBox;
+
+pub use mp4san::parse::{
+    box_type, fourcc, AnyMp4Box, BoxData, BoxDepth, BoxHeader, BoxType, Boxes, ConstFullBoxHeader, FourCC, FtypBox,
+    FullBoxHeader, HdlrBox, Mp4Box, Mp4Prim, Mp4Value, ParseBox, ParsedBox,
+};
+
+/// Parse an item ID, which is 16 bits wide in earlier versions of a box, and 32 bits wide when `wide`.
+fn parse_item_id<B: Buf>(buf: B, wide: bool) -> Result<u32, ParseError> {
+    match wide {
+        false => <u16 as Mp4Prim>::parse(buf).map(u32::from),
+        true => <u32 as Mp4Prim>::parse(buf),
+    }
+}
+
+/// The encoded length of an item ID, which is 16 bits wide in earlier versions of a box, and 32 bits wide when `wide`.
+fn item_id_len(wide: bool) -> u64 {
+    match wide {
+        false => <u16 as Mp4Prim>::encoded_len(),
+        true => <u32 as Mp4Prim>::encoded_len(),
+    }
+}
+
+/// Write an item ID, which is 16 bits wide in earlier versions of a box, and 32 bits wide when `wide`.
+fn put_item_id<B: BufMut>(item_id: u32, wide: bool, out: B) {
+    match wide {
+        false => Mp4Prim::put_buf(&(item_id as u16), out),
+        true => Mp4Prim::put_buf(&item_id, out),
+    }
+}
@@ -0,0 +1,26 @@
+//! Error types returned by the unstable parsing API.
+
+use derive_more::Display;
+
+use super::BoxType;
+
+/// Error type returned by the HEIF parser, which is shared with the MP4 parser.
+pub use mp4san::parse::ParseError;
+
+pub(crate) use mp4san::parse::error::__ParseResultExt as ParseResultExt;
+
+#[derive(Clone, Copy, Debug, Display)]
+#[display(fmt = "multiple `{}` boxes", _0)]
+pub(crate) struct MultipleBoxes(pub(crate) BoxType);
+
+#[derive(Clone, Copy, Debug, Display)]
+#[display(fmt = "while parsing `{}` box", _0)]
+pub(crate) struct WhileParsingBox(pub(crate) BoxType);
+
+#[derive(Clone, Copy, Debug, Display)]
+#[display(fmt = "while parsing `{}` box field `{}`", _0, _1)]
+pub(crate) struct WhileParsingField<T>(pub(crate) BoxType, pub(crate) T);
+
+#[derive(Clone, Copy, Debug, Display)]
+#[display(fmt = "while parsing item {}", _0)]
+pub(crate) struct WhileParsingItem(pub(crate) u32);
@@ -0,0 +1,37 @@
+#![allow(missing_docs)]
+
+use mp4san::parse::UnboundedArray;
+
+use super::{ParseBox, ParsedBox};
+
+#[derive(Clone, Debug, Default, ParseBox, ParsedBox)]
+#[box_type = "idat"]
+pub struct IdatBox {
+    data: UnboundedArray<u8>,
+}
+
+impl IdatBox {
+    pub fn new(data: &[u8]) -> Self {
+        Self { data: data.iter().copied().collect() }
+    }
+
+    /// Returns the length of the item data, in bytes.
+    pub fn data_len(&self) -> u64 {
+        self.data.entry_count() as u64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut buf = BytesMut::new();
+        IdatBox::new(b"abcd").put_buf(&mut buf);
+        assert_eq!(&buf[..], b"abcd");
+        assert_eq!(IdatBox::parse(&mut buf).unwrap().data_len(), 4);
+    }
+}
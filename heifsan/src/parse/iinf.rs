@@ -0,0 +1,120 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+use mediasan_common::Result;
+
+use super::error::{ParseResultExt, WhileParsingBox};
+use super::{item_id_len, parse_item_id, put_item_id};
+use super::{
+    AnyMp4Box, BoxDepth, BoxType, Boxes, FullBoxHeader, InfeBox, Mp4Box, Mp4Value, ParseBox, ParseError, ParsedBox,
+};
+
+#[derive(Clone, Debug)]
+pub struct IinfBox {
+    header: FullBoxHeader,
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::IINF;
+
+impl IinfBox {
+    pub fn with_entries(entries: impl IntoIterator<Item = InfeBox>) -> Result<Self, ParseError> {
+        let children = entries
+            .into_iter()
+            .map(|infe| Ok(AnyMp4Box::from(Mp4Box::with_data(infe.into())?)))
+            .collect::<Result<Vec<_>, ParseError>>()?;
+        let version = if children.len() > u16::MAX.into() { 1 } else { 0 };
+        Ok(Self { header: FullBoxHeader { version, flags: 0 }, children: children.into() })
+    }
+
+    /// Returns the item information entry of each item.
+    pub fn infes(&mut self) -> impl Iterator<Item = Result<&mut InfeBox, ParseError>> + '_ {
+        self.children
+            .get_mut()
+            .map(|infe| infe.while_parsing_child(NAME, BoxType::INFE))
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.children.box_types().len()
+    }
+}
+
+impl ParseBox for IinfBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        Self::parse_at_depth(buf, Default::default())
+    }
+
+    fn parse_at_depth(buf: &mut BytesMut, depth: BoxDepth) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        let wide = match header.version {
+            0 => false,
+            1 => true,
+            version => bail_attach!(
+                ParseError::InvalidInput,
+                format!("unsupported box version {version}"),
+                WhileParsingBox(NAME),
+            ),
+        };
+        let entry_count = parse_item_id(&mut *buf, wide).while_parsing_field(NAME, "entry_count")?;
+        let children: Boxes = Boxes::parse_at_depth(&mut *buf, depth).while_parsing_field(NAME, "item_infos")?;
+        children.ensure_only(NAME, &[BoxType::INFE])?;
+        ensure_attach!(
+            children.box_types().len() as u64 == entry_count.into(),
+            ParseError::InvalidInput,
+            format!(
+                "entry count {entry_count} does not match {} entries",
+                children.box_types().len()
+            ),
+            WhileParsingBox(NAME),
+        );
+        Ok(Self { header, children })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for IinfBox {
+    fn encoded_len(&self) -> u64 {
+        self.header.encoded_len() + item_id_len(self.header.version != 0) + self.children.encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        put_item_id(self.entry_count() as u32, self.header.version != 0, &mut out);
+        self.children.put_buf(&mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::parse::FourCC;
+
+    const AV01: FourCC = FourCC { value: *b"av01" };
+
+    #[test]
+    fn roundtrip() {
+        let iinf = IinfBox::with_entries([InfeBox::new(1, AV01), InfeBox::new(2, AV01)]).unwrap();
+        let mut buf = BytesMut::new();
+        iinf.put_buf(&mut buf);
+        assert_eq!(buf.len() as u64, iinf.encoded_len());
+        let mut iinf = IinfBox::parse(&mut buf).unwrap();
+        assert!(buf.is_empty());
+        let item_ids: Vec<_> = iinf.infes().map(|infe| infe.unwrap().item_id).collect();
+        assert_eq!(item_ids, [1, 2]);
+    }
+
+    #[test]
+    fn entry_count_mismatch() {
+        let mut buf = BytesMut::new();
+        IinfBox::with_entries([InfeBox::new(1, AV01)])
+            .unwrap()
+            .put_buf(&mut buf);
+        buf[5] = 2;
+        let err = IinfBox::parse(&mut buf).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err:?}");
+    }
+}
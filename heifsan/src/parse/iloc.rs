@@ -0,0 +1,430 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+use mediasan_common::util::checked_add_signed;
+use mediasan_common::{Result, ResultExt};
+
+use super::error::{ParseResultExt, WhileParsingBox, WhileParsingField, WhileParsingItem};
+use super::{item_id_len, parse_item_id, put_item_id};
+use super::{BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IlocBox {
+    header: FullBoxHeader,
+    offset_size: u8,
+    length_size: u8,
+    base_offset_size: u8,
+    index_size: u8,
+    pub items: Vec<IlocItem>,
+}
+
+/// The location of an item's data, as a list of extents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IlocItem {
+    pub item_id: u32,
+    pub construction_method: ConstructionMethod,
+    pub data_reference_index: u16,
+    pub base_offset: u64,
+    pub extents: Vec<IlocExtent>,
+}
+
+/// A contiguous range of an item's data, relative to the item's base offset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IlocExtent {
+    pub index: u64,
+    pub offset: u64,
+
+    /// The length of the extent, where `0` indicates that the extent extends to the end of the referenced data.
+    pub length: u64,
+}
+
+/// Where the data of an item is located.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConstructionMethod {
+    /// The item data is located in a file, usually this one, by absolute offset.
+    #[default]
+    File,
+
+    /// The item data is located in the item data box (`idat`) of the same meta box.
+    Idat,
+
+    /// The item data is located in the data of other items.
+    Item,
+}
+
+const NAME: BoxType = BoxType::ILOC;
+
+impl IlocBox {
+    pub fn new(items: Vec<IlocItem>) -> Self {
+        let wide = items.len() > u16::MAX.into() || items.iter().any(|item| item.item_id > u16::MAX.into());
+        let mut iloc = Self {
+            header: FullBoxHeader { version: if wide { 2 } else { 1 }, flags: 0 },
+            offset_size: 0,
+            length_size: 0,
+            base_offset_size: 0,
+            index_size: 0,
+            items,
+        };
+        iloc.widen_to_fit();
+        iloc
+    }
+
+    /// Displaces the offset of every item located in a file by `displacement`.
+    ///
+    /// Offsets are added to the base offset of each item where possible, falling back to adding to the offset of each
+    /// of its extents. Field sizes are widened to fit the displaced offsets, but are never narrowed.
+    pub fn displace_file_offsets(&mut self, displacement: i64) -> Result<(), ParseError> {
+        for item in &mut self.items {
+            if item.construction_method != ConstructionMethod::File {
+                continue;
+            }
+            if let Some(base_offset) = checked_add_signed(item.base_offset, displacement) {
+                item.base_offset = base_offset;
+                continue;
+            }
+            // The base offset can only be displaced out of range below zero if it's smaller than `-displacement`.
+            let base_displacement = (displacement < 0).then(|| displacement + item.base_offset as i64);
+            for extent in &mut item.extents {
+                let offset = base_displacement
+                    .and_then(|base_displacement| checked_add_signed(extent.offset, base_displacement));
+                extent.offset = offset.ok_or_else(|| {
+                    report_attach!(
+                        ParseError::InvalidInput,
+                        "displaced extent offset out of range",
+                        WhileParsingItem(item.item_id),
+                        WhileParsingBox(NAME),
+                    )
+                })?;
+            }
+            item.base_offset = 0;
+        }
+        self.widen_to_fit();
+        Ok(())
+    }
+
+    /// Widens each field size to be at least as wide as that of `other`.
+    pub fn widen_fields(&mut self, other: &IlocBox) {
+        self.offset_size = self.offset_size.max(other.offset_size);
+        self.length_size = self.length_size.max(other.length_size);
+        self.base_offset_size = self.base_offset_size.max(other.base_offset_size);
+        self.index_size = self.index_size.max(other.index_size);
+    }
+
+    fn widen_to_fit(&mut self) {
+        let extents = || self.items.iter().flat_map(|item| &item.extents);
+        let offset_size = field_size_for(extents().map(|extent| extent.offset));
+        let length_size = field_size_for(extents().map(|extent| extent.length));
+        let base_offset_size = field_size_for(self.items.iter().map(|item| item.base_offset));
+        let index_size = field_size_for(extents().map(|extent| extent.index));
+        self.offset_size = self.offset_size.max(offset_size);
+        self.length_size = self.length_size.max(length_size);
+        self.base_offset_size = self.base_offset_size.max(base_offset_size);
+        if self.header.version >= 1 {
+            self.index_size = self.index_size.max(index_size);
+        }
+    }
+
+    fn has_construction_method(&self) -> bool {
+        self.header.version >= 1
+    }
+
+    fn has_extent_index(&self) -> bool {
+        self.header.version >= 1 && self.index_size > 0
+    }
+
+    fn wide_item_ids(&self) -> bool {
+        self.header.version >= 2
+    }
+}
+
+impl ParseBox for IlocBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version <= 2,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingBox(NAME),
+        );
+        let sizes = u8::parse(&mut *buf).while_parsing_field(NAME, "offset_size")?;
+        let (offset_size, length_size) = (sizes >> 4, sizes & 0xf);
+        let sizes = u8::parse(&mut *buf).while_parsing_field(NAME, "base_offset_size")?;
+        let (base_offset_size, index_size) = match header.version {
+            0 => (sizes >> 4, 0),
+            _ => (sizes >> 4, sizes & 0xf),
+        };
+        for (size, field_name) in [
+            (offset_size, "offset_size"),
+            (length_size, "length_size"),
+            (base_offset_size, "base_offset_size"),
+            (index_size, "index_size"),
+        ] {
+            ensure_attach!(
+                matches!(size, 0 | 4 | 8),
+                ParseError::InvalidInput,
+                format!("invalid field size {size}"),
+                WhileParsingField(NAME, field_name),
+            );
+        }
+
+        let mut iloc = Self { header, offset_size, length_size, base_offset_size, index_size, items: Vec::new() };
+        let item_count = parse_item_id(&mut *buf, iloc.wide_item_ids()).while_parsing_field(NAME, "item_count")?;
+        for _ in 0..item_count {
+            let item = iloc.parse_item(&mut *buf).while_parsing_field(NAME, "items")?;
+            iloc.items.push(item);
+        }
+        ensure_attach!(
+            !buf.has_remaining(),
+            ParseError::InvalidInput,
+            "extra unparsed data",
+            WhileParsingBox(NAME),
+        );
+        Ok(iloc)
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl IlocBox {
+    fn parse_item(&self, buf: &mut BytesMut) -> Result<IlocItem, ParseError> {
+        let item_id = parse_item_id(&mut *buf, self.wide_item_ids())?;
+        let construction_method = match self.has_construction_method() {
+            false => ConstructionMethod::File,
+            true => {
+                let construction_method = u16::parse(&mut *buf).attach_printable(WhileParsingItem(item_id))?;
+                ConstructionMethod::try_from(construction_method & 0xf).attach_printable(WhileParsingItem(item_id))?
+            }
+        };
+        let data_reference_index = u16::parse(&mut *buf).attach_printable(WhileParsingItem(item_id))?;
+        let base_offset = parse_sized(&mut *buf, self.base_offset_size).attach_printable(WhileParsingItem(item_id))?;
+        let extent_count = u16::parse(&mut *buf).attach_printable(WhileParsingItem(item_id))?;
+        let mut extents = Vec::new();
+        for _ in 0..extent_count {
+            let index = match self.has_extent_index() {
+                false => 0,
+                true => parse_sized(&mut *buf, self.index_size).attach_printable(WhileParsingItem(item_id))?,
+            };
+            let offset = parse_sized(&mut *buf, self.offset_size).attach_printable(WhileParsingItem(item_id))?;
+            let length = parse_sized(&mut *buf, self.length_size).attach_printable(WhileParsingItem(item_id))?;
+            extents.push(IlocExtent { index, offset, length });
+        }
+        Ok(IlocItem { item_id, construction_method, data_reference_index, base_offset, extents })
+    }
+}
+
+impl ParsedBox for IlocBox {
+    fn encoded_len(&self) -> u64 {
+        let extent_len = match self.has_extent_index() {
+            false => 0,
+            true => self.index_size as u64,
+        } + self.offset_size as u64
+            + self.length_size as u64;
+        let item_len = item_id_len(self.wide_item_ids())
+            + match self.has_construction_method() {
+                false => 0,
+                true => u16::encoded_len(),
+            }
+            + u16::encoded_len()
+            + self.base_offset_size as u64
+            + u16::encoded_len();
+        let extent_count: u64 = self.items.iter().map(|item| item.extents.len() as u64).sum();
+        FullBoxHeader::encoded_len()
+            + 2 * u8::encoded_len()
+            + item_id_len(self.wide_item_ids())
+            + self.items.len() as u64 * item_len
+            + extent_count * extent_len
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        ((self.offset_size << 4) | self.length_size).put_buf(&mut out);
+        ((self.base_offset_size << 4) | self.index_size).put_buf(&mut out);
+        put_item_id(self.items.len() as u32, self.wide_item_ids(), &mut out);
+        for item in &self.items {
+            put_item_id(item.item_id, self.wide_item_ids(), &mut out);
+            if self.has_construction_method() {
+                u16::from(item.construction_method).put_buf(&mut out);
+            }
+            item.data_reference_index.put_buf(&mut out);
+            put_sized(item.base_offset, self.base_offset_size, &mut out);
+            (item.extents.len() as u16).put_buf(&mut out);
+            for extent in &item.extents {
+                if self.has_extent_index() {
+                    put_sized(extent.index, self.index_size, &mut out);
+                }
+                put_sized(extent.offset, self.offset_size, &mut out);
+                put_sized(extent.length, self.length_size, &mut out);
+            }
+        }
+    }
+}
+
+//
+// ConstructionMethod impls
+//
+
+impl TryFrom<u16> for ConstructionMethod {
+    type Error = mediasan_common::Report<ParseError>;
+
+    fn try_from(construction_method: u16) -> Result<Self, ParseError> {
+        match construction_method {
+            0 => Ok(Self::File),
+            1 => Ok(Self::Idat),
+            2 => Ok(Self::Item),
+            _ => bail_attach!(
+                ParseError::InvalidInput,
+                format!("unsupported construction method {construction_method}"),
+            ),
+        }
+    }
+}
+
+impl From<ConstructionMethod> for u16 {
+    fn from(construction_method: ConstructionMethod) -> Self {
+        match construction_method {
+            ConstructionMethod::File => 0,
+            ConstructionMethod::Idat => 1,
+            ConstructionMethod::Item => 2,
+        }
+    }
+}
+
+//
+// private functions
+//
+
+/// Returns the narrowest field size able to represent each of `values`.
+fn field_size_for(mut values: impl Iterator<Item = u64>) -> u8 {
+    values
+        .try_fold(0, |size, value| match value {
+            0 => Some(size),
+            1..=0xffff_ffff => Some(size.max(4)),
+            _ => None,
+        })
+        .unwrap_or(8)
+}
+
+fn parse_sized<B: Buf>(buf: B, size: u8) -> Result<u64, ParseError> {
+    match size {
+        4 => u32::parse(buf).map(u64::from),
+        8 => u64::parse(buf),
+        _ => Ok(0),
+    }
+}
+
+fn put_sized<B: BufMut>(value: u64, size: u8, out: B) {
+    match size {
+        4 => (value as u32).put_buf(out),
+        8 => value.put_buf(out),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_iloc() -> IlocBox {
+        IlocBox::new(vec![
+            IlocItem {
+                item_id: 1,
+                construction_method: ConstructionMethod::File,
+                data_reference_index: 0,
+                base_offset: 0,
+                extents: vec![IlocExtent { index: 0, offset: 100, length: 10 }],
+            },
+            IlocItem {
+                item_id: 2,
+                construction_method: ConstructionMethod::Idat,
+                data_reference_index: 0,
+                base_offset: 0,
+                extents: vec![IlocExtent { index: 0, offset: 0, length: 4 }],
+            },
+        ])
+    }
+
+    fn roundtrip_iloc(iloc: &IlocBox) -> IlocBox {
+        let mut buf = BytesMut::new();
+        iloc.put_buf(&mut buf);
+        assert_eq!(buf.len() as u64, iloc.encoded_len());
+        IlocBox::parse(&mut buf).unwrap()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let iloc = test_iloc();
+        assert_eq!(roundtrip_iloc(&iloc), iloc);
+    }
+
+    #[test]
+    fn roundtrip_version_0() {
+        let mut iloc = test_iloc();
+        iloc.header.version = 0;
+        iloc.items.truncate(1);
+        assert_eq!(roundtrip_iloc(&iloc), iloc);
+    }
+
+    #[test]
+    fn roundtrip_wide() {
+        let mut iloc = test_iloc();
+        iloc.items[0].item_id = u32::MAX;
+        iloc.items[0].extents[0].offset = u64::MAX;
+        let iloc = IlocBox::new(iloc.items);
+        assert_eq!(roundtrip_iloc(&iloc), iloc);
+    }
+
+    #[test]
+    fn displace_file_offsets() {
+        let mut iloc = test_iloc();
+        iloc.displace_file_offsets(u32::MAX.into()).unwrap();
+        assert_eq!(iloc.items[0].base_offset, u32::MAX.into());
+        assert_eq!(iloc.items[0].extents[0].offset, 100);
+        assert_eq!(iloc.items[1].base_offset, 0);
+        assert_eq!(iloc.base_offset_size, 4);
+        assert_eq!(roundtrip_iloc(&iloc), iloc);
+    }
+
+    #[test]
+    fn displace_file_offsets_negative() {
+        let mut iloc = test_iloc();
+        iloc.displace_file_offsets(-50).unwrap();
+        assert_eq!(iloc.items[0].base_offset, 0);
+        assert_eq!(iloc.items[0].extents[0].offset, 50);
+        assert_eq!(roundtrip_iloc(&iloc), iloc);
+
+        let err = iloc.displace_file_offsets(-51).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err:?}");
+    }
+
+    #[test]
+    fn widen_fields() {
+        let mut wide = test_iloc();
+        wide.displace_file_offsets(u32::MAX as i64 + 1).unwrap();
+        assert_eq!(wide.base_offset_size, 8);
+        let mut iloc = test_iloc();
+        iloc.widen_fields(&wide);
+        assert_eq!(iloc.base_offset_size, 8);
+        assert_eq!(roundtrip_iloc(&iloc), iloc);
+    }
+
+    #[test]
+    fn invalid_field_size() {
+        let mut buf = BytesMut::new();
+        test_iloc().put_buf(&mut buf);
+        buf[4] = 0x34;
+        let err = IlocBox::parse(&mut buf).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err:?}");
+    }
+
+    #[test]
+    fn unsupported_construction_method() {
+        let mut buf = BytesMut::new();
+        test_iloc().put_buf(&mut buf);
+        // header (4) + sizes (2) + item_count (2) + item_ID (2) + construction_method (2)
+        buf[11] = 3;
+        let err = IlocBox::parse(&mut buf).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err:?}");
+    }
+}
@@ -0,0 +1,104 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+use mediasan_common::Result;
+use mp4san::parse::UnboundedArray;
+
+use super::error::{ParseResultExt, WhileParsingBox};
+use super::{item_id_len, parse_item_id, put_item_id};
+use super::{BoxType, FourCC, FullBoxHeader, Mp4Value, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InfeBox {
+    header: FullBoxHeader,
+    pub item_id: u32,
+    pub item_protection_index: u16,
+    pub item_type: FourCC,
+
+    /// The item name, followed by any fields specific to the item type, such as the MIME content type.
+    item_info: UnboundedArray<u8>,
+}
+
+const NAME: BoxType = BoxType::INFE;
+
+impl InfeBox {
+    pub fn new(item_id: u32, item_type: FourCC) -> Self {
+        let version = if item_id > u16::MAX.into() { 3 } else { 2 };
+        Self {
+            header: FullBoxHeader { version, flags: 0 },
+            item_id,
+            item_protection_index: 0,
+            item_type,
+            item_info: [0].into_iter().collect(),
+        }
+    }
+}
+
+impl ParseBox for InfeBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        // NB: Versions 0 and 1 predate item types, and are not used by HEIF.
+        let wide = match header.version {
+            2 => false,
+            3 => true,
+            version => bail_attach!(
+                ParseError::InvalidInput,
+                format!("unsupported box version {version}"),
+                WhileParsingBox(NAME),
+            ),
+        };
+        let item_id = parse_item_id(&mut *buf, wide).while_parsing_field(NAME, "item_ID")?;
+        let item_protection_index = u16::parse(&mut *buf).while_parsing_field(NAME, "item_protection_index")?;
+        let item_type = <FourCC as Mp4Value>::parse(&mut *buf).while_parsing_field(NAME, "item_type")?;
+        let item_info = UnboundedArray::parse(&mut *buf).while_parsing_field(NAME, "item_name")?;
+        Ok(Self { header, item_id, item_protection_index, item_type, item_info })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for InfeBox {
+    fn encoded_len(&self) -> u64 {
+        self.header.encoded_len()
+            + item_id_len(self.header.version == 3)
+            + self.item_protection_index.encoded_len()
+            + self.item_type.encoded_len()
+            + self.item_info.encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        put_item_id(self.item_id, self.header.version == 3, &mut out);
+        self.item_protection_index.put_buf(&mut out);
+        self.item_type.put_buf(&mut out);
+        self.item_info.put_buf(&mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HVC1: FourCC = FourCC { value: *b"hvc1" };
+
+    #[test]
+    fn roundtrip() {
+        for item_id in [1, u32::MAX] {
+            let infe = InfeBox::new(item_id, HVC1);
+            let mut buf = BytesMut::new();
+            infe.put_buf(&mut buf);
+            assert_eq!(buf.len() as u64, infe.encoded_len());
+            assert_eq!(InfeBox::parse(&mut buf).unwrap(), infe);
+            assert!(buf.is_empty());
+        }
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut buf = BytesMut::from(&[1, 0, 0, 0, 0, 1, 0, 0, b'h', b'v', b'c', b'1', 0][..]);
+        let err = InfeBox::parse(&mut buf).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err:?}");
+    }
+}
@@ -0,0 +1,40 @@
+#![allow(missing_docs)]
+
+use super::{Boxes, ParseBox, ParsedBox};
+
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "ipco"]
+pub struct IpcoBox {
+    children: Boxes,
+}
+
+impl IpcoBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes>>(children: C) -> Self {
+        Self { children: children.into() }
+    }
+
+    /// Returns the number of item properties, which are indexed starting at `1` by property associations.
+    pub fn property_count(&self) -> usize {
+        self.children.box_types().len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::{AnyMp4Box, BoxType, FourCC};
+
+    use super::*;
+
+    const ISPE: BoxType = BoxType::FourCC(FourCC { value: *b"ispe" });
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        let ispe = AnyMp4Box::with_bytes(ISPE, BytesMut::from(&[0; 12][..]));
+        IpcoBox::with_children(vec![ispe.clone(), ispe]).put_buf(&mut data);
+        assert_eq!(IpcoBox::parse(&mut data).unwrap().property_count(), 2);
+    }
+}
@@ -0,0 +1,181 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+use mediasan_common::{Result, ResultExt};
+
+use super::error::{ParseResultExt, WhileParsingBox, WhileParsingItem};
+use super::{item_id_len, parse_item_id, put_item_id};
+use super::{BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpmaBox {
+    header: FullBoxHeader,
+    pub entries: Vec<IpmaEntry>,
+}
+
+/// The item properties associated with an item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpmaEntry {
+    pub item_id: u32,
+    pub associations: Vec<PropertyAssociation>,
+}
+
+/// An association of an item property with an item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PropertyAssociation {
+    /// Whether the property must be understood in order to process the item.
+    pub essential: bool,
+
+    /// The one-based index of the property within the item property container box (`ipco`), where `0` indicates that
+    /// no property is associated.
+    pub property_index: u16,
+}
+
+const NAME: BoxType = BoxType::IPMA;
+
+/// The flag indicating that property indices are 15 bits wide, rather than 7 bits.
+const WIDE_PROPERTY_INDEX_FLAG: u32 = 0x1;
+
+impl IpmaBox {
+    pub fn new(entries: Vec<IpmaEntry>) -> Self {
+        let wide_item_ids = entries.iter().any(|entry| entry.item_id > u16::MAX.into());
+        let wide_property_indices = (entries.iter())
+            .flat_map(|entry| &entry.associations)
+            .any(|association| association.property_index > 0x7f);
+        let header = FullBoxHeader {
+            version: if wide_item_ids { 1 } else { 0 },
+            flags: if wide_property_indices {
+                WIDE_PROPERTY_INDEX_FLAG
+            } else {
+                0
+            },
+        };
+        Self { header, entries }
+    }
+
+    fn wide_item_ids(&self) -> bool {
+        self.header.version >= 1
+    }
+
+    fn wide_property_indices(&self) -> bool {
+        self.header.flags & WIDE_PROPERTY_INDEX_FLAG != 0
+    }
+}
+
+impl ParseBox for IpmaBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version <= 1,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingBox(NAME),
+        );
+        let mut ipma = Self { header, entries: Vec::new() };
+        let entry_count = u32::parse(&mut *buf).while_parsing_field(NAME, "entry_count")?;
+        for _ in 0..entry_count {
+            let entry = ipma.parse_entry(&mut *buf).while_parsing_field(NAME, "entries")?;
+            ipma.entries.push(entry);
+        }
+        ensure_attach!(
+            !buf.has_remaining(),
+            ParseError::InvalidInput,
+            "extra unparsed data",
+            WhileParsingBox(NAME),
+        );
+        Ok(ipma)
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl IpmaBox {
+    fn parse_entry(&self, buf: &mut BytesMut) -> Result<IpmaEntry, ParseError> {
+        let item_id = parse_item_id(&mut *buf, self.wide_item_ids())?;
+        let association_count = u8::parse(&mut *buf).attach_printable(WhileParsingItem(item_id))?;
+        let mut associations = Vec::with_capacity(association_count.into());
+        for _ in 0..association_count {
+            let association = match self.wide_property_indices() {
+                false => {
+                    let association = u8::parse(&mut *buf).attach_printable(WhileParsingItem(item_id))?;
+                    PropertyAssociation {
+                        essential: association & 0x80 != 0,
+                        property_index: (association & 0x7f).into(),
+                    }
+                }
+                true => {
+                    let association = u16::parse(&mut *buf).attach_printable(WhileParsingItem(item_id))?;
+                    PropertyAssociation { essential: association & 0x8000 != 0, property_index: association & 0x7fff }
+                }
+            };
+            associations.push(association);
+        }
+        Ok(IpmaEntry { item_id, associations })
+    }
+}
+
+impl ParsedBox for IpmaBox {
+    fn encoded_len(&self) -> u64 {
+        let association_len = match self.wide_property_indices() {
+            false => u8::encoded_len(),
+            true => u16::encoded_len(),
+        };
+        let association_count: u64 = self.entries.iter().map(|entry| entry.associations.len() as u64).sum();
+        FullBoxHeader::encoded_len()
+            + u32::encoded_len()
+            + self.entries.len() as u64 * (item_id_len(self.wide_item_ids()) + u8::encoded_len())
+            + association_count * association_len
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        (self.entries.len() as u32).put_buf(&mut out);
+        for entry in &self.entries {
+            put_item_id(entry.item_id, self.wide_item_ids(), &mut out);
+            (entry.associations.len() as u8).put_buf(&mut out);
+            for association in &entry.associations {
+                match self.wide_property_indices() {
+                    false => ((association.essential as u8) << 7 | association.property_index as u8).put_buf(&mut out),
+                    true => ((association.essential as u16) << 15 | association.property_index).put_buf(&mut out),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn roundtrip_ipma(ipma: &IpmaBox) -> IpmaBox {
+        let mut buf = BytesMut::new();
+        ipma.put_buf(&mut buf);
+        assert_eq!(buf.len() as u64, ipma.encoded_len());
+        IpmaBox::parse(&mut buf).unwrap()
+    }
+
+    #[test]
+    fn roundtrip() {
+        for (item_id, property_index) in [(1, 1), (1, 0x7fff), (u32::MAX, 2)] {
+            let associations = vec![
+                PropertyAssociation { essential: true, property_index },
+                PropertyAssociation { essential: false, property_index: 1 },
+            ];
+            let ipma = IpmaBox::new(vec![IpmaEntry { item_id, associations }]);
+            assert_eq!(roundtrip_ipma(&ipma), ipma);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let association = PropertyAssociation { essential: true, property_index: 1 };
+        let ipma = IpmaBox::new(vec![IpmaEntry { item_id: 1, associations: vec![association] }]);
+        let mut buf = BytesMut::new();
+        ipma.put_buf(&mut buf);
+        buf.truncate(buf.len() - 1);
+        let err = IpmaBox::parse(&mut buf).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err:?}");
+    }
+}
@@ -0,0 +1,67 @@
+#![allow(missing_docs)]
+
+use mediasan_common::Result;
+
+use super::error::ParseResultExt;
+use super::{BoxType, Boxes, IpcoBox, IpmaBox, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "iprp"]
+pub struct IprpBox {
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::IPRP;
+
+impl IprpBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes>>(children: C) -> Self {
+        Self { children: children.into() }
+    }
+
+    pub fn ipco_mut(&mut self) -> Result<&mut IpcoBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::IPCO)
+    }
+
+    pub fn ipmas(&mut self) -> impl Iterator<Item = Result<&mut IpmaBox, ParseError>> + '_ {
+        self.children
+            .get_mut()
+            .map(|ipma| ipma.while_parsing_child(NAME, BoxType::IPMA))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::{IpmaEntry, Mp4Box, PropertyAssociation};
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        let ipco = Mp4Box::with_data(IpcoBox::with_children(vec![]).into()).unwrap();
+        let association = PropertyAssociation { essential: true, property_index: 1 };
+        let ipma = IpmaBox::new(vec![IpmaEntry { item_id: 1, associations: vec![association] }]);
+        let ipma = Mp4Box::with_data(ipma.into()).unwrap();
+        IprpBox::with_children(vec![ipco.into(), ipma.into()]).put_buf(&mut data);
+
+        let mut iprp = IprpBox::parse(&mut data).unwrap();
+        assert_eq!(iprp.ipco_mut().unwrap().property_count(), 0);
+        let ipmas: Vec<_> = iprp.ipmas().collect::<Result<_, _>>().unwrap();
+        assert_eq!(ipmas.len(), 1);
+        assert_eq!(ipmas[0].entries[0].associations, [association]);
+    }
+
+    #[test]
+    fn no_ipco() {
+        let mut data = BytesMut::new();
+        IprpBox::with_children(vec![]).put_buf(&mut data);
+        let err = IprpBox::parse(&mut data).unwrap().ipco_mut().unwrap_err();
+        assert!(
+            matches!(err.get_ref(), ParseError::MissingRequiredBox(BoxType::IPCO)),
+            "{err}",
+        );
+    }
+}
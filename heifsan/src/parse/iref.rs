@@ -0,0 +1,156 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+use mediasan_common::Result;
+
+use super::error::{ParseResultExt, WhileParsingBox, WhileParsingField};
+use super::{item_id_len, parse_item_id, put_item_id};
+use super::{BoxHeader, BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IrefBox {
+    header: FullBoxHeader,
+    references: Vec<ItemReference>,
+}
+
+/// A single item reference box within an [`IrefBox`], referencing other items by their item IDs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemReference {
+    /// The type of the reference, such as `thmb`, `cdsc`, or `dimg`.
+    pub reference_type: BoxType,
+    pub from_item_id: u32,
+    pub to_item_ids: Vec<u32>,
+}
+
+const NAME: BoxType = BoxType::IREF;
+
+impl IrefBox {
+    pub fn new(references: Vec<ItemReference>) -> Self {
+        let wide = references.iter().any(|reference| {
+            reference.from_item_id > u16::MAX.into() || reference.to_item_ids.iter().any(|id| *id > u16::MAX.into())
+        });
+        Self { header: FullBoxHeader { version: if wide { 1 } else { 0 }, flags: 0 }, references }
+    }
+
+    pub fn references(&self) -> &[ItemReference] {
+        &self.references
+    }
+
+    fn wide_item_ids(&self) -> bool {
+        self.header.version >= 1
+    }
+
+    fn reference_data_len(&self, reference: &ItemReference) -> u64 {
+        (1 + reference.to_item_ids.len() as u64) * item_id_len(self.wide_item_ids()) + u16::encoded_len()
+    }
+
+    fn reference_header(&self, reference: &ItemReference) -> BoxHeader {
+        BoxHeader::with_u32_data_size(reference.reference_type, self.reference_data_len(reference) as u32)
+    }
+}
+
+impl ParseBox for IrefBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version <= 1,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingBox(NAME),
+        );
+        let mut references = Vec::new();
+        while buf.has_remaining() {
+            let reference = parse_reference(&mut *buf, header.version >= 1).while_parsing_field(NAME, "references")?;
+            references.push(reference);
+        }
+        Ok(Self { header, references })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+fn parse_reference(buf: &mut BytesMut, wide: bool) -> Result<ItemReference, ParseError> {
+    let header = BoxHeader::parse(&mut *buf)?;
+    let data_size = header.box_data_size()?.unwrap_or(buf.remaining() as u64);
+    ensure_attach!(
+        data_size <= buf.remaining() as u64,
+        ParseError::TruncatedBox,
+        WhileParsingField(header.box_type(), "to_item_IDs"),
+    );
+    let mut data = buf.split_to(data_size as usize);
+    let from_item_id = parse_item_id(&mut data, wide).while_parsing_field(header.box_type(), "from_item_ID")?;
+    let reference_count = u16::parse(&mut data).while_parsing_field(header.box_type(), "reference_count")?;
+    let mut to_item_ids = Vec::with_capacity(reference_count.into());
+    for _ in 0..reference_count {
+        let to_item_id = parse_item_id(&mut data, wide).while_parsing_field(header.box_type(), "to_item_ID")?;
+        to_item_ids.push(to_item_id);
+    }
+    ensure_attach!(
+        !data.has_remaining(),
+        ParseError::InvalidInput,
+        "extra unparsed data",
+        WhileParsingBox(header.box_type()),
+    );
+    Ok(ItemReference { reference_type: header.box_type(), from_item_id, to_item_ids })
+}
+
+impl ParsedBox for IrefBox {
+    fn encoded_len(&self) -> u64 {
+        FullBoxHeader::encoded_len()
+            + (self.references.iter())
+                .map(|reference| self.reference_header(reference).encoded_len() + self.reference_data_len(reference))
+                .sum::<u64>()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        for reference in &self.references {
+            self.reference_header(reference).put_buf(&mut out);
+            put_item_id(reference.from_item_id, self.wide_item_ids(), &mut out);
+            (reference.to_item_ids.len() as u16).put_buf(&mut out);
+            for to_item_id in &reference.to_item_ids {
+                put_item_id(*to_item_id, self.wide_item_ids(), &mut out);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::FourCC;
+
+    use super::*;
+
+    const CDSC: BoxType = BoxType::FourCC(FourCC { value: *b"cdsc" });
+    const THMB: BoxType = BoxType::FourCC(FourCC { value: *b"thmb" });
+
+    #[test]
+    fn roundtrip() {
+        for to_item_id in [1, u32::MAX] {
+            let iref = IrefBox::new(vec![
+                ItemReference { reference_type: CDSC, from_item_id: 2, to_item_ids: vec![to_item_id] },
+                ItemReference { reference_type: THMB, from_item_id: 3, to_item_ids: vec![1, 2] },
+            ]);
+            let mut data = BytesMut::new();
+            iref.put_buf(&mut data);
+            assert_eq!(data.len() as u64, iref.encoded_len());
+            assert_eq!(IrefBox::parse(&mut data).unwrap(), iref);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let iref = IrefBox::new(vec![ItemReference {
+            reference_type: CDSC,
+            from_item_id: 2,
+            to_item_ids: vec![1],
+        }]);
+        let mut data = BytesMut::new();
+        iref.put_buf(&mut data);
+        data.truncate(data.len() - 1);
+        let err = IrefBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
@@ -0,0 +1,92 @@
+#![allow(missing_docs)]
+
+use mediasan_common::Result;
+
+use super::error::ParseResultExt;
+use super::{
+    BoxType, Boxes, ConstFullBoxHeader, HdlrBox, IdatBox, IinfBox, IlocBox, IprpBox, IrefBox, ParseBox, ParseError,
+    ParsedBox, PitmBox,
+};
+
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "meta"]
+pub struct MetaBox {
+    header: ConstFullBoxHeader,
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::META;
+
+impl MetaBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes>>(children: C) -> Self {
+        Self { header: Default::default(), children: children.into() }
+    }
+
+    pub fn hdlr_mut(&mut self) -> Result<&mut HdlrBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::HDLR)
+    }
+
+    pub fn pitm_mut(&mut self) -> Result<&mut PitmBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::PITM)
+    }
+
+    pub fn iloc_mut(&mut self) -> Result<&mut IlocBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::ILOC)
+    }
+
+    pub fn iinf_mut(&mut self) -> Result<&mut IinfBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::IINF)
+    }
+
+    pub fn iprp_mut(&mut self) -> Result<&mut IprpBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::IPRP)
+    }
+
+    pub fn iref_mut(&mut self) -> Result<Option<&mut IrefBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::IREF)
+    }
+
+    pub fn idat_mut(&mut self) -> Result<Option<&mut IdatBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::IDAT)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::{FourCC, InfeBox, Mp4Box};
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let hvc1 = FourCC { value: *b"hvc1" };
+        let mut data = BytesMut::new();
+        MetaBox::with_children(vec![
+            Mp4Box::with_data(HdlrBox::new(FourCC { value: *b"pict" }).into())
+                .unwrap()
+                .into(),
+            Mp4Box::with_data(PitmBox::new(1).into()).unwrap().into(),
+            Mp4Box::with_data(IinfBox::with_entries([InfeBox::new(1, hvc1)]).unwrap().into())
+                .unwrap()
+                .into(),
+        ])
+        .put_buf(&mut data);
+        let mut meta = MetaBox::parse(&mut data).unwrap();
+        assert_eq!(meta.hdlr_mut().unwrap().handler_type, FourCC { value: *b"pict" });
+        assert_eq!(meta.pitm_mut().unwrap().item_id, 1);
+        assert_eq!(meta.iinf_mut().unwrap().entry_count(), 1);
+        assert!(meta.iref_mut().unwrap().is_none());
+        let err = meta.iloc_mut().unwrap_err();
+        assert!(
+            matches!(err.get_ref(), ParseError::MissingRequiredBox(BoxType::ILOC)),
+            "{err}",
+        );
+    }
+}
@@ -0,0 +1,79 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+use mediasan_common::Result;
+
+use super::error::{ParseResultExt, WhileParsingBox};
+use super::{item_id_len, parse_item_id, put_item_id};
+use super::{BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PitmBox {
+    header: FullBoxHeader,
+    pub item_id: u32,
+}
+
+const NAME: BoxType = BoxType::PITM;
+
+impl PitmBox {
+    pub fn new(item_id: u32) -> Self {
+        let version = if item_id > u16::MAX.into() { 1 } else { 0 };
+        Self { header: FullBoxHeader { version, flags: 0 }, item_id }
+    }
+}
+
+impl ParseBox for PitmBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        let wide = match header.version {
+            0 => false,
+            1 => true,
+            version => bail_attach!(
+                ParseError::InvalidInput,
+                format!("unsupported box version {version}"),
+                WhileParsingBox(NAME),
+            ),
+        };
+        let item_id = parse_item_id(&mut *buf, wide).while_parsing_field(NAME, "item_ID")?;
+        Ok(Self { header, item_id })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for PitmBox {
+    fn encoded_len(&self) -> u64 {
+        FullBoxHeader::encoded_len() + item_id_len(self.header.version != 0)
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        put_item_id(self.item_id, self.header.version != 0, &mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for item_id in [1, u32::MAX] {
+            let pitm = PitmBox::new(item_id);
+            let mut buf = BytesMut::new();
+            pitm.put_buf(&mut buf);
+            assert_eq!(buf.len() as u64, pitm.encoded_len());
+            assert_eq!(PitmBox::parse(&mut buf).unwrap(), pitm);
+            assert!(buf.is_empty());
+        }
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut buf = BytesMut::from(&[2, 0, 0, 0, 0, 0, 0, 1][..]);
+        let err = PitmBox::parse(&mut buf).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err:?}");
+    }
+}
@@ -0,0 +1,249 @@
+use bytes::{BufMut, BytesMut};
+use derive_builder::Builder;
+
+use crate::parse::{
+    AnyMp4Box, BoxHeader, BoxType, ConstructionMethod, FourCC, FtypBox, HdlrBox, IdatBox, IinfBox, IlocBox, IlocExtent,
+    IlocItem, InfeBox, IpcoBox, IpmaBox, IpmaEntry, IprpBox, IrefBox, MetaBox, Mp4Box, Mp4Value, ParsedBox, PitmBox,
+    PropertyAssociation,
+};
+use crate::{InputSpan, SanitizedMetadata};
+
+pub const HEIC: FourCC = FourCC { value: *b"heic" };
+pub const ISOM: FourCC = FourCC { value: *b"isom" };
+pub const MIF1: FourCC = FourCC { value: *b"mif1" };
+pub const PICT: FourCC = FourCC { value: *b"pict" };
+
+pub const CDSC: BoxType = BoxType::FourCC(FourCC { value: *b"cdsc" });
+pub const ISPE: BoxType = BoxType::FourCC(FourCC { value: *b"ispe" });
+
+pub const HVC1: FourCC = FourCC { value: *b"hvc1" };
+pub const EXIF: FourCC = FourCC { value: *b"Exif" };
+
+pub const TEST_ITEM_ID: u32 = 1;
+pub const TEST_ITEM_DATA: &[u8] = &[0xBA, 0xDC, 0x0F, 0xFE, 0xBE, 0xEF];
+
+pub const TEST_IDAT_ITEM_ID: u32 = 2;
+pub const TEST_IDAT_ITEM_DATA: &[u8] = b"Exif\0\0";
+
+pub use mediasan_common_test::init_logger;
+
+#[derive(Builder)]
+#[builder(name = "TestHeifBuilder", build_fn(name = "build_spec"))]
+pub struct TestHeifSpec {
+    #[builder(default = "vec![BoxType::FTYP, BoxType::META, BoxType::MDAT]")]
+    boxes: Vec<BoxType>,
+
+    #[builder(default = "vec![MIF1, HEIC]")]
+    compatible_brands: Vec<FourCC>,
+
+    #[builder(default = "PICT")]
+    handler_type: FourCC,
+
+    #[builder(default = "TEST_ITEM_ID")]
+    primary_item_id: u32,
+
+    /// Whether to locate [`TEST_ITEM_DATA`] within the media data box (`mdat`).
+    #[builder(default = "true")]
+    file_item: bool,
+
+    #[builder(default = "TEST_ITEM_ID")]
+    file_item_id: u32,
+
+    /// Whether to split the offset of the file item into its base offset, rather than its extent offset.
+    #[builder(default = "true")]
+    base_offset: bool,
+
+    /// An amount to displace the extent of the file item by from its actual location.
+    #[builder(default)]
+    extent_displacement: i64,
+
+    #[builder(default = "Some(TEST_ITEM_DATA.len() as u64)")]
+    #[builder(setter(strip_option))]
+    extent_len: Option<u64>,
+
+    #[builder(default)]
+    duplicate_iloc_item: bool,
+
+    /// Whether to locate [`TEST_IDAT_ITEM_DATA`] within the item data box (`idat`).
+    #[builder(default)]
+    idat_item: bool,
+
+    #[builder(default = "TEST_IDAT_ITEM_DATA.len()")]
+    idat_len: usize,
+
+    #[builder(default = "vec![test_ipma_entry()]")]
+    ipma_entries: Vec<IpmaEntry>,
+
+    #[builder(default, setter(strip_option))]
+    iref: Option<IrefBox>,
+
+    #[builder(default)]
+    free_len: u32,
+}
+
+pub struct TestHeif {
+    pub data: Vec<u8>,
+    pub mdat: InputSpan,
+}
+
+pub fn test_heif() -> TestHeifBuilder {
+    TestHeifBuilder::default()
+}
+
+pub fn test_ipma_entry() -> IpmaEntry {
+    IpmaEntry { item_id: TEST_ITEM_ID, associations: vec![PropertyAssociation { essential: true, property_index: 1 }] }
+}
+
+impl TestHeifBuilder {
+    pub fn build(&self) -> TestHeif {
+        let spec = self.build_spec().unwrap();
+
+        // The offset of the media data depends on the length of the meta box, which depends on the offset of the media
+        // data, so write the file until the offset stops changing.
+        let mut mdat_data_offset = 0;
+        loop {
+            let test_heif = spec.write(mdat_data_offset);
+            let new_mdat_data_offset =
+                test_heif.mdat.offset + BoxHeader::with_u32_data_size(BoxType::MDAT, 0).encoded_len();
+            if new_mdat_data_offset == mdat_data_offset {
+                return test_heif;
+            }
+            mdat_data_offset = new_mdat_data_offset;
+        }
+    }
+}
+
+impl TestHeifSpec {
+    fn write(&self, mdat_data_offset: u64) -> TestHeif {
+        let mut data = Vec::new();
+        let mut mdat = None;
+        for &box_type in &self.boxes {
+            match box_type {
+                BoxType::FTYP => {
+                    let ftyp = FtypBox::new(HEIC, 0, self.compatible_brands.iter().copied());
+                    Mp4Box::with_data(ftyp.into()).unwrap().put_buf(&mut data);
+                }
+                BoxType::META => self.meta(mdat_data_offset).put_buf(&mut data),
+                BoxType::MDAT => {
+                    let offset = data.len() as u64;
+                    BoxHeader::with_u32_data_size(BoxType::MDAT, TEST_ITEM_DATA.len() as u32).put_buf(&mut data);
+                    data.extend_from_slice(TEST_ITEM_DATA);
+                    mdat.get_or_insert(InputSpan { offset, len: data.len() as u64 - offset });
+                }
+                BoxType::FREE => {
+                    BoxHeader::with_u32_data_size(BoxType::FREE, self.free_len).put_buf(&mut data);
+                    data.put_bytes(0, self.free_len as usize);
+                }
+                box_type => BoxHeader::with_u32_data_size(box_type, 0).put_buf(&mut data),
+            }
+        }
+        let mdat = mdat.unwrap_or(InputSpan { offset: data.len() as u64, len: 0 });
+        TestHeif { data, mdat }
+    }
+
+    fn meta(&self, mdat_data_offset: u64) -> Mp4Box<MetaBox> {
+        let mut infes = vec![InfeBox::new(TEST_ITEM_ID, HVC1)];
+        let mut iloc_items = vec![];
+        if self.file_item {
+            // NB: The first write of the file may not yet know the offset of the media data.
+            let offset = mdat_data_offset.saturating_add_signed(self.extent_displacement);
+            let (base_offset, extent_offset) = match self.base_offset {
+                true => (offset, 0),
+                false => (0, offset),
+            };
+            let extent = IlocExtent { index: 0, offset: extent_offset, length: self.extent_len.unwrap_or_default() };
+            let item = IlocItem {
+                item_id: self.file_item_id,
+                construction_method: ConstructionMethod::File,
+                data_reference_index: 0,
+                base_offset,
+                extents: vec![extent],
+            };
+            if self.duplicate_iloc_item {
+                iloc_items.push(item.clone());
+            }
+            iloc_items.push(item);
+        }
+        let mut idat = None;
+        if self.idat_item {
+            infes.push(InfeBox::new(TEST_IDAT_ITEM_ID, EXIF));
+            let extent = IlocExtent { index: 0, offset: 0, length: TEST_IDAT_ITEM_DATA.len() as u64 };
+            iloc_items.push(IlocItem {
+                item_id: TEST_IDAT_ITEM_ID,
+                construction_method: ConstructionMethod::Idat,
+                data_reference_index: 0,
+                base_offset: 0,
+                extents: vec![extent],
+            });
+            idat = Some(IdatBox::new(&TEST_IDAT_ITEM_DATA[..self.idat_len]));
+        }
+
+        let ispe = AnyMp4Box::with_bytes(ISPE, BytesMut::from(&[0; 12][..]));
+        let ipco = IpcoBox::with_children(vec![ispe]);
+        let ipma = IpmaBox::new(self.ipma_entries.clone());
+        let iprp = IprpBox::with_children(vec![any_box(ipco), any_box(ipma)]);
+
+        let mut children = vec![
+            any_box(HdlrBox::new(self.handler_type)),
+            any_box(PitmBox::new(self.primary_item_id)),
+            any_box(IlocBox::new(iloc_items)),
+            any_box(IinfBox::with_entries(infes).unwrap()),
+            any_box(iprp),
+        ];
+        children.extend(self.iref.clone().map(any_box));
+        children.extend(idat.map(any_box));
+        Mp4Box::with_data(MetaBox::with_children(children).into()).unwrap()
+    }
+}
+
+fn any_box<T: ParsedBox + crate::parse::ParseBox>(data: T) -> AnyMp4Box {
+    Mp4Box::with_data(data.into()).unwrap().into()
+}
+
+pub fn sanitized_data(sanitized: SanitizedMetadata, data: &[u8]) -> Vec<u8> {
+    match &sanitized.metadata {
+        Some(metadata) => {
+            let mut sanitized_data = metadata.clone();
+            let span = sanitized.data;
+            sanitized_data.extend_from_slice(&data[span.offset as usize..][..span.len as usize]);
+            sanitized_data
+        }
+        None => data.to_vec(),
+    }
+}
+
+/// Read the data of the item `item_id` from the HEIF file `data`, as located by its item location box (`iloc`).
+pub fn item_data(data: &[u8], item_id: u32) -> Vec<u8> {
+    let mut offset = 0;
+    let mut meta = loop {
+        let header = BoxHeader::parse(&data[offset..]).unwrap();
+        let box_size = header.box_size().unwrap() as usize;
+        if header.box_type() == BoxType::META {
+            break Mp4Box::<MetaBox>::parse(&mut BytesMut::from(&data[offset..][..box_size])).unwrap();
+        }
+        offset += box_size;
+    };
+    let meta = meta.data.parse().unwrap();
+
+    let mut idat_data = vec![];
+    if let Some(idat) = meta.idat_mut().unwrap() {
+        idat.put_buf(&mut idat_data);
+    }
+
+    let iloc = meta.iloc_mut().unwrap();
+    let item = iloc.items.iter().find(|item| item.item_id == item_id).unwrap();
+    let container = match item.construction_method {
+        ConstructionMethod::File => data,
+        ConstructionMethod::Idat => &idat_data[..],
+        ConstructionMethod::Item => unimplemented!(),
+    };
+    let mut item_data = vec![];
+    for extent in &item.extents {
+        let extent_data = &container[(item.base_offset + extent.offset) as usize..];
+        match extent.length {
+            0 => item_data.extend_from_slice(extent_data),
+            length => item_data.extend_from_slice(&extent_data[..length as usize]),
+        }
+    }
+    item_data
+}
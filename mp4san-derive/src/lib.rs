@@ -101,19 +101,26 @@ fn derive_read_fn(input: &DeriveInput) -> TokenStream2 {
                 }
             }
             quote! {
-                fn parse(buf: &mut bytes::BytesMut) -> std::result::Result<Self, mp4san::Report<mp4san::parse::ParseError>> {
+                fn parse(buf: &mut bytes::BytesMut) -> std::result::Result<Self, mp4san::error::Report<mp4san::parse::ParseError>> {
+                    Self::parse_at_depth(buf, std::default::Default::default())
+                }
+
+                fn parse_at_depth(
+                    buf: &mut bytes::BytesMut,
+                    depth: mp4san::parse::BoxDepth,
+                ) -> std::result::Result<Self, mp4san::error::Report<mp4san::parse::ParseError>> {
                     #(
                         let #bind_ident: #field_ty =
-                            mp4san::parse::error::ParseResultExt::while_parsing_field(
-                                mp4san::parse::Mp4Value::parse(&mut *buf),
+                            mp4san::parse::error::__ParseResultExt::while_parsing_field(
+                                mp4san::parse::Mp4Value::parse_at_depth(&mut *buf, depth),
                                 #ident::box_type(),
                                 stringify!(#field_ty),
                             )?;
                     )*
                     if !buf.is_empty() {
                         return
-                            mp4san::parse::error::ParseResultExt::while_parsing_box(
-                                mp4san::error::ResultExt::attach_printable(
+                            mp4san::parse::error::__ParseResultExt::while_parsing_box(
+                                mp4san::error::__ResultExt::attach_printable(
                                     Err(mp4san::parse::ParseError::InvalidInput.into()),
                                     "extra unparsed data",
                                 ),
@@ -22,6 +22,7 @@ fn main() {
                     }
                     _ => panic!(),
                 },
+                mp4san::Error::Cancelled => panic!(),
                 mp4san::Error::Parse(error) => {
                     eprintln!("mp4san returned a parse error: {error}\n{error:?}");
                 }
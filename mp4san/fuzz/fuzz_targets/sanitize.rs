@@ -24,6 +24,7 @@ fuzz_target!(|data: &[u8]| {
                 }
                 _ => panic!(),
             },
+            mp4san::Error::Cancelled => panic!(),
             mp4san::Error::Parse(error) => {
                 #[cfg(fuzzing_repro)]
                 eprintln!("mp4san returned a parse error: {error}\n{error:?}");
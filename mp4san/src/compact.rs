@@ -0,0 +1,169 @@
+use crate::parse::{BoxHeader, BoxType, FtypBox, MoovBox, Mp4Box, Mp4Value};
+use crate::{displace, Error, InputSpan, MediaInfo, SanitizedMetadata};
+
+//
+// public functions
+//
+
+/// Rewrite the media `data` to contain only the sample data referenced by the sample tables in `moov`.
+///
+/// The returned metadata ends with the header of a new mdat box containing the referenced spans of media data.
+pub fn compact_data(
+    ftyp: Mp4Box<FtypBox>,
+    moov: Mp4Box<MoovBox>,
+    data: &[InputSpan],
+    media_info: Option<MediaInfo>,
+) -> Result<SanitizedMetadata, Error> {
+    // Make sure none of the metadata boxes use BoxSize::UntilEof, as we want the caller to be able to concatenate movie
+    // data to the end of the metadata.
+    let ftyp = Mp4Box::with_data(ftyp.data)?;
+    let mut moov = Mp4Box::with_data(moov.data)?;
+
+    let referenced_data = referenced_data_spans(moov.data.parse()?)?;
+    let data_len: u64 = data.iter().map(|span| span.len).sum();
+    let referenced_data_len: u64 = referenced_data.iter().map(|span| span.len).sum();
+    let mdat_header = BoxHeader::with_data_size(BoxType::MDAT, referenced_data_len)?;
+    log::info!(
+        "mdat: compacting 0x{data_len:08x} bytes to 0x{referenced_data_len:08x} bytes in {span_count} spans",
+        span_count = referenced_data.len(),
+    );
+
+    let (metadata_len, data_displacements) = loop {
+        let metadata_len = ftyp.encoded_len() + moov.encoded_len() + mdat_header.encoded_len();
+        let data_displacements = displace::data_displacements(&referenced_data, metadata_len)?;
+        if !displace::upgrade_displaced_chunk_offsets(moov.data.parse()?, &data_displacements)? {
+            break (metadata_len, data_displacements);
+        }
+    };
+    displace::displace_chunk_offsets(moov.data.parse()?, &data_displacements)?;
+
+    let mut metadata = Vec::with_capacity(metadata_len as usize);
+    ftyp.put_buf(&mut metadata);
+    moov.put_buf(&mut metadata);
+    mdat_header.put_buf(&mut metadata);
+    log::info!("metadata: 0x{metadata_len:08x} bytes");
+
+    let (first_data, extra_data) = match referenced_data.split_first() {
+        Some((&first_data, extra_data)) => (first_data, extra_data.to_vec()),
+        None => (InputSpan { offset: data[0].offset, len: 0 }, vec![]),
+    };
+    Ok(SanitizedMetadata { metadata: Some(metadata), data: first_data, extra_data, media_info })
+}
+
+//
+// private functions
+//
+
+/// Compute the spans of media data referenced by the sample tables in `moov`, in order of offset.
+///
+/// Overlapping or adjacent chunks are merged into a single span.
+fn referenced_data_spans(moov: &mut MoovBox) -> Result<Vec<InputSpan>, Error> {
+    let mut chunks = Vec::new();
+    for trak in moov.traks() {
+        chunks.extend(trak?.chunk_spans()?);
+    }
+    chunks.sort_unstable_by_key(|chunk| chunk.offset);
+
+    let mut spans: Vec<InputSpan> = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        match spans.last_mut() {
+            Some(span) if chunk.offset <= span.offset + span.len => {
+                span.len = span.len.max(chunk.offset + chunk.len - span.offset);
+            }
+            _ => spans.push(chunk),
+        }
+    }
+    Ok(spans)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use assert_matches::assert_matches;
+
+    use crate::parse::box_type::MDAT;
+    use crate::parse::ParseError;
+    use crate::util::test::{
+        init_logger, sanitized_chunk_offsets, sanitized_data, test_discontiguous_mdat_mp4, test_fragmented_mp4,
+        test_moov, test_trak, test_two_track_mp4,
+    };
+    use crate::{sanitize_to_writer_with_config, sanitize_with_config, Config};
+
+    use super::*;
+
+    #[test]
+    fn compact_media_data() {
+        init_logger();
+        let data = test_two_track_mp4(&mut test_moov(), &mut test_trak(), vec![0], vec![2, 3]);
+        let config = Config::builder().compact_media_data(true).build();
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        let metadata = sanitized.metadata.clone().unwrap();
+        let mut mdat_header = vec![];
+        BoxHeader::with_u32_data_size(MDAT, 3).put_buf(&mut mdat_header);
+        assert!(metadata.ends_with(&mdat_header));
+        assert_eq!(sanitized.data_spans().map(|span| span.len).collect::<Vec<_>>(), [1, 2]);
+
+        let chunk_offsets = sanitized_chunk_offsets(&metadata);
+        let sanitized_data = sanitized_data(sanitized, &data);
+        assert_eq!(sanitized_data.len(), metadata.len() + 3);
+        let chunks: Vec<_> = chunk_offsets
+            .into_iter()
+            .map(|chunk_offset| sanitized_data[chunk_offset as usize])
+            .collect();
+        assert_eq!(chunks, b"acd");
+    }
+
+    #[test]
+    fn compact_media_data_discontiguous_mdat() {
+        init_logger();
+        let (data, [first_mdat, second_mdat]) = test_discontiguous_mdat_mp4();
+        let config = Config::builder()
+            .allow_discontiguous_mdat(true)
+            .compact_media_data(true)
+            .build();
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        let mdat_header_len = BoxHeader::with_u32_data_size(MDAT, 0).encoded_len();
+        assert_eq!(
+            sanitized.data,
+            InputSpan { offset: first_mdat.offset + mdat_header_len, len: 1 }
+        );
+        assert_eq!(
+            sanitized.extra_data,
+            [InputSpan { offset: second_mdat.offset + mdat_header_len, len: 1 }]
+        );
+
+        let chunk_offsets = sanitized_chunk_offsets(sanitized.metadata.as_ref().unwrap());
+        let sanitized_data = sanitized_data(sanitized, &data);
+        let chunks: Vec<_> = chunk_offsets
+            .into_iter()
+            .map(|chunk_offset| sanitized_data[chunk_offset as usize])
+            .collect();
+        assert_eq!(chunks, b"ad");
+    }
+
+    #[test]
+    fn compact_media_data_to_writer() {
+        init_logger();
+        let data = test_two_track_mp4(&mut test_moov(), &mut test_trak(), vec![1], vec![3]);
+        let config = Config::builder().compact_media_data(true).build();
+        let mut output = vec![];
+        let sanitized = sanitize_to_writer_with_config(io::Cursor::new(&data), &mut output, config).unwrap();
+        assert_eq!(output, sanitized_data(sanitized, &data));
+        assert!(output.ends_with(b"bd"));
+    }
+
+    #[test]
+    fn compact_media_data_fragmented() {
+        init_logger();
+        let (data, _) = test_fragmented_mp4(0);
+        let config = Config::builder()
+            .allow_fragmented(true)
+            .compact_media_data(true)
+            .build();
+        let err = sanitize_with_config(io::Cursor::new(&data), config).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBoxLayout);
+        });
+    }
+}
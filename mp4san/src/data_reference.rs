@@ -0,0 +1,77 @@
+use crate::parse::error::WhileParsingBox;
+use crate::parse::{BoxType, MoovBox, ParseError};
+use crate::Error;
+
+//
+// public functions
+//
+
+/// Validate that the data references of each track in `moov` point to the same file, replacing any external data
+/// references with references to the same file if `replace_external_data_references` is set.
+///
+/// Returns whether any data references were replaced.
+pub fn validate_data_references(moov: &mut MoovBox, replace_external_data_references: bool) -> Result<bool, Error> {
+    let mut replaced = false;
+    // A track without a data information box (`dinf`) has no data references, and its media data can only be in the
+    // same file.
+    for trak in moov.traks() {
+        let Some(dref) = trak?.dref_mut()? else {
+            continue;
+        };
+        if replace_external_data_references {
+            replaced |= dref.make_self_contained()?;
+        } else {
+            ensure_attach!(
+                dref.is_self_contained()?,
+                ParseError::UnsupportedBoxLayout,
+                "data reference to separate file",
+                WhileParsingBox(BoxType::DREF),
+            );
+        }
+    }
+    Ok(replaced)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use assert_matches::assert_matches;
+
+    use crate::parse::box_type::{FTYP, MDAT, MOOV};
+    use crate::util::test::{sanitized_data, test_moov, test_mp4};
+    use crate::{sanitize, sanitize_with_config, Config};
+
+    use super::*;
+
+    #[test]
+    fn external_data_reference() {
+        let test = test_mp4()
+            .moov(test_moov().external_data_reference(true).clone())
+            .build();
+        assert_matches!(sanitize(test).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBoxLayout);
+        });
+    }
+
+    #[test]
+    fn external_data_reference_replaced() {
+        let test = test_mp4()
+            .boxes(&[FTYP, MOOV, MDAT][..])
+            .moov(test_moov().external_data_reference(true).clone())
+            .build();
+        let config = Config::builder().replace_external_data_references(true).build();
+        let sanitized = sanitize_with_config(test.clone(), config).unwrap();
+        assert_eq!(sanitized.data, test.mdat);
+        assert!(sanitized.metadata.is_some());
+        let sanitized_data = sanitized_data(sanitized, &test.data);
+        assert_matches!(sanitize(io::Cursor::new(&sanitized_data)).unwrap().metadata, None);
+    }
+
+    #[test]
+    fn self_contained_data_reference_not_replaced() {
+        let config = Config::builder().replace_external_data_references(true).build();
+        let sanitized = sanitize_with_config(test_mp4().boxes(&[FTYP, MOOV, MDAT][..]).build(), config).unwrap();
+        assert_eq!(sanitized.metadata, None);
+    }
+}
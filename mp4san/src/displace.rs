@@ -0,0 +1,199 @@
+use mediasan_common::util::checked_add_signed;
+
+use crate::parse::{MoovBox, ParseError, StblCoMut};
+use crate::{Error, InputSpan};
+
+//
+// public functions
+//
+
+/// Compute the amount each span of media `data` is displaced by when concatenated in order at `data_output_offset`.
+pub fn data_displacements(data: &[InputSpan], mut data_output_offset: u64) -> Result<Vec<(InputSpan, i64)>, Error> {
+    let mut data_displacements = Vec::with_capacity(data.len());
+    for &span in data {
+        let mdat_displacement = match data_output_offset.checked_sub(span.offset) {
+            Some(mdat_forward_displacement) => mdat_forward_displacement.try_into().ok(),
+            None => (span.offset - data_output_offset)
+                .try_into()
+                .ok()
+                .and_then(i64::checked_neg),
+        };
+        let mdat_displacement: i64 = mdat_displacement
+            .ok_or_else(|| report_attach!(ParseError::UnsupportedBoxLayout, "mdat displaced too far"))?;
+        if mdat_displacement != 0 {
+            log::info!(
+                "mdat @ 0x{offset:08x}: displacing chunk offsets by 0x{mdat_displacement:08x}",
+                offset = span.offset,
+            );
+        }
+        data_displacements.push((span, mdat_displacement));
+        data_output_offset = data_output_offset
+            .checked_add(span.len)
+            .ok_or_else(|| report_attach!(ParseError::UnsupportedBoxLayout, "mdat displaced too far"))?;
+    }
+    Ok(data_displacements)
+}
+
+/// Convert the stco box of each track in `moov` to a co64 box if any of its chunk offsets would no longer fit in a
+/// `u32` when displaced by `data_displacements`.
+///
+/// Returns whether any track was converted, in which case `moov` has grown and the displacements must be recomputed.
+pub fn upgrade_displaced_chunk_offsets(
+    moov: &mut MoovBox,
+    data_displacements: &[(InputSpan, i64)],
+) -> Result<bool, Error> {
+    // Chunk offsets can only overflow when media data is displaced forward.
+    if data_displacements
+        .iter()
+        .all(|&(_, mdat_displacement)| mdat_displacement <= 0)
+    {
+        return Ok(false);
+    }
+    let mut upgraded = false;
+    for (trak_index, trak) in moov.traks().enumerate() {
+        let stbl = trak?.mdia_mut()?.minf_mut()?.stbl_mut()?;
+        let StblCoMut::Stco(stco) = stbl.co_mut()? else {
+            continue;
+        };
+        let overflowed = stco.entries_mut().any(|entry| {
+            let value = u64::from(entry.get().unwrap_or_else(|_| unreachable!()));
+            chunk_displacement(data_displacements, value)
+                .and_then(|mdat_displacement| checked_add_signed(value, mdat_displacement))
+                .is_some_and(|displaced_value| displaced_value > u32::MAX.into())
+        });
+        if overflowed {
+            log::info!("trak {trak_index}: converting stco to co64 for displaced chunk offsets");
+            stbl.upgrade_stco_to_co64()?;
+            upgraded = true;
+        }
+    }
+    Ok(upgraded)
+}
+
+/// Displace the chunk offsets in stco/co64 boxes by the amount the span of media data containing them was displaced.
+///
+/// Any stco box whose displaced chunk offsets would not fit in a `u32` must already have been converted to a co64 box
+/// by [`upgrade_displaced_chunk_offsets`].
+pub fn displace_chunk_offsets(moov: &mut MoovBox, data_displacements: &[(InputSpan, i64)]) -> Result<(), Error> {
+    let displace = |value: u64| {
+        chunk_displacement(data_displacements, value)
+            .and_then(|mdat_displacement| checked_add_signed(value, mdat_displacement))
+            .ok_or_else(|| report_attach!(ParseError::InvalidInput, "chunk offset not within mdat"))
+    };
+    for trak in moov.traks() {
+        match trak?.co_mut()? {
+            StblCoMut::Stco(stco) => {
+                for mut entry in &mut stco.entries_mut() {
+                    let value = entry.get().unwrap_or_else(|_| unreachable!());
+                    let displaced_value = displace(value.into())?
+                        .try_into()
+                        .map_err(|_| report_attach!(ParseError::InvalidInput, "chunk offset not within mdat"))?;
+                    entry.set(displaced_value);
+                }
+            }
+            StblCoMut::Co64(co64) => {
+                for mut entry in &mut co64.entries_mut() {
+                    let value = entry.get().unwrap_or_else(|_| unreachable!());
+                    entry.set(displace(value)?);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+//
+// private functions
+//
+
+/// Returns the amount the span of media data containing `chunk_offset` was displaced by, if any.
+///
+/// If there is only a single span of media data, all chunk offsets are displaced by the same amount.
+fn chunk_displacement(data_displacements: &[(InputSpan, i64)], chunk_offset: u64) -> Option<i64> {
+    match data_displacements {
+        [(_, mdat_displacement)] => Some(*mdat_displacement),
+        _ => data_displacements
+            .iter()
+            .find(|(span, _)| span.offset <= chunk_offset && chunk_offset - span.offset <= span.len)
+            .map(|&(_, mdat_displacement)| mdat_displacement),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use assert_matches::assert_matches;
+    use bytes::BytesMut;
+
+    use crate::parse::{AnyMp4Box, Mp4Box, Mp4Value};
+    use crate::util::test::sparse::SparseInput;
+    use crate::util::test::{
+        init_logger, sanitized_chunk_offsets, sanitized_data, test_discontiguous_mdat_mp4, test_ftyp, test_moov,
+        test_trak, write_mdat_header,
+    };
+    use crate::{sanitize_with_config, Config};
+
+    use super::*;
+
+    #[test]
+    fn discontiguous_mdat() {
+        init_logger();
+        let (data, [first_mdat, second_mdat]) = test_discontiguous_mdat_mp4();
+        let config = Config::builder().allow_discontiguous_mdat(true).build();
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        assert_eq!(sanitized.data, first_mdat);
+        assert_eq!(sanitized.extra_data, [second_mdat]);
+
+        let chunk_offsets = sanitized_chunk_offsets(sanitized.metadata.as_ref().unwrap());
+        let sanitized_data = sanitized_data(sanitized, &data);
+        let chunks: Vec<_> = chunk_offsets
+            .into_iter()
+            .map(|chunk_offset| sanitized_data[chunk_offset as usize])
+            .collect();
+        assert_eq!(chunks, b"ad");
+    }
+
+    #[test]
+    fn stco_displaced_past_u32_max() {
+        init_logger();
+        let mut data = vec![];
+        test_ftyp().build().put_buf(&mut data);
+        let mdat_data_len = u64::from(u32::MAX) - data.len() as u64;
+        let mdat = write_mdat_header(&mut data, Some(mdat_data_len));
+        let mdat_data_offset = mdat.offset + mdat.len;
+        let mdat_end = mdat_data_offset + mdat_data_len;
+        let last_chunk_offset = u64::from(u32::MAX) - 1;
+
+        // Only the chunk offsets of the first track are displaced past u32::MAX.
+        let moov = test_moov()
+            .co_entries(vec![mdat_data_offset, last_chunk_offset])
+            .add_trak(test_trak().co_entries(vec![mdat_data_offset + 1]).clone())
+            .build();
+        let mut moov_data = vec![];
+        moov.put_buf(&mut moov_data);
+        let input = SparseInput::new(mdat_end + moov_data.len() as u64)
+            .with_part(0, data)
+            .with_part(mdat_end, moov_data);
+
+        let config = Config::builder().verify_output(true).build();
+        let sanitized = sanitize_with_config(input, config).unwrap();
+        assert_eq!(
+            sanitized.data,
+            InputSpan { offset: mdat.offset, len: mdat_end - mdat.offset }
+        );
+        let metadata = sanitized.metadata.unwrap();
+        let mdat_displacement = metadata.len() as u64 - mdat.offset;
+        assert_eq!(
+            sanitized_chunk_offsets(&metadata),
+            [mdat_data_offset, last_chunk_offset, mdat_data_offset + 1].map(|offset| offset + mdat_displacement),
+        );
+
+        let mut metadata = BytesMut::from(&metadata[..]);
+        AnyMp4Box::parse(&mut metadata).unwrap();
+        let mut moov = Mp4Box::<MoovBox>::parse(&mut metadata).unwrap();
+        let mut traks = moov.data.parse().unwrap().traks();
+        assert_matches!(traks.next().unwrap().unwrap().co_mut().unwrap(), StblCoMut::Co64(_));
+        assert_matches!(traks.next().unwrap().unwrap().co_mut().unwrap(), StblCoMut::Stco(_));
+    }
+}
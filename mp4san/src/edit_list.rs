@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use crate::parse::error::WhileParsingBox;
+use crate::parse::{BoxType, ElstEntry, MoovBox, ParseError};
+use crate::{Config, Error, LimitExceeded};
+
+//
+// public functions
+//
+
+/// Validate the edit list of each track in `moov` against the limits in `config` and the duration of the track's media.
+pub fn validate_edit_lists(moov: &mut MoovBox, config: &Config) -> Result<(), Error> {
+    let mut edit_list_durations = Vec::new();
+    for trak in moov.traks() {
+        let trak = trak?;
+        if trak.edts_mut()?.is_none() {
+            continue;
+        }
+        let media_duration = trak.mdia_mut()?.mdhd_mut()?.duration;
+        let Some(elst) = trak.elst_mut()? else {
+            continue;
+        };
+
+        let entry_count = elst.entries().len();
+        ensure_attach!(
+            entry_count as u64 <= u64::from(config.max_edit_list_entries),
+            ParseError::InvalidInput,
+            LimitExceeded(
+                "edit list entry count",
+                entry_count as u64,
+                config.max_edit_list_entries.into()
+            ),
+            WhileParsingBox(BoxType::ELST),
+        );
+
+        for entry in elst.entries() {
+            if entry.media_time == ElstEntry::EMPTY_EDIT_MEDIA_TIME {
+                continue;
+            }
+            let Ok(media_time) = u64::try_from(entry.media_time) else {
+                bail_attach!(
+                    ParseError::InvalidInput,
+                    format!("negative media time {}", entry.media_time),
+                    WhileParsingBox(BoxType::ELST),
+                );
+            };
+            ensure_attach!(
+                media_duration == 0 || media_time <= media_duration,
+                ParseError::InvalidInput,
+                format!("edit list media time {media_time} past end of media"),
+                WhileParsingBox(BoxType::ELST),
+            );
+        }
+
+        edit_list_durations.push(elst.total_segment_duration());
+    }
+    if edit_list_durations.is_empty() {
+        return Ok(());
+    }
+
+    // Segment durations are in units of the movie timescale.
+    let timescale = moov.mvhd_mut()?.timescale;
+    ensure_attach!(
+        timescale != 0,
+        ParseError::InvalidInput,
+        "zero movie timescale",
+        WhileParsingBox(BoxType::MVHD),
+    );
+    for duration in edit_list_durations {
+        let Some(duration) = duration else {
+            bail_attach!(
+                ParseError::InvalidInput,
+                "edit list duration overflow",
+                WhileParsingBox(BoxType::ELST)
+            );
+        };
+        let nanos = u128::from(duration % u64::from(timescale)) * 1_000_000_000 / u128::from(timescale);
+        let duration = Duration::new(duration / u64::from(timescale), nanos as u32);
+        ensure_attach!(
+            duration <= config.max_edit_list_duration,
+            ParseError::InvalidInput,
+            LimitExceeded("edit list duration", duration, config.max_edit_list_duration),
+            WhileParsingBox(BoxType::ELST),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+
+    use crate::util::test::mp4::TestMp4;
+    use crate::util::test::{init_logger, test_moov, test_mp4, test_trak};
+    use crate::{sanitize, sanitize_with_config};
+
+    use super::*;
+
+    fn test_elst_entry(segment_duration: u64, media_time: i64) -> ElstEntry {
+        ElstEntry { segment_duration, media_time, media_rate_integer: 1, media_rate_fraction: 0 }
+    }
+
+    fn test_edit_list_mp4(media_duration: u32, edit_list: Vec<ElstEntry>) -> TestMp4 {
+        let trak = test_trak().media_duration(media_duration).edit_list(edit_list).clone();
+        test_mp4().moov(test_moov().first_trak(trak).clone()).build()
+    }
+
+    #[test]
+    fn edit_list() {
+        init_logger();
+        let test = test_edit_list_mp4(3, vec![test_elst_entry(1, -1), test_elst_entry(2, 1)]);
+        test.sanitize_ok();
+        let config = Config::builder().strict_validation(true).build();
+        test.sanitize_ok_with_config(config);
+    }
+
+    #[test]
+    fn edit_list_too_many_entries() {
+        init_logger();
+        let test = test_edit_list_mp4(3, vec![test_elst_entry(1, 0); 3]);
+        let config = Config::builder().max_edit_list_entries(2).build();
+        assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Parse(err) => {
+            assert!(format!("{err:?}").contains("edit list entry count limit exceeded: 3 > 2"), "{err:?}");
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn edit_list_duration_too_long() {
+        init_logger();
+        let test = test_edit_list_mp4(3, vec![test_elst_entry(30, 0), test_elst_entry(31, 0)]);
+        let config = Config::builder()
+            .max_edit_list_duration(Duration::from_secs(60))
+            .build();
+        assert_matches!(sanitize_with_config(test.clone(), config).unwrap_err(), Error::Parse(err) => {
+            assert!(format!("{err:?}").contains("edit list duration limit exceeded"), "{err:?}");
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+
+        let test = test_edit_list_mp4(3, vec![test_elst_entry(u64::MAX, 0), test_elst_entry(1, 0)]);
+        assert_matches!(sanitize(test).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn edit_list_media_time_past_end() {
+        init_logger();
+        let test = test_edit_list_mp4(3, vec![test_elst_entry(1, 4)]);
+        assert_matches!(sanitize(test).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn edit_list_negative_media_time() {
+        init_logger();
+        let test = test_edit_list_mp4(3, vec![test_elst_entry(1, -2)]);
+        assert_matches!(sanitize(test).unwrap_err(), Error::Parse(err) => {
+            assert!(format!("{err:?}").contains("negative media time -2"), "{err:?}");
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn edit_list_unknown_media_duration() {
+        init_logger();
+        let test = test_edit_list_mp4(0, vec![test_elst_entry(1, 1024)]);
+        test.sanitize_ok();
+    }
+}
@@ -0,0 +1,175 @@
+use crate::parse::error::WhileParsingBox;
+use crate::parse::{BoxType, MoofBox, ParseError, TrexBox};
+use crate::{Error, InputSpan};
+
+//
+// public functions
+//
+
+/// Compute the spans of sample data referenced by the movie fragment `moof` at `moof_offset`, with the track defaults
+/// in `trexs`.
+///
+/// Movie fragments are passed through as media data, so track fragments of removed tracks can't be removed from them.
+/// Fragments containing any track fragment of a track in `dropped_track_ids` are rejected instead.
+pub fn fragment_sample_data_spans(
+    moof: &mut MoofBox,
+    moof_offset: u64,
+    trexs: &[TrexBox],
+    dropped_track_ids: &[u32],
+) -> Result<Vec<InputSpan>, Error> {
+    for traf in moof.trafs() {
+        let track_id = traf?.tfhd_mut()?.track_id;
+        ensure_attach!(
+            !dropped_track_ids.contains(&track_id),
+            ParseError::UnsupportedBoxLayout,
+            format!("track fragment of removed track {track_id}"),
+            WhileParsingBox(BoxType::MOOF),
+        );
+    }
+    Ok(moof.sample_data_spans(moof_offset, trexs)?)
+}
+
+/// Validate that the sample data of a movie fragment, as computed by [`fragment_sample_data_spans`], lies within the
+/// data of the mdat following it.
+pub fn validate_fragment_data(fragment_data: &[InputSpan], mdat_data: InputSpan) -> Result<(), Error> {
+    for span in fragment_data {
+        ensure_attach!(
+            mdat_data.offset <= span.offset && span.offset + span.len <= mdat_data.offset + mdat_data.len,
+            ParseError::InvalidInput,
+            "fragment sample data not within mdat",
+            WhileParsingBox(BoxType::MOOF),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use assert_matches::assert_matches;
+
+    use crate::parse::box_type::{MFRA, MOOF, MVEX};
+    use crate::parse::{HdlrBox, Mp4Value};
+    use crate::util::test::{
+        fragmented_config, sanitized_data, test_fragmented_mp4, test_free, test_ftyp, test_moov, test_trak,
+        write_test_fragment,
+    };
+    use crate::{sanitize, sanitize_to_writer_with_config, sanitize_with_config, Config};
+
+    use super::*;
+
+    #[test]
+    fn fragmented() {
+        let (data, fragments) = test_fragmented_mp4(0);
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), fragmented_config()).unwrap();
+        assert_eq!(sanitized.data, fragments);
+        assert_eq!(sanitized.metadata, None);
+    }
+
+    #[test]
+    fn fragmented_removed_track() {
+        let mut data = vec![];
+        test_ftyp().build().put_buf(&mut data);
+        test_moov()
+            .mvex(true)
+            .first_trak(test_trak().handler_type(HdlrBox::VIDEO).clone())
+            .add_trak(test_trak())
+            .build()
+            .put_buf(&mut data);
+        let fragment = write_test_fragment(&mut data, 1, b"abcdefg", 0);
+        let config = Config { keep_handler_types: Some(vec![HdlrBox::VIDEO]), ..fragmented_config() };
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), config.clone()).unwrap();
+        assert_eq!(sanitized.data, fragment);
+        assert_eq!(sanitized.media_info.unwrap().tracks.len(), 1);
+
+        write_test_fragment(&mut data, 2, b"hijk", 0);
+        assert_matches!(sanitize_with_config(io::Cursor::new(&data), config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBoxLayout);
+        });
+        sanitize_with_config(io::Cursor::new(&data), fragmented_config()).unwrap();
+    }
+
+    #[test]
+    fn fragmented_not_allowed() {
+        let (data, _) = test_fragmented_mp4(0);
+        assert_matches!(sanitize(io::Cursor::new(&data)).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBox(MOOF));
+        });
+    }
+
+    #[test]
+    fn fragmented_mfra() {
+        let (mut data, fragments) = test_fragmented_mp4(0);
+        test_free(MFRA, 16).put_buf(&mut data);
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), fragmented_config()).unwrap();
+        assert!(sanitized.metadata.is_some());
+        assert_eq!(sanitized.data, fragments);
+    }
+
+    #[test]
+    fn fragmented_mfra_to_writer() {
+        let (mut data, fragments) = test_fragmented_mp4(0);
+        let input_len = data.len() as u64;
+        test_free(MFRA, 16).put_buf(&mut data);
+        let mut output = vec![];
+        let sanitized =
+            sanitize_to_writer_with_config(io::Cursor::new(&data), &mut output, fragmented_config()).unwrap();
+        let metadata_len = sanitized.metadata.as_ref().unwrap().len() as u64;
+        assert_eq!(fragments.offset + fragments.len, input_len);
+        assert_eq!(output.len() as u64, metadata_len + fragments.len);
+        assert_eq!(output, sanitized_data(sanitized, &data));
+    }
+
+    #[test]
+    fn fragment_data_before_mdat() {
+        let (data, _) = test_fragmented_mp4(-1);
+        let err = sanitize_with_config(io::Cursor::new(&data), fragmented_config()).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn fragment_data_after_mdat() {
+        let (data, _) = test_fragmented_mp4(1);
+        let err = sanitize_with_config(io::Cursor::new(&data), fragmented_config()).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn moof_without_mdat() {
+        let (mut data, _) = test_fragmented_mp4(0);
+        data.truncate(data.len() - b"hijk".len() - 8);
+        let err = sanitize_with_config(io::Cursor::new(&data), fragmented_config()).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidBoxLayout);
+        });
+    }
+
+    #[test]
+    fn moof_without_mvex() {
+        let mut data = vec![];
+        test_ftyp().build().put_buf(&mut data);
+        test_moov().build().put_buf(&mut data);
+        write_test_fragment(&mut data, 1, b"abcdefg", 0);
+        let err = sanitize_with_config(io::Cursor::new(&data), fragmented_config()).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::MissingRequiredBox(MVEX));
+        });
+    }
+
+    #[test]
+    fn moof_before_moov() {
+        let mut data = vec![];
+        test_ftyp().build().put_buf(&mut data);
+        write_test_fragment(&mut data, 1, b"abcdefg", 0);
+        test_moov().mvex(true).build().put_buf(&mut data);
+        let err = sanitize_with_config(io::Cursor::new(&data), fragmented_config()).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidBoxLayout);
+        });
+    }
+}
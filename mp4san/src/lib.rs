@@ -6,23 +6,41 @@
 //!
 //! - Return all presentation metadata present in the input as a self-contained contiguous byte array.
 //! - Find and return a pointer to the span in the input containing the (contiguous) media data.
+//! - Return a [summary](MediaInfo) of the movie and its tracks, such as their durations, codecs, and video dimensions.
+//!
+//! The sanitizer can optionally [strip user metadata](Config::strip_user_metadata), such as location or device
+//! information, from the presentation metadata, to [keep only the tracks](Config::keep_handler_types) of given kinds,
+//! such as video and audio, and to [restrict the codecs](Config::allowed_sample_entries) used by each track, rejecting
+//! the input or dropping the tracks which use any other codec. H.264, H.265, and AAC decoder
+//! configurations can also optionally be [validated](Config::validate_decoder_configurations), and any media data not
+//! referenced by the sample tables of any track can optionally be [dropped](Config::compact_media_data).
 //!
 //! "Presentation" metadata means any metadata which is required by an MP4 player to play the file. "Self-contained and
 //! contiguous" means that the returned metadata can be concatenated with the media data to form a valid MP4 file.
 //!
 //! The original metadata may or may not need to be modified in order to perform these functions. In the case that the
 //! original metadata does not need to be modified, the returned [`SanitizedMetadata::metadata`] will be [`None`] to
-//! prevent needless data copying.
+//! prevent needless data copying. When modified, the sanitized output can optionally be
+//! [verified](Config::verify_output) by sanitizing it again.
+//!
+//! "Fragmented" MP4 files, which are mostly used for adaptive-bitrate streaming, are supported when enabled with
+//! [`Config::allow_fragmented`]. The movie fragments (`moof`) are then considered part of the media data, and the
+//! sample data referenced by each fragment is checked to lie within the media data box (`mdat`) following it.
 //!
 //! # Unsupported MP4 features
 //!
 //! The sanitizer does not currently support:
 //!
-//! - "Fragmented" MP4 files, which are mostly used for adaptive-bitrate streaming.
-//! - Discontiguous media data, i.e. media data (`mdat`) boxes interspersed with presentation metadata (`moov`).
-//! - Media data references (`dref`) pointing to separate files.
+//! - "Fragmented" MP4 files, unless [enabled](Config::allow_fragmented).
+//! - Discontiguous media data, i.e. media data (`mdat`) boxes interspersed with presentation metadata (`moov`) or other
+//!   boxes, unless [enabled](Config::allow_discontiguous_mdat).
+//! - Media data references (`dref`) pointing to separate files. Inputs containing them are rejected, unless
+//!   [replacement](Config::replace_external_data_references) with references to the same file is enabled.
 //! - Any similar format, e.g. Quicktime File Format (`mov`) or the legacy MP4 version 1, which does not contain the
-//!   [`isom` compatible brand](COMPATIBLE_BRAND) in its file type header (`ftyp`).
+//!   [`isom` compatible brand](COMPATIBLE_BRAND) in its file type header (`ftyp`). QuickTime files with the
+//!   [`qt  ` brand](QUICKTIME_BRAND) are supported when [enabled](Config::allow_quicktime), except for compressed
+//!   movie metadata (`cmov`). 3GPP files with one of the [3GPP brands](THREE_GPP_BRANDS) are supported when
+//!   [enabled](Config::allow_3gpp).
 //!
 //! # Usage
 //!
@@ -43,10 +61,22 @@
 //! # Ok::<(), mp4san::Error>(())
 //! ```
 //!
+//! When the input also implements [`Seek`], [`sanitize_to_writer`]/[`sanitize_async_to_writer`] can instead be used to
+//! write the complete sanitized output, i.e. the sanitized metadata followed by the media data copied from the input,
+//! to a [`Write`] or [`AsyncWrite`] output. If the metadata did not need to be modified, nothing is written, and the
+//! input can be used unchanged.
+//!
+//! Inputs supporting positional reads, such as files or memory maps, can implement [`ReadAt`] instead and be sanitized
+//! using [`sanitize_read_at`], which skips over media data without reading or seeking the input.
+//!
+//! With the `tokio` cargo feature enabled, `sanitize_tokio` can be used with [`tokio`](https://docs.rs/tokio) IO types
+//! such as `tokio::fs::File` instead, which are adapted to [`AsyncRead`] + [`AsyncSkip`] using `TokioSkipAdapter`.
+//!
 //! The [`parse`] module also contains a less stable and undocumented API which can be used to parse individual MP4 box
 //! types.
 //!
 //! [`Seek`]: std::io::Seek
+//! [`Write`]: std::io::Write
 
 // Used by the derive macros' generated code.
 extern crate self as mp4san;
@@ -54,24 +84,44 @@ extern crate self as mp4san;
 #[macro_use]
 extern crate mediasan_common;
 
+mod compact;
+mod data_reference;
+mod displace;
+mod edit_list;
 pub mod error;
+mod fragment;
+mod limits;
+mod media_info;
 pub mod parse;
+mod read_at;
+mod sample_ranges;
+mod track_filter;
 mod util;
+mod uuid_box;
+mod verify;
+mod writer;
 
-use std::io::Read;
+use std::io::{Read, Seek, Write};
+use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Duration;
 
+use bytes::{Bytes, BytesMut};
 use derive_builder::Builder;
 use derive_more::Display;
 use futures_util::io::BufReader;
-use futures_util::{pin_mut, AsyncBufReadExt, AsyncRead};
+use futures_util::{pin_mut, AsyncBufReadExt, AsyncRead, AsyncSeek, AsyncWrite};
 use mediasan_common::sync;
-use mediasan_common::util::{checked_add_signed, IoResultExt};
+use mediasan_common::util::IoResultExt;
 use mediasan_common::AsyncSkipExt;
 
-use crate::error::Report;
 use crate::parse::error::{MultipleBoxes, WhileParsingBox};
-use crate::parse::{BoxHeader, BoxType, FourCC, FtypBox, MoovBox, Mp4Box, Mp4Value, ParseError, StblCoMut};
+use crate::parse::{
+    BoxDepth, BoxHeader, BoxType, BoxUuid, FourCC, FtypBox, MoofBox, MoovBox, Mp4Box, Mp4Value, ParseError,
+    StrictValidationOptions, TrexBox,
+};
 
 //
 // public types
@@ -90,10 +140,334 @@ pub struct Config {
     /// The default is 1 GiB.
     #[builder(default = "1024 * 1024 * 1024")]
     pub max_metadata_size: u64,
+
+    /// Whether to allow "fragmented" MP4 files, which contain movie fragment boxes (`moof`).
+    ///
+    /// When enabled, the movie fragments, along with any segment type (`styp`) and segment index (`sidx`) boxes, are
+    /// treated as part of the media data. Each movie fragment is parsed, and the sample data it references is checked
+    /// to lie within the media data box (`mdat`) following it. Any movie fragment random access box (`mfra`) is
+    /// dropped.
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub allow_fragmented: bool,
+
+    /// Whether to allow media data boxes (`mdat`) which are not contiguous with each other in the input.
+    ///
+    /// When enabled, each contiguous span of media data is returned in the [`SanitizedMetadata`], to be concatenated in
+    /// order after the metadata, and the chunk offsets in the metadata are rewritten to point into the concatenated
+    /// media data. Any boxes in between the spans of media data are dropped.
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub allow_discontiguous_mdat: bool,
+
+    /// Whether to replace media data references (`dref` entries) pointing to separate files, rather than rejecting the
+    /// input.
+    ///
+    /// When enabled, each such data reference is replaced with one indicating that the media data is in the same file.
+    /// Any samples which referred to the separate file will then refer to the (likely unrelated) media data in the
+    /// sanitized file, but no separate file will ever be referenced by the sanitized metadata.
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub replace_external_data_references: bool,
+
+    /// Whether to allow QuickTime File Format (`mov`) files.
+    ///
+    /// When enabled, inputs with the [`qt  ` brand](QUICKTIME_BRAND) as their major or a compatible brand in their file
+    /// type header (`ftyp`) are accepted, and QuickTime `wide` atoms are treated as free space. Compressed movie
+    /// metadata (`cmov`) is not supported, and is always rejected.
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub allow_quicktime: bool,
+
+    /// Whether to allow 3GPP (`3gp`) and 3GPP2 (`3g2`) files.
+    ///
+    /// When enabled, inputs with one of the [3GPP brands](THREE_GPP_BRANDS) as their major or a compatible brand in
+    /// their file type header (`ftyp`) are accepted. The 3GPP asset boxes, such as the title (`titl`) or location
+    /// (`loci`), in the user data boxes (`udta`) of such inputs are parsed and validated, unless they are
+    /// [stripped](Self::strip_user_metadata), and are also accepted by [strict validation](Self::strict_validation).
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub allow_3gpp: bool,
+
+    /// Whether to remove user data (`udta`) and metadata (`meta`) boxes from the movie (`moov`) and each of its tracks
+    /// (`trak`).
+    ///
+    /// These boxes are not required to play the file, and commonly contain privacy-sensitive information such as the
+    /// location the file was recorded at and the make and model of the recording device.
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub strip_user_metadata: bool,
+
+    /// The `uuid` extension boxes to allow, with the action to take for each, or `None` to use the default handling.
+    ///
+    /// Extension boxes identified by a [`BoxUuid`] rather than a four-character code are commonly used to store
+    /// vendor-specific metadata, such as XMP metadata or camera telemetry. When set, the action for the UUID of each
+    /// `uuid` box at the top level of the input, or in the movie (`moov`) or any of its tracks (`trak`), is looked up
+    /// in the list, and any `uuid` box with a UUID not in the list is rejected. See [`UuidBoxAction`] for the available
+    /// actions.
+    ///
+    /// ```
+    /// # use mp4san::parse::BoxUuid;
+    /// # use mp4san::UuidBoxAction;
+    /// const XMP_UUID: BoxUuid = BoxUuid { value: 0xbe7acfcb_97a9_42e8_9c71_999491e3afacu128.to_be_bytes() };
+    ///
+    /// let config = mp4san::Config::builder()
+    ///     .uuid_boxes(vec![(XMP_UUID, UuidBoxAction::Drop)])
+    ///     .build();
+    /// ```
+    ///
+    /// When `None`, any `uuid` box at the top level of the input is rejected with [`ParseError::UnsupportedBox`], and
+    /// any within the movie is kept, unless [strict validation](Self::strict_validation) is enabled.
+    ///
+    /// The default is `None`.
+    #[builder(default, setter(into, strip_option))]
+    pub uuid_boxes: Option<Vec<(BoxUuid, UuidBoxAction)>>,
+
+    /// Whether to parse and validate every box within the movie (`moov`), rejecting any box not known to the parser.
+    ///
+    /// When enabled, only the boxes describing the structure of the movie and its tracks, such as track headers, edit
+    /// lists, media headers, handler references, and sample tables, are accepted. Other boxes, such as user data
+    /// (`udta`), cause the input to be rejected with [`ParseError::UnsupportedBox`], unless they are
+    /// [stripped](Self::strip_user_metadata) or contain only 3GPP asset boxes in an [allowed](Self::allow_3gpp) 3GPP
+    /// input. `uuid` extension boxes in the movie and its tracks are accepted when [kept](Self::uuid_boxes). The
+    /// contents of sample entries in the sample description box (`stsd`) are not validated.
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub strict_validation: bool,
+
+    /// The handler types of the tracks to keep, or `None` to keep every track.
+    ///
+    /// The handler type in the handler reference box (`hdlr`) of each track identifies the kind of media it contains,
+    /// such as [`vide`](parse::HdlrBox::VIDEO) for video or [`soun`](parse::HdlrBox::SOUND) for audio. When set, any
+    /// track with a handler type not in the list is removed, along with any references to it from the track reference
+    /// boxes (`tref`) of the remaining tracks. The sample data of removed tracks is not validated.
+    ///
+    /// Inputs in which every track would be removed are rejected with [`ParseError::MissingRequiredBox`]. As movie
+    /// fragments (`moof`) are not modified, [fragmented](Self::allow_fragmented) inputs containing track fragments
+    /// (`traf`) of removed tracks are rejected with [`ParseError::UnsupportedBoxLayout`].
+    ///
+    /// ```
+    /// # use mp4san::parse::HdlrBox;
+    /// let config = mp4san::Config::builder()
+    ///     .keep_handler_types(vec![HdlrBox::VIDEO, HdlrBox::SOUND])
+    ///     .build();
+    /// ```
+    ///
+    /// The default is `None`.
+    #[builder(default, setter(into, strip_option))]
+    pub keep_handler_types: Option<Vec<FourCC>>,
+
+    /// The sample entry types to allow in the sample description box (`stsd`) of each track, or `None` to allow any.
+    ///
+    /// The type of a sample entry identifies the codec used to encode the track's samples, such as
+    /// [`avc1`](parse::box_type::AVC1) for H.264 video or [`mp4a`](parse::box_type::MP4A) for MPEG-4 audio. When set,
+    /// any track with a sample entry whose type is not in the list causes the input to be rejected with
+    /// [`ParseError::UnsupportedBox`], unless [`drop_disallowed_tracks`](Self::drop_disallowed_tracks) is enabled.
+    ///
+    /// The default is `None`.
+    #[builder(default, setter(into, strip_option))]
+    pub allowed_sample_entries: Option<Vec<BoxType>>,
+
+    /// Whether to remove tracks with sample entries not in [`allowed_sample_entries`](Self::allowed_sample_entries),
+    /// rather than rejecting the input.
+    ///
+    /// Inputs in which every track would be removed are still rejected, as are fragmented inputs containing track
+    /// fragments of removed tracks, as with [`keep_handler_types`](Self::keep_handler_types).
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub drop_disallowed_tracks: bool,
+
+    /// How to handle encrypted tracks, i.e. tracks with protected sample entries such as `encv` or `enca`, and
+    /// protection system specific headers (`pssh`).
+    ///
+    /// Encrypted content is identified after any tracks are removed by
+    /// [`keep_handler_types`](Self::keep_handler_types) or [`allowed_sample_entries`](Self::allowed_sample_entries),
+    /// and is reported by [`TrackInfo::encrypted`] regardless of this setting. See [`EncryptedContent`] for the
+    /// available options.
+    ///
+    /// The default is [`EncryptedContent::Allow`].
+    #[builder(default)]
+    pub encrypted_content: EncryptedContent,
+
+    /// Whether to parse and validate the decoder configuration of each H.264 (`avc1`) and H.265 (`hvc1`/`hev1`) sample
+    /// entry, including the parameter sets (SPS, PPS, and VPS) it contains.
+    ///
+    /// When enabled, the profile, level, picture dimensions, and number of reference frames of each parameter set are
+    /// checked against the limits of the highest level defined by the codec specification, and any malformed or
+    /// out-of-range parameter set causes the input to be rejected with [`ParseError::InvalidInput`]. This can help
+    /// protect decoders which may be vulnerable to malformed parameter sets. Parameter sets contained in the samples
+    /// themselves are not validated.
+    ///
+    /// The elementary stream descriptor (`esds`) of each MPEG-4 audio (`mp4a`) sample entry is also parsed, and its
+    /// `AudioSpecificConfig` must describe an AAC stream, optionally with SBR or PS, with a valid sampling frequency
+    /// and a channel configuration of at most 7.1 channels.
+    ///
+    /// The decoder configuration of each 3GPP H.263 (`s263`) sample entry must specify a profile and level defined by
+    /// ITU-T H.263, and that of each AMR (`samr`) or AMR-WB (`sawb`) sample entry must contain at least one mode of the
+    /// codec and at most 15 frames per sample.
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub validate_decoder_configurations: bool,
+
+    /// Whether to drop any media data not referenced by the sample tables of any track.
+    ///
+    /// When enabled, the chunk offset (`stco`/`co64`), sample-to-chunk (`stsc`), and sample size (`stsz`) boxes of each
+    /// track are used to compute the byte ranges of media data containing samples. The sanitized metadata then ends
+    /// with the header of a new media data box (`mdat`), and only the referenced byte ranges are returned as the spans
+    /// of media data to be concatenated after it, with the chunk offsets rewritten to point into the new media data
+    /// box. Any unreferenced data, such as padding or hidden payloads between or around samples, is dropped.
+    ///
+    /// Fragmented MP4 files are not supported, and are rejected with [`ParseError::UnsupportedBoxLayout`].
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub compact_media_data: bool,
+
+    /// The maximum number of top-level boxes to allow in the input.
+    ///
+    /// Exceeding this limit, like any of the other resource limits, causes the input to be rejected with
+    /// [`ParseError::InvalidInput`].
+    ///
+    /// The default is 1048576 (2^20).
+    #[builder(default = "1 << 20")]
+    pub max_top_level_boxes: u64,
+
+    /// The maximum depth to allow of boxes containing child boxes within the movie (`moov`) or movie fragment (`moof`)
+    /// boxes, counting the `moov` or `moof` box itself as depth 1.
+    ///
+    /// The depth is tracked as boxes are parsed, so only boxes whose children are parsed, such as track (`trak`),
+    /// sample table (`stbl`), or sample description (`stsd`) boxes, are counted.
+    ///
+    /// The default is 16.
+    #[builder(default = "16")]
+    pub max_box_depth: u32,
+
+    /// The maximum number of tracks (`trak`) to allow in the movie.
+    ///
+    /// The default is 1024.
+    #[builder(default = "1024")]
+    pub max_track_count: u32,
+
+    /// The maximum total number of samples to allow in the sample tables of all tracks.
+    ///
+    /// The default is 268435456 (2^28).
+    #[builder(default = "1 << 28")]
+    pub max_sample_count: u64,
+
+    /// The maximum total number of chunk offset (`stco` or `co64`) entries to allow in the sample tables of all tracks.
+    ///
+    /// The default is 16777216 (2^24).
+    #[builder(default = "1 << 24")]
+    pub max_chunk_offset_entries: u64,
+
+    /// The maximum number of entries to allow in the edit list (`elst`) of each track.
+    ///
+    /// The edit list of each track is always parsed. Besides this limit and
+    /// [`max_edit_list_duration`](Self::max_edit_list_duration), any entry with a negative media time, other than one
+    /// indicating an empty edit, or with a media time past the end of the track's media is rejected with
+    /// [`ParseError::InvalidInput`]. Media times are not checked for tracks with an unknown media duration of zero, as
+    /// is typical for fragmented MP4 files.
+    ///
+    /// The default is 1024.
+    #[builder(default = "1024")]
+    pub max_edit_list_entries: u32,
+
+    /// The maximum total duration of the segments in the edit list (`elst`) of each track.
+    ///
+    /// The default is 7 days.
+    #[builder(default = "Duration::from_secs(7 * 24 * 60 * 60)")]
+    pub max_edit_list_duration: Duration,
+
+    /// A callback invoked with the sanitizer's [`Progress`] as it advances through each top-level box of the input.
+    ///
+    /// The callback is invoked synchronously from the sanitizer, and so should return quickly.
+    ///
+    /// The default is `None`.
+    #[builder(default, setter(custom))]
+    pub progress_callback: Option<ProgressCallback>,
+
+    /// A token which can be used to [cancel](CancellationToken::cancel) the sanitizer, causing it to return
+    /// [`Error::Cancelled`].
+    ///
+    /// The token is checked before processing each top-level box of the input, so cancellation takes effect as soon as
+    /// the sanitizer is finished reading the current box.
+    ///
+    /// The default is `None`.
+    #[builder(default, setter(strip_option))]
+    pub cancellation_token: Option<CancellationToken>,
+
+    /// Whether to verify the sanitized output by sanitizing it again.
+    ///
+    /// When enabled and the metadata was modified, the concatenation of the sanitized
+    /// [metadata](SanitizedMetadata::metadata) and [media data](SanitizedMetadata::data_spans) is sanitized again using
+    /// the same configuration, without reading the media data itself. The output must parse successfully, must not need
+    /// to be modified further, and must resolve the chunks of each track to the same media data as the input.
+    /// Otherwise, the input is rejected with [`ParseError::InvalidOutput`]. This guards against bugs in the sanitizer
+    /// which could otherwise produce broken output.
+    ///
+    /// The default is `false`.
+    #[builder(default)]
+    pub verify_output: bool,
+}
+
+/// How the sanitizer handles encrypted content, as set by [`Config::encrypted_content`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EncryptedContent {
+    /// Encrypted content is passed through without validating its protection boxes.
+    ///
+    /// When [strict validation](Config::strict_validation) is enabled, protection system specific headers (`pssh`) and
+    /// sample encryption boxes (`senc`, `saiz`, and `saio`) are rejected with [`ParseError::UnsupportedBox`], as they
+    /// are not parsed.
+    #[default]
+    Allow,
+
+    /// Inputs containing any encrypted content are rejected with [`ParseError::UnsupportedBox`].
+    Reject,
+
+    /// The protection boxes of encrypted content are parsed and validated.
+    ///
+    /// Each protected visual (`encv`) or audio (`enca`) sample entry must use one of the common encryption schemes
+    /// defined by ISO/IEC 23001-7 (`cenc`, `cbc1`, `cens`, or `cbcs`), and contain an original format box (`frma`),
+    /// a scheme type box (`schm`), and a track encryption box (`tenc`) with valid initialization vector sizes. The
+    /// protection system specific headers (`pssh`) in the movie must be well-formed, and any sample encryption (`senc`)
+    /// and auxiliary information (`saiz`/`saio`) boxes in a sample table must describe each of the track's samples.
+    /// Other protected sample entries, such as `enct` or `encs`, and other protection schemes are rejected with
+    /// [`ParseError::UnsupportedBox`].
+    ///
+    /// The offsets in auxiliary information offsets boxes (`saio`) are neither checked nor rewritten when the metadata
+    /// is modified, and the encrypted samples themselves are not decrypted or validated.
+    Validate,
+}
+
+/// The action taken by the sanitizer for a `uuid` extension box, as set by [`Config::uuid_boxes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UuidBoxAction {
+    /// The box is kept.
+    ///
+    /// Like other top-level boxes besides the file type (`ftyp`) and movie (`moov`) boxes, a `uuid` box at the top
+    /// level of the input is only kept when the metadata is not [modified](SanitizedMetadata::metadata), or when it
+    /// directly follows the [media data](SanitizedMetadata::data).
+    Keep,
+
+    /// The box is removed.
+    Drop,
+
+    /// Inputs containing the box are rejected with [`ParseError::UnsupportedBox`].
+    Reject,
 }
 
 /// Sanitized metadata returned by the sanitizer.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct SanitizedMetadata {
     /// The sanitized metadata from the given input, as a self-contained contiguous byte array.
     ///
@@ -105,24 +479,154 @@ pub struct SanitizedMetadata {
 
     /// A pointer to the span in the input containing the (contiguous) media data.
     pub data: InputSpan,
+
+    /// Pointers to any further spans in the input containing media data, which must be concatenated in order after the
+    /// [media data](Self::data).
+    ///
+    /// This is always empty unless [`Config::allow_discontiguous_mdat`] or [`Config::compact_media_data`] is enabled.
+    pub extra_data: Vec<InputSpan>,
+
+    /// A summary of the movie and its tracks, as described by the sanitized metadata.
+    ///
+    /// This is [`None`] if the movie is missing any box needed to describe it, such as a movie header (`mvhd`) or a
+    /// track's media header (`mdhd`), or if any such box could not be parsed.
+    pub media_info: Option<MediaInfo>,
+}
+
+/// A summary of a movie, as returned in [`SanitizedMetadata::media_info`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MediaInfo {
+    /// The timescale of the movie, in units per second, from the movie header (`mvhd`).
+    pub timescale: u32,
+
+    /// The duration of the movie, in units of its [`timescale`](Self::timescale), from the movie header (`mvhd`).
+    pub duration: u64,
+
+    /// A summary of each of the movie's tracks, in order.
+    pub tracks: Vec<TrackInfo>,
+}
+
+/// A summary of a track, as returned in [`MediaInfo::tracks`].
+///
+/// Fragmented MP4 files describe samples in their movie fragments rather than in the movie, so the sample count and
+/// durations of their tracks only cover the samples described in the movie, if any.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TrackInfo {
+    /// The ID of the track, from its track header (`tkhd`).
+    pub track_id: u32,
+
+    /// The handler type of the track, such as `vide` for video or `soun` for audio, from its handler reference box
+    /// (`hdlr`).
+    pub handler_type: FourCC,
+
+    /// The types of the sample entries in the track's sample description box (`stsd`), identifying the codecs used by
+    /// the track, such as [`avc1`](parse::box_type::AVC1) or [`mp4a`](parse::box_type::MP4A).
+    pub sample_entry_types: Vec<BoxType>,
+
+    /// The timescale of the track's media, in units per second, from its media header (`mdhd`).
+    pub timescale: u32,
+
+    /// The duration of the track's media, in units of its [`timescale`](Self::timescale), from its media header
+    /// (`mdhd`).
+    pub duration: u64,
+
+    /// The number of samples in the track, from its sample size box (`stsz`).
+    pub sample_count: u32,
+
+    /// The visual presentation width of the track in pixels, from its track header (`tkhd`), or zero for non-visual
+    /// tracks.
+    pub width: u32,
+
+    /// The visual presentation height of the track in pixels, from its track header (`tkhd`), or zero for non-visual
+    /// tracks.
+    pub height: u32,
+
+    /// Whether the track is encrypted, i.e. any of its sample entries is a protected sample entry, such as `encv` or
+    /// `enca`.
+    ///
+    /// The [`sample_entry_types`](Self::sample_entry_types) of an encrypted track are those of the protected sample
+    /// entries, not of the original formats they protect.
+    pub encrypted: bool,
+}
+
+/// The progress of the sanitizer through its input, as reported to [`Config::progress_callback`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of bytes of the input processed so far, i.e. the offset in the input of the current box.
+    pub bytes_processed: u64,
+
+    /// The type of the current top-level box.
+    pub box_type: BoxType,
+}
+
+/// A callback invoked with the sanitizer's [`Progress`], as set with [`ConfigBuilder::progress_callback`].
+///
+/// The callback need not be [`RefUnwindSafe`](std::panic::RefUnwindSafe), but is asserted to be so that [`Config`] is.
+pub struct ProgressCallback(AssertUnwindSafe<Arc<dyn Fn(Progress) + Send + Sync>>);
+
+/// A token which can be used to cancel the sanitizer from another thread or task.
+///
+/// Once [cancelled](Self::cancel), a sanitizer using the token through [`Config::cancellation_token`] stops before
+/// processing its next top-level box and returns [`Error::Cancelled`].
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
 }
 
-pub use mediasan_common::{AsyncSkip, InputSpan, SeekSkipAdapter, Skip};
+#[cfg(feature = "tokio")]
+pub use mediasan_common::TokioSkipAdapter;
+pub use mediasan_common::{AsyncSkip, InputSpan, ReadAt, ReadAtAdapter, SeekSkipAdapter, Skip};
 
 /// The ISO Base Media File Format "compatble brand" recognized by the sanitizer.
 ///
 /// This compatible brand must be present in the input's file type header (`ftyp`) in order to be parsed by the
-/// sanitizer.
+/// sanitizer, unless the input is a QuickTime file and [`Config::allow_quicktime`] is enabled, or a 3GPP file and
+/// [`Config::allow_3gpp`] is enabled.
 pub const COMPATIBLE_BRAND: FourCC = FourCC { value: *b"isom" };
 
+/// The QuickTime File Format brand recognized by the sanitizer when [`Config::allow_quicktime`] is enabled.
+pub const QUICKTIME_BRAND: FourCC = FourCC { value: *b"qt  " };
+
+/// The 3GPP and 3GPP2 file format brands recognized by the sanitizer when [`Config::allow_3gpp`] is enabled.
+pub const THREE_GPP_BRANDS: &[FourCC] = &[
+    FourCC { value: *b"3gp4" },
+    FourCC { value: *b"3gp5" },
+    FourCC { value: *b"3gp6" },
+    FourCC { value: *b"3gp7" },
+    FourCC { value: *b"3gp8" },
+    FourCC { value: *b"3gp9" },
+    FourCC { value: *b"3g2a" },
+    FourCC { value: *b"3g2b" },
+    FourCC { value: *b"3g2c" },
+];
+
 //
 // private types
 //
 
+/// The result of sanitizing an input, along with what's needed to verify the sanitized output.
+pub(crate) struct SanitizedInput {
+    sanitized: SanitizedMetadata,
+
+    /// The spans in the input of the non-empty chunks of each track, in order.
+    chunks: Vec<InputSpan>,
+
+    /// The header of each box read from the input, or the data of each movie fragment, by offset in the input.
+    ///
+    /// This is only recorded when [`Config::verify_output`] is enabled.
+    read_boxes: Vec<(u64, Bytes)>,
+}
+
 #[derive(Clone, Copy, Debug, Display)]
 #[display(fmt = "box data too large: {} > {}", _0, _1)]
 struct BoxDataTooLarge(u64, u64);
 
+#[derive(Clone, Copy, Debug, Display)]
+#[display(fmt = "{} limit exceeded: {:?} > {:?}", _0, _1, _2)]
+struct LimitExceeded<T>(&'static str, T, T);
+
 const MAX_FTYP_SIZE: u64 = 1024;
 
 //
@@ -174,6 +678,47 @@ pub fn sanitize_with_config<R: Read + Skip + Unpin>(input: R, config: Config) ->
     sync::sanitize(input, |input| sanitize_async_with_config(input, config))
 }
 
+/// Sanitize an MP4 input supporting positional reads, with the default [`Config`].
+///
+/// The `input` must implement [`ReadAt`], such as a [`File`](std::fs::File) or a byte slice. Unlike with [`sanitize`],
+/// skipping over the media data to read metadata following it, such as a movie box (`moov`) at the end of the input,
+/// doesn't require a forward [skip](Skip::skip) of the input.
+///
+/// The header of each top-level box is first read at its offset, and then the movie box (`moov`), along with any other
+/// box whose data is parsed, is read at its offset with a single read. The media data is never read.
+///
+/// # Examples
+///
+/// ```
+/// # use mp4san::sanitize_read_at;
+/// # use mp4san_test::{example_ftyp, example_mdat, example_moov};
+/// #
+/// let example_input = [example_ftyp(), example_mdat(), example_moov()].concat();
+///
+/// let sanitized = sanitize_read_at(&example_input[..])?;
+///
+/// assert_eq!(sanitized.metadata, Some([example_ftyp(), example_moov()].concat()));
+/// # Ok::<(), mp4san::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs, an [`Error`] is returned.
+pub fn sanitize_read_at<R: ReadAt + Unpin>(input: R) -> Result<SanitizedMetadata, Error> {
+    sanitize_read_at_with_config(input, Config::default())
+}
+
+/// Sanitize an MP4 input supporting positional reads, with the given [`Config`].
+///
+/// See [`sanitize_read_at`] for details.
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs, an [`Error`] is returned.
+pub fn sanitize_read_at_with_config<R: ReadAt + Unpin>(input: R, config: Config) -> Result<SanitizedMetadata, Error> {
+    read_at::sanitize_with_config(input, config)
+}
+
 /// Sanitize an MP4 input asynchronously, with the default [`Config`].
 ///
 /// The `input` must implement [`AsyncRead`] + [`AsyncSkip`], where [`AsyncSkip`] represents a subset of the
@@ -249,32 +794,121 @@ pub async fn sanitize_async_with_config<R: AsyncRead + AsyncSkip>(
     input: R,
     config: Config,
 ) -> Result<SanitizedMetadata, Error> {
+    let sanitized = sanitize_input(input, &config).await?;
+    if config.verify_output {
+        verify::verify_output(&sanitized, &config).await?;
+    }
+    Ok(sanitized.sanitized)
+}
+
+/// Sanitize an MP4 input asynchronously from a [`tokio`] IO type, with the default [`Config`].
+///
+/// The `input` must implement [`tokio::io::AsyncRead`] + [`tokio::io::AsyncSeek`], such as [`tokio::fs::File`] or
+/// [`tokio::io::BufReader`]. It is adapted to [`AsyncRead`] + [`AsyncSkip`] using [`TokioSkipAdapter`].
+///
+/// # Examples
+///
+/// ```
+/// # use mp4san::sanitize_tokio;
+/// # use mp4san_test::{example_ftyp, example_mdat, example_moov};
+/// #
+/// # fn main() -> Result<(), mp4san::Error> {
+/// #     futures_util::FutureExt::now_or_never(run()).unwrap()
+/// # }
+/// #
+/// # async fn run() -> Result<(), mp4san::Error> {
+/// let example_input = [example_ftyp(), example_mdat(), example_moov()].concat();
+///
+/// let sanitized = sanitize_tokio(std::io::Cursor::new(example_input)).await?;
+///
+/// assert_eq!(sanitized.metadata, Some([example_ftyp(), example_moov()].concat()));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs, an [`Error`] is returned.
+///
+/// [`tokio::fs::File`]: https://docs.rs/tokio/latest/tokio/fs/struct.File.html
+/// [`tokio::io::BufReader`]: https://docs.rs/tokio/latest/tokio/io/struct.BufReader.html
+#[cfg(feature = "tokio")]
+pub async fn sanitize_tokio<R>(input: R) -> Result<SanitizedMetadata, Error>
+where
+    R: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin,
+{
+    sanitize_tokio_with_config(input, Config::default()).await
+}
+
+/// Sanitize an MP4 input asynchronously from a [`tokio`] IO type, with the given [`Config`].
+///
+/// See [`sanitize_tokio`] for details.
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs, an [`Error`] is returned.
+#[cfg(feature = "tokio")]
+pub async fn sanitize_tokio_with_config<R>(input: R, config: Config) -> Result<SanitizedMetadata, Error>
+where
+    R: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin,
+{
+    sanitize_async_with_config(TokioSkipAdapter::new(input), config).await
+}
+
+/// Sanitize an MP4 input, returning along with the [`SanitizedMetadata`] what's needed to verify the sanitized output.
+pub(crate) async fn sanitize_input<R: AsyncRead + AsyncSkip>(
+    input: R,
+    config: &Config,
+) -> Result<SanitizedInput, Error> {
     let reader = BufReader::with_capacity(BoxHeader::MAX_SIZE as usize, input);
     pin_mut!(reader);
 
     let mut ftyp: Option<Mp4Box<FtypBox>> = None;
+    let mut is_3gpp = false;
     let mut moov: Option<Mp4Box<MoovBox>> = None;
-    let mut data: Option<InputSpan> = None;
+    let mut data: Vec<InputSpan> = Vec::new();
     let mut moov_offset = None;
+    let mut moov_modified = false;
+    let mut dropped_track_ids = Vec::new();
+    let mut top_level_boxes_dropped = false;
+    let mut trexs: Option<Vec<TrexBox>> = None;
+    let mut fragment_data: Option<Vec<InputSpan>> = None;
+    let mut fragment_base_data_offsets = false;
+    let mut read_boxes = Vec::new();
+    let mut top_level_box_count = 0;
 
     while !reader.as_mut().fill_buf().await?.is_empty() {
+        ensure_not_cancelled(config)?;
         let start_pos = reader.as_mut().stream_position().await?;
 
+        top_level_box_count += 1;
+        ensure_attach!(
+            top_level_box_count <= config.max_top_level_boxes,
+            ParseError::InvalidInput,
+            LimitExceeded("top-level box count", top_level_box_count, config.max_top_level_boxes),
+        );
+
         let header = BoxHeader::read(&mut reader)
             .await
             .map_eof(|_| Error::Parse(report_attach!(ParseError::TruncatedBox, "while parsing box header")))?;
+        if let Some(progress_callback) = &config.progress_callback {
+            progress_callback.call(Progress { bytes_processed: start_pos, box_type: header.box_type() });
+        }
+        if config.verify_output {
+            let mut header_bytes = BytesMut::new();
+            header.put_buf(&mut header_bytes);
+            read_boxes.push((start_pos, header_bytes.freeze()));
+        }
 
         match header.box_type() {
-            name @ (BoxType::FREE | BoxType::SKIP) => {
+            name @ (BoxType::FREE | BoxType::SKIP | BoxType::WIDE)
+                if name != BoxType::WIDE || config.allow_quicktime =>
+            {
                 let box_size = skip_box(reader.as_mut(), &header).await? + header.encoded_len();
                 log::info!("{name} @ 0x{start_pos:08x}: {box_size} bytes");
 
                 // Try to extend any already accumulated data in case there's more mdat boxes to come.
-                if let Some(data) = &mut data {
-                    if data.offset + data.len == start_pos {
-                        data.len += box_size;
-                    }
-                }
+                extend_contiguous_data(&mut data, start_pos, box_size);
             }
 
             BoxType::FTYP => {
@@ -289,8 +923,15 @@ pub async fn sanitize_async_with_config<R: AsyncRead + AsyncSkip>(
                 let FtypBox { major_brand, minor_version, .. } = ftyp_data;
                 log::info!("ftyp @ 0x{start_pos:08x}: {major_brand} version {minor_version}, {compatible_brand_count} compatible brands");
 
+                let is_quicktime = ftyp_data.major_brand == QUICKTIME_BRAND
+                    || ftyp_data.compatible_brands().any(|b| b == QUICKTIME_BRAND);
+                is_3gpp = config.allow_3gpp
+                    && (THREE_GPP_BRANDS.contains(&ftyp_data.major_brand)
+                        || ftyp_data.compatible_brands().any(|b| THREE_GPP_BRANDS.contains(&b)));
                 ensure_attach!(
-                    ftyp_data.compatible_brands().any(|b| b == COMPATIBLE_BRAND),
+                    ftyp_data.compatible_brands().any(|b| b == COMPATIBLE_BRAND)
+                        || (config.allow_quicktime && is_quicktime)
+                        || is_3gpp,
                     ParseError::UnsupportedFormat(ftyp_data.major_brand)
                 );
 
@@ -309,44 +950,190 @@ pub async fn sanitize_async_with_config<R: AsyncRead + AsyncSkip>(
                 let box_size = skip_box(reader.as_mut(), &header).await? + header.encoded_len();
                 log::info!("mdat @ 0x{start_pos:08x}: {box_size} bytes");
 
-                if let Some(data) = &mut data {
-                    // Try to extend already accumulated data.
-                    ensure_attach!(
-                        data.offset + data.len == start_pos,
-                        ParseError::UnsupportedBoxLayout,
-                        "discontiguous mdat boxes",
-                    );
-                    data.len += box_size;
-                } else {
-                    data = Some(InputSpan { offset: start_pos, len: box_size });
+                if let Some(fragment_data) = fragment_data.take() {
+                    let mdat_data =
+                        InputSpan { offset: start_pos + header.encoded_len(), len: box_size - header.encoded_len() };
+                    fragment::validate_fragment_data(&fragment_data, mdat_data)?;
                 }
+
+                extend_data(&mut data, start_pos, box_size, config)?;
             }
 
             BoxType::MOOV => {
                 let mut read_moov = Mp4Box::read_data(reader.as_mut(), header, config.max_metadata_size).await?;
+                let moov_data: &mut MoovBox = read_moov
+                    .data
+                    .parse_at_depth(BoxDepth::top_level(config.max_box_depth))?;
+                let (trak_count, chunk_count, sample_count) = limits::validate_moov_limits(moov_data, config)?;
+
+                log::info!("moov @ 0x{start_pos:08x}: {trak_count} traks {chunk_count} chunks {sample_count} samples");
+
+                if config.strip_user_metadata && moov_data.strip_user_metadata()? {
+                    log::info!("moov @ 0x{start_pos:08x}: stripped user metadata");
+                    moov_modified = true;
+                }
+
+                if let Some(uuid_boxes) = &config.uuid_boxes {
+                    let removed_count =
+                        moov_data.retain_uuid_boxes(|uuid| uuid_box::retain_uuid_box(uuid_boxes, uuid))?;
+                    if removed_count != 0 {
+                        log::info!("moov @ 0x{start_pos:08x}: removed {removed_count} uuid boxes");
+                        moov_modified = true;
+                    }
+                }
+
+                if let Some(keep_handler_types) = &config.keep_handler_types {
+                    let removed_track_ids = track_filter::retain_handler_types(moov_data, keep_handler_types)?;
+                    if !removed_track_ids.is_empty() {
+                        log::info!(
+                            "moov @ 0x{start_pos:08x}: removed tracks {removed_track_ids:?} with other handler types"
+                        );
+                        dropped_track_ids.extend(removed_track_ids);
+                        moov_modified = true;
+                    }
+                }
+
+                if let Some(allowed_sample_entries) = &config.allowed_sample_entries {
+                    let removed_track_ids = track_filter::retain_allowed_sample_entries(
+                        moov_data,
+                        allowed_sample_entries,
+                        config.drop_disallowed_tracks,
+                    )?;
+                    if !removed_track_ids.is_empty() {
+                        log::info!("moov @ 0x{start_pos:08x}: removed tracks {removed_track_ids:?} with disallowed sample entries");
+                        dropped_track_ids.extend(removed_track_ids);
+                        moov_modified = true;
+                    }
+                }
+
+                match config.encrypted_content {
+                    EncryptedContent::Allow => {}
+                    EncryptedContent::Reject => {
+                        if let Some(protected_box_type) = moov_data.protected_box_type()? {
+                            bail_attach!(
+                                ParseError::UnsupportedBox(protected_box_type),
+                                "encrypted content not allowed",
+                                WhileParsingBox(BoxType::MOOV),
+                            );
+                        }
+                    }
+                    EncryptedContent::Validate => moov_data.validate_common_encryption()?,
+                }
+
+                edit_list::validate_edit_lists(moov_data, config)?;
+
+                if is_3gpp {
+                    moov_data.validate_3gpp_assets()?;
+                }
+
+                if config.strict_validation {
+                    moov_data.validate_strict_with(StrictValidationOptions {
+                        allow_3gpp_assets: is_3gpp,
+                        allow_common_encryption: config.encrypted_content == EncryptedContent::Validate,
+                        allow_uuid_boxes: config.uuid_boxes.is_some(),
+                    })?;
+                }
+
+                if config.validate_decoder_configurations {
+                    for trak in moov_data.traks() {
+                        let stsd = trak?.mdia_mut()?.minf_mut()?.stbl_mut()?.stsd_mut()?;
+                        stsd.validate_decoder_configurations()?;
+                    }
+                }
+
+                if data_reference::validate_data_references(moov_data, config.replace_external_data_references)? {
+                    log::info!("moov @ 0x{start_pos:08x}: replaced external data references");
+                    moov_modified = true;
+                }
 
-                let moov_data: &mut MoovBox = read_moov.data.parse()?;
-                let trak_chunk_counts = moov_data
-                    .traks()
-                    .map(|trak| Ok::<_, Report<_>>(trak?.co_mut()?.entry_count()));
-                let chunk_count = trak_chunk_counts.reduce(|a, b| Ok(a? + b?)).unwrap_or(Ok(0))?;
-                let trak_count = moov_data.traks().count();
+                if config.allow_fragmented {
+                    if let Some(mvex) = moov_data.mvex_mut()? {
+                        trexs = Some(mvex.trexs().map(|trex| trex.cloned()).collect::<Result<_, _>>()?);
+                    }
+                }
 
-                log::info!("moov @ 0x{start_pos:08x}: {trak_count} traks {chunk_count} chunks");
                 moov = Some(read_moov);
                 moov_offset = Some(start_pos);
             }
 
+            BoxType::MOOF if config.allow_fragmented => {
+                ensure_attach!(moov.is_some(), ParseError::InvalidBoxLayout, "moof before moov");
+                ensure_attach!(
+                    !config.compact_media_data,
+                    ParseError::UnsupportedBoxLayout,
+                    "media data compaction of fragmented mp4",
+                );
+                ensure_attach!(
+                    fragment_data.is_none(),
+                    ParseError::InvalidBoxLayout,
+                    "moof not followed by mdat",
+                );
+                let Some(trexs) = &trexs else {
+                    bail_attach!(
+                        ParseError::MissingRequiredBox(BoxType::MVEX),
+                        WhileParsingBox(BoxType::MOOV),
+                    );
+                };
+
+                let mut read_moof = Mp4Box::read_data(reader.as_mut(), header, config.max_metadata_size).await?;
+                let box_size = header.encoded_len() + read_moof.data.encoded_len();
+                if config.verify_output {
+                    let mut moof_bytes = BytesMut::new();
+                    read_moof.data.put_buf(&mut moof_bytes);
+                    read_boxes.push((start_pos + header.encoded_len(), moof_bytes.freeze()));
+                }
+                let moof_data: &mut MoofBox = read_moof
+                    .data
+                    .parse_at_depth(BoxDepth::top_level(config.max_box_depth))?;
+                let sequence_number = moof_data.mfhd_mut()?.sequence_number;
+                let spans = fragment::fragment_sample_data_spans(moof_data, start_pos, trexs, &dropped_track_ids)?;
+                fragment_base_data_offsets |= moof_data.has_base_data_offset()?;
+
+                let span_count = spans.len();
+                log::info!("moof @ 0x{start_pos:08x}: {box_size} bytes, sequence number {sequence_number}, {span_count} sample data spans");
+
+                if !spans.is_empty() {
+                    fragment_data = Some(spans);
+                }
+                extend_data(&mut data, start_pos, box_size, config)?;
+            }
+
+            name @ (BoxType::STYP | BoxType::SIDX) if config.allow_fragmented => {
+                let box_size = skip_box(reader.as_mut(), &header).await? + header.encoded_len();
+                log::info!("{name} @ 0x{start_pos:08x}: {box_size} bytes");
+
+                extend_data(&mut data, start_pos, box_size, config)?;
+            }
+
+            name @ BoxType::MFRA if config.allow_fragmented => {
+                let box_size = skip_box(reader.as_mut(), &header).await? + header.encoded_len();
+                log::info!("{name} @ 0x{start_pos:08x}: {box_size} bytes; dropping");
+                top_level_boxes_dropped = true;
+            }
+
             name @ (BoxType::META | BoxType::MECO) => {
                 let box_size = skip_box(reader.as_mut(), &header).await? + header.encoded_len();
                 log::info!("{name} @ 0x{start_pos:08x}: {box_size} bytes");
 
                 // Try to extend any already accumulated data in case there's more mdat boxes to come.
-                if let Some(data) = &mut data {
-                    if data.offset + data.len == start_pos {
-                        data.len += box_size;
-                    }
+                extend_contiguous_data(&mut data, start_pos, box_size);
+            }
+
+            name @ BoxType::Uuid(uuid) => {
+                let box_size = skip_box(reader.as_mut(), &header).await? + header.encoded_len();
+                let Some(uuid_boxes) = &config.uuid_boxes else {
+                    log::info!("{name} @ 0x{start_pos:08x}: {box_size} bytes");
+                    bail_attach!(ParseError::UnsupportedBox(name));
+                };
+                if !uuid_box::retain_uuid_box(uuid_boxes, uuid)? {
+                    log::info!("{name} @ 0x{start_pos:08x}: {box_size} bytes; dropping");
+                    top_level_boxes_dropped = true;
+                    continue;
                 }
+                log::info!("{name} @ 0x{start_pos:08x}: {box_size} bytes");
+
+                // Try to extend any already accumulated data in case there's more mdat boxes to come.
+                extend_contiguous_data(&mut data, start_pos, box_size);
             }
 
             name => {
@@ -360,18 +1147,40 @@ pub async fn sanitize_async_with_config<R: AsyncRead + AsyncSkip>(
     let Some(ftyp) = ftyp else {
         bail_attach!(ParseError::MissingRequiredBox(BoxType::FTYP));
     };
-    let (Some(moov), Some(moov_offset)) = (moov, moov_offset) else {
+    let (Some(mut moov), Some(moov_offset)) = (moov, moov_offset) else {
         bail_attach!(ParseError::MissingRequiredBox(BoxType::MOOV));
     };
-    let Some(data) = data else {
+    let Some(&first_data) = data.first() else {
         bail_attach!(ParseError::MissingRequiredBox(BoxType::MDAT));
     };
-
-    // Return early if there's nothing to sanitize. Since the only thing the sanitizer does currently is move the moov
-    // to before the mdat to make the mp4 streamable, return if we don't need to do that.
-    if moov_offset < data.offset {
+    ensure_attach!(
+        fragment_data.is_none(),
+        ParseError::InvalidBoxLayout,
+        "moof not followed by mdat",
+    );
+
+    let chunks = sample_ranges::validate_sample_ranges(moov.data.parse()?, &data)?;
+
+    let media_info = match media_info::media_info(moov.data.parse()?) {
+        Ok(media_info) => Some(media_info),
+        Err(err) => {
+            log::info!("media info unavailable: {err}");
+            None
+        }
+    };
+
+    if config.compact_media_data {
+        let sanitized = compact::compact_data(ftyp, moov, &data, media_info)?;
+        return Ok(SanitizedInput { sanitized, chunks, read_boxes });
+    }
+
+    // Return early if there's nothing to sanitize. Since the only things the sanitizer does currently are to move the
+    // moov to before the mdat to make the mp4 streamable and to modify the moov itself, return if we don't need to do
+    // either, or to merge discontiguous mdat boxes, or to drop top-level boxes such as uuid or mfra boxes.
+    if moov_offset < first_data.offset && data.len() == 1 && !moov_modified && !top_level_boxes_dropped {
         log::info!("metadata: nothing to sanitize");
-        return Ok(SanitizedMetadata { metadata: None, data });
+        let sanitized = SanitizedMetadata { metadata: None, data: first_data, extra_data: vec![], media_info };
+        return Ok(SanitizedInput { sanitized, chunks, read_boxes });
     }
 
     // Make sure none of the metadata boxes use BoxSize::UntilEof, as we want the caller to be able to concatenate movie
@@ -380,54 +1189,41 @@ pub async fn sanitize_async_with_config<R: AsyncRead + AsyncSkip>(
     let mut moov = Mp4Box::with_data(moov.data)?;
 
     // Add a free box to pad, if one will fit, if the mdat box would move backward. If one won't fit, or if the mdat box
-    // would move forward, adjust mdat offsets in stco/co64 the amount it was displaced.
-    let metadata_len = ftyp.encoded_len() + moov.encoded_len();
-    let mut pad_size = 0;
+    // would move forward, adjust mdat offsets in stco/co64 the amount it was displaced. Any further spans of media data
+    // are concatenated after the first, each being displaced by a different amount.
+    //
+    // If a displaced chunk offset no longer fits in an stco box, the moov grows when the stco is converted to a co64,
+    // displacing the media data further, so this is repeated until no more conversions are needed.
     const PAD_HEADER_SIZE: u64 = BoxHeader::with_u32_data_size(BoxType::FREE, 0).encoded_len();
     const MAX_PAD_SIZE: u64 = u32::MAX as u64 - PAD_HEADER_SIZE;
-    match data.offset.checked_sub(metadata_len) {
-        Some(0) => {
-            log::info!("metadata: 0x{metadata_len:08x} bytes");
-        }
-        Some(size @ PAD_HEADER_SIZE..=MAX_PAD_SIZE) => {
-            pad_size = size;
-            log::info!("metadata: 0x{metadata_len:08x} bytes; adding padding of 0x{pad_size:08x} bytes");
-        }
-        mdat_backward_displacement => {
-            let mdat_displacement = match mdat_backward_displacement {
-                Some(mdat_backward_displacement) => {
-                    mdat_backward_displacement.try_into().ok().and_then(i32::checked_neg)
-                }
-                None => metadata_len.checked_sub(data.offset).unwrap().try_into().ok(),
-            };
-            let mdat_displacement: i32 = mdat_displacement
-                .ok_or_else(|| report_attach!(ParseError::UnsupportedBoxLayout, "mdat displaced too far"))?;
-
-            log::info!("metadata: 0x{metadata_len:08x} bytes; displacing chunk offsets by 0x{mdat_displacement:08x}");
-
-            for trak in &mut moov.data.parse()?.traks() {
-                let co = trak?.co_mut()?;
-                if let StblCoMut::Stco(stco) = co {
-                    for mut entry in &mut stco.entries_mut() {
-                        let value = entry.get().unwrap_or_else(|_| unreachable!());
-                        entry.set(
-                            checked_add_signed(value, mdat_displacement).ok_or_else(|| {
-                                report_attach!(ParseError::InvalidInput, "chunk offset not within mdat")
-                            })?,
-                        );
-                    }
-                } else if let StblCoMut::Co64(co64) = co {
-                    for mut entry in &mut co64.entries_mut() {
-                        let value = entry.get().unwrap_or_else(|_| unreachable!());
-                        entry.set(
-                            checked_add_signed(value, mdat_displacement.into()).ok_or_else(|| {
-                                report_attach!(ParseError::InvalidInput, "chunk offset not within mdat")
-                            })?,
-                        );
-                    }
-                }
-            }
+    let (metadata_len, pad_size, data_displacements) = loop {
+        let metadata_len = ftyp.encoded_len() + moov.encoded_len();
+        let pad_size = first_data
+            .offset
+            .checked_sub(metadata_len)
+            .filter(|size| (PAD_HEADER_SIZE..=MAX_PAD_SIZE).contains(size))
+            .unwrap_or_default();
+        let data_displacements = displace::data_displacements(&data, metadata_len + pad_size)?;
+        if !displace::upgrade_displaced_chunk_offsets(moov.data.parse()?, &data_displacements)? {
+            break (metadata_len, pad_size, data_displacements);
         }
+    };
+    if pad_size != 0 {
+        log::info!("metadata: 0x{metadata_len:08x} bytes; adding padding of 0x{pad_size:08x} bytes");
+    } else {
+        log::info!("metadata: 0x{metadata_len:08x} bytes");
+    }
+
+    if data_displacements
+        .iter()
+        .any(|&(_, mdat_displacement)| mdat_displacement != 0)
+    {
+        ensure_attach!(
+            !fragment_base_data_offsets,
+            ParseError::UnsupportedBoxLayout,
+            "moof with base data offset displaced",
+        );
+        displace::displace_chunk_offsets(moov.data.parse()?, &data_displacements)?;
     }
 
     let mut metadata = Vec::with_capacity((metadata_len + pad_size) as usize);
@@ -439,7 +1235,109 @@ pub async fn sanitize_async_with_config<R: AsyncRead + AsyncSkip>(
         metadata.resize((metadata_len + pad_size) as usize, 0);
     }
 
-    Ok(SanitizedMetadata { metadata: Some(metadata), data })
+    let extra_data = data[1..].to_vec();
+    let sanitized = SanitizedMetadata { metadata: Some(metadata), data: first_data, extra_data, media_info };
+    Ok(SanitizedInput { sanitized, chunks, read_boxes })
+}
+
+/// Sanitize an MP4 input, with the default [`Config`], writing the sanitized output to `output`.
+///
+/// See [`sanitize_to_writer_with_config`] for details.
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs while reading the input or writing the output, an [`Error`] is
+/// returned.
+pub fn sanitize_to_writer<R: Read + Seek, W: Write>(input: R, output: W) -> Result<SanitizedMetadata, Error> {
+    sanitize_to_writer_with_config(input, output, Config::default())
+}
+
+/// Sanitize an MP4 input, with the given [`Config`], writing the sanitized output to `output`.
+///
+/// The [sanitized metadata](SanitizedMetadata::metadata) is written to `output`, followed by the [media
+/// data](SanitizedMetadata::data_spans), which is copied from `input` in fixed-size chunks. The returned
+/// [`SanitizedMetadata`] describes the written output in terms of the input, as [`sanitize_with_config`] does.
+///
+/// If the metadata did not need to be modified, i.e. [`SanitizedMetadata::metadata`] is [`None`], nothing is written to
+/// `output`, and the input can be used unchanged instead.
+///
+/// Unlike [`sanitize_with_config`], the `input` must implement [`Seek`], as the media data is read after the metadata
+/// which may follow it in the input. Any data written to `output` before an error occurs is not removed.
+///
+/// ```
+/// # use mp4san_test::{example_ftyp, example_mdat, example_moov};
+/// #
+/// let example_input = [example_ftyp(), example_mdat(), example_moov()].concat();
+///
+/// let mut output = vec![];
+/// let config = mp4san::Config::default();
+/// mp4san::sanitize_to_writer_with_config(std::io::Cursor::new(example_input), &mut output, config)?;
+///
+/// assert_eq!(output, [example_ftyp(), example_moov(), example_mdat()].concat());
+/// #
+/// # Ok::<(), mp4san::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs while reading the input or writing the output, an [`Error`] is
+/// returned.
+pub fn sanitize_to_writer_with_config<R: Read + Seek, W: Write>(
+    input: R,
+    output: W,
+    config: Config,
+) -> Result<SanitizedMetadata, Error> {
+    writer::sanitize_to_writer_with_config(input, output, config)
+}
+
+/// Sanitize an MP4 input asynchronously, with the default [`Config`], writing the sanitized output to `output`.
+///
+/// See [`sanitize_to_writer_with_config`] for details.
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs while reading the input or writing the output, an [`Error`] is
+/// returned.
+pub async fn sanitize_async_to_writer<R, W>(input: R, output: W) -> Result<SanitizedMetadata, Error>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    sanitize_async_to_writer_with_config(input, output, Config::default()).await
+}
+
+/// Sanitize an MP4 input asynchronously, with the given [`Config`], writing the sanitized output to `output`.
+///
+/// See [`sanitize_to_writer_with_config`] for details.
+///
+/// # Errors
+///
+/// If the input cannot be parsed, or an IO error occurs while reading the input or writing the output, an [`Error`] is
+/// returned.
+pub async fn sanitize_async_to_writer_with_config<R, W>(
+    input: R,
+    output: W,
+    config: Config,
+) -> Result<SanitizedMetadata, Error>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    writer::sanitize_async_to_writer_with_config(input, output, config).await
+}
+
+//
+// SanitizedMetadata impls
+//
+
+impl SanitizedMetadata {
+    /// Returns pointers to all the spans in the input containing media data, in the order they must be concatenated
+    /// after the metadata.
+    ///
+    /// This is the [media data](Self::data) followed by any [extra media data](Self::extra_data).
+    pub fn data_spans(&self) -> impl Iterator<Item = InputSpan> + '_ {
+        [self.data].into_iter().chain(self.extra_data.iter().copied())
+    }
 }
 
 //
@@ -470,12 +1368,99 @@ impl ConfigBuilder {
     pub fn build(&self) -> Config {
         self.try_build().unwrap()
     }
+
+    /// Set the [`progress_callback`](Config::progress_callback).
+    pub fn progress_callback<F: Fn(Progress) + Send + Sync + 'static>(&mut self, progress_callback: F) -> &mut Self {
+        self.progress_callback = Some(Some(ProgressCallback::new(progress_callback)));
+        self
+    }
+}
+
+//
+// ProgressCallback impls
+//
+
+impl ProgressCallback {
+    /// Construct a new callback invoking `callback`.
+    pub fn new<F: Fn(Progress) + Send + Sync + 'static>(callback: F) -> Self {
+        Self(AssertUnwindSafe(Arc::new(callback)))
+    }
+
+    fn call(&self, progress: Progress) {
+        (self.0)(progress)
+    }
+}
+
+impl Clone for ProgressCallback {
+    fn clone(&self) -> Self {
+        Self(AssertUnwindSafe(Arc::clone(&self.0)))
+    }
+}
+
+//
+// CancellationToken impls
+//
+
+impl CancellationToken {
+    /// Construct a new, uncancelled token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel any sanitizer using this token, or any of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, atomic::Ordering::Relaxed);
+    }
+
+    /// Returns whether this token has been [cancelled](Self::cancel).
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(atomic::Ordering::Relaxed)
+    }
 }
 
 //
 // private functions
 //
 
+/// Return [`Error::Cancelled`] if the sanitizer has been cancelled through [`Config::cancellation_token`].
+fn ensure_not_cancelled(config: &Config) -> Result<(), Error> {
+    match &config.cancellation_token {
+        Some(cancellation_token) if cancellation_token.is_cancelled() => Err(Error::Cancelled),
+        _ => Ok(()),
+    }
+}
+
+/// Extend the accumulated media data with the box at `box_offset`.
+///
+/// If the box is not contiguous with the accumulated media data, a new span of media data is begun, if allowed by the
+/// `config`.
+fn extend_data(data: &mut Vec<InputSpan>, box_offset: u64, box_size: u64, config: &Config) -> Result<(), Error> {
+    match data.last_mut() {
+        Some(last_data) if last_data.offset + last_data.len == box_offset => {
+            last_data.len += box_size;
+        }
+        Some(_) => {
+            ensure_attach!(
+                config.allow_discontiguous_mdat,
+                ParseError::UnsupportedBoxLayout,
+                "discontiguous mdat boxes",
+            );
+            data.push(InputSpan { offset: box_offset, len: box_size });
+        }
+        None => data.push(InputSpan { offset: box_offset, len: box_size }),
+    }
+    Ok(())
+}
+
+/// Extend the last span of accumulated media data with the box at `box_offset`, if it's contiguous with it.
+fn extend_contiguous_data(data: &mut [InputSpan], box_offset: u64, box_size: u64) {
+    if let Some(last_data) = data.last_mut() {
+        if last_data.offset + last_data.len == box_offset {
+            last_data.len += box_size;
+        }
+    }
+}
+
 /// Skip a box's data assuming its header has already been read.
 ///
 /// Returns the amount of data that was skipped.
@@ -503,12 +1488,27 @@ pub mod readme {}
 #[cfg(test)]
 mod test {
     use std::io;
+    use std::panic::{RefUnwindSafe, UnwindSafe};
+    use std::sync::Mutex;
 
     use assert_matches::assert_matches;
 
-    use crate::parse::box_type::{CO64, FREE, FTYP, MDAT, MDIA, MECO, META, MINF, MOOV, SKIP, STBL, STCO, TRAK};
+    use crate::parse::box_type::{
+        CMOV, CO64, ENCA, ENCV, FREE, FTYP, MDAT, MDIA, MECO, META, METT, MINF, MOOV, PSSH, SAIO, SAMR, SAWB, SENC,
+        SKIP, STBL, STCO, TITL, TRAK, UDTA, WIDE,
+    };
+    use crate::parse::{AnyMp4Box, HdlrBox, SaioBox, SaizBox};
+    use crate::util::test::mp4::TestMp4;
+    use crate::util::test::nal::{
+        test_avc1, test_avc_pps, test_avc_sps, test_hevc_sps, test_hvc1, test_hvcc_data, write_test_avcc_data,
+        TEST_AVC_PROFILE_HIGH,
+    };
     use crate::util::test::{
-        init_logger, sanitized_data, test_ftyp, test_moov, test_mp4, write_test_mdat, ISOM, MP41, MP42, TEST_UUID,
+        assert_limit_exceeded, fragmented_config, init_logger, sanitized_chunk_offsets, sanitized_data,
+        test_3gpp_asset, test_3gpp_udta, test_amr_sample_entry, test_discontiguous_mdat_mp4, test_enca, test_encv,
+        test_ftyp, test_moov, test_mp4, test_mp4a, test_pssh, test_s263, test_trak, test_two_track_mp4,
+        write_test_fragment, write_test_mdat, write_test_senc_data, TestFtypBuilder, TestMoovBuilder, TestTrakBuilder,
+        ISOM, MP41, MP42, QT, TEST_UUID, THREE_GP4, THREE_GP6,
     };
 
     use super::*;
@@ -694,6 +1694,265 @@ mod test {
             .sanitize_ok();
     }
 
+    #[test]
+    fn discontiguous_mdat_not_allowed() {
+        init_logger();
+        let (data, _) = test_discontiguous_mdat_mp4();
+        assert_matches!(sanitize(io::Cursor::new(&data)).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBoxLayout);
+        });
+    }
+
+    #[test]
+    fn discontiguous_mdat_chunk_offset_outside_mdat() {
+        init_logger();
+        let mut data = vec![];
+        test_ftyp().build().put_buf(&mut data);
+        write_test_mdat(&mut data, b"abc");
+        test_moov().co_entries(vec![0]).build().put_buf(&mut data);
+        write_test_mdat(&mut data, b"defg");
+        let config = Config::builder().allow_discontiguous_mdat(true).build();
+        let err = sanitize_with_config(io::Cursor::new(&data), config).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidSampleRange);
+        });
+    }
+
+    #[test]
+    fn verify_output_discontiguous_mdat() {
+        init_logger();
+        let (data, _) = test_discontiguous_mdat_mp4();
+        let config = Config::builder().allow_discontiguous_mdat(true).build();
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), config.clone()).unwrap();
+        let verify_config = Config { verify_output: true, ..config };
+        assert_eq!(
+            sanitize_with_config(io::Cursor::new(&data), verify_config).unwrap(),
+            sanitized
+        );
+    }
+
+    #[test]
+    fn verify_output_compact_media_data() {
+        init_logger();
+        let data = test_two_track_mp4(&mut test_moov(), &mut test_trak(), vec![0], vec![2, 3]);
+        let config = Config::builder().compact_media_data(true).verify_output(true).build();
+        sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+    }
+
+    #[test]
+    fn verify_output_fragmented() {
+        init_logger();
+        let mut data = vec![];
+        test_ftyp().build().put_buf(&mut data);
+        test_moov().mvex(true).user_metadata(true).build().put_buf(&mut data);
+        write_test_fragment(&mut data, 1, b"abcdefg", 0);
+        write_test_fragment(&mut data, 1, b"hijk", 0);
+        let config = Config { strip_user_metadata: true, verify_output: true, ..fragmented_config() };
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        assert!(sanitized.metadata.is_some());
+    }
+
+    #[test]
+    fn max_top_level_boxes() {
+        init_logger();
+        let test = test_mp4().build();
+        test.sanitize_ok_with_config(Config::builder().max_top_level_boxes(3).build());
+        let config = Config::builder().max_top_level_boxes(2).build();
+        assert_limit_exceeded(sanitize_with_config(test, config));
+    }
+
+    #[test]
+    fn max_box_depth() {
+        init_logger();
+        let test = test_mp4().build();
+        test.sanitize_ok_with_config(Config::builder().max_box_depth(6).build());
+        for max_box_depth in [0, 1, 5] {
+            let config = Config::builder().max_box_depth(max_box_depth).build();
+            assert_limit_exceeded(sanitize_with_config(test.clone(), config));
+        }
+    }
+
+    #[test]
+    fn max_box_depth_protected_sample_entry() {
+        init_logger();
+        let test = test_mp4()
+            .moov(
+                test_moov()
+                    .first_trak(test_trak().sample_entry_box(test_enca(16)).clone())
+                    .clone(),
+            )
+            .build();
+        let config = encrypted_content_config(EncryptedContent::Validate);
+        sanitize_with_config(test.clone(), Config { max_box_depth: 9, ..config.clone() }).unwrap();
+        assert_limit_exceeded(sanitize_with_config(test, Config { max_box_depth: 8, ..config }));
+    }
+
+    #[test]
+    fn progress_callback() {
+        init_logger();
+        let test = test_mp4().build();
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let config = Config::builder()
+            .progress_callback({
+                let progress = Arc::clone(&progress);
+                move |current| progress.lock().unwrap().push(current)
+            })
+            .build();
+        sanitize_with_config(test.clone(), config).unwrap();
+
+        let progress = progress.lock().unwrap();
+        let box_types: Vec<_> = progress.iter().map(|progress| progress.box_type).collect();
+        assert_eq!(box_types, [FTYP, MDAT, MOOV]);
+        assert_eq!(progress[0].bytes_processed, 0);
+        assert_eq!(progress[1].bytes_processed, test.mdat.offset);
+        assert_eq!(progress[2].bytes_processed, test.mdat.offset + test.mdat.len);
+    }
+
+    #[test]
+    fn config_unwind_safe() {
+        fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>(_: &T) {}
+        let inner_callback: Box<dyn Fn(Progress) + Send + Sync> = Box::new(|_| ());
+        let config = Config::builder().progress_callback(inner_callback).build();
+        assert_unwind_safe(&config);
+    }
+
+    #[test]
+    fn cancelled() {
+        init_logger();
+        let test = test_mp4().build();
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+        let config = Config::builder().cancellation_token(cancellation_token).build();
+        assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Cancelled);
+    }
+
+    #[test]
+    fn cancelled_during_sanitize() {
+        init_logger();
+        let test = test_mp4().build();
+        let cancellation_token = CancellationToken::new();
+        let config = Config::builder()
+            .cancellation_token(cancellation_token.clone())
+            .progress_callback(move |progress| {
+                if progress.box_type == MDAT {
+                    cancellation_token.cancel();
+                }
+            })
+            .build();
+        assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Cancelled);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn sanitize_tokio() {
+        use futures_util::FutureExt;
+
+        init_logger();
+        let test = test_mp4().build();
+        let sanitized = super::sanitize_tokio(io::Cursor::new(&test.data))
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(sanitized, sanitize(test).unwrap());
+    }
+
+    #[test]
+    fn sanitize_read_at() {
+        init_logger();
+        let test = test_mp4().build();
+        let sanitized = super::sanitize_read_at(&test.data[..]).unwrap();
+        assert_eq!(sanitized, sanitize(test.clone()).unwrap());
+
+        let config = Config::builder().strip_user_metadata(true).build();
+        let sanitized = sanitize_read_at_with_config(test.data.to_vec(), config.clone()).unwrap();
+        assert_eq!(sanitized, sanitize_with_config(test, config).unwrap());
+    }
+
+    #[test]
+    fn sanitize_read_at_truncated() {
+        init_logger();
+        let test = test_mp4().build();
+        let data = &test.data[..test.data.len() - 1];
+        assert_matches!(super::sanitize_read_at(data).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::TruncatedBox);
+        });
+    }
+
+    fn test_decoder_configuration_mp4(sample_entry: AnyMp4Box) -> TestMp4 {
+        test_mp4()
+            .moov(
+                test_moov()
+                    .first_trak(test_trak().sample_entry_box(sample_entry).clone())
+                    .clone(),
+            )
+            .build()
+    }
+
+    fn decoder_configuration_config() -> Config {
+        Config::builder().validate_decoder_configurations(true).build()
+    }
+
+    #[test]
+    fn valid_avc_decoder_configuration() {
+        init_logger();
+        let (sps, pps) = (test_avc_sps(TEST_AVC_PROFILE_HIGH, 4, 20, 15), test_avc_pps(0));
+        let mut avcc = vec![];
+        write_test_avcc_data(&mut avcc, TEST_AVC_PROFILE_HIGH, &[&sps], &[&pps]);
+        let test = test_decoder_configuration_mp4(test_avc1(&avcc));
+        sanitize_with_config(test, decoder_configuration_config()).unwrap();
+    }
+
+    #[test]
+    fn invalid_avc_decoder_configuration() {
+        init_logger();
+        let (sps, pps) = (test_avc_sps(TEST_AVC_PROFILE_HIGH, 4, 2000, 2000), test_avc_pps(0));
+        let mut avcc = vec![];
+        write_test_avcc_data(&mut avcc, TEST_AVC_PROFILE_HIGH, &[&sps], &[&pps]);
+        let test = test_decoder_configuration_mp4(test_avc1(&avcc));
+        sanitize(test.clone()).unwrap();
+        let err = sanitize_with_config(test, decoder_configuration_config()).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn valid_hevc_decoder_configuration() {
+        init_logger();
+        let test = test_decoder_configuration_mp4(test_hvc1(&test_hvcc_data(&test_hevc_sps(93, 1280, 720, 5))));
+        sanitize_with_config(test, decoder_configuration_config()).unwrap();
+    }
+
+    #[test]
+    fn invalid_hevc_decoder_configuration() {
+        init_logger();
+        let test = test_decoder_configuration_mp4(test_hvc1(&test_hvcc_data(&test_hevc_sps(93, 1280, 720, 32))));
+        sanitize(test.clone()).unwrap();
+        let err = sanitize_with_config(test, decoder_configuration_config()).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn valid_aac_decoder_configuration() {
+        init_logger();
+        let test = test_decoder_configuration_mp4(test_mp4a(&[0x12, 0x10]));
+        sanitize_with_config(test, decoder_configuration_config()).unwrap();
+    }
+
+    #[test]
+    fn invalid_aac_decoder_configuration() {
+        init_logger();
+        // channelConfiguration 0
+        let test = test_decoder_configuration_mp4(test_mp4a(&[0x12, 0x00]));
+        sanitize(test.clone()).unwrap();
+        let err = sanitize_with_config(test, decoder_configuration_config()).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
     #[test]
     fn uuid() {
         let test = test_mp4().boxes(&[FTYP, MOOV, TEST_UUID, MDAT][..]).build();
@@ -732,6 +1991,344 @@ mod test {
         });
     }
 
+    #[test]
+    fn quicktime() {
+        let config = Config::builder().allow_quicktime(true).build();
+        test_mp4()
+            .ftyp(test_ftyp().major_brand(QT).add_compatible_brand(QT).clone())
+            .boxes(&[FTYP, WIDE, MDAT, MOOV][..])
+            .build()
+            .sanitize_ok_with_config(config);
+    }
+
+    #[test]
+    fn quicktime_not_allowed() {
+        let test = test_mp4()
+            .ftyp(test_ftyp().major_brand(QT).add_compatible_brand(QT).clone())
+            .build();
+        assert_matches!(sanitize(test).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedFormat(QT));
+        });
+    }
+
+    #[test]
+    fn wide_not_allowed() {
+        let test = test_mp4().boxes(&[FTYP, WIDE, MDAT, MOOV][..]).build();
+        assert_matches!(sanitize(test).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBox(WIDE));
+        });
+    }
+
+    #[test]
+    fn cmov() {
+        let config = Config::builder().allow_quicktime(true).build();
+        let test = test_mp4()
+            .ftyp(test_ftyp().major_brand(QT).add_compatible_brand(QT).clone())
+            .moov(test_moov().cmov(true).clone())
+            .build();
+        assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBox(CMOV));
+        });
+    }
+
+    fn three_gpp_config() -> Config {
+        Config::builder()
+            .allow_3gpp(true)
+            .strict_validation(true)
+            .validate_decoder_configurations(true)
+            .build()
+    }
+
+    fn test_3gpp_ftyp() -> TestFtypBuilder {
+        test_ftyp()
+            .major_brand(THREE_GP4)
+            .compatible_brands(vec![THREE_GP4, THREE_GP6])
+            .clone()
+    }
+
+    #[test]
+    fn three_gpp() {
+        for sample_entry in [
+            test_amr_sample_entry(SAMR),
+            test_amr_sample_entry(SAWB),
+            test_s263(10, 0),
+        ] {
+            let moov = test_moov()
+                .first_trak(
+                    test_trak()
+                        .sample_entry_box(sample_entry)
+                        .udta(test_3gpp_udta())
+                        .clone(),
+                )
+                .udta(test_3gpp_udta())
+                .clone();
+            test_mp4()
+                .ftyp(test_3gpp_ftyp())
+                .moov(moov)
+                .build()
+                .sanitize_ok_with_config(three_gpp_config());
+        }
+    }
+
+    #[test]
+    fn three_gpp_not_allowed() {
+        let test = test_mp4().ftyp(test_3gpp_ftyp()).build();
+        assert_matches!(sanitize(test).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedFormat(THREE_GP4));
+        });
+    }
+
+    #[test]
+    fn three_gpp_invalid_asset() {
+        let udta = Mp4Box::with_bytes(UDTA, {
+            let mut data = BytesMut::new();
+            test_3gpp_asset(TITL, b"\x15\xc7unterminated").put_buf(&mut data);
+            data
+        });
+        let test = test_mp4()
+            .ftyp(test_3gpp_ftyp())
+            .moov(test_moov().udta(udta).clone())
+            .build();
+        let config = Config::builder().allow_3gpp(true).build();
+        assert_matches!(sanitize_with_config(test.clone(), config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::TruncatedBox);
+        });
+
+        let config = Config::builder().allow_3gpp(true).strip_user_metadata(true).build();
+        sanitize_with_config(test, config).unwrap();
+    }
+
+    #[test]
+    fn three_gpp_strict_validation_unknown_box() {
+        let test = test_mp4()
+            .ftyp(test_3gpp_ftyp())
+            .moov(test_moov().user_metadata(true).clone())
+            .build();
+        assert_matches!(sanitize_with_config(test, three_gpp_config()).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBox(_));
+        });
+    }
+
+    #[test]
+    fn three_gpp_invalid_decoder_configuration() {
+        let test = test_mp4()
+            .ftyp(test_3gpp_ftyp())
+            .moov(
+                test_moov()
+                    .first_trak(test_trak().sample_entry_box(test_s263(11, 0)).clone())
+                    .clone(),
+            )
+            .build();
+        assert_matches!(sanitize_with_config(test, three_gpp_config()).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn isom_udta_not_validated_as_3gpp() {
+        let udta = Mp4Box::with_bytes(UDTA, {
+            let mut data = BytesMut::new();
+            test_3gpp_asset(TITL, b"\x15\xc7unterminated").put_buf(&mut data);
+            data
+        });
+        let test = test_mp4().moov(test_moov().udta(udta).clone()).build();
+        let config = Config::builder().allow_3gpp(true).build();
+        test.sanitize_ok_with_config(config);
+    }
+
+    fn test_encrypted_trak() -> TestTrakBuilder {
+        let mut senc = BytesMut::new();
+        write_test_senc_data(&mut senc, 6, 8, true);
+        let saiz = Mp4Box::with_data(SaizBox::with_default_size(16, 6).into()).unwrap();
+        let saio = Mp4Box::with_data(SaioBox::with_offsets([0]).into()).unwrap();
+        test_trak()
+            .handler_type(HdlrBox::VIDEO)
+            .sample_entry_box(test_encv(8))
+            .stbl_boxes(vec![Mp4Box::with_bytes(SENC, senc), saiz.into(), saio.into()])
+            .clone()
+    }
+
+    fn test_encrypted_moov(trak: &TestTrakBuilder) -> TestMoovBuilder {
+        test_moov().first_trak(trak.clone()).add_moov_box(test_pssh()).clone()
+    }
+
+    fn encrypted_content_config(encrypted_content: EncryptedContent) -> Config {
+        Config::builder()
+            .encrypted_content(encrypted_content)
+            .strict_validation(encrypted_content == EncryptedContent::Validate)
+            .build()
+    }
+
+    #[test]
+    fn encrypted_content_allowed() {
+        let test = test_mp4().moov(test_encrypted_moov(&test_encrypted_trak())).build();
+        let sanitized = sanitize(test.clone()).unwrap();
+        let media_info = sanitized.media_info.unwrap();
+        assert!(media_info.tracks[0].encrypted);
+        assert_eq!(media_info.tracks[0].sample_entry_types, [ENCV]);
+
+        let config = Config::builder().strict_validation(true).build();
+        assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBox(PSSH));
+        });
+    }
+
+    #[test]
+    fn encrypted_content_rejected() {
+        let config = encrypted_content_config(EncryptedContent::Reject);
+        let test = test_mp4().moov(test_encrypted_moov(&test_encrypted_trak())).build();
+        assert_matches!(sanitize_with_config(test, config.clone()).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBox(PSSH));
+        });
+
+        let test = test_mp4()
+            .moov(
+                test_moov()
+                    .first_trak(test_trak().sample_entry_box(test_enca(16)).clone())
+                    .clone(),
+            )
+            .build();
+        assert_matches!(sanitize_with_config(test, config.clone()).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBox(ENCA));
+        });
+
+        test_mp4().build().sanitize_ok_with_config(config);
+    }
+
+    #[test]
+    fn encrypted_content_rejected_after_dropping_tracks() {
+        init_logger();
+        let data = test_two_track_mp4(&mut test_moov(), test_trak().sample_entry(ENCV), vec![0, 1], vec![2]);
+        let config = Config::builder()
+            .encrypted_content(EncryptedContent::Reject)
+            .allowed_sample_entries(vec![METT])
+            .drop_disallowed_tracks(true)
+            .build();
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        let media_info = sanitized.media_info.unwrap();
+        assert_eq!(media_info.tracks.len(), 1);
+        assert!(!media_info.tracks[0].encrypted);
+    }
+
+    #[test]
+    fn encrypted_content_validated() {
+        let config = encrypted_content_config(EncryptedContent::Validate);
+        let test = test_mp4().moov(test_encrypted_moov(&test_encrypted_trak())).build();
+        let sanitized = sanitize_with_config(test, config.clone()).unwrap();
+        assert!(sanitized.media_info.unwrap().tracks[0].encrypted);
+
+        let test = test_mp4()
+            .moov(
+                test_moov()
+                    .first_trak(test_trak().sample_entry_box(test_enca(16)).clone())
+                    .clone(),
+            )
+            .build();
+        sanitize_with_config(test, config).unwrap();
+    }
+
+    #[test]
+    fn encrypted_content_senc_sample_count_mismatch() {
+        let mut senc = BytesMut::new();
+        write_test_senc_data(&mut senc, 5, 8, true);
+        let moov = test_encrypted_moov(test_encrypted_trak().stbl_boxes(vec![Mp4Box::with_bytes(SENC, senc)]));
+        let test = test_mp4().moov(moov).build();
+        let config = encrypted_content_config(EncryptedContent::Validate);
+        assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn encrypted_content_senc_iv_size_mismatch() {
+        let mut senc = BytesMut::new();
+        write_test_senc_data(&mut senc, 6, 16, true);
+        let moov = test_encrypted_moov(test_encrypted_trak().stbl_boxes(vec![Mp4Box::with_bytes(SENC, senc)]));
+        let test = test_mp4().moov(moov).build();
+        let config = encrypted_content_config(EncryptedContent::Validate);
+        assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::TruncatedBox);
+        });
+    }
+
+    #[test]
+    fn encrypted_content_saiz_without_saio() {
+        let saiz = Mp4Box::with_data(SaizBox::with_default_size(16, 6).into()).unwrap();
+        let moov = test_encrypted_moov(test_encrypted_trak().stbl_boxes(vec![saiz.into()]));
+        let test = test_mp4().moov(moov).build();
+        let config = encrypted_content_config(EncryptedContent::Validate);
+        assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::MissingRequiredBox(SAIO));
+        });
+    }
+
+    #[test]
+    fn encrypted_content_saio_entry_count_mismatch() {
+        let saiz = Mp4Box::with_data(SaizBox::with_default_size(16, 6).into()).unwrap();
+        let saio = Mp4Box::with_data(SaioBox::with_offsets([0, 16]).into()).unwrap();
+        let moov = test_encrypted_moov(test_encrypted_trak().stbl_boxes(vec![saiz.into(), saio.into()]));
+        let test = test_mp4().moov(moov).build();
+        let config = encrypted_content_config(EncryptedContent::Validate);
+        assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidInput);
+        });
+    }
+
+    #[test]
+    fn strip_user_metadata() {
+        for boxes in [&[FTYP, MDAT, MOOV][..], &[FTYP, MOOV, MDAT][..]] {
+            let test = test_mp4()
+                .boxes(boxes)
+                .moov(test_moov().user_metadata(true).clone())
+                .build();
+            let config = Config::builder().strip_user_metadata(true).build();
+            let sanitized = sanitize_with_config(test.clone(), config).unwrap();
+            let metadata = sanitized.metadata.clone().unwrap();
+            assert!(!metadata.windows(4).any(|name| name == b"udta" || name == b"\xa9xyz"));
+
+            let chunk_offsets = sanitized_chunk_offsets(&metadata);
+            let sanitized_data = sanitized_data(sanitized, &test.data);
+            let chunks: Vec<_> = chunk_offsets
+                .into_iter()
+                .map(|chunk_offset| sanitized_data[chunk_offset as usize])
+                .collect();
+            assert_eq!(chunks, test.mdat_data);
+        }
+    }
+
+    #[test]
+    fn user_metadata_not_stripped() {
+        test_mp4()
+            .moov(test_moov().user_metadata(true).clone())
+            .build()
+            .sanitize_ok();
+    }
+
+    #[test]
+    fn strict_validation() {
+        let config = Config::builder().strict_validation(true).build();
+        test_mp4().build().sanitize_ok_with_config(config);
+    }
+
+    #[test]
+    fn strict_validation_unknown_box() {
+        let config = Config::builder().strict_validation(true).build();
+        let test = test_mp4().moov(test_moov().user_metadata(true).clone()).build();
+        assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBox(UDTA));
+        });
+    }
+
+    #[test]
+    fn strict_validation_stripped_user_metadata() {
+        let config = Config::builder()
+            .strict_validation(true)
+            .strip_user_metadata(true)
+            .build();
+        let test = test_mp4().moov(test_moov().user_metadata(true).clone()).build();
+        sanitize_with_config(test, config).unwrap();
+    }
+
     #[test]
     fn no_trak() {
         let test = test_mp4().moov(test_moov().trak(false).clone()).build();
@@ -773,6 +2370,15 @@ mod test {
         });
     }
 
+    #[test]
+    fn no_dinf() {
+        test_mp4()
+            .boxes(&[FTYP, MDAT, MOOV][..])
+            .moov(test_moov().dinf(false).clone())
+            .build()
+            .sanitize_ok();
+    }
+
     #[test]
     fn no_stco() {
         let test = test_mp4()
@@ -0,0 +1,84 @@
+use crate::parse::error::WhileParsingBox;
+use crate::parse::{BoxType, MoovBox, ParseError};
+use crate::{Config, Error, LimitExceeded};
+
+//
+// public functions
+//
+
+/// Validate the number of tracks in `moov`, and the total number of chunks and samples in all of its tracks, against
+/// the limits in `config`.
+///
+/// Returns the number of tracks, chunks, and samples in `moov`.
+pub fn validate_moov_limits(moov: &mut MoovBox, config: &Config) -> Result<(usize, u64, u64), Error> {
+    let trak_count = moov.traks().count();
+    ensure_attach!(
+        trak_count as u64 <= config.max_track_count.into(),
+        ParseError::InvalidInput,
+        LimitExceeded("track count", trak_count as u64, config.max_track_count.into()),
+        WhileParsingBox(BoxType::MOOV),
+    );
+    let (mut chunk_count, mut sample_count) = (0, 0);
+    for trak in moov.traks() {
+        let stbl = trak?.mdia_mut()?.minf_mut()?.stbl_mut()?;
+        let trak_chunk_count = stbl.co_mut()?.entry_count();
+        chunk_count += u64::from(trak_chunk_count);
+        // A track without chunks has no samples, and may lack a sample size box (`stsz`).
+        if trak_chunk_count != 0 {
+            sample_count += u64::from(stbl.stsz_mut()?.sample_count());
+        }
+    }
+    ensure_attach!(
+        chunk_count <= config.max_chunk_offset_entries,
+        ParseError::InvalidInput,
+        LimitExceeded("chunk offset entry count", chunk_count, config.max_chunk_offset_entries),
+        WhileParsingBox(BoxType::MOOV),
+    );
+    ensure_attach!(
+        sample_count <= config.max_sample_count,
+        ParseError::InvalidInput,
+        LimitExceeded("sample count", sample_count, config.max_sample_count),
+        WhileParsingBox(BoxType::MOOV),
+    );
+    Ok((trak_count, chunk_count, sample_count))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use crate::sanitize_with_config;
+    use crate::util::test::{assert_limit_exceeded, init_logger, test_moov, test_trak, test_two_track_mp4};
+
+    use super::*;
+
+    #[test]
+    fn max_track_count() {
+        init_logger();
+        let data = test_two_track_mp4(&mut test_moov(), &mut test_trak(), vec![0], vec![2, 3]);
+        let config = Config::builder().max_track_count(2).build();
+        sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        let config = Config::builder().max_track_count(1).build();
+        assert_limit_exceeded(sanitize_with_config(io::Cursor::new(&data), config));
+    }
+
+    #[test]
+    fn max_sample_count() {
+        init_logger();
+        let data = test_two_track_mp4(&mut test_moov(), &mut test_trak(), vec![0], vec![2, 3]);
+        let config = Config::builder().max_sample_count(3).build();
+        sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        let config = Config::builder().max_sample_count(2).build();
+        assert_limit_exceeded(sanitize_with_config(io::Cursor::new(&data), config));
+    }
+
+    #[test]
+    fn max_chunk_offset_entries() {
+        init_logger();
+        let data = test_two_track_mp4(&mut test_moov(), &mut test_trak(), vec![0], vec![2, 3]);
+        let config = Config::builder().max_chunk_offset_entries(3).build();
+        sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        let config = Config::builder().max_chunk_offset_entries(2).build();
+        assert_limit_exceeded(sanitize_with_config(io::Cursor::new(&data), config));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::parse::MoovBox;
+use crate::{Error, MediaInfo, TrackInfo};
+
+//
+// public functions
+//
+
+/// Summarize the movie and its tracks described by `moov`.
+pub fn media_info(moov: &mut MoovBox) -> Result<MediaInfo, Error> {
+    let mvhd = moov.mvhd_mut()?;
+    let (timescale, duration) = (mvhd.timescale, mvhd.duration);
+    let mut tracks = Vec::new();
+    for trak in moov.traks() {
+        let trak = trak?;
+        let tkhd = trak.tkhd_mut()?;
+        // The track width and height are 16.16 fixed-point numbers.
+        let (track_id, width, height) = (tkhd.track_id, tkhd.width >> 16, tkhd.height >> 16);
+        let mdia = trak.mdia_mut()?;
+        let mdhd = mdia.mdhd_mut()?;
+        let (media_timescale, media_duration) = (mdhd.timescale, mdhd.duration);
+        let handler_type = mdia.hdlr_mut()?.handler_type;
+        let stbl = mdia.minf_mut()?.stbl_mut()?;
+        let stsd = stbl.stsd_mut()?;
+        let sample_entry_types = stsd.entry_types().collect();
+        let encrypted = stsd.is_protected();
+        let sample_count = stbl.stsz_mut()?.sample_count();
+        tracks.push(TrackInfo {
+            track_id,
+            handler_type,
+            sample_entry_types,
+            timescale: media_timescale,
+            duration: media_duration,
+            sample_count,
+            width,
+            height,
+            encrypted,
+        });
+    }
+    Ok(MediaInfo { timescale, duration, tracks })
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use mp4san_test::{example_ftyp, example_mdat, example_moov};
+
+    use crate::parse::box_type::{METT, MP4A};
+    use crate::parse::HdlrBox;
+    use crate::sanitize;
+    use crate::util::test::{init_logger, test_moov, test_trak, test_two_track_mp4};
+
+    use super::*;
+
+    #[test]
+    fn media_info() {
+        init_logger();
+        let mut moov = test_moov();
+        moov.first_trak(test_trak().handler_type(HdlrBox::VIDEO).media_duration(2).clone());
+        let mut second_trak = test_trak();
+        second_trak
+            .handler_type(HdlrBox::SOUND)
+            .sample_entry(MP4A)
+            .media_duration(2);
+        let data = test_two_track_mp4(&mut moov, &mut second_trak, vec![0, 1], vec![2]);
+        let sanitized = sanitize(io::Cursor::new(&data)).unwrap();
+        let track_info = |track_id, handler_type, sample_entry_type, sample_count| TrackInfo {
+            track_id,
+            handler_type,
+            sample_entry_types: vec![sample_entry_type],
+            timescale: 1,
+            duration: 2,
+            sample_count,
+            width: 0,
+            height: 0,
+            encrypted: false,
+        };
+        let expected = MediaInfo {
+            timescale: 1,
+            duration: 0,
+            tracks: vec![
+                track_info(1, HdlrBox::VIDEO, METT, 2),
+                track_info(2, HdlrBox::SOUND, MP4A, 1),
+            ],
+        };
+        assert_eq!(sanitized.media_info, Some(expected));
+    }
+
+    #[test]
+    fn media_info_unavailable() {
+        init_logger();
+        let data = [example_ftyp(), example_mdat(), example_moov()].concat();
+        let sanitized = sanitize(io::Cursor::new(&data)).unwrap();
+        assert_eq!(sanitized.media_info, None);
+    }
+}
@@ -1,34 +1,151 @@
 //! Unstable API for parsing individual MP4 box types.
 
+mod alis;
 mod array;
+mod audio_sample_entry;
+mod audio_specific_config;
+mod avc1;
+mod avcc;
 mod co64;
+mod ctts;
+mod d263;
+mod damr;
+mod descriptor;
+mod dinf;
+mod dref;
+mod edts;
+mod elst;
+mod enca;
+mod encv;
 pub mod error;
+mod esds;
+mod frma;
 mod ftyp;
+mod h264;
+mod h265;
+mod hdlr;
 mod header;
+mod hev1;
+mod hvc1;
+mod hvcc;
 mod integers;
+mod mdhd;
 mod mdia;
+mod mfhd;
 mod minf;
+mod moof;
 mod moov;
+mod mp4a;
 mod mp4box;
+mod mvex;
+mod mvhd;
+mod nmhd;
+mod pssh;
+mod rbsp;
+mod s263;
+mod saio;
+mod saiz;
+mod samr;
+mod sawb;
+mod schi;
+mod schm;
+mod senc;
+mod sinf;
+mod smhd;
 mod stbl;
 mod stco;
+mod stsc;
+mod stsd;
+mod stss;
+mod stsz;
+mod stts;
+mod tenc;
+mod tfdt;
+mod tfhd;
+mod tkhd;
+mod traf;
 mod trak;
+mod tref;
+mod trex;
+mod trun;
+mod udta;
+mod url;
 mod value;
+mod visual_sample_entry;
+mod vmhd;
 
+pub use alis::AlisBox;
 pub use array::{ArrayEntry, ArrayEntryMut, BoundedArray, UnboundedArray};
+pub use audio_sample_entry::{AudioSampleEntry, QuickTimeSoundV1Fields};
+pub use audio_specific_config::{AudioSpecificConfig, AudioSpecificConfigExtension};
+pub use avc1::Avc1Box;
+pub use avcc::AvccBox;
 pub use co64::Co64Box;
+pub use ctts::{CttsBox, CttsEntry};
+pub use d263::D263Box;
+pub use damr::DamrBox;
+pub use descriptor::DescriptorHeader;
+pub use dinf::DinfBox;
+pub use dref::DrefBox;
+pub use edts::EdtsBox;
+pub use elst::{ElstBox, ElstEntry};
+pub use enca::EncaBox;
+pub use encv::EncvBox;
 pub use error::ParseError;
+pub use esds::{DecoderConfigDescriptor, DecoderSpecificInfo, EsDescriptor, EsdsBox};
+pub use frma::FrmaBox;
 pub use ftyp::FtypBox;
+pub use h264::{AvcPps, AvcSps};
+pub use h265::{HevcPps, HevcProfileTierLevel, HevcSps, HevcVps};
+pub use hdlr::HdlrBox;
 pub use header::{box_type, fourcc, BoxHeader, BoxSize, BoxType, BoxUuid, ConstFullBoxHeader, FullBoxHeader};
+pub use hev1::Hev1Box;
+pub use hvc1::Hvc1Box;
+pub use hvcc::{HvccBox, HvccNalUnitArray};
 pub use integers::Mp4Prim;
+pub use mdhd::MdhdBox;
 pub use mdia::MdiaBox;
+pub use mfhd::MfhdBox;
 pub use minf::MinfBox;
-pub use moov::MoovBox;
-pub use mp4box::{AnyMp4Box, BoxData, Boxes, BoxesValidator, Mp4Box, ParseBox, ParsedBox};
+pub use moof::MoofBox;
+pub use moov::{MoovBox, StrictValidationOptions};
+pub use mp4a::Mp4aBox;
+pub use mp4box::{AnyMp4Box, BoxData, BoxDepth, Boxes, BoxesValidator, Mp4Box, ParseBox, ParsedBox};
+pub use mvex::MvexBox;
+pub use mvhd::MvhdBox;
+pub use nmhd::NmhdBox;
+pub use pssh::PsshBox;
+pub use s263::S263Box;
+pub use saio::SaioBox;
+pub use saiz::SaizBox;
+pub use samr::SamrBox;
+pub use sawb::SawbBox;
+pub use schi::SchiBox;
+pub use schm::SchmBox;
+pub use senc::SencBox;
+pub use sinf::SinfBox;
+pub use smhd::SmhdBox;
 pub use stbl::{StblBox, StblCoMut};
 pub use stco::StcoBox;
+pub use stsc::{StscBox, StscEntry};
+pub use stsd::StsdBox;
+pub use stss::StssBox;
+pub use stsz::StszBox;
+pub use stts::{SttsBox, SttsEntry};
+pub use tenc::TencBox;
+pub use tfdt::TfdtBox;
+pub use tfhd::TfhdBox;
+pub use tkhd::TkhdBox;
+pub use traf::TrafBox;
 pub use trak::TrakBox;
+pub use tref::{TrackReference, TrefBox};
+pub use trex::TrexBox;
+pub use trun::{TrunBox, TrunSample};
+pub use udta::{UdtaBox, THREE_GPP_ASSETS};
+pub use url::UrlBox;
 pub use value::{Mp4Value, Mp4ValueReaderExt, Mp4ValueWriterExt};
+pub use visual_sample_entry::VisualSampleEntry;
+pub use vmhd::VmhdBox;
 
 pub use mediasan_common::parse::FourCC;
 pub use mp4san_derive::{ParseBox, ParsedBox};
@@ -58,6 +175,7 @@ mod test {
     #[box_type = "xa04"]
     pub struct Fifth;
 
+    #[allow(dead_code)]
     #[derive(Clone, Debug, ParseBox, ParsedBox)]
     #[box_type = "test"]
     pub struct ArrayBox {
@@ -71,7 +189,7 @@ mod test {
         let not_a_real = NotARealBox { bar_ax: u64::MAX, foo_by: u32::MAX };
         assert_eq!(
             not_a_real.encoded_len(),
-            (<u64 as Mp4Prim>::encoded_len() + <u32 as Mp4Prim>::encoded_len()) as u64
+            <u64 as Mp4Prim>::encoded_len() + <u32 as Mp4Prim>::encoded_len()
         );
     }
 
@@ -0,0 +1,64 @@
+#![allow(missing_docs)]
+
+use super::{FullBoxHeader, ParseBox, ParsedBox, UnboundedArray};
+
+/// A QuickTime alias data reference.
+#[derive(Clone, Debug, Default, ParseBox, ParsedBox)]
+#[box_type = "alis"]
+pub struct AlisBox {
+    header: FullBoxHeader,
+    pub alias: UnboundedArray<u8>,
+}
+
+impl AlisBox {
+    /// Flag indicating that the media data is in the same file as the box containing this data reference.
+    pub const SELF_CONTAINED_FLAG: u32 = 0x000001;
+
+    pub fn flags(&self) -> u32 {
+        self.header.flags
+    }
+
+    /// Returns whether this data reference refers to the same file as the box containing it.
+    ///
+    /// A data reference with the self-contained flag set must have no alias record, as downstream parsers may still
+    /// follow one.
+    pub fn is_self_contained(&self) -> bool {
+        self.header.flags & Self::SELF_CONTAINED_FLAG != 0 && self.alias.entry_count() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::Mp4Value;
+
+    use super::*;
+
+    #[test]
+    fn self_contained() {
+        let mut data = BytesMut::new();
+        FullBoxHeader { version: 0, flags: AlisBox::SELF_CONTAINED_FLAG }.put_buf(&mut data);
+        let alis = AlisBox::parse(&mut data).unwrap();
+        assert!(alis.is_self_contained());
+    }
+
+    #[test]
+    fn external() {
+        let mut data = BytesMut::new();
+        FullBoxHeader::default().put_buf(&mut data);
+        data.extend_from_slice(&[0; 32]);
+        let alis = AlisBox::parse(&mut data).unwrap();
+        assert!(!alis.is_self_contained());
+        assert_eq!(alis.alias.entry_count(), 32);
+    }
+
+    #[test]
+    fn self_contained_with_alias() {
+        let mut data = BytesMut::new();
+        FullBoxHeader { version: 0, flags: AlisBox::SELF_CONTAINED_FLAG }.put_buf(&mut data);
+        data.extend_from_slice(&[0; 32]);
+        let alis = AlisBox::parse(&mut data).unwrap();
+        assert!(!alis.is_self_contained());
+    }
+}
@@ -43,11 +43,11 @@ pub struct ArrayEntryMut<'a, T> {
 //
 
 impl<C: Clone, T: Mp4Prim> BoundedArray<C, T> {
-    pub fn entries(&self) -> impl Iterator<Item = ArrayEntry<'_, T>> + ExactSizeIterator + '_ {
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = ArrayEntry<'_, T>> + '_ {
         self.array.entries()
     }
 
-    pub fn entries_mut(&mut self) -> impl Iterator<Item = ArrayEntryMut<'_, T>> + ExactSizeIterator + '_ {
+    pub fn entries_mut(&mut self) -> impl ExactSizeIterator<Item = ArrayEntryMut<'_, T>> + '_ {
         self.array.entries_mut()
     }
 
@@ -94,13 +94,13 @@ impl<C: From<u32>, T: Mp4Prim> FromIterator<T> for BoundedArray<C, T> {
 //
 
 impl<T: Mp4Prim> UnboundedArray<T> {
-    pub fn entries(&self) -> impl Iterator<Item = ArrayEntry<'_, T>> + ExactSizeIterator + '_ {
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = ArrayEntry<'_, T>> + '_ {
         self.entries
             .chunks_exact(T::encoded_len() as usize)
             .map(|data| ArrayEntry { data, _t: PhantomData })
     }
 
-    pub fn entries_mut(&mut self) -> impl Iterator<Item = ArrayEntryMut<'_, T>> + ExactSizeIterator + '_ {
+    pub fn entries_mut(&mut self) -> impl ExactSizeIterator<Item = ArrayEntryMut<'_, T>> + '_ {
         self.entries
             .chunks_exact_mut(T::encoded_len() as usize)
             .map(|data| ArrayEntryMut { data, _t: PhantomData })
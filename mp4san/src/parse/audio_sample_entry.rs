@@ -0,0 +1,170 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut};
+use mediasan_common::error::WhileParsingType;
+
+use crate::error::Result;
+
+use super::{Mp4Prim, ParseError};
+
+/// The fields common to all audio sample entries, as defined by ISO/IEC 14496-12 section 12.2.3.
+///
+/// In QuickTime sound sample descriptions, the first reserved field holds a version, which determines the number of
+/// additional fields following this structure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AudioSampleEntry {
+    reserved_1: [u8; 6],
+    pub data_reference_index: u16,
+    version: u16,
+    reserved_2: [u8; 6],
+    pub channelcount: u16,
+    pub samplesize: u16,
+    pre_defined: u16,
+    reserved_3: u16,
+    pub samplerate: u32,
+}
+
+/// The additional fields of a version 1 QuickTime sound sample description, following its [`AudioSampleEntry`].
+///
+/// In such a sample description, a sample size of 1 in the sample size box (`stsz`) counts uncompressed frames rather
+/// than bytes, and the frames are stored in packets of [`bytes_per_frame`](Self::bytes_per_frame) bytes each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuickTimeSoundV1Fields {
+    pub samples_per_packet: u32,
+    pub bytes_per_packet: u32,
+    pub bytes_per_frame: u32,
+    pub bytes_per_sample: u32,
+}
+
+impl AudioSampleEntry {
+    pub fn new(channelcount: u16, samplerate: u16) -> Self {
+        Self {
+            reserved_1: Default::default(),
+            data_reference_index: 1,
+            version: 0,
+            reserved_2: Default::default(),
+            channelcount,
+            samplesize: 16,
+            pre_defined: 0,
+            reserved_3: 0,
+            samplerate: u32::from(samplerate) << 16,
+        }
+    }
+
+    /// Returns the QuickTime sound sample description version, which is zero for ISO audio sample entries.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+}
+
+impl Mp4Prim for AudioSampleEntry {
+    fn parse<B: Buf>(mut buf: B) -> Result<Self, ParseError> {
+        ensure_attach!(
+            buf.remaining() >= Self::encoded_len() as usize,
+            ParseError::TruncatedBox,
+            WhileParsingType::new::<Self>(),
+        );
+        fn parse<T: Mp4Prim, B: Buf>(buf: B) -> T {
+            T::parse(buf).unwrap_or_else(|_| unreachable!())
+        }
+        Ok(Self {
+            reserved_1: parse(&mut buf),
+            data_reference_index: parse(&mut buf),
+            version: parse(&mut buf),
+            reserved_2: parse(&mut buf),
+            channelcount: parse(&mut buf),
+            samplesize: parse(&mut buf),
+            pre_defined: parse(&mut buf),
+            reserved_3: parse(&mut buf),
+            samplerate: parse(&mut buf),
+        })
+    }
+
+    fn encoded_len() -> u64 {
+        28
+    }
+
+    fn put_buf<B: BufMut>(&self, mut buf: B) {
+        self.reserved_1.put_buf(&mut buf);
+        self.data_reference_index.put_buf(&mut buf);
+        self.version.put_buf(&mut buf);
+        self.reserved_2.put_buf(&mut buf);
+        self.channelcount.put_buf(&mut buf);
+        self.samplesize.put_buf(&mut buf);
+        self.pre_defined.put_buf(&mut buf);
+        self.reserved_3.put_buf(&mut buf);
+        self.samplerate.put_buf(&mut buf);
+    }
+}
+
+impl QuickTimeSoundV1Fields {
+    /// Returns the length in bytes of `sample_count` uncompressed frames, rounded up to a whole number of packets.
+    ///
+    /// Returns `None` if [`samples_per_packet`](Self::samples_per_packet) is zero.
+    pub fn samples_len(&self, sample_count: u32) -> Option<u64> {
+        let samples_per_packet = u64::from(self.samples_per_packet);
+        if samples_per_packet == 0 {
+            return None;
+        }
+        let packet_count = (u64::from(sample_count) + samples_per_packet - 1) / samples_per_packet;
+        Some(packet_count * u64::from(self.bytes_per_frame))
+    }
+}
+
+impl Mp4Prim for QuickTimeSoundV1Fields {
+    fn parse<B: Buf>(mut buf: B) -> Result<Self, ParseError> {
+        ensure_attach!(
+            buf.remaining() >= Self::encoded_len() as usize,
+            ParseError::TruncatedBox,
+            WhileParsingType::new::<Self>(),
+        );
+        Ok(Self {
+            samples_per_packet: buf.get_u32(),
+            bytes_per_packet: buf.get_u32(),
+            bytes_per_frame: buf.get_u32(),
+            bytes_per_sample: buf.get_u32(),
+        })
+    }
+
+    fn encoded_len() -> u64 {
+        16
+    }
+
+    fn put_buf<B: BufMut>(&self, mut buf: B) {
+        buf.put_u32(self.samples_per_packet);
+        buf.put_u32(self.bytes_per_packet);
+        buf.put_u32(self.bytes_per_frame);
+        buf.put_u32(self.bytes_per_sample);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quicktime_sound_v1_samples_len() {
+        let fields = QuickTimeSoundV1Fields {
+            samples_per_packet: 64,
+            bytes_per_packet: 34,
+            bytes_per_frame: 34,
+            bytes_per_sample: 2,
+        };
+        assert_eq!(fields.samples_len(0), Some(0));
+        assert_eq!(fields.samples_len(1), Some(34));
+        assert_eq!(fields.samples_len(64), Some(34));
+        assert_eq!(fields.samples_len(65), Some(68));
+        assert_eq!(fields.samples_len(u32::MAX), Some(67108864 * 34));
+    }
+
+    #[test]
+    fn quicktime_sound_v1_samples_len_zero_samples_per_packet() {
+        let fields = QuickTimeSoundV1Fields {
+            samples_per_packet: 0,
+            bytes_per_packet: 34,
+            bytes_per_frame: 34,
+            bytes_per_sample: 2,
+        };
+        assert_eq!(fields.samples_len(64), None);
+    }
+}
@@ -0,0 +1,218 @@
+#![allow(missing_docs)]
+
+use std::io::Cursor;
+
+use bitstream_io::{BigEndian, BitRead, BitReader, Numeric};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingStructureField};
+use super::ParseError;
+
+/// A parsed MPEG-4 `AudioSpecificConfig`, as defined by ISO/IEC 14496-3 section 1.6.2.1.
+///
+/// Only the fields preceding the object type specific configuration are parsed, in addition to those of the
+/// `GASpecificConfig` of AAC object types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AudioSpecificConfig {
+    pub audio_object_type: u8,
+    pub sampling_frequency: u32,
+    pub channel_configuration: u8,
+
+    /// The audio object type and sampling frequency of the SBR or PS extension, when signalled explicitly.
+    pub extension: Option<AudioSpecificConfigExtension>,
+}
+
+/// The explicitly signalled extension of an [`AudioSpecificConfig`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AudioSpecificConfigExtension {
+    pub audio_object_type: u8,
+    pub sampling_frequency: u32,
+}
+
+pub const AUDIO_OBJECT_TYPE_AAC_MAIN: u8 = 1;
+pub const AUDIO_OBJECT_TYPE_AAC_LC: u8 = 2;
+pub const AUDIO_OBJECT_TYPE_SBR: u8 = 5;
+pub const AUDIO_OBJECT_TYPE_PS: u8 = 29;
+
+/// The audio object types which may be used, either directly or as the core of an SBR or PS extension.
+const SUPPORTED_AUDIO_OBJECT_TYPES: &[u8] = &[AUDIO_OBJECT_TYPE_AAC_MAIN, AUDIO_OBJECT_TYPE_AAC_LC];
+
+const AUDIO_OBJECT_TYPE_ESCAPE: u8 = 31;
+
+/// The sampling frequencies indexed by `samplingFrequencyIndex`, as defined by ISO/IEC 14496-3 Table 1.18.
+const SAMPLING_FREQUENCIES: &[u32] = &[
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+];
+
+const SAMPLING_FREQUENCY_INDEX_ESCAPE: u8 = 0xf;
+
+const MAX_SAMPLING_FREQUENCY: u32 = 96000;
+
+/// The highest supported `channelConfiguration`, corresponding to 7.1 channels.
+///
+/// Channel configuration 0, which describes the channels with a program config element, is not supported.
+const MAX_CHANNEL_CONFIGURATION: u8 = 7;
+
+const AUDIO_SPECIFIC_CONFIG: &str = "AudioSpecificConfig";
+const GA_SPECIFIC_CONFIG: &str = "GASpecificConfig";
+
+impl AudioSpecificConfig {
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = BitReader::endian(Cursor::new(data), BigEndian);
+
+        let mut audio_object_type = parse_audio_object_type(&mut reader)
+            .while_parsing_structure_field(AUDIO_SPECIFIC_CONFIG, "audioObjectType")?;
+        let sampling_frequency = parse_sampling_frequency(&mut reader)
+            .while_parsing_structure_field(AUDIO_SPECIFIC_CONFIG, "samplingFrequencyIndex")?;
+        let channel_configuration: u8 =
+            read(&mut reader, 4).while_parsing_structure_field(AUDIO_SPECIFIC_CONFIG, "channelConfiguration")?;
+        ensure_attach!(
+            (1..=MAX_CHANNEL_CONFIGURATION).contains(&channel_configuration),
+            ParseError::InvalidInput,
+            format!("unsupported channel configuration {channel_configuration}"),
+            WhileParsingStructureField(AUDIO_SPECIFIC_CONFIG, "channelConfiguration"),
+        );
+
+        let mut extension = None;
+        if audio_object_type == AUDIO_OBJECT_TYPE_SBR || audio_object_type == AUDIO_OBJECT_TYPE_PS {
+            let extension_sampling_frequency = parse_sampling_frequency(&mut reader)
+                .while_parsing_structure_field(AUDIO_SPECIFIC_CONFIG, "extensionSamplingFrequencyIndex")?;
+            extension = Some(AudioSpecificConfigExtension {
+                audio_object_type,
+                sampling_frequency: extension_sampling_frequency,
+            });
+            audio_object_type = parse_audio_object_type(&mut reader)
+                .while_parsing_structure_field(AUDIO_SPECIFIC_CONFIG, "audioObjectType")?;
+        }
+        ensure_attach!(
+            SUPPORTED_AUDIO_OBJECT_TYPES.contains(&audio_object_type),
+            ParseError::InvalidInput,
+            format!("unsupported audio object type {audio_object_type}"),
+            WhileParsingStructureField(AUDIO_SPECIFIC_CONFIG, "audioObjectType"),
+        );
+
+        read::<u8>(&mut reader, 1).while_parsing_structure_field(GA_SPECIFIC_CONFIG, "frameLengthFlag")?;
+        let depends_on_core_coder: u8 =
+            read(&mut reader, 1).while_parsing_structure_field(GA_SPECIFIC_CONFIG, "dependsOnCoreCoder")?;
+        if depends_on_core_coder != 0 {
+            read::<u16>(&mut reader, 14).while_parsing_structure_field(GA_SPECIFIC_CONFIG, "coreCoderDelay")?;
+        }
+        let extension_flag: u8 =
+            read(&mut reader, 1).while_parsing_structure_field(GA_SPECIFIC_CONFIG, "extensionFlag")?;
+        ensure_attach!(
+            extension_flag == 0,
+            ParseError::InvalidInput,
+            "extension flag set for AAC object type",
+            WhileParsingStructureField(GA_SPECIFIC_CONFIG, "extensionFlag"),
+        );
+
+        Ok(Self { audio_object_type, sampling_frequency, channel_configuration, extension })
+    }
+}
+
+fn parse_audio_object_type(reader: &mut BitReader<Cursor<&[u8]>, BigEndian>) -> Result<u8, ParseError> {
+    let audio_object_type: u8 = read(reader, 5)?;
+    if audio_object_type == AUDIO_OBJECT_TYPE_ESCAPE {
+        let audio_object_type_ext: u8 = read(reader, 6)?;
+        return Ok(32 + audio_object_type_ext);
+    }
+    Ok(audio_object_type)
+}
+
+fn parse_sampling_frequency(reader: &mut BitReader<Cursor<&[u8]>, BigEndian>) -> Result<u32, ParseError> {
+    let sampling_frequency_index: u8 = read(reader, 4)?;
+    if sampling_frequency_index == SAMPLING_FREQUENCY_INDEX_ESCAPE {
+        let sampling_frequency: u32 = read(reader, 24)?;
+        ensure_attach!(
+            (1..=MAX_SAMPLING_FREQUENCY).contains(&sampling_frequency),
+            ParseError::InvalidInput,
+            format!("unsupported sampling frequency {sampling_frequency}"),
+        );
+        return Ok(sampling_frequency);
+    }
+    let Some(&sampling_frequency) = SAMPLING_FREQUENCIES.get(usize::from(sampling_frequency_index)) else {
+        bail_attach!(
+            ParseError::InvalidInput,
+            format!("reserved sampling frequency index {sampling_frequency_index}"),
+        );
+    };
+    Ok(sampling_frequency)
+}
+
+fn read<T: Numeric>(reader: &mut BitReader<Cursor<&[u8]>, BigEndian>, bits: u32) -> Result<T, ParseError> {
+    reader
+        .read(bits)
+        .map_err(|_| report_attach!(ParseError::TruncatedBox, "truncated AudioSpecificConfig"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aac_lc() {
+        // audioObjectType 2, samplingFrequencyIndex 4, channelConfiguration 2, GASpecificConfig 000
+        let config = AudioSpecificConfig::parse(&[0x12, 0x10]).unwrap();
+        assert_eq!(
+            config,
+            AudioSpecificConfig {
+                audio_object_type: AUDIO_OBJECT_TYPE_AAC_LC,
+                sampling_frequency: 44100,
+                channel_configuration: 2,
+                extension: None,
+            }
+        );
+    }
+
+    #[test]
+    fn he_aac() {
+        // audioObjectType 5, samplingFrequencyIndex 6, channelConfiguration 2, extensionSamplingFrequencyIndex 3,
+        // audioObjectType 2, GASpecificConfig 000
+        let config = AudioSpecificConfig::parse(&[0x2b, 0x11, 0x88, 0x00]).unwrap();
+        assert_eq!(config.audio_object_type, AUDIO_OBJECT_TYPE_AAC_LC);
+        assert_eq!(config.sampling_frequency, 24000);
+        assert_eq!(
+            config.extension,
+            Some(AudioSpecificConfigExtension { audio_object_type: AUDIO_OBJECT_TYPE_SBR, sampling_frequency: 48000 })
+        );
+    }
+
+    #[test]
+    fn explicit_sampling_frequency() {
+        // audioObjectType 2, samplingFrequencyIndex 15, samplingFrequency 8000, channelConfiguration 1, 000
+        let config = AudioSpecificConfig::parse(&[0x17, 0x80, 0x0f, 0xa0, 0x08]).unwrap();
+        assert_eq!(config.sampling_frequency, 8000);
+        assert_eq!(config.channel_configuration, 1);
+    }
+
+    #[test]
+    fn unsupported_audio_object_type() {
+        // audioObjectType 23 (ER AAC LD), samplingFrequencyIndex 4, channelConfiguration 2
+        let err = AudioSpecificConfig::parse(&[0xba, 0x10]).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn reserved_sampling_frequency_index() {
+        // audioObjectType 2, samplingFrequencyIndex 13, channelConfiguration 2
+        let err = AudioSpecificConfig::parse(&[0x16, 0x90]).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn unsupported_channel_configuration() {
+        // audioObjectType 2, samplingFrequencyIndex 4, channelConfiguration 0
+        let err = AudioSpecificConfig::parse(&[0x12, 0x00]).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+        // audioObjectType 2, samplingFrequencyIndex 4, channelConfiguration 8
+        let err = AudioSpecificConfig::parse(&[0x12, 0x40]).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn truncated() {
+        let err = AudioSpecificConfig::parse(&[0x12]).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
@@ -0,0 +1,56 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{AvccBox, BoxType, Boxes, ParseBox, ParseError, ParsedBox, VisualSampleEntry};
+
+/// An H.264 (AVC) visual sample entry, as defined by ISO/IEC 14496-15 section 5.4.2.1.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "avc1"]
+pub struct Avc1Box {
+    pub entry: VisualSampleEntry,
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::AVC1;
+
+impl Avc1Box {
+    pub fn with_children<C: Into<Boxes>>(entry: VisualSampleEntry, children: C) -> Self {
+        Self { entry, children: children.into() }
+    }
+
+    pub fn avcc_mut(&mut self) -> Result<&mut AvccBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::AVCC)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::Mp4Value;
+
+    use crate::util::test::nal::{
+        test_avc1, test_avc_pps, test_avc_sps, write_test_avcc_data, TEST_AVC_PROFILE_BASELINE,
+    };
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let (sps, pps) = (test_avc_sps(TEST_AVC_PROFILE_BASELINE, 1, 20, 15), test_avc_pps(0));
+        let mut avcc = vec![];
+        write_test_avcc_data(&mut avcc, TEST_AVC_PROFILE_BASELINE, &[&sps], &[&pps]);
+        let mut avc1_box = test_avc1(&avcc);
+        let avc1 = avc1_box.parse_data_as::<Avc1Box>().unwrap().unwrap();
+        assert_eq!((avc1.entry.width, avc1.entry.height), (320, 240));
+        assert_eq!(avc1.avcc_mut().unwrap().sequence_parameter_sets, [&sps[..]]);
+
+        let mut expected = BytesMut::new();
+        test_avc1(&avcc).put_buf(&mut expected);
+        let mut encoded = BytesMut::new();
+        avc1_box.put_buf(&mut encoded);
+        assert_eq!(encoded, expected);
+    }
+}
@@ -0,0 +1,212 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{AvcPps, AvcSps, BoxType, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+/// An `AVCDecoderConfigurationRecord`, as defined by ISO/IEC 14496-15 section 5.3.3.1.
+#[derive(Clone, Debug)]
+pub struct AvccBox {
+    pub configuration_version: u8,
+    pub profile_indication: u8,
+    pub profile_compatibility: u8,
+    pub level_indication: u8,
+    length_size_minus_one: u8,
+    pub sequence_parameter_sets: Vec<BytesMut>,
+    pub picture_parameter_sets: Vec<BytesMut>,
+    extension: BytesMut,
+}
+
+const NAME: BoxType = BoxType::AVCC;
+
+const RESERVED_LENGTH_SIZE_MINUS_ONE_BITS: u8 = 0b1111_1100;
+const RESERVED_SEQUENCE_PARAMETER_SET_COUNT_BITS: u8 = 0b1110_0000;
+
+impl AvccBox {
+    /// Returns the size in bytes of the length prefix of each NAL unit in the samples.
+    pub fn length_size(&self) -> u8 {
+        self.length_size_minus_one + 1
+    }
+
+    /// Parses each sequence parameter set (SPS).
+    pub fn parse_sequence_parameter_sets(&self) -> Result<Vec<AvcSps>, ParseError> {
+        let sps = self.sequence_parameter_sets.iter().map(|sps| AvcSps::parse(sps));
+        sps.collect::<Result<_, _>>()
+            .while_parsing_field(NAME, "sequence_parameter_sets")
+    }
+
+    /// Parses each picture parameter set (PPS).
+    pub fn parse_picture_parameter_sets(&self) -> Result<Vec<AvcPps>, ParseError> {
+        let pps = self.picture_parameter_sets.iter().map(|pps| AvcPps::parse(pps));
+        pps.collect::<Result<_, _>>()
+            .while_parsing_field(NAME, "picture_parameter_sets")
+    }
+}
+
+impl ParseBox for AvccBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let configuration_version = u8::parse(&mut *buf).while_parsing_field(NAME, "configuration_version")?;
+        ensure_attach!(
+            configuration_version == 1,
+            ParseError::InvalidInput,
+            format!("unsupported configuration version {configuration_version}"),
+            WhileParsingField(NAME, "configuration_version"),
+        );
+        let profile_indication = u8::parse(&mut *buf).while_parsing_field(NAME, "profile_indication")?;
+        let profile_compatibility = u8::parse(&mut *buf).while_parsing_field(NAME, "profile_compatibility")?;
+        let level_indication = u8::parse(&mut *buf).while_parsing_field(NAME, "level_indication")?;
+        let length_size_minus_one = u8::parse(&mut *buf).while_parsing_field(NAME, "length_size_minus_one")?
+            & !RESERVED_LENGTH_SIZE_MINUS_ONE_BITS;
+        ensure_attach!(
+            length_size_minus_one != 2,
+            ParseError::InvalidInput,
+            "invalid NAL unit length size 3",
+            WhileParsingField(NAME, "length_size_minus_one"),
+        );
+
+        let sequence_parameter_set_count = u8::parse(&mut *buf)
+            .while_parsing_field(NAME, "num_of_sequence_parameter_sets")?
+            & !RESERVED_SEQUENCE_PARAMETER_SET_COUNT_BITS;
+        let sequence_parameter_sets = parse_nal_units(&mut *buf, sequence_parameter_set_count.into())
+            .while_parsing_field(NAME, "sequence_parameter_sets")?;
+        let picture_parameter_set_count =
+            u8::parse(&mut *buf).while_parsing_field(NAME, "num_of_picture_parameter_sets")?;
+        let picture_parameter_sets = parse_nal_units(&mut *buf, picture_parameter_set_count.into())
+            .while_parsing_field(NAME, "picture_parameter_sets")?;
+        let extension = buf.split();
+
+        let avcc = Self {
+            configuration_version,
+            profile_indication,
+            profile_compatibility,
+            level_indication,
+            length_size_minus_one,
+            sequence_parameter_sets,
+            picture_parameter_sets,
+            extension,
+        };
+
+        let sps = avcc.parse_sequence_parameter_sets()?;
+        for sps in &sps {
+            ensure_attach!(
+                sps.profile_idc == profile_indication,
+                ParseError::InvalidInput,
+                format!("SPS profile {} does not match {profile_indication}", sps.profile_idc),
+                WhileParsingField(NAME, "sequence_parameter_sets"),
+            );
+        }
+        for pps in avcc.parse_picture_parameter_sets()? {
+            ensure_attach!(
+                sps.iter()
+                    .any(|sps| sps.seq_parameter_set_id == pps.seq_parameter_set_id),
+                ParseError::InvalidInput,
+                format!("PPS references unknown SPS {}", pps.seq_parameter_set_id),
+                WhileParsingField(NAME, "picture_parameter_sets"),
+            );
+        }
+        Ok(avcc)
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for AvccBox {
+    fn encoded_len(&self) -> u64 {
+        6 + nal_units_encoded_len(&self.sequence_parameter_sets)
+            + 1
+            + nal_units_encoded_len(&self.picture_parameter_sets)
+            + self.extension.len() as u64
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.configuration_version.put_buf(&mut out);
+        self.profile_indication.put_buf(&mut out);
+        self.profile_compatibility.put_buf(&mut out);
+        self.level_indication.put_buf(&mut out);
+        (RESERVED_LENGTH_SIZE_MINUS_ONE_BITS | self.length_size_minus_one).put_buf(&mut out);
+        (RESERVED_SEQUENCE_PARAMETER_SET_COUNT_BITS | self.sequence_parameter_sets.len() as u8).put_buf(&mut out);
+        put_nal_units(&self.sequence_parameter_sets, &mut out);
+        (self.picture_parameter_sets.len() as u8).put_buf(&mut out);
+        put_nal_units(&self.picture_parameter_sets, &mut out);
+        out.put_slice(&self.extension);
+    }
+}
+
+/// Parse `count` NAL units, each prefixed by its 16-bit length, as in decoder configuration records.
+pub(super) fn parse_nal_units(buf: &mut BytesMut, count: usize) -> Result<Vec<BytesMut>, ParseError> {
+    let mut nal_units = Vec::with_capacity(count);
+    for _ in 0..count {
+        let len = u16::parse(&mut *buf)?;
+        ensure_attach!(buf.remaining() >= len.into(), ParseError::TruncatedBox);
+        nal_units.push(buf.split_to(len.into()));
+    }
+    Ok(nal_units)
+}
+
+pub(super) fn nal_units_encoded_len(nal_units: &[BytesMut]) -> u64 {
+    nal_units.iter().map(|nal_unit| 2 + nal_unit.len() as u64).sum()
+}
+
+pub(super) fn put_nal_units(nal_units: &[BytesMut], mut out: &mut dyn BufMut) {
+    for nal_unit in nal_units {
+        (nal_unit.len() as u16).put_buf(&mut out);
+        out.put_slice(nal_unit);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::nal::{test_avc_pps, test_avc_sps, write_test_avcc_data, TEST_AVC_PROFILE_HIGH};
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let (sps, pps) = (test_avc_sps(TEST_AVC_PROFILE_HIGH, 4, 20, 15), test_avc_pps(0));
+        let mut data = BytesMut::new();
+        write_test_avcc_data(&mut data, TEST_AVC_PROFILE_HIGH, &[&sps], &[&pps]);
+        let expected = data.clone();
+        let avcc = AvccBox::parse(&mut data).unwrap();
+        assert_eq!(avcc.length_size(), 4);
+        assert_eq!(avcc.sequence_parameter_sets, [&sps[..]]);
+        assert_eq!(avcc.picture_parameter_sets, [&pps[..]]);
+        assert_eq!(avcc.parse_sequence_parameter_sets().unwrap()[0].width(), 320);
+        let mut encoded = BytesMut::new();
+        avcc.put_buf(&mut encoded);
+        assert_eq!(encoded.len() as u64, avcc.encoded_len());
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn profile_mismatch() {
+        let (sps, pps) = (test_avc_sps(TEST_AVC_PROFILE_HIGH, 4, 20, 15), test_avc_pps(0));
+        let mut data = BytesMut::new();
+        write_test_avcc_data(&mut data, 66, &[&sps], &[&pps]);
+        let err = AvccBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn pps_unknown_sps() {
+        let (sps, pps) = (test_avc_sps(TEST_AVC_PROFILE_HIGH, 4, 20, 15), test_avc_pps(1));
+        let mut data = BytesMut::new();
+        write_test_avcc_data(&mut data, TEST_AVC_PROFILE_HIGH, &[&sps], &[&pps]);
+        let err = AvccBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn truncated_nal_unit() {
+        let sps = test_avc_sps(TEST_AVC_PROFILE_HIGH, 4, 20, 15);
+        let mut data = BytesMut::new();
+        write_test_avcc_data(&mut data, TEST_AVC_PROFILE_HIGH, &[&sps], &[]);
+        data.truncate(data.len() - 2);
+        let err = AvccBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
@@ -10,7 +10,7 @@ pub struct Co64Box {
 }
 
 impl Co64Box {
-    pub fn entries_mut(&mut self) -> impl Iterator<Item = ArrayEntryMut<'_, u64>> + ExactSizeIterator + '_ {
+    pub fn entries_mut(&mut self) -> impl ExactSizeIterator<Item = ArrayEntryMut<'_, u64>> + '_ {
         self.entries.entries_mut()
     }
 
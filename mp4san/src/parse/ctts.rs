@@ -0,0 +1,151 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+use mediasan_common::error::WhileParsingType;
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingBox};
+use super::{BoundedArray, BoxType, FullBoxHeader, Mp4Value, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, Default)]
+pub struct CttsBox {
+    header: FullBoxHeader,
+    entries: BoundedArray<u32, RawCttsEntry>,
+}
+
+/// A run of samples with the same composition time offset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CttsEntry {
+    pub sample_count: u32,
+    pub sample_offset: i64,
+}
+
+/// The encoded form of a [`CttsEntry`], whose sample offset is signed only in version 1 of the box.
+#[derive(Clone, Copy, Debug, Default)]
+struct RawCttsEntry {
+    sample_count: u32,
+    sample_offset: u32,
+}
+
+const NAME: BoxType = BoxType::CTTS;
+
+impl CttsBox {
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = CttsEntry> + '_ {
+        let signed = self.header.version == 1;
+        self.entries.entries().map(move |entry| {
+            let RawCttsEntry { sample_count, sample_offset } = entry.get().unwrap_or_else(|_| unreachable!());
+            let sample_offset = match signed {
+                true => (sample_offset as i32).into(),
+                false => sample_offset.into(),
+            };
+            CttsEntry { sample_count, sample_offset }
+        })
+    }
+
+    pub fn entry_count(&self) -> u32 {
+        self.entries.entry_count()
+    }
+}
+
+impl ParseBox for CttsBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version <= 1,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingBox(NAME),
+        );
+        let entries = BoundedArray::parse(&mut *buf).while_parsing_field(NAME, "entries")?;
+        Ok(Self { header, entries })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for CttsBox {
+    fn encoded_len(&self) -> u64 {
+        self.header.encoded_len() + self.entries.encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.entries.put_buf(&mut out);
+    }
+}
+
+impl FromIterator<CttsEntry> for CttsBox {
+    /// Collects entries into a version 1 box, with signed sample offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any sample offset does not fit in an `i32`.
+    fn from_iter<I: IntoIterator<Item = CttsEntry>>(entries: I) -> Self {
+        let entries = entries.into_iter().map(|CttsEntry { sample_count, sample_offset }| {
+            let sample_offset = i32::try_from(sample_offset).expect("sample offset out of range") as u32;
+            RawCttsEntry { sample_count, sample_offset }
+        });
+        Self { header: FullBoxHeader { version: 1, flags: 0 }, entries: entries.collect() }
+    }
+}
+
+impl super::Mp4Prim for RawCttsEntry {
+    fn parse<B: Buf>(mut buf: B) -> Result<Self, ParseError> {
+        ensure_attach!(
+            buf.remaining() >= 8,
+            ParseError::TruncatedBox,
+            WhileParsingType::new::<CttsEntry>(),
+        );
+        Ok(Self { sample_count: buf.get_u32(), sample_offset: buf.get_u32() })
+    }
+
+    fn encoded_len() -> u64 {
+        8
+    }
+
+    fn put_buf<B: BufMut>(&self, mut buf: B) {
+        buf.put_u32(self.sample_count);
+        buf.put_u32(self.sample_offset);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let entries = [
+            CttsEntry { sample_count: 2, sample_offset: -1 },
+            CttsEntry { sample_count: 1, sample_offset: 3 },
+        ];
+        let mut data = BytesMut::new();
+        CttsBox::from_iter(entries).put_buf(&mut data);
+        let ctts = CttsBox::parse(&mut data).unwrap();
+        assert_eq!(ctts.entries().collect::<Vec<_>>(), entries);
+    }
+
+    #[test]
+    fn version_0_unsigned() {
+        let mut data = BytesMut::new();
+        FullBoxHeader::default().put_buf(&mut data);
+        1u32.put_buf(&mut data);
+        1u32.put_buf(&mut data);
+        u32::MAX.put_buf(&mut data);
+        let ctts = CttsBox::parse(&mut data).unwrap();
+        let expected = CttsEntry { sample_count: 1, sample_offset: u32::MAX.into() };
+        assert_eq!(ctts.entries().collect::<Vec<_>>(), [expected]);
+    }
+
+    #[test]
+    fn invalid_version() {
+        let mut data = BytesMut::new();
+        FullBoxHeader { version: 2, flags: 0 }.put_buf(&mut data);
+        0u32.put_buf(&mut data);
+        let err = CttsBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
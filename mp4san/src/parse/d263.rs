@@ -0,0 +1,79 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::WhileParsingField;
+use super::{BoxType, Boxes, FourCC, ParseBox, ParseError, ParsedBox};
+
+/// An H.263 decoder configuration, as defined by 3GPP TS 26.244 section 6.8.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "d263"]
+pub struct D263Box {
+    pub vendor: FourCC,
+    pub decoder_version: u8,
+    pub h263_level: u8,
+    pub h263_profile: u8,
+
+    /// An optional bitrate box (`bitr`).
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::D263;
+
+/// The levels defined by ITU-T H.263 Annex X.
+const H263_LEVELS: [u8; 8] = [10, 20, 30, 40, 45, 50, 60, 70];
+
+/// The highest profile defined by ITU-T H.263 Annex X.
+const MAX_H263_PROFILE: u8 = 8;
+
+impl D263Box {
+    pub fn new(h263_level: u8, h263_profile: u8) -> Self {
+        Self { vendor: FourCC { value: [0; 4] }, decoder_version: 0, h263_level, h263_profile, children: vec![].into() }
+    }
+
+    /// Validates the H.263 profile and level against those defined by ITU-T H.263 Annex X.
+    pub fn validate(&self) -> Result<(), ParseError> {
+        ensure_attach!(
+            self.h263_profile <= MAX_H263_PROFILE,
+            ParseError::InvalidInput,
+            format!("invalid H.263 profile {}", self.h263_profile),
+            WhileParsingField(NAME, "H263_Profile"),
+        );
+        ensure_attach!(
+            H263_LEVELS.contains(&self.h263_level),
+            ParseError::InvalidInput,
+            format!("invalid H.263 level {}", self.h263_level),
+            WhileParsingField(NAME, "H263_Level"),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        D263Box::new(45, 3).put_buf(&mut data);
+        assert_eq!(data.len() as u64, D263Box::new(45, 3).encoded_len());
+        let d263 = D263Box::parse(&mut data).unwrap();
+        assert_eq!((d263.h263_level, d263.h263_profile), (45, 3));
+        d263.validate().unwrap();
+    }
+
+    #[test]
+    fn invalid_level() {
+        let err = D263Box::new(11, 0).validate().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn invalid_profile() {
+        let err = D263Box::new(10, MAX_H263_PROFILE + 1).validate().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
@@ -0,0 +1,98 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::WhileParsingField;
+use super::{BoxType, FourCC, ParseBox, ParseError, ParsedBox};
+
+/// An AMR or AMR-WB decoder configuration, as defined by 3GPP TS 26.244 section 6.7.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "damr"]
+pub struct DamrBox {
+    pub vendor: FourCC,
+    pub decoder_version: u8,
+    pub mode_set: u16,
+    pub mode_change_period: u8,
+    pub frames_per_sample: u8,
+}
+
+const NAME: BoxType = BoxType::DAMR;
+
+/// The bits of the mode set representing the modes of the AMR codec, including comfort noise.
+const AMR_MODE_SET_MASK: u16 = 0x01ff;
+
+/// The bits of the mode set representing the modes of the AMR-WB codec, including comfort noise.
+const AMR_WB_MODE_SET_MASK: u16 = 0x03ff;
+
+/// The maximum number of frames per sample allowed by 3GPP TS 26.244.
+const MAX_FRAMES_PER_SAMPLE: u8 = 15;
+
+impl DamrBox {
+    pub fn new(mode_set: u16, frames_per_sample: u8) -> Self {
+        Self {
+            vendor: FourCC { value: [0; 4] },
+            decoder_version: 0,
+            mode_set,
+            mode_change_period: 0,
+            frames_per_sample,
+        }
+    }
+
+    /// Validates that the mode set contains at least one mode of the AMR codec, or of the AMR-WB codec if `wideband`,
+    /// and that the number of frames per sample is within range.
+    ///
+    /// Bits of the mode set not representing any mode are ignored, as some writers set them.
+    pub fn validate(&self, wideband: bool) -> Result<(), ParseError> {
+        let mode_set_mask = if wideband {
+            AMR_WB_MODE_SET_MASK
+        } else {
+            AMR_MODE_SET_MASK
+        };
+        ensure_attach!(
+            self.mode_set & mode_set_mask != 0,
+            ParseError::InvalidInput,
+            format!("no valid modes in mode set 0x{:04x}", self.mode_set),
+            WhileParsingField(NAME, "mode_set"),
+        );
+        ensure_attach!(
+            (1..=MAX_FRAMES_PER_SAMPLE).contains(&self.frames_per_sample),
+            ParseError::InvalidInput,
+            format!("invalid frames per sample {}", self.frames_per_sample),
+            WhileParsingField(NAME, "frames_per_sample"),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        DamrBox::new(0x81ff, 1).put_buf(&mut data);
+        assert_eq!(data.len(), 9);
+        let damr = DamrBox::parse(&mut data).unwrap();
+        assert_eq!((damr.mode_set, damr.frames_per_sample), (0x81ff, 1));
+        damr.validate(false).unwrap();
+        damr.validate(true).unwrap();
+    }
+
+    #[test]
+    fn no_valid_modes() {
+        DamrBox::new(0x0200, 1).validate(true).unwrap();
+        let err = DamrBox::new(0x0200, 1).validate(false).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn invalid_frames_per_sample() {
+        for frames_per_sample in [0, MAX_FRAMES_PER_SAMPLE + 1] {
+            let err = DamrBox::new(0x01ff, frames_per_sample).validate(false).unwrap_err();
+            assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+        }
+    }
+}
@@ -0,0 +1,157 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::{Mp4Prim, ParseError};
+
+/// The header of an MPEG-4 descriptor, as defined by ISO/IEC 14496-1 section 8.3.3.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DescriptorHeader {
+    pub tag: u8,
+    pub size: u32,
+    size_len: u8,
+}
+
+/// The maximum number of bytes in the expandable size field of a descriptor header.
+const MAX_SIZE_LEN: u8 = 4;
+
+const SIZE_CONTINUATION_BIT: u8 = 0b1000_0000;
+const SIZE_BITS: u8 = 0b0111_1111;
+
+impl DescriptorHeader {
+    pub fn new(tag: u8, size: u32) -> Self {
+        Self { tag, size, size_len: 0 }
+    }
+
+    /// Construct a header for a descriptor with `tag` and data of `size` bytes, encoding its size field with at least
+    /// as many bytes as this header did.
+    pub fn with_size(&self, size: u32) -> Self {
+        Self { size, ..*self }
+    }
+
+    pub fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let tag = u8::parse(&mut *buf)?;
+        let mut size = 0;
+        let mut size_len = 0;
+        loop {
+            ensure_attach!(
+                size_len < MAX_SIZE_LEN,
+                ParseError::InvalidInput,
+                format!("descriptor tag {tag} size too long"),
+            );
+            let size_byte = u8::parse(&mut *buf)?;
+            size = (size << 7) | u32::from(size_byte & SIZE_BITS);
+            size_len += 1;
+            if size_byte & SIZE_CONTINUATION_BIT == 0 {
+                break;
+            }
+        }
+        Ok(Self { tag, size, size_len })
+    }
+
+    pub fn encoded_len(&self) -> u64 {
+        1 + u64::from(self.encoded_size_len())
+    }
+
+    pub fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.tag.put_buf(&mut out);
+        let size_len = self.encoded_size_len();
+        for size_index in (0..size_len).rev() {
+            let size_bits = (self.size >> (7 * u32::from(size_index))) as u8 & SIZE_BITS;
+            let continuation_bit = if size_index != 0 { SIZE_CONTINUATION_BIT } else { 0 };
+            out.put_u8(continuation_bit | size_bits);
+        }
+    }
+
+    fn encoded_size_len(&self) -> u8 {
+        let min_size_len = ((u32::BITS - self.size.leading_zeros()).max(1) + 6) / 7;
+        (min_size_len as u8).max(self.size_len)
+    }
+}
+
+/// Parse the header of a descriptor with `tag` from `buf`, and split its data from `buf`.
+///
+/// The descriptor size is checked against the remaining data, as [`BoxData::get_from_bytes_mut`] does for box sizes.
+///
+/// [`BoxData::get_from_bytes_mut`]: super::BoxData::get_from_bytes_mut
+pub(super) fn parse_descriptor(buf: &mut BytesMut, tag: u8) -> Result<(DescriptorHeader, BytesMut), ParseError> {
+    let header = DescriptorHeader::parse(&mut *buf)?;
+    ensure_attach!(
+        header.tag == tag,
+        ParseError::InvalidInput,
+        format!("expected descriptor tag {tag}, found {}", header.tag),
+    );
+    let data = split_descriptor_data(buf, &header)?;
+    Ok((header, data))
+}
+
+/// Check the sizes of the sequence of descriptors filling `buf`, without parsing their contents.
+pub(super) fn validate_descriptors(buf: &BytesMut) -> Result<(), ParseError> {
+    let mut buf = buf.clone();
+    while buf.has_remaining() {
+        let header = DescriptorHeader::parse(&mut buf)?;
+        split_descriptor_data(&mut buf, &header)?;
+    }
+    Ok(())
+}
+
+fn split_descriptor_data(buf: &mut BytesMut, header: &DescriptorHeader) -> Result<BytesMut, ParseError> {
+    ensure_attach!(
+        header.size as usize <= buf.len(),
+        ParseError::TruncatedBox,
+        format!("descriptor tag {} size {} too large", header.tag, header.size),
+    );
+    Ok(buf.split_to(header.size as usize))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip_minimal_size() {
+        for size in [0, 0x7f, 0x80, 0x3fff, 0x4000, 0x0fff_ffff] {
+            let header = DescriptorHeader::new(3, size);
+            let mut encoded = BytesMut::new();
+            header.put_buf(&mut encoded);
+            assert_eq!(encoded.len() as u64, header.encoded_len());
+            let parsed = DescriptorHeader::parse(&mut encoded).unwrap();
+            assert_eq!((parsed.tag, parsed.size), (3, size));
+            assert!(encoded.is_empty());
+        }
+    }
+
+    #[test]
+    fn roundtrip_padded_size() {
+        let mut data = BytesMut::from(&[5, 0x80, 0x80, 0x80, 0x02][..]);
+        let expected = data.clone();
+        let header = DescriptorHeader::parse(&mut data).unwrap();
+        assert_eq!((header.tag, header.size), (5, 2));
+        let mut encoded = BytesMut::new();
+        header.put_buf(&mut encoded);
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn size_too_long() {
+        let mut data = BytesMut::from(&[5, 0x80, 0x80, 0x80, 0x80, 0x02][..]);
+        let err = DescriptorHeader::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn size_past_end() {
+        let mut data = BytesMut::from(&[5, 0x03, 0, 0][..]);
+        let err = parse_descriptor(&mut data, 5).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+
+    #[test]
+    fn wrong_tag() {
+        let mut data = BytesMut::from(&[5, 0x00][..]);
+        let err = parse_descriptor(&mut data, 4).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
@@ -0,0 +1,73 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, Boxes, BoxesValidator, DrefBox, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "dinf"]
+pub struct DinfBox {
+    children: Boxes<DinfChildrenValidator>,
+}
+
+pub(crate) struct DinfChildrenValidator;
+
+const NAME: BoxType = BoxType::DINF;
+
+impl DinfBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes<DinfChildrenValidator>>>(children: C) -> Self {
+        Self { children: children.into() }
+    }
+
+    pub fn dref_mut(&mut self) -> Result<&mut DrefBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::DREF)
+    }
+
+    /// Parses every descendant box, returning [`ParseError::UnsupportedBox`] for any box not known to the parser.
+    pub fn validate_strict(&mut self) -> Result<(), ParseError> {
+        self.children.ensure_only(NAME, &[BoxType::DREF])?;
+        self.dref_mut()?.validate_strict()
+    }
+}
+
+impl BoxesValidator for DinfChildrenValidator {
+    fn validate<V>(children: &Boxes<V>) -> Result<(), ParseError> {
+        ensure_attach!(
+            children.box_types().any(|box_type| box_type == BoxType::DREF),
+            ParseError::MissingRequiredBox(BoxType::DREF),
+            WhileParsingField(NAME, "children"),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::Mp4Box;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        let dref = Mp4Box::with_data(DrefBox::self_contained().into()).unwrap();
+        DinfBox::with_children(vec![dref.into()]).put_buf(&mut data);
+        let mut dinf = DinfBox::parse(&mut data).unwrap();
+        assert!(dinf.dref_mut().unwrap().is_self_contained().unwrap());
+    }
+
+    #[test]
+    fn no_dref() {
+        let mut data = BytesMut::new();
+        DinfBox::with_children(vec![]).put_buf(&mut data);
+        let err = DinfBox::parse(&mut data).unwrap_err();
+        assert!(
+            matches!(err.get_ref(), ParseError::MissingRequiredBox(BoxType::DREF)),
+            "{err}",
+        );
+    }
+}
@@ -0,0 +1,198 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{
+    AlisBox, AnyMp4Box, BoxDepth, BoxType, Boxes, ConstFullBoxHeader, Mp4Box, Mp4Value, ParseBox, ParseError,
+    ParsedBox, UrlBox,
+};
+
+#[derive(Clone, Debug)]
+pub struct DrefBox {
+    header: ConstFullBoxHeader,
+    entries: Boxes,
+}
+
+const NAME: BoxType = BoxType::DREF;
+
+impl DrefBox {
+    /// Returns a data reference box with a single entry referring to the same file as the box containing it.
+    pub fn self_contained() -> Self {
+        Self::with_entries(vec![Self::self_contained_entry()])
+    }
+
+    pub fn with_entries<C: Into<Boxes>>(entries: C) -> Self {
+        Self { header: Default::default(), entries: entries.into() }
+    }
+
+    pub fn entry_count(&self) -> u32 {
+        self.entries.box_types().len() as u32
+    }
+
+    /// Returns whether all data entries refer to the same file as the box containing them.
+    pub fn is_self_contained(&mut self) -> Result<bool, ParseError> {
+        for entry in self.entries.iter_mut() {
+            if !Self::is_self_contained_entry(entry)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Replaces any data entries not referring to the same file as the box containing them with ones that do.
+    ///
+    /// Returns whether any data entries were replaced. Data entries are replaced in place, so that their indices as
+    /// referenced by sample entries are preserved.
+    pub fn make_self_contained(&mut self) -> Result<bool, ParseError> {
+        let mut replaced = false;
+        for entry in self.entries.iter_mut() {
+            if !Self::is_self_contained_entry(entry)? {
+                *entry = Self::self_contained_entry();
+                replaced = true;
+            }
+        }
+        Ok(replaced)
+    }
+
+    /// Parses every data entry, returning [`ParseError::UnsupportedBox`] for any entry not known to the parser.
+    pub fn validate_strict(&mut self) -> Result<(), ParseError> {
+        self.entries.ensure_only(NAME, &[BoxType::URL, BoxType::ALIS])?;
+        for entry in self.entries.iter_mut() {
+            Self::is_self_contained_entry(entry)?;
+        }
+        Ok(())
+    }
+
+    fn self_contained_entry() -> AnyMp4Box {
+        Mp4Box::with_data(UrlBox::self_contained().into())
+            .unwrap_or_else(|_| unreachable!())
+            .into()
+    }
+
+    fn is_self_contained_entry(entry: &mut AnyMp4Box) -> Result<bool, ParseError> {
+        if let Some(url) = entry
+            .parse_data_as::<UrlBox>()
+            .while_parsing_child(NAME, BoxType::URL)?
+        {
+            return Ok(url.is_self_contained());
+        }
+        if let Some(alis) = entry
+            .parse_data_as::<AlisBox>()
+            .while_parsing_child(NAME, BoxType::ALIS)?
+        {
+            return Ok(alis.is_self_contained());
+        }
+        // Other entry types, such as `urn `, are treated as referring to a separate file regardless of their flags.
+        Ok(false)
+    }
+}
+
+impl ParseBox for DrefBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        Self::parse_at_depth(buf, Default::default())
+    }
+
+    fn parse_at_depth(buf: &mut BytesMut, depth: BoxDepth) -> Result<Self, ParseError> {
+        let header = ConstFullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        let entry_count = u32::parse(&mut *buf).while_parsing_field(NAME, "entry_count")?;
+        let entries = Boxes::parse_at_depth(&mut *buf, depth).while_parsing_field(NAME, "entries")?;
+        ensure_attach!(
+            entries.box_types().len() == entry_count as usize,
+            ParseError::InvalidInput,
+            "entry count mismatch",
+            WhileParsingField(NAME, "entry_count"),
+        );
+        Ok(Self { header, entries })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for DrefBox {
+    fn encoded_len(&self) -> u64 {
+        self.header.encoded_len() + self.entry_count().encoded_len() + self.entries.encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.entry_count().put_buf(&mut out);
+        self.entries.put_buf(&mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::box_type::URL;
+    use crate::parse::FullBoxHeader;
+
+    use super::*;
+
+    fn external_url(flags: u32) -> AnyMp4Box {
+        let mut data = BytesMut::new();
+        FullBoxHeader { version: 0, flags }.put_buf(&mut data);
+        data.extend_from_slice(b"https://example.com/\0");
+        Mp4Box::with_bytes(URL, data)
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        let dref = DrefBox::self_contained();
+        dref.put_buf(&mut data);
+        assert_eq!(data.len() as u64, dref.encoded_len());
+        let mut dref = DrefBox::parse(&mut data).unwrap();
+        assert_eq!(dref.entry_count(), 1);
+        assert!(dref.is_self_contained().unwrap());
+    }
+
+    #[test]
+    fn entry_count_mismatch() {
+        let mut data = BytesMut::new();
+        DrefBox::self_contained().put_buf(&mut data);
+        data[7] = 2;
+        let err = DrefBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn external() {
+        let mut dref = DrefBox::with_entries(vec![DrefBox::self_contained_entry(), external_url(0)]);
+        assert!(!dref.is_self_contained().unwrap());
+        assert!(dref.make_self_contained().unwrap());
+        assert_eq!(dref.entry_count(), 2);
+        assert!(dref.is_self_contained().unwrap());
+        assert!(!dref.make_self_contained().unwrap());
+    }
+
+    #[test]
+    fn self_contained_url_with_location() {
+        let mut dref = DrefBox::with_entries(vec![external_url(UrlBox::SELF_CONTAINED_FLAG)]);
+        assert!(!dref.is_self_contained().unwrap());
+        assert!(dref.make_self_contained().unwrap());
+        let (mut data, mut expected) = (BytesMut::new(), BytesMut::new());
+        dref.put_buf(&mut data);
+        DrefBox::self_contained().put_buf(&mut expected);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn self_contained_alis() {
+        let mut data = BytesMut::new();
+        FullBoxHeader { version: 0, flags: AlisBox::SELF_CONTAINED_FLAG }.put_buf(&mut data);
+        let mut dref = DrefBox::with_entries(vec![Mp4Box::with_bytes(BoxType::ALIS, data)]);
+        assert!(dref.is_self_contained().unwrap());
+    }
+
+    #[test]
+    fn unknown_entry() {
+        let mut dref = DrefBox::with_entries(vec![Mp4Box::with_bytes(BoxType::FREE, BytesMut::new())]);
+        assert!(!dref.is_self_contained().unwrap());
+        assert!(dref.make_self_contained().unwrap());
+        assert!(dref.is_self_contained().unwrap());
+    }
+}
@@ -0,0 +1,62 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::mp4box::Boxes;
+use super::{BoxType, ElstBox, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "edts"]
+pub struct EdtsBox {
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::EDTS;
+
+impl EdtsBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes>>(children: C) -> Self {
+        Self { children: children.into() }
+    }
+
+    pub fn elst_mut(&mut self) -> Result<Option<&mut ElstBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::ELST)
+    }
+
+    /// Parses every descendant box, returning [`ParseError::UnsupportedBox`] for any box not known to the parser.
+    pub fn validate_strict(&mut self) -> Result<(), ParseError> {
+        self.children.ensure_only(NAME, &[BoxType::ELST])?;
+        self.elst_mut()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::{ElstEntry, Mp4Box};
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let entry = ElstEntry { segment_duration: 10, media_time: 0, media_rate_integer: 1, media_rate_fraction: 0 };
+        let elst = Mp4Box::with_data(ElstBox::with_entries(vec![entry]).into()).unwrap();
+        let mut data = BytesMut::new();
+        EdtsBox::with_children(vec![elst.into()]).put_buf(&mut data);
+        let mut edts = EdtsBox::parse(&mut data).unwrap();
+        assert_eq!(edts.elst_mut().unwrap().unwrap().entries(), [entry]);
+    }
+
+    #[test]
+    fn no_elst() {
+        let mut data = BytesMut::new();
+        EdtsBox::with_children(vec![]).put_buf(&mut data);
+        let mut edts = EdtsBox::parse(&mut data).unwrap();
+        assert!(edts.elst_mut().unwrap().is_none());
+    }
+}
@@ -0,0 +1,190 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ElstBox {
+    header: FullBoxHeader,
+    entries: Vec<ElstEntry>,
+}
+
+/// An edit list entry, mapping a segment of the movie timeline to a segment of the media timeline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ElstEntry {
+    /// The duration of the segment, in units of the movie timescale.
+    pub segment_duration: u64,
+
+    /// The starting time of the segment within the media, in units of the media timescale, or
+    /// [`EMPTY_EDIT_MEDIA_TIME`](ElstEntry::EMPTY_EDIT_MEDIA_TIME) for an empty edit.
+    pub media_time: i64,
+
+    pub media_rate_integer: i16,
+    pub media_rate_fraction: i16,
+}
+
+const NAME: BoxType = BoxType::ELST;
+
+impl ElstBox {
+    /// Construct an edit list with the given `entries`, using the version 1 layout only if required to encode them.
+    pub fn with_entries(entries: Vec<ElstEntry>) -> Self {
+        let needs_version_1 = entries
+            .iter()
+            .any(|entry| u32::try_from(entry.segment_duration).is_err() || i32::try_from(entry.media_time).is_err());
+        let header = FullBoxHeader { version: needs_version_1.into(), flags: 0 };
+        Self { header, entries }
+    }
+
+    pub fn entries(&self) -> &[ElstEntry] {
+        &self.entries
+    }
+
+    /// Returns the sum of the segment durations of all entries, in units of the movie timescale, or [`None`] on
+    /// overflow.
+    pub fn total_segment_duration(&self) -> Option<u64> {
+        (self.entries.iter()).try_fold(0u64, |duration, entry| duration.checked_add(entry.segment_duration))
+    }
+
+    fn entry_len(&self) -> u64 {
+        self.header.versioned_u64_len() * 2 + 2 * i16::encoded_len()
+    }
+}
+
+impl ElstEntry {
+    /// The media time of an empty edit, during which no media is presented.
+    pub const EMPTY_EDIT_MEDIA_TIME: i64 = -1;
+}
+
+impl ParseBox for ElstBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version <= 1,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingField(NAME, "header"),
+        );
+        let entry_count = u32::parse(&mut *buf).while_parsing_field(NAME, "entry_count")?;
+
+        let mut elst = Self { header, entries: Vec::new() };
+        ensure_attach!(
+            u64::from(entry_count) * elst.entry_len() <= buf.remaining() as u64,
+            ParseError::TruncatedBox,
+            WhileParsingField(NAME, "entries"),
+        );
+        elst.entries.reserve(entry_count as usize);
+        for _ in 0..entry_count {
+            let entry = parse_entry(&header, &mut *buf).while_parsing_field(NAME, "entries")?;
+            elst.entries.push(entry);
+        }
+        Ok(elst)
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+fn parse_entry(header: &FullBoxHeader, mut buf: &mut BytesMut) -> Result<ElstEntry, ParseError> {
+    let segment_duration = header.parse_versioned_u64(&mut buf)?;
+    let media_time = match header.version {
+        0 => i32::parse(&mut buf)?.into(),
+        _ => i64::parse(&mut buf)?,
+    };
+    ensure_attach!(
+        media_time >= ElstEntry::EMPTY_EDIT_MEDIA_TIME,
+        ParseError::InvalidInput,
+        format!("negative media time {media_time}"),
+    );
+    let media_rate_integer = i16::parse(&mut buf)?;
+    let media_rate_fraction = i16::parse(&mut buf)?;
+    Ok(ElstEntry { segment_duration, media_time, media_rate_integer, media_rate_fraction })
+}
+
+impl ParsedBox for ElstBox {
+    fn encoded_len(&self) -> u64 {
+        FullBoxHeader::encoded_len() + u32::encoded_len() + self.entries.len() as u64 * self.entry_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        (self.entries.len() as u32).put_buf(&mut out);
+        for entry in &self.entries {
+            self.header.put_versioned_u64(entry.segment_duration, &mut out);
+            match self.header.version {
+                0 => (entry.media_time as i32).put_buf(&mut out),
+                _ => entry.media_time.put_buf(&mut out),
+            }
+            entry.media_rate_integer.put_buf(&mut out);
+            entry.media_rate_fraction.put_buf(&mut out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_entry(segment_duration: u64, media_time: i64) -> ElstEntry {
+        ElstEntry { segment_duration, media_time, media_rate_integer: 1, media_rate_fraction: 0 }
+    }
+
+    #[test]
+    fn roundtrip_version_0() {
+        let elst = ElstBox::with_entries(vec![test_entry(10, -1), test_entry(20, 1024)]);
+        let mut data = BytesMut::new();
+        elst.put_buf(&mut data);
+        assert_eq!(data.len() as u64, elst.encoded_len());
+        assert_eq!(data.len(), 4 + 4 + 2 * 12);
+        assert_eq!(ElstBox::parse(&mut data).unwrap(), elst);
+    }
+
+    #[test]
+    fn roundtrip_version_1() {
+        let elst = ElstBox::with_entries(vec![test_entry(u64::from(u32::MAX) + 1, -1), test_entry(20, 1024)]);
+        let mut data = BytesMut::new();
+        elst.put_buf(&mut data);
+        assert_eq!(data.len() as u64, elst.encoded_len());
+        assert_eq!(data.len(), 4 + 4 + 2 * 20);
+        assert_eq!(ElstBox::parse(&mut data).unwrap(), elst);
+        assert_eq!(elst.total_segment_duration(), Some(u64::from(u32::MAX) + 21));
+    }
+
+    #[test]
+    fn total_segment_duration_overflow() {
+        let elst = ElstBox::with_entries(vec![test_entry(u64::MAX, 0), test_entry(1, 0)]);
+        assert_eq!(elst.total_segment_duration(), None);
+    }
+
+    #[test]
+    fn negative_media_time() {
+        let elst = ElstBox::with_entries(vec![test_entry(10, -2)]);
+        let mut data = BytesMut::new();
+        elst.put_buf(&mut data);
+        let err = ElstBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn entry_count_too_large() {
+        let mut data = BytesMut::new();
+        FullBoxHeader::default().put_buf(&mut data);
+        u32::MAX.put_buf(&mut data);
+        data.extend_from_slice(&[0; 12]);
+        let err = ElstBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+
+    #[test]
+    fn invalid_version() {
+        let mut data = BytesMut::new();
+        FullBoxHeader { version: 2, flags: 0 }.put_buf(&mut data);
+        0u32.put_buf(&mut data);
+        let err = ElstBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
@@ -0,0 +1,50 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{AudioSampleEntry, BoxType, Boxes, ParseBox, ParseError, ParsedBox, SinfBox};
+
+/// An encrypted audio sample entry, as defined by ISO/IEC 14496-12 section 8.12.
+///
+/// Besides the protection scheme information boxes (`sinf`), its children are those of the original sample entry.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "enca"]
+pub struct EncaBox {
+    pub entry: AudioSampleEntry,
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::ENCA;
+
+impl EncaBox {
+    pub fn with_children<C: Into<Boxes>>(entry: AudioSampleEntry, children: C) -> Self {
+        Self { entry, children: children.into() }
+    }
+
+    /// Returns the protection scheme information boxes (`sinf`), of which there must be at least one.
+    pub fn sinfs(&mut self) -> Result<Vec<&mut SinfBox>, ParseError> {
+        let sinfs = self.children.get_mut().collect::<Result<Vec<_>, _>>();
+        let sinfs = sinfs.while_parsing_child(NAME, BoxType::SINF)?;
+        ensure_attach!(!sinfs.is_empty(), ParseError::MissingRequiredBox(BoxType::SINF));
+        Ok(sinfs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::fourcc;
+    use crate::util::test::test_enca;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut enca_box = test_enca(8);
+        let enca = enca_box.parse_data_as::<EncaBox>().unwrap().unwrap();
+        assert_eq!(enca.entry.channelcount, 2);
+        let mut sinfs = enca.sinfs().unwrap();
+        assert_eq!(sinfs.len(), 1);
+        assert_ne!(sinfs[0].frma_mut().unwrap().data_format, fourcc::ENCA);
+    }
+}
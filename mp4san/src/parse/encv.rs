@@ -0,0 +1,50 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{BoxType, Boxes, ParseBox, ParseError, ParsedBox, SinfBox, VisualSampleEntry};
+
+/// An encrypted visual sample entry, as defined by ISO/IEC 14496-12 section 8.12.
+///
+/// Besides the protection scheme information boxes (`sinf`), its children are those of the original sample entry.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "encv"]
+pub struct EncvBox {
+    pub entry: VisualSampleEntry,
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::ENCV;
+
+impl EncvBox {
+    pub fn with_children<C: Into<Boxes>>(entry: VisualSampleEntry, children: C) -> Self {
+        Self { entry, children: children.into() }
+    }
+
+    /// Returns the protection scheme information boxes (`sinf`), of which there must be at least one.
+    pub fn sinfs(&mut self) -> Result<Vec<&mut SinfBox>, ParseError> {
+        let sinfs = self.children.get_mut().collect::<Result<Vec<_>, _>>();
+        let sinfs = sinfs.while_parsing_child(NAME, BoxType::SINF)?;
+        ensure_attach!(!sinfs.is_empty(), ParseError::MissingRequiredBox(BoxType::SINF));
+        Ok(sinfs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::fourcc;
+    use crate::util::test::test_encv;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut encv_box = test_encv(8);
+        let encv = encv_box.parse_data_as::<EncvBox>().unwrap().unwrap();
+        assert_eq!((encv.entry.width, encv.entry.height), (320, 240));
+        let mut sinfs = encv.sinfs().unwrap();
+        assert_eq!(sinfs.len(), 1);
+        assert_ne!(sinfs[0].frma_mut().unwrap().data_format, fourcc::ENCV);
+    }
+}
@@ -24,6 +24,17 @@ pub enum ParseError {
     #[error("Invalid input")]
     InvalidInput,
 
+    /// The sanitized output failed verification, indicating a bug in the sanitizer.
+    ///
+    /// This is only returned when [`Config::verify_output`](crate::Config::verify_output) is enabled.
+    #[error("Invalid sanitized output")]
+    InvalidOutput,
+
+    /// The input is invalid because the byte range of a sample, as computed from the sample tables, lies outside the
+    /// media data or overlaps the samples of another track.
+    #[error("Invalid sample range")]
+    InvalidSampleRange,
+
     /// The input is invalid because it is missing a box required by the ISO specification.
     #[error("Missing required `{_0}` box")]
     MissingRequiredBox(BoxType),
@@ -62,6 +73,10 @@ pub trait __ParseResultExt: ResultExt + Sized {
         self.attach_printable(WhileParsingField(box_type, field_name))
     }
 
+    fn while_parsing_structure_field(self, structure_name: &'static str, field_name: &'static str) -> Self {
+        self.attach_printable(WhileParsingStructureField(structure_name, field_name))
+    }
+
     fn while_parsing_child(self, box_type: BoxType, child_box_type: BoxType) -> Self {
         self.attach_printable(WhileParsingChild(box_type, child_box_type))
     }
@@ -88,6 +103,11 @@ pub(crate) struct WhileParsingBox(pub(crate) BoxType);
 #[display(fmt = "while parsing `{}` box field `{}`", _0, _1)]
 pub(crate) struct WhileParsingField<T>(pub(crate) BoxType, pub(crate) T);
 
+/// A field of a syntax structure which is not a box, such as a NAL unit or a descriptor.
+#[derive(Clone, Copy, Debug, Display)]
+#[display(fmt = "while parsing {} field `{}`", _0, _1)]
+pub(crate) struct WhileParsingStructureField(pub(crate) &'static str, pub(crate) &'static str);
+
 #[derive(Clone, Copy, Debug, Display)]
 #[display(fmt = "while parsing `{}` box child `{}`", _0, _1)]
 pub(crate) struct WhileParsingChild(pub(crate) BoxType, pub(crate) BoxType);
@@ -0,0 +1,342 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::descriptor::{parse_descriptor, validate_descriptors, DescriptorHeader};
+use super::error::{ParseResultExt, WhileParsingField, WhileParsingStructureField};
+use super::{AudioSpecificConfig, BoxType, ConstFullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+/// An elementary stream descriptor box, as defined by ISO/IEC 14496-14 section 6.7.2.
+#[derive(Clone, Debug)]
+pub struct EsdsBox {
+    header: ConstFullBoxHeader,
+    pub es_descriptor: EsDescriptor,
+}
+
+/// An `ES_Descriptor`, as defined by ISO/IEC 14496-1 section 7.2.6.5.
+#[derive(Clone, Debug)]
+pub struct EsDescriptor {
+    header: DescriptorHeader,
+    pub es_id: u16,
+    flags: u8,
+    depends_on_es_id: Option<u16>,
+    url: Option<BytesMut>,
+    ocr_es_id: Option<u16>,
+    pub decoder_config: DecoderConfigDescriptor,
+    descriptors: BytesMut,
+}
+
+/// A `DecoderConfigDescriptor`, as defined by ISO/IEC 14496-1 section 7.2.6.6.
+#[derive(Clone, Debug)]
+pub struct DecoderConfigDescriptor {
+    header: DescriptorHeader,
+    pub object_type_indication: u8,
+    stream_type_and_flags: u8,
+    buffer_size_db: [u8; 3],
+    pub max_bitrate: u32,
+    pub avg_bitrate: u32,
+    pub decoder_specific_info: Option<DecoderSpecificInfo>,
+    descriptors: BytesMut,
+}
+
+/// A `DecoderSpecificInfo` descriptor, as defined by ISO/IEC 14496-1 section 7.2.6.7.
+#[derive(Clone, Debug)]
+pub struct DecoderSpecificInfo {
+    header: DescriptorHeader,
+    pub data: BytesMut,
+}
+
+const NAME: BoxType = BoxType::ESDS;
+
+pub const ES_DESCRIPTOR_TAG: u8 = 0x03;
+pub const DECODER_CONFIG_DESCRIPTOR_TAG: u8 = 0x04;
+pub const DECODER_SPECIFIC_INFO_TAG: u8 = 0x05;
+
+/// The `objectTypeIndication` of an MPEG-4 audio (ISO/IEC 14496-3) stream.
+pub const OBJECT_TYPE_INDICATION_MPEG4_AUDIO: u8 = 0x40;
+
+const STREAM_DEPENDENCE_FLAG: u8 = 0b1000_0000;
+const URL_FLAG: u8 = 0b0100_0000;
+const OCR_STREAM_FLAG: u8 = 0b0010_0000;
+
+const ES_DESCRIPTOR: &str = "ES_Descriptor";
+const DECODER_CONFIG_DESCRIPTOR: &str = "DecoderConfigDescriptor";
+
+impl EsdsBox {
+    /// Parses the `AudioSpecificConfig` in the decoder specific info of an MPEG-4 audio stream.
+    pub fn parse_audio_specific_config(&self) -> Result<AudioSpecificConfig, ParseError> {
+        let decoder_config = &self.es_descriptor.decoder_config;
+        let object_type_indication = decoder_config.object_type_indication;
+        ensure_attach!(
+            object_type_indication == OBJECT_TYPE_INDICATION_MPEG4_AUDIO,
+            ParseError::InvalidInput,
+            format!("unsupported object type indication {object_type_indication:#04x}"),
+            WhileParsingStructureField(DECODER_CONFIG_DESCRIPTOR, "objectTypeIndication"),
+            WhileParsingField(NAME, "es_descriptor"),
+        );
+        let Some(decoder_specific_info) = &decoder_config.decoder_specific_info else {
+            bail_attach!(
+                ParseError::InvalidInput,
+                "missing decoder specific info",
+                WhileParsingStructureField(DECODER_CONFIG_DESCRIPTOR, "decSpecificInfo"),
+                WhileParsingField(NAME, "es_descriptor"),
+            );
+        };
+        AudioSpecificConfig::parse(&decoder_specific_info.data)
+            .while_parsing_structure_field(DECODER_CONFIG_DESCRIPTOR, "decSpecificInfo")
+            .while_parsing_field(NAME, "es_descriptor")
+    }
+}
+
+impl ParseBox for EsdsBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = ConstFullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        let es_descriptor = EsDescriptor::parse(&mut *buf).while_parsing_field(NAME, "es_descriptor")?;
+        ensure_attach!(
+            buf.is_empty(),
+            ParseError::InvalidInput,
+            "extra unparsed data",
+            WhileParsingField(NAME, "es_descriptor"),
+        );
+        Ok(Self { header, es_descriptor })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for EsdsBox {
+    fn encoded_len(&self) -> u64 {
+        <ConstFullBoxHeader>::encoded_len() + self.es_descriptor.encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.es_descriptor.put_buf(out);
+    }
+}
+
+impl EsDescriptor {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let (header, mut data) = parse_descriptor(buf, ES_DESCRIPTOR_TAG)?;
+        let es_id = u16::parse(&mut data).while_parsing_structure_field(ES_DESCRIPTOR, "ES_ID")?;
+        let flags = u8::parse(&mut data).while_parsing_structure_field(ES_DESCRIPTOR, "flags")?;
+        let mut depends_on_es_id = None;
+        if flags & STREAM_DEPENDENCE_FLAG != 0 {
+            depends_on_es_id =
+                Some(u16::parse(&mut data).while_parsing_structure_field(ES_DESCRIPTOR, "dependsOn_ES_ID")?);
+        }
+        let mut url = None;
+        if flags & URL_FLAG != 0 {
+            let url_len = u8::parse(&mut data).while_parsing_structure_field(ES_DESCRIPTOR, "URLlength")?;
+            ensure_attach!(
+                usize::from(url_len) <= data.len(),
+                ParseError::TruncatedBox,
+                WhileParsingStructureField(ES_DESCRIPTOR, "URLstring"),
+            );
+            url = Some(data.split_to(url_len.into()));
+        }
+        let mut ocr_es_id = None;
+        if flags & OCR_STREAM_FLAG != 0 {
+            ocr_es_id = Some(u16::parse(&mut data).while_parsing_structure_field(ES_DESCRIPTOR, "OCR_ES_Id")?);
+        }
+        let decoder_config =
+            DecoderConfigDescriptor::parse(&mut data).while_parsing_structure_field(ES_DESCRIPTOR, "decConfigDescr")?;
+        validate_descriptors(&data).while_parsing_structure_field(ES_DESCRIPTOR, "descriptors")?;
+        Ok(Self { header, es_id, flags, depends_on_es_id, url, ocr_es_id, decoder_config, descriptors: data })
+    }
+
+    fn data_len(&self) -> u64 {
+        let url_len = self.url.as_ref().map(|url| 1 + url.len() as u64).unwrap_or_default();
+        2 + 1
+            + 2 * self.depends_on_es_id.iter().count() as u64
+            + url_len
+            + 2 * self.ocr_es_id.iter().count() as u64
+            + self.decoder_config.encoded_len()
+            + self.descriptors.len() as u64
+    }
+
+    fn calculated_header(&self) -> DescriptorHeader {
+        self.header.with_size(self.data_len() as u32)
+    }
+
+    fn encoded_len(&self) -> u64 {
+        self.calculated_header().encoded_len() + self.data_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.calculated_header().put_buf(out);
+        self.es_id.put_buf(&mut out);
+        self.flags.put_buf(&mut out);
+        if let Some(depends_on_es_id) = self.depends_on_es_id {
+            depends_on_es_id.put_buf(&mut out);
+        }
+        if let Some(url) = &self.url {
+            (url.len() as u8).put_buf(&mut out);
+            out.put_slice(url);
+        }
+        if let Some(ocr_es_id) = self.ocr_es_id {
+            ocr_es_id.put_buf(&mut out);
+        }
+        self.decoder_config.put_buf(out);
+        out.put_slice(&self.descriptors);
+    }
+}
+
+impl DecoderConfigDescriptor {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let (header, mut data) = parse_descriptor(buf, DECODER_CONFIG_DESCRIPTOR_TAG)?;
+        let object_type_indication =
+            u8::parse(&mut data).while_parsing_structure_field(DECODER_CONFIG_DESCRIPTOR, "objectTypeIndication")?;
+        let stream_type_and_flags =
+            u8::parse(&mut data).while_parsing_structure_field(DECODER_CONFIG_DESCRIPTOR, "streamType")?;
+        let buffer_size_db =
+            <[u8; 3]>::parse(&mut data).while_parsing_structure_field(DECODER_CONFIG_DESCRIPTOR, "bufferSizeDB")?;
+        let max_bitrate =
+            u32::parse(&mut data).while_parsing_structure_field(DECODER_CONFIG_DESCRIPTOR, "maxBitrate")?;
+        let avg_bitrate =
+            u32::parse(&mut data).while_parsing_structure_field(DECODER_CONFIG_DESCRIPTOR, "avgBitrate")?;
+        let mut decoder_specific_info = None;
+        if data.first() == Some(&DECODER_SPECIFIC_INFO_TAG) {
+            let (header, data) = parse_descriptor(&mut data, DECODER_SPECIFIC_INFO_TAG)
+                .while_parsing_structure_field(DECODER_CONFIG_DESCRIPTOR, "decSpecificInfo")?;
+            decoder_specific_info = Some(DecoderSpecificInfo { header, data });
+        }
+        validate_descriptors(&data).while_parsing_structure_field(DECODER_CONFIG_DESCRIPTOR, "descriptors")?;
+        Ok(Self {
+            header,
+            object_type_indication,
+            stream_type_and_flags,
+            buffer_size_db,
+            max_bitrate,
+            avg_bitrate,
+            decoder_specific_info,
+            descriptors: data,
+        })
+    }
+
+    fn data_len(&self) -> u64 {
+        13 + self
+            .decoder_specific_info
+            .as_ref()
+            .map(DecoderSpecificInfo::encoded_len)
+            .unwrap_or_default()
+            + self.descriptors.len() as u64
+    }
+
+    fn calculated_header(&self) -> DescriptorHeader {
+        self.header.with_size(self.data_len() as u32)
+    }
+
+    fn encoded_len(&self) -> u64 {
+        self.calculated_header().encoded_len() + self.data_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.calculated_header().put_buf(out);
+        self.object_type_indication.put_buf(&mut out);
+        self.stream_type_and_flags.put_buf(&mut out);
+        self.buffer_size_db.put_buf(&mut out);
+        self.max_bitrate.put_buf(&mut out);
+        self.avg_bitrate.put_buf(&mut out);
+        if let Some(decoder_specific_info) = &self.decoder_specific_info {
+            decoder_specific_info.put_buf(out);
+        }
+        out.put_slice(&self.descriptors);
+    }
+}
+
+impl DecoderSpecificInfo {
+    fn calculated_header(&self) -> DescriptorHeader {
+        self.header.with_size(self.data.len() as u32)
+    }
+
+    fn encoded_len(&self) -> u64 {
+        self.calculated_header().encoded_len() + self.data.len() as u64
+    }
+
+    fn put_buf(&self, out: &mut dyn BufMut) {
+        self.calculated_header().put_buf(out);
+        out.put_slice(&self.data);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::write_test_esds_data;
+
+    use super::*;
+
+    const TEST_AUDIO_SPECIFIC_CONFIG: &[u8] = &[0x12, 0x10];
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        write_test_esds_data(&mut data, TEST_AUDIO_SPECIFIC_CONFIG);
+        let expected = data.clone();
+        let esds = EsdsBox::parse(&mut data).unwrap();
+        assert_eq!(esds.es_descriptor.es_id, 1);
+        assert_eq!(
+            esds.es_descriptor.decoder_config.object_type_indication,
+            OBJECT_TYPE_INDICATION_MPEG4_AUDIO
+        );
+        assert_eq!(esds.parse_audio_specific_config().unwrap().sampling_frequency, 44100);
+        let mut encoded = BytesMut::new();
+        esds.put_buf(&mut encoded);
+        assert_eq!(encoded.len() as u64, esds.encoded_len());
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn modified_decoder_specific_info() {
+        let mut data = BytesMut::new();
+        write_test_esds_data(&mut data, TEST_AUDIO_SPECIFIC_CONFIG);
+        let mut esds = EsdsBox::parse(&mut data).unwrap();
+        let decoder_specific_info = esds
+            .es_descriptor
+            .decoder_config
+            .decoder_specific_info
+            .as_mut()
+            .unwrap();
+        decoder_specific_info.data.extend_from_slice(&[0; 0x100]);
+        let mut encoded = BytesMut::new();
+        esds.put_buf(&mut encoded);
+        assert_eq!(encoded.len() as u64, esds.encoded_len());
+        let esds = EsdsBox::parse(&mut encoded).unwrap();
+        let decoder_specific_info = esds.es_descriptor.decoder_config.decoder_specific_info.unwrap();
+        assert_eq!(decoder_specific_info.data.len(), 0x102);
+    }
+
+    #[test]
+    fn descriptor_size_past_end() {
+        let mut data = BytesMut::new();
+        write_test_esds_data(&mut data, TEST_AUDIO_SPECIFIC_CONFIG);
+        data[8] += 1; // ES_Descriptor size
+        let err = EsdsBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+
+    #[test]
+    fn nested_descriptor_size_past_end() {
+        let mut data = BytesMut::new();
+        write_test_esds_data(&mut data, TEST_AUDIO_SPECIFIC_CONFIG);
+        data[8] += 1; // ES_Descriptor size
+        data[16] += 2; // DecoderConfigDescriptor size
+        data.put_u8(0);
+        let err = EsdsBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+
+    #[test]
+    fn unsupported_object_type_indication() {
+        let mut data = BytesMut::new();
+        write_test_esds_data(&mut data, TEST_AUDIO_SPECIFIC_CONFIG);
+        data[17] = 0x6b; // MPEG-1 audio
+        let esds = EsdsBox::parse(&mut data).unwrap();
+        let err = esds.parse_audio_specific_config().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
@@ -0,0 +1,35 @@
+#![allow(missing_docs)]
+
+use super::{FourCC, ParseBox, ParsedBox};
+
+/// An original format box, as defined by ISO/IEC 14496-12 section 8.12.2.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "frma"]
+pub struct FrmaBox {
+    /// The type of the sample entry before it was transformed into a protected sample entry.
+    pub data_format: FourCC,
+}
+
+impl FrmaBox {
+    pub fn new(data_format: FourCC) -> Self {
+        Self { data_format }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::fourcc;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        FrmaBox::new(fourcc::AVC1).put_buf(&mut data);
+        assert_eq!(data.len(), 4);
+        let frma = FrmaBox::parse(&mut data).unwrap();
+        assert_eq!(frma.data_format, fourcc::AVC1);
+    }
+}
@@ -15,7 +15,7 @@ impl FtypBox {
         Self { major_brand, minor_version, compatible_brands: compatible_brands.into_iter().collect() }
     }
 
-    pub fn compatible_brands(&self) -> impl Iterator<Item = FourCC> + ExactSizeIterator + '_ {
+    pub fn compatible_brands(&self) -> impl ExactSizeIterator<Item = FourCC> + '_ {
         self.compatible_brands.entries().map(|entry| entry.get().unwrap())
     }
 }
@@ -0,0 +1,440 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingStructureField};
+use super::rbsp::RbspReader;
+use super::ParseError;
+
+/// A parsed H.264 sequence parameter set (SPS), as defined by ISO/IEC 14496-10 section 7.3.2.1.1.
+///
+/// Only the fields up to the frame cropping rectangle are parsed; video usability information is not.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AvcSps {
+    pub profile_idc: u8,
+    pub constraint_set_flags: u8,
+    pub level_idc: u8,
+    pub seq_parameter_set_id: u32,
+    pub chroma_format_idc: u32,
+    pub bit_depth_luma: u32,
+    pub bit_depth_chroma: u32,
+    pub max_num_ref_frames: u32,
+    pub width_in_mbs: u32,
+    pub height_in_mbs: u32,
+}
+
+/// A parsed H.264 picture parameter set (PPS), as defined by ISO/IEC 14496-10 section 7.3.2.2.
+///
+/// Only the fields up to `redundant_pic_cnt_present_flag` are parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AvcPps {
+    pub pic_parameter_set_id: u32,
+    pub seq_parameter_set_id: u32,
+}
+
+pub const AVC_NAL_UNIT_TYPE_SPS: u8 = 7;
+pub const AVC_NAL_UNIT_TYPE_PPS: u8 = 8;
+
+/// The `profile_idc` values defined by ISO/IEC 14496-10.
+const PROFILES: &[u8] = &[44, 66, 77, 83, 86, 88, 100, 110, 118, 122, 128, 134, 135, 138, 139, 244];
+
+/// The `profile_idc` values for which the SPS contains chroma format and bit depth fields.
+const HIGH_PROFILES: &[u8] = &[44, 83, 86, 100, 110, 118, 122, 128, 134, 135, 138, 139, 244];
+
+/// The `level_idc` values defined by ISO/IEC 14496-10 Table A-1.
+const LEVELS: &[u8] = &[
+    9, 10, 11, 12, 13, 20, 21, 22, 30, 31, 32, 40, 41, 42, 50, 51, 52, 60, 61, 62,
+];
+
+/// The maximum frame size in macroblocks of the highest level, as defined by ISO/IEC 14496-10 Table A-1.
+const MAX_FRAME_SIZE_IN_MBS: u32 = 139264;
+
+/// The maximum frame width or height in macroblocks of the highest level, `Sqrt(MaxFS * 8)`.
+const MAX_FRAME_DIMENSION_IN_MBS: u32 = 1055;
+
+/// The maximum size of the decoded picture buffer in frames, as defined by ISO/IEC 14496-10 section A.3.1.
+const MAX_NUM_REF_FRAMES: u32 = 16;
+
+const MAX_BIT_DEPTH_MINUS_8: u32 = 6;
+
+const SPS: &str = "SPS";
+const PPS: &str = "PPS";
+
+impl AvcSps {
+    pub fn parse(nal_unit: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = RbspReader::new(nal_unit);
+        parse_nal_unit_header(&mut reader, AVC_NAL_UNIT_TYPE_SPS)
+            .while_parsing_structure_field(SPS, "nal_unit_header")?;
+
+        let profile_idc = reader.read(8).while_parsing_structure_field(SPS, "profile_idc")?;
+        ensure_attach!(
+            PROFILES.contains(&profile_idc),
+            ParseError::InvalidInput,
+            format!("unknown profile {profile_idc}"),
+            WhileParsingStructureField(SPS, "profile_idc"),
+        );
+        let constraint_set_flags = reader
+            .read(8)
+            .while_parsing_structure_field(SPS, "constraint_set_flags")?;
+        let level_idc = reader.read(8).while_parsing_structure_field(SPS, "level_idc")?;
+        ensure_attach!(
+            LEVELS.contains(&level_idc),
+            ParseError::InvalidInput,
+            format!("unknown level {level_idc}"),
+            WhileParsingStructureField(SPS, "level_idc"),
+        );
+        let seq_parameter_set_id = reader
+            .read_ue_max(31)
+            .while_parsing_structure_field(SPS, "seq_parameter_set_id")?;
+
+        let mut chroma_format_idc = 1;
+        let mut bit_depth_luma_minus8 = 0;
+        let mut bit_depth_chroma_minus8 = 0;
+        if HIGH_PROFILES.contains(&profile_idc) {
+            chroma_format_idc = reader
+                .read_ue_max(3)
+                .while_parsing_structure_field(SPS, "chroma_format_idc")?;
+            if chroma_format_idc == 3 {
+                reader
+                    .skip(1)
+                    .while_parsing_structure_field(SPS, "separate_colour_plane_flag")?;
+            }
+            bit_depth_luma_minus8 = reader
+                .read_ue_max(MAX_BIT_DEPTH_MINUS_8)
+                .while_parsing_structure_field(SPS, "bit_depth_luma_minus8")?;
+            bit_depth_chroma_minus8 = reader
+                .read_ue_max(MAX_BIT_DEPTH_MINUS_8)
+                .while_parsing_structure_field(SPS, "bit_depth_chroma_minus8")?;
+            reader
+                .skip(1)
+                .while_parsing_structure_field(SPS, "qpprime_y_zero_transform_bypass_flag")?;
+            if reader
+                .read_bit()
+                .while_parsing_structure_field(SPS, "seq_scaling_matrix_present_flag")?
+            {
+                let scaling_list_count = if chroma_format_idc != 3 { 8 } else { 12 };
+                for scaling_list_index in 0..scaling_list_count {
+                    if reader
+                        .read_bit()
+                        .while_parsing_structure_field(SPS, "seq_scaling_list_present_flag")?
+                    {
+                        let size = if scaling_list_index < 6 { 16 } else { 64 };
+                        parse_scaling_list(&mut reader, size).while_parsing_structure_field(SPS, "scaling_list")?;
+                    }
+                }
+            }
+        }
+
+        reader
+            .read_ue_max(12)
+            .while_parsing_structure_field(SPS, "log2_max_frame_num_minus4")?;
+        let pic_order_cnt_type = reader
+            .read_ue_max(2)
+            .while_parsing_structure_field(SPS, "pic_order_cnt_type")?;
+        match pic_order_cnt_type {
+            0 => {
+                reader
+                    .read_ue_max(12)
+                    .while_parsing_structure_field(SPS, "log2_max_pic_order_cnt_lsb_minus4")?;
+            }
+            1 => {
+                reader
+                    .skip(1)
+                    .while_parsing_structure_field(SPS, "delta_pic_order_always_zero_flag")?;
+                reader
+                    .read_se()
+                    .while_parsing_structure_field(SPS, "offset_for_non_ref_pic")?;
+                reader
+                    .read_se()
+                    .while_parsing_structure_field(SPS, "offset_for_top_to_bottom_field")?;
+                let num_ref_frames_in_pic_order_cnt_cycle = reader
+                    .read_ue_max(255)
+                    .while_parsing_structure_field(SPS, "num_ref_frames_in_pic_order_cnt_cycle")?;
+                for _ in 0..num_ref_frames_in_pic_order_cnt_cycle {
+                    reader
+                        .read_se()
+                        .while_parsing_structure_field(SPS, "offset_for_ref_frame")?;
+                }
+            }
+            _ => {}
+        }
+
+        let max_num_ref_frames = reader
+            .read_ue_max(MAX_NUM_REF_FRAMES)
+            .while_parsing_structure_field(SPS, "max_num_ref_frames")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(SPS, "gaps_in_frame_num_value_allowed_flag")?;
+        let width_in_mbs = reader
+            .read_ue_max(MAX_FRAME_DIMENSION_IN_MBS - 1)
+            .while_parsing_structure_field(SPS, "pic_width_in_mbs_minus1")?
+            + 1;
+        let height_in_map_units = reader
+            .read_ue_max(MAX_FRAME_DIMENSION_IN_MBS - 1)
+            .while_parsing_structure_field(SPS, "pic_height_in_map_units_minus1")?
+            + 1;
+        let frame_mbs_only_flag = reader
+            .read_bit()
+            .while_parsing_structure_field(SPS, "frame_mbs_only_flag")?;
+        let height_in_mbs = if frame_mbs_only_flag {
+            height_in_map_units
+        } else {
+            height_in_map_units * 2
+        };
+        ensure_attach!(
+            height_in_mbs <= MAX_FRAME_DIMENSION_IN_MBS && width_in_mbs * height_in_mbs <= MAX_FRAME_SIZE_IN_MBS,
+            ParseError::InvalidInput,
+            format!("frame size {width_in_mbs}x{height_in_mbs} macroblocks too large"),
+            WhileParsingStructureField(SPS, "pic_height_in_map_units_minus1"),
+        );
+        if !frame_mbs_only_flag {
+            reader
+                .skip(1)
+                .while_parsing_structure_field(SPS, "mb_adaptive_frame_field_flag")?;
+        }
+        reader
+            .skip(1)
+            .while_parsing_structure_field(SPS, "direct_8x8_inference_flag")?;
+        if reader
+            .read_bit()
+            .while_parsing_structure_field(SPS, "frame_cropping_flag")?
+        {
+            for field_name in [
+                "frame_crop_left_offset",
+                "frame_crop_right_offset",
+                "frame_crop_top_offset",
+                "frame_crop_bottom_offset",
+            ] {
+                reader
+                    .read_ue_max(MAX_FRAME_DIMENSION_IN_MBS * 16)
+                    .while_parsing_structure_field(SPS, field_name)?;
+            }
+        }
+
+        Ok(Self {
+            profile_idc,
+            constraint_set_flags,
+            level_idc,
+            seq_parameter_set_id,
+            chroma_format_idc,
+            bit_depth_luma: bit_depth_luma_minus8 + 8,
+            bit_depth_chroma: bit_depth_chroma_minus8 + 8,
+            max_num_ref_frames,
+            width_in_mbs,
+            height_in_mbs,
+        })
+    }
+
+    /// Returns the frame width in luma samples, before cropping.
+    pub fn width(&self) -> u32 {
+        self.width_in_mbs * 16
+    }
+
+    /// Returns the frame height in luma samples, before cropping.
+    pub fn height(&self) -> u32 {
+        self.height_in_mbs * 16
+    }
+}
+
+impl AvcPps {
+    pub fn parse(nal_unit: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = RbspReader::new(nal_unit);
+        parse_nal_unit_header(&mut reader, AVC_NAL_UNIT_TYPE_PPS)
+            .while_parsing_structure_field(PPS, "nal_unit_header")?;
+
+        let pic_parameter_set_id = reader
+            .read_ue_max(255)
+            .while_parsing_structure_field(PPS, "pic_parameter_set_id")?;
+        let seq_parameter_set_id = reader
+            .read_ue_max(31)
+            .while_parsing_structure_field(PPS, "seq_parameter_set_id")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(PPS, "entropy_coding_mode_flag")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(PPS, "bottom_field_pic_order_in_frame_present_flag")?;
+
+        let num_slice_groups = reader
+            .read_ue_max(7)
+            .while_parsing_structure_field(PPS, "num_slice_groups_minus1")?
+            + 1;
+        if num_slice_groups > 1 {
+            let slice_group_map_type = reader
+                .read_ue_max(6)
+                .while_parsing_structure_field(PPS, "slice_group_map_type")?;
+            match slice_group_map_type {
+                0 => {
+                    for _ in 0..num_slice_groups {
+                        reader
+                            .read_ue()
+                            .while_parsing_structure_field(PPS, "run_length_minus1")?;
+                    }
+                }
+                2 => {
+                    for _ in 1..num_slice_groups {
+                        reader.read_ue().while_parsing_structure_field(PPS, "top_left")?;
+                        reader.read_ue().while_parsing_structure_field(PPS, "bottom_right")?;
+                    }
+                }
+                3..=5 => {
+                    reader
+                        .skip(1)
+                        .while_parsing_structure_field(PPS, "slice_group_change_direction_flag")?;
+                    reader
+                        .read_ue_max(MAX_FRAME_SIZE_IN_MBS - 1)
+                        .while_parsing_structure_field(PPS, "slice_group_change_rate_minus1")?;
+                }
+                6 => {
+                    let pic_size_in_map_units = reader
+                        .read_ue_max(MAX_FRAME_SIZE_IN_MBS - 1)
+                        .while_parsing_structure_field(PPS, "pic_size_in_map_units_minus1")?
+                        + 1;
+                    let slice_group_id_bits = u32::BITS - (num_slice_groups - 1).leading_zeros();
+                    for _ in 0..pic_size_in_map_units {
+                        let slice_group_id: u32 = reader
+                            .read(slice_group_id_bits)
+                            .while_parsing_structure_field(PPS, "slice_group_id")?;
+                        ensure_attach!(
+                            slice_group_id < num_slice_groups,
+                            ParseError::InvalidInput,
+                            format!("slice group {slice_group_id} out of range"),
+                            WhileParsingStructureField(PPS, "slice_group_id"),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        reader
+            .read_ue_max(31)
+            .while_parsing_structure_field(PPS, "num_ref_idx_l0_default_active_minus1")?;
+        reader
+            .read_ue_max(31)
+            .while_parsing_structure_field(PPS, "num_ref_idx_l1_default_active_minus1")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(PPS, "weighted_pred_flag")?;
+        let weighted_bipred_idc: u8 = reader
+            .read(2)
+            .while_parsing_structure_field(PPS, "weighted_bipred_idc")?;
+        ensure_attach!(
+            weighted_bipred_idc <= 2,
+            ParseError::InvalidInput,
+            format!("invalid weighted_bipred_idc {weighted_bipred_idc}"),
+            WhileParsingStructureField(PPS, "weighted_bipred_idc"),
+        );
+        let min_qp = -26 - 6 * MAX_BIT_DEPTH_MINUS_8 as i32;
+        reader
+            .read_se_range(min_qp, 25)
+            .while_parsing_structure_field(PPS, "pic_init_qp_minus26")?;
+        reader
+            .read_se_range(-26, 25)
+            .while_parsing_structure_field(PPS, "pic_init_qs_minus26")?;
+        reader
+            .read_se_range(-12, 12)
+            .while_parsing_structure_field(PPS, "chroma_qp_index_offset")?;
+        for field_name in [
+            "deblocking_filter_control_present_flag",
+            "constrained_intra_pred_flag",
+            "redundant_pic_cnt_present_flag",
+        ] {
+            reader.skip(1).while_parsing_structure_field(PPS, field_name)?;
+        }
+
+        Ok(Self { pic_parameter_set_id, seq_parameter_set_id })
+    }
+}
+
+fn parse_nal_unit_header(reader: &mut RbspReader, expected_nal_unit_type: u8) -> Result<(), ParseError> {
+    let forbidden_zero_bit = reader.read_bit()?;
+    ensure_attach!(!forbidden_zero_bit, ParseError::InvalidInput, "forbidden_zero_bit set");
+    reader.skip(2)?;
+    let nal_unit_type: u8 = reader.read(5)?;
+    ensure_attach!(
+        nal_unit_type == expected_nal_unit_type,
+        ParseError::InvalidInput,
+        format!("unexpected nal_unit_type {nal_unit_type}"),
+    );
+    Ok(())
+}
+
+fn parse_scaling_list(reader: &mut RbspReader, size: usize) -> Result<(), ParseError> {
+    let mut last_scale = 8;
+    let mut next_scale = 8;
+    for _ in 0..size {
+        if next_scale != 0 {
+            let delta_scale = reader.read_se_range(-128, 127)?;
+            next_scale = (last_scale + delta_scale + 256) % 256;
+        }
+        if next_scale != 0 {
+            last_scale = next_scale;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::nal::{test_avc_pps, test_avc_sps, TEST_AVC_PROFILE_BASELINE, TEST_AVC_PROFILE_HIGH};
+
+    use super::*;
+
+    #[test]
+    fn sps_baseline() {
+        let sps = AvcSps::parse(&test_avc_sps(TEST_AVC_PROFILE_BASELINE, 4, 20, 15)).unwrap();
+        assert_eq!(sps.profile_idc, TEST_AVC_PROFILE_BASELINE);
+        assert_eq!(sps.level_idc, 31);
+        assert_eq!(sps.max_num_ref_frames, 4);
+        assert_eq!((sps.width(), sps.height()), (320, 240));
+    }
+
+    #[test]
+    fn sps_high() {
+        let sps = AvcSps::parse(&test_avc_sps(TEST_AVC_PROFILE_HIGH, 1, 120, 68)).unwrap();
+        assert_eq!(sps.profile_idc, TEST_AVC_PROFILE_HIGH);
+        assert_eq!(sps.chroma_format_idc, 1);
+        assert_eq!((sps.bit_depth_luma, sps.bit_depth_chroma), (8, 8));
+        assert_eq!((sps.width(), sps.height()), (1920, 1088));
+    }
+
+    #[test]
+    fn sps_frame_too_large() {
+        let err = AvcSps::parse(&test_avc_sps(TEST_AVC_PROFILE_BASELINE, 1, 1000, 1000)).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn sps_too_many_ref_frames() {
+        let err = AvcSps::parse(&test_avc_sps(TEST_AVC_PROFILE_BASELINE, 17, 20, 15)).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn sps_unknown_profile() {
+        let err = AvcSps::parse(&test_avc_sps(1, 1, 20, 15)).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn sps_truncated() {
+        let sps = test_avc_sps(TEST_AVC_PROFILE_BASELINE, 1, 20, 15);
+        let err = AvcSps::parse(&sps[..5]).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn pps() {
+        let pps = AvcPps::parse(&test_avc_pps(3)).unwrap();
+        assert_eq!(pps, AvcPps { pic_parameter_set_id: 0, seq_parameter_set_id: 3 });
+    }
+
+    #[test]
+    fn wrong_nal_unit_type() {
+        let err = AvcPps::parse(&test_avc_sps(TEST_AVC_PROFILE_BASELINE, 1, 20, 15)).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+        let err = AvcSps::parse(&test_avc_pps(0)).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
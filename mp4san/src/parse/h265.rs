@@ -0,0 +1,393 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingStructureField};
+use super::rbsp::RbspReader;
+use super::ParseError;
+
+/// A parsed H.265 video parameter set (VPS), as defined by ITU-T H.265 section 7.3.2.1.
+///
+/// Only the fields up to the sub-layer ordering information are parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HevcVps {
+    pub video_parameter_set_id: u8,
+    pub profile_tier_level: HevcProfileTierLevel,
+}
+
+/// A parsed H.265 sequence parameter set (SPS), as defined by ITU-T H.265 section 7.3.2.2.
+///
+/// Only the fields up to the sub-layer ordering information are parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HevcSps {
+    pub video_parameter_set_id: u8,
+    pub profile_tier_level: HevcProfileTierLevel,
+    pub seq_parameter_set_id: u32,
+    pub chroma_format_idc: u32,
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth_luma: u32,
+    pub bit_depth_chroma: u32,
+    pub max_dec_pic_buffering: u32,
+}
+
+/// A parsed H.265 picture parameter set (PPS), as defined by ITU-T H.265 section 7.3.2.3.
+///
+/// Only the fields up to `init_qp_minus26` are parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HevcPps {
+    pub pic_parameter_set_id: u32,
+    pub seq_parameter_set_id: u32,
+}
+
+/// The general profile, tier, and level of an H.265 bitstream, as defined by ITU-T H.265 section 7.3.3.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HevcProfileTierLevel {
+    pub general_tier_flag: bool,
+    pub general_profile_idc: u8,
+    pub general_level_idc: u8,
+}
+
+pub const HEVC_NAL_UNIT_TYPE_VPS: u8 = 32;
+pub const HEVC_NAL_UNIT_TYPE_SPS: u8 = 33;
+pub const HEVC_NAL_UNIT_TYPE_PPS: u8 = 34;
+
+/// The highest `general_profile_idc` defined by ITU-T H.265 Annex A.
+const MAX_PROFILE_IDC: u8 = 11;
+
+/// The `general_level_idc` values defined by ITU-T H.265 Table A.8, i.e. 30 times the level number.
+const LEVELS: &[u8] = &[30, 60, 63, 90, 93, 120, 123, 150, 153, 156, 180, 183, 186];
+
+/// The maximum luma picture size of the highest level, as defined by ITU-T H.265 Table A.8.
+const MAX_LUMA_PICTURE_SIZE: u64 = 35651584;
+
+/// The maximum luma picture width or height of the highest level, `Sqrt(MaxLumaPs * 8)`.
+const MAX_LUMA_PICTURE_DIMENSION: u32 = 16888;
+
+/// The maximum size of the decoded picture buffer in pictures, as defined by ITU-T H.265 section A.4.2.
+const MAX_DPB_SIZE: u32 = 16;
+
+const MAX_SUB_LAYERS: u8 = 7;
+
+const MAX_BIT_DEPTH_MINUS_8: u32 = 8;
+
+const VPS: &str = "VPS";
+const SPS: &str = "SPS";
+const PPS: &str = "PPS";
+
+impl HevcVps {
+    pub fn parse(nal_unit: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = RbspReader::new(nal_unit);
+        parse_nal_unit_header(&mut reader, HEVC_NAL_UNIT_TYPE_VPS)
+            .while_parsing_structure_field(VPS, "nal_unit_header")?;
+
+        let video_parameter_set_id = reader
+            .read(4)
+            .while_parsing_structure_field(VPS, "vps_video_parameter_set_id")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(VPS, "vps_base_layer_internal_flag")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(VPS, "vps_base_layer_available_flag")?;
+        reader
+            .skip(6)
+            .while_parsing_structure_field(VPS, "vps_max_layers_minus1")?;
+        let max_sub_layers =
+            parse_max_sub_layers(&mut reader).while_parsing_structure_field(VPS, "vps_max_sub_layers_minus1")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(VPS, "vps_temporal_id_nesting_flag")?;
+        let reserved: u16 = reader
+            .read(16)
+            .while_parsing_structure_field(VPS, "vps_reserved_0xffff_16bits")?;
+        ensure_attach!(
+            reserved == 0xffff,
+            ParseError::InvalidInput,
+            WhileParsingStructureField(VPS, "vps_reserved_0xffff_16bits"),
+        );
+        let profile_tier_level = HevcProfileTierLevel::parse(&mut reader, max_sub_layers)
+            .while_parsing_structure_field(VPS, "profile_tier_level")?;
+        parse_sub_layer_ordering_info(&mut reader, max_sub_layers)
+            .while_parsing_structure_field(VPS, "sub_layer_ordering_info")?;
+
+        Ok(Self { video_parameter_set_id, profile_tier_level })
+    }
+}
+
+impl HevcSps {
+    pub fn parse(nal_unit: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = RbspReader::new(nal_unit);
+        parse_nal_unit_header(&mut reader, HEVC_NAL_UNIT_TYPE_SPS)
+            .while_parsing_structure_field(SPS, "nal_unit_header")?;
+
+        let video_parameter_set_id = reader
+            .read(4)
+            .while_parsing_structure_field(SPS, "sps_video_parameter_set_id")?;
+        let max_sub_layers =
+            parse_max_sub_layers(&mut reader).while_parsing_structure_field(SPS, "sps_max_sub_layers_minus1")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(SPS, "sps_temporal_id_nesting_flag")?;
+        let profile_tier_level = HevcProfileTierLevel::parse(&mut reader, max_sub_layers)
+            .while_parsing_structure_field(SPS, "profile_tier_level")?;
+        let seq_parameter_set_id = reader
+            .read_ue_max(15)
+            .while_parsing_structure_field(SPS, "sps_seq_parameter_set_id")?;
+        let chroma_format_idc = reader
+            .read_ue_max(3)
+            .while_parsing_structure_field(SPS, "chroma_format_idc")?;
+        if chroma_format_idc == 3 {
+            reader
+                .skip(1)
+                .while_parsing_structure_field(SPS, "separate_colour_plane_flag")?;
+        }
+        let width = reader
+            .read_ue_max(MAX_LUMA_PICTURE_DIMENSION)
+            .while_parsing_structure_field(SPS, "pic_width_in_luma_samples")?;
+        let height = reader
+            .read_ue_max(MAX_LUMA_PICTURE_DIMENSION)
+            .while_parsing_structure_field(SPS, "pic_height_in_luma_samples")?;
+        ensure_attach!(
+            width != 0 && height != 0 && u64::from(width) * u64::from(height) <= MAX_LUMA_PICTURE_SIZE,
+            ParseError::InvalidInput,
+            format!("invalid picture size {width}x{height}"),
+            WhileParsingStructureField(SPS, "pic_height_in_luma_samples"),
+        );
+        if reader
+            .read_bit()
+            .while_parsing_structure_field(SPS, "conformance_window_flag")?
+        {
+            for field_name in [
+                "conf_win_left_offset",
+                "conf_win_right_offset",
+                "conf_win_top_offset",
+                "conf_win_bottom_offset",
+            ] {
+                reader
+                    .read_ue_max(MAX_LUMA_PICTURE_DIMENSION)
+                    .while_parsing_structure_field(SPS, field_name)?;
+            }
+        }
+        let bit_depth_luma_minus8 = reader
+            .read_ue_max(MAX_BIT_DEPTH_MINUS_8)
+            .while_parsing_structure_field(SPS, "bit_depth_luma_minus8")?;
+        let bit_depth_chroma_minus8 = reader
+            .read_ue_max(MAX_BIT_DEPTH_MINUS_8)
+            .while_parsing_structure_field(SPS, "bit_depth_chroma_minus8")?;
+        reader
+            .read_ue_max(12)
+            .while_parsing_structure_field(SPS, "log2_max_pic_order_cnt_lsb_minus4")?;
+        let max_dec_pic_buffering = parse_sub_layer_ordering_info(&mut reader, max_sub_layers)
+            .while_parsing_structure_field(SPS, "sub_layer_ordering_info")?;
+
+        Ok(Self {
+            video_parameter_set_id,
+            profile_tier_level,
+            seq_parameter_set_id,
+            chroma_format_idc,
+            width,
+            height,
+            bit_depth_luma: bit_depth_luma_minus8 + 8,
+            bit_depth_chroma: bit_depth_chroma_minus8 + 8,
+            max_dec_pic_buffering,
+        })
+    }
+}
+
+impl HevcPps {
+    pub fn parse(nal_unit: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = RbspReader::new(nal_unit);
+        parse_nal_unit_header(&mut reader, HEVC_NAL_UNIT_TYPE_PPS)
+            .while_parsing_structure_field(PPS, "nal_unit_header")?;
+
+        let pic_parameter_set_id = reader
+            .read_ue_max(63)
+            .while_parsing_structure_field(PPS, "pps_pic_parameter_set_id")?;
+        let seq_parameter_set_id = reader
+            .read_ue_max(15)
+            .while_parsing_structure_field(PPS, "pps_seq_parameter_set_id")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(PPS, "dependent_slice_segments_enabled_flag")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(PPS, "output_flag_present_flag")?;
+        reader
+            .skip(3)
+            .while_parsing_structure_field(PPS, "num_extra_slice_header_bits")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(PPS, "sign_data_hiding_enabled_flag")?;
+        reader
+            .skip(1)
+            .while_parsing_structure_field(PPS, "cabac_init_present_flag")?;
+        reader
+            .read_ue_max(14)
+            .while_parsing_structure_field(PPS, "num_ref_idx_l0_default_active_minus1")?;
+        reader
+            .read_ue_max(14)
+            .while_parsing_structure_field(PPS, "num_ref_idx_l1_default_active_minus1")?;
+        let min_qp = -26 - 6 * MAX_BIT_DEPTH_MINUS_8 as i32;
+        reader
+            .read_se_range(min_qp, 25)
+            .while_parsing_structure_field(PPS, "init_qp_minus26")?;
+
+        Ok(Self { pic_parameter_set_id, seq_parameter_set_id })
+    }
+}
+
+impl HevcProfileTierLevel {
+    fn parse(reader: &mut RbspReader, max_sub_layers: u8) -> Result<Self, ParseError> {
+        let general_profile_space: u8 = reader.read(2)?;
+        ensure_attach!(
+            general_profile_space == 0,
+            ParseError::InvalidInput,
+            format!("unknown general_profile_space {general_profile_space}"),
+        );
+        let general_tier_flag = reader.read_bit()?;
+        let general_profile_idc = reader.read(5)?;
+        ensure_attach!(
+            (1..=MAX_PROFILE_IDC).contains(&general_profile_idc),
+            ParseError::InvalidInput,
+            format!("unknown general_profile_idc {general_profile_idc}"),
+        );
+        // general_profile_compatibility_flag[32], general_*_source_flag[4], and general constraint flags[44]
+        reader.skip(32 + 4 + 43 + 1)?;
+        let general_level_idc = reader.read(8)?;
+        ensure_attach!(
+            LEVELS.contains(&general_level_idc),
+            ParseError::InvalidInput,
+            format!("unknown general_level_idc {general_level_idc}"),
+        );
+
+        let mut sub_layer_flags = Vec::with_capacity(max_sub_layers as usize - 1);
+        for _ in 1..max_sub_layers {
+            let sub_layer_profile_present_flag = reader.read_bit()?;
+            let sub_layer_level_present_flag = reader.read_bit()?;
+            sub_layer_flags.push((sub_layer_profile_present_flag, sub_layer_level_present_flag));
+        }
+        if max_sub_layers > 1 {
+            // reserved_zero_2bits for each of the remaining eight possible sub-layers
+            reader.skip(2 * u32::from(8 - (max_sub_layers - 1)))?;
+        }
+        for (sub_layer_profile_present_flag, sub_layer_level_present_flag) in sub_layer_flags {
+            if sub_layer_profile_present_flag {
+                reader.skip(88)?;
+            }
+            if sub_layer_level_present_flag {
+                reader.skip(8)?;
+            }
+        }
+
+        Ok(Self { general_tier_flag, general_profile_idc, general_level_idc })
+    }
+}
+
+fn parse_nal_unit_header(reader: &mut RbspReader, expected_nal_unit_type: u8) -> Result<(), ParseError> {
+    let forbidden_zero_bit = reader.read_bit()?;
+    ensure_attach!(!forbidden_zero_bit, ParseError::InvalidInput, "forbidden_zero_bit set");
+    let nal_unit_type: u8 = reader.read(6)?;
+    ensure_attach!(
+        nal_unit_type == expected_nal_unit_type,
+        ParseError::InvalidInput,
+        format!("unexpected nal_unit_type {nal_unit_type}"),
+    );
+    reader.skip(6)?;
+    let nuh_temporal_id_plus1: u8 = reader.read(3)?;
+    ensure_attach!(
+        nuh_temporal_id_plus1 != 0,
+        ParseError::InvalidInput,
+        "zero nuh_temporal_id_plus1"
+    );
+    Ok(())
+}
+
+/// Parse a `*_max_sub_layers_minus1` field, returning the number of sub-layers.
+fn parse_max_sub_layers(reader: &mut RbspReader) -> Result<u8, ParseError> {
+    let max_sub_layers = reader.read::<u8>(3)? + 1;
+    ensure_attach!(
+        max_sub_layers <= MAX_SUB_LAYERS,
+        ParseError::InvalidInput,
+        format!("invalid sub-layer count {max_sub_layers}"),
+    );
+    Ok(max_sub_layers)
+}
+
+/// Parse the `*_sub_layer_ordering_info_present_flag` and following fields, returning the largest decoded picture
+/// buffer size in pictures.
+fn parse_sub_layer_ordering_info(reader: &mut RbspReader, max_sub_layers: u8) -> Result<u32, ParseError> {
+    let sub_layer_ordering_info_present_flag = reader.read_bit()?;
+    let sub_layer_ordering_info_count = if sub_layer_ordering_info_present_flag {
+        max_sub_layers
+    } else {
+        1
+    };
+    let mut max_dec_pic_buffering = 0;
+    for _ in 0..sub_layer_ordering_info_count {
+        let dec_pic_buffering = reader.read_ue_max(MAX_DPB_SIZE - 1)? + 1;
+        reader.read_ue_max(dec_pic_buffering - 1)?; // max_num_reorder_pics
+        reader.read_ue()?; // max_latency_increase_plus1
+        max_dec_pic_buffering = max_dec_pic_buffering.max(dec_pic_buffering);
+    }
+    Ok(max_dec_pic_buffering)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::nal::{test_hevc_pps, test_hevc_sps, test_hevc_vps};
+
+    use super::*;
+
+    const TEST_PROFILE_TIER_LEVEL: HevcProfileTierLevel =
+        HevcProfileTierLevel { general_tier_flag: false, general_profile_idc: 1, general_level_idc: 93 };
+
+    #[test]
+    fn vps() {
+        let vps = HevcVps::parse(&test_hevc_vps()).unwrap();
+        assert_eq!(
+            vps,
+            HevcVps { video_parameter_set_id: 0, profile_tier_level: TEST_PROFILE_TIER_LEVEL }
+        );
+    }
+
+    #[test]
+    fn sps() {
+        let sps = HevcSps::parse(&test_hevc_sps(93, 1280, 720, 5)).unwrap();
+        assert_eq!(sps.profile_tier_level, TEST_PROFILE_TIER_LEVEL);
+        assert_eq!(sps.chroma_format_idc, 1);
+        assert_eq!((sps.width, sps.height), (1280, 720));
+        assert_eq!((sps.bit_depth_luma, sps.bit_depth_chroma), (8, 8));
+        assert_eq!(sps.max_dec_pic_buffering, 5);
+    }
+
+    #[test]
+    fn sps_picture_too_large() {
+        let err = HevcSps::parse(&test_hevc_sps(93, 16384, 16384, 1)).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn sps_dpb_too_large() {
+        let err = HevcSps::parse(&test_hevc_sps(93, 1280, 720, 17)).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn sps_unknown_level() {
+        let err = HevcSps::parse(&test_hevc_sps(94, 1280, 720, 1)).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn pps() {
+        let pps = HevcPps::parse(&test_hevc_pps(2)).unwrap();
+        assert_eq!(pps, HevcPps { pic_parameter_set_id: 0, seq_parameter_set_id: 2 });
+    }
+
+    #[test]
+    fn wrong_nal_unit_type() {
+        let err = HevcSps::parse(&test_hevc_vps()).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
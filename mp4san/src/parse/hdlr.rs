@@ -0,0 +1,54 @@
+#![allow(missing_docs)]
+
+use super::{ConstFullBoxHeader, FourCC, ParseBox, ParsedBox, UnboundedArray};
+
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "hdlr"]
+pub struct HdlrBox {
+    header: ConstFullBoxHeader,
+    pre_defined: u32,
+    pub handler_type: FourCC,
+    reserved: [u32; 3],
+    pub name: UnboundedArray<u8>,
+}
+
+impl HdlrBox {
+    /// The handler type of video tracks.
+    pub const VIDEO: FourCC = FourCC::from_str("vide");
+
+    /// The handler type of audio tracks.
+    pub const SOUND: FourCC = FourCC::from_str("soun");
+
+    pub fn new(handler_type: FourCC) -> Self {
+        Self {
+            header: Default::default(),
+            pre_defined: 0,
+            handler_type,
+            reserved: Default::default(),
+            name: [0].into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::fourcc;
+    use crate::util::test::write_hdlr_data;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        write_hdlr_data(&mut data, fourcc::META);
+        let expected = data.clone();
+        let hdlr = HdlrBox::parse(&mut data).unwrap();
+        assert_eq!(hdlr.handler_type, fourcc::META);
+        assert_eq!(hdlr.name.entry_count(), 1);
+        let mut encoded = BytesMut::new();
+        hdlr.put_buf(&mut encoded);
+        assert_eq!(encoded, expected);
+    }
+}
@@ -101,7 +101,8 @@ impl BoxHeader {
             })
     }
 
-    pub(crate) async fn read<R: AsyncRead>(input: R) -> io::Result<Self> {
+    /// Read a box header, without reading past its end.
+    pub async fn read<R: AsyncRead>(input: R) -> io::Result<Self> {
         pin_mut!(input);
 
         let mut size = [0; 4];
@@ -200,20 +201,20 @@ impl BoxSize {
 }
 
 macro_rules! box_type {
-    ($($name:ident),+ $(,)?) => {
+    ($($name:ident $(= $value:literal)?),+ $(,)?) => {
         paste::paste! {
             #[allow(missing_docs)]
             pub mod fourcc {
                 use super::*;
                 $(
-                    #[doc = concat!("The `", stringify!([<$name:lower>]), "` box type.")]
-                    pub const $name: FourCC = FourCC::from_str(stringify!([<$name:lower>]));
+                    #[doc = box_type!(@doc $name $($value)?)]
+                    pub const $name: FourCC = FourCC::from_str(box_type!(@value $name $($value)?));
                 )+
             }
 
             impl BoxType {
                 $(
-                    #[doc = concat!("The `", stringify!([<$name:lower>]), "` box type.")]
+                    #[doc = box_type!(@doc $name $($value)?)]
                     pub const $name: Self = Self::FourCC(fourcc::$name);
                 )+
             }
@@ -222,41 +223,123 @@ macro_rules! box_type {
             pub mod box_type {
                 use super::BoxType;
                 $(
-                    #[doc = concat!("The `", stringify!([<$name:lower>]), "` box type.")]
+                    #[doc = box_type!(@doc $name $($value)?)]
                     pub const $name: BoxType = BoxType::$name;
                 )+
             }
         }
     };
+    (@value $name:ident $value:literal) => {
+        $value
+    };
+    (@value $name:ident) => {
+        paste::paste! { stringify!([<$name:lower>]) }
+    };
+    (@doc $name:ident $value:literal) => {
+        concat!("The `", $value, "` box type.")
+    };
+    (@doc $name:ident) => {
+        paste::paste! { concat!("The `", stringify!([<$name:lower>]), "` box type.") }
+    };
 }
 
 box_type! {
+    ALBM,
+    ALIS,
+    AUTH,
+    AVC1,
+    AVCC = "avcC",
+    CLSF,
+    CMOV,
     CO64,
+    CPRT,
+    CTTS,
+    D263,
+    DAMR,
     DINF,
     DREF,
+    DSCP,
+    EDTS,
+    ELST,
+    ENCA,
+    ENCS,
+    ENCT,
+    ENCV,
+    ESDS,
     FREE,
+    FRMA,
     FTYP,
+    GNRE,
     HDLR,
+    HEV1,
+    HVC1,
+    HVCC = "hvcC",
+    IDAT,
+    IINF,
+    ILOC,
+    INFE,
+    IPCO,
+    IPMA,
+    IPRP,
+    IREF,
+    KYWD,
+    LOCI,
     MDAT,
     MDHD,
     MDIA,
     MECO,
+    MEHD,
     META,
     METT,
+    MFHD,
+    MFRA,
     MINF,
+    MOOF,
     MOOV,
+    MP4A,
+    MVEX,
     MVHD,
+    NMHD,
+    PERF,
+    PITM,
+    PSSH,
+    RTNG,
+    S263,
+    SAIO,
+    SAIZ,
+    SAMR,
+    SAWB,
+    SCHI,
+    SCHM,
+    SENC,
+    SIDX,
+    SINF,
     SKIP,
+    SMHD,
     STBL,
     STCO,
     STSC,
     STSD,
+    STSS,
     STSZ,
     STTS,
+    STYP,
+    TENC,
+    TFDT,
+    TFHD,
+    TITL,
     TKHD,
+    TRAF,
     TRAK,
+    TREF,
+    TREX,
+    TRUN,
+    UDTA,
     URL,
     UUID,
+    VMHD,
+    WIDE,
+    YRRC,
 }
 
 impl fmt::Display for BoxUuid {
@@ -275,6 +358,31 @@ impl FullBoxHeader {
         Self { version: 0, flags: 0 }
     }
 
+    /// Parse a field which is 32 bits wide in version 0 of a box, and 64 bits wide in version 1.
+    pub fn parse_versioned_u64<B: Buf>(&self, buf: B) -> Result<u64, ParseError> {
+        match self.version {
+            0 => u32::parse(buf).map(u64::from),
+            1 => u64::parse(buf),
+            version => bail_attach!(ParseError::InvalidInput, format!("unsupported box version {version}")),
+        }
+    }
+
+    /// The encoded length of a field which is 32 bits wide in version 0 of a box, and 64 bits wide in version 1.
+    pub fn versioned_u64_len(&self) -> u64 {
+        match self.version {
+            0 => u32::encoded_len(),
+            _ => u64::encoded_len(),
+        }
+    }
+
+    /// Write a field which is 32 bits wide in version 0 of a box, and 64 bits wide in version 1.
+    pub fn put_versioned_u64<B: BufMut>(&self, value: u64, out: B) {
+        match self.version {
+            0 => (value as u32).put_buf(out),
+            _ => value.put_buf(out),
+        }
+    }
+
     pub fn ensure_eq(&self, other: &Self) -> Result<(), ParseError> {
         ensure_attach!(
             self.version == other.version,
@@ -0,0 +1,26 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{BoxType, Boxes, HvccBox, ParseBox, ParseError, ParsedBox, VisualSampleEntry};
+
+/// An H.265 (HEVC) visual sample entry, as defined by ISO/IEC 14496-15 section 8.4.1.1.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "hvc1"]
+pub struct Hvc1Box {
+    pub entry: VisualSampleEntry,
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::HVC1;
+
+impl Hvc1Box {
+    pub fn with_children<C: Into<Boxes>>(entry: VisualSampleEntry, children: C) -> Self {
+        Self { entry, children: children.into() }
+    }
+
+    pub fn hvcc_mut(&mut self) -> Result<&mut HvccBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::HVCC)
+    }
+}
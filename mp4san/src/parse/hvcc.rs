@@ -0,0 +1,236 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::avcc::{nal_units_encoded_len, parse_nal_units, put_nal_units};
+use super::error::{ParseResultExt, WhileParsingField};
+use super::h265::{HEVC_NAL_UNIT_TYPE_PPS, HEVC_NAL_UNIT_TYPE_SPS, HEVC_NAL_UNIT_TYPE_VPS};
+use super::{BoxType, HevcPps, HevcSps, HevcVps, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+/// An `HEVCDecoderConfigurationRecord`, as defined by ISO/IEC 14496-15 section 8.3.3.1.
+#[derive(Clone, Debug)]
+pub struct HvccBox {
+    pub configuration_version: u8,
+    general_profile_tier_level: [u8; 12],
+    format: [u8; 8],
+    frame_rate_and_length_size_minus_one: u8,
+    pub arrays: Vec<HvccNalUnitArray>,
+}
+
+/// An array of NAL units of a single type in an [`HvccBox`].
+#[derive(Clone, Debug)]
+pub struct HvccNalUnitArray {
+    completeness_and_nal_unit_type: u8,
+    pub nal_units: Vec<BytesMut>,
+}
+
+const NAME: BoxType = BoxType::HVCC;
+
+const NAL_UNIT_TYPE_BITS: u8 = 0b0011_1111;
+
+impl HvccBox {
+    pub fn general_profile_space(&self) -> u8 {
+        self.general_profile_tier_level[0] >> 6
+    }
+
+    pub fn general_profile_idc(&self) -> u8 {
+        self.general_profile_tier_level[0] & 0b0001_1111
+    }
+
+    pub fn general_level_idc(&self) -> u8 {
+        self.general_profile_tier_level[11]
+    }
+
+    /// Returns the size in bytes of the length prefix of each NAL unit in the samples.
+    pub fn length_size(&self) -> u8 {
+        (self.frame_rate_and_length_size_minus_one & 0b11) + 1
+    }
+
+    /// Parses each video parameter set (VPS).
+    pub fn parse_video_parameter_sets(&self) -> Result<Vec<HevcVps>, ParseError> {
+        let vps = self.nal_units(HEVC_NAL_UNIT_TYPE_VPS).map(|vps| HevcVps::parse(vps));
+        vps.collect::<Result<_, _>>().while_parsing_field(NAME, "arrays")
+    }
+
+    /// Parses each sequence parameter set (SPS).
+    pub fn parse_sequence_parameter_sets(&self) -> Result<Vec<HevcSps>, ParseError> {
+        let sps = self.nal_units(HEVC_NAL_UNIT_TYPE_SPS).map(|sps| HevcSps::parse(sps));
+        sps.collect::<Result<_, _>>().while_parsing_field(NAME, "arrays")
+    }
+
+    /// Parses each picture parameter set (PPS).
+    pub fn parse_picture_parameter_sets(&self) -> Result<Vec<HevcPps>, ParseError> {
+        let pps = self.nal_units(HEVC_NAL_UNIT_TYPE_PPS).map(|pps| HevcPps::parse(pps));
+        pps.collect::<Result<_, _>>().while_parsing_field(NAME, "arrays")
+    }
+
+    fn nal_units(&self, nal_unit_type: u8) -> impl Iterator<Item = &BytesMut> + '_ {
+        (self.arrays.iter())
+            .filter(move |array| array.nal_unit_type() == nal_unit_type)
+            .flat_map(|array| &array.nal_units)
+    }
+}
+
+impl ParseBox for HvccBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let configuration_version = u8::parse(&mut *buf).while_parsing_field(NAME, "configuration_version")?;
+        ensure_attach!(
+            configuration_version == 1,
+            ParseError::InvalidInput,
+            format!("unsupported configuration version {configuration_version}"),
+            WhileParsingField(NAME, "configuration_version"),
+        );
+        let general_profile_tier_level =
+            <[u8; 12]>::parse(&mut *buf).while_parsing_field(NAME, "general_profile_tier_level")?;
+        let format = <[u8; 8]>::parse(&mut *buf).while_parsing_field(NAME, "format")?;
+        let frame_rate_and_length_size_minus_one =
+            u8::parse(&mut *buf).while_parsing_field(NAME, "length_size_minus_one")?;
+        let array_count = u8::parse(&mut *buf).while_parsing_field(NAME, "num_of_arrays")?;
+        let mut arrays = Vec::with_capacity(array_count.into());
+        for _ in 0..array_count {
+            arrays.push(HvccNalUnitArray::parse(&mut *buf).while_parsing_field(NAME, "arrays")?);
+        }
+        ensure_attach!(
+            buf.is_empty(),
+            ParseError::InvalidInput,
+            "extra unparsed data",
+            WhileParsingField(NAME, "arrays"),
+        );
+
+        let hvcc = Self {
+            configuration_version,
+            general_profile_tier_level,
+            format,
+            frame_rate_and_length_size_minus_one,
+            arrays,
+        };
+        ensure_attach!(
+            hvcc.general_profile_space() == 0,
+            ParseError::InvalidInput,
+            format!("unknown general profile space {}", hvcc.general_profile_space()),
+            WhileParsingField(NAME, "general_profile_tier_level"),
+        );
+        ensure_attach!(
+            hvcc.length_size() != 3,
+            ParseError::InvalidInput,
+            "invalid NAL unit length size 3",
+            WhileParsingField(NAME, "length_size_minus_one"),
+        );
+
+        hvcc.parse_video_parameter_sets()?;
+        let sps = hvcc.parse_sequence_parameter_sets()?;
+        for pps in hvcc.parse_picture_parameter_sets()? {
+            ensure_attach!(
+                sps.iter()
+                    .any(|sps| sps.seq_parameter_set_id == pps.seq_parameter_set_id),
+                ParseError::InvalidInput,
+                format!("PPS references unknown SPS {}", pps.seq_parameter_set_id),
+                WhileParsingField(NAME, "arrays"),
+            );
+        }
+        Ok(hvcc)
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for HvccBox {
+    fn encoded_len(&self) -> u64 {
+        23 + self.arrays.iter().map(HvccNalUnitArray::encoded_len).sum::<u64>()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.configuration_version.put_buf(&mut out);
+        self.general_profile_tier_level.put_buf(&mut out);
+        self.format.put_buf(&mut out);
+        self.frame_rate_and_length_size_minus_one.put_buf(&mut out);
+        (self.arrays.len() as u8).put_buf(&mut out);
+        for array in &self.arrays {
+            array.put_buf(&mut out);
+        }
+    }
+}
+
+impl HvccNalUnitArray {
+    pub fn nal_unit_type(&self) -> u8 {
+        self.completeness_and_nal_unit_type & NAL_UNIT_TYPE_BITS
+    }
+
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let completeness_and_nal_unit_type = u8::parse(&mut *buf)?;
+        let nal_unit_type = completeness_and_nal_unit_type & NAL_UNIT_TYPE_BITS;
+        let nal_unit_count = u16::parse(&mut *buf)?;
+        let nal_units = parse_nal_units(buf, nal_unit_count.into())?;
+        for nal_unit in &nal_units {
+            let nal_unit_header_type = nal_unit.first().map(|header| (header >> 1) & NAL_UNIT_TYPE_BITS);
+            ensure_attach!(
+                nal_unit_header_type == Some(nal_unit_type),
+                ParseError::InvalidInput,
+                format!("NAL unit type {nal_unit_header_type:?} in array of type {nal_unit_type}"),
+            );
+        }
+        Ok(Self { completeness_and_nal_unit_type, nal_units })
+    }
+
+    fn encoded_len(&self) -> u64 {
+        3 + nal_units_encoded_len(&self.nal_units)
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.completeness_and_nal_unit_type.put_buf(&mut out);
+        (self.nal_units.len() as u16).put_buf(&mut out);
+        put_nal_units(&self.nal_units, out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::nal::{test_hevc_pps, test_hevc_sps, test_hevc_vps, test_hvcc_data, write_test_hvcc_data};
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::from(&test_hvcc_data(&test_hevc_sps(93, 1280, 720, 5))[..]);
+        let expected = data.clone();
+        let hvcc = HvccBox::parse(&mut data).unwrap();
+        assert_eq!(hvcc.general_profile_idc(), 1);
+        assert_eq!(hvcc.general_level_idc(), 93);
+        assert_eq!(hvcc.length_size(), 4);
+        assert_eq!(hvcc.arrays.len(), 3);
+        assert_eq!(hvcc.parse_sequence_parameter_sets().unwrap()[0].width, 1280);
+        let mut encoded = BytesMut::new();
+        hvcc.put_buf(&mut encoded);
+        assert_eq!(encoded.len() as u64, hvcc.encoded_len());
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn invalid_sps() {
+        let mut data = BytesMut::from(&test_hvcc_data(&test_hevc_sps(93, 16384, 16384, 5))[..]);
+        let err = HvccBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn pps_unknown_sps() {
+        let (vps, sps, pps) = (test_hevc_vps(), test_hevc_sps(93, 1280, 720, 5), test_hevc_pps(1));
+        let mut data = BytesMut::new();
+        write_test_hvcc_data(&mut data, &[(32, &[&vps]), (33, &[&sps]), (34, &[&pps])]);
+        let err = HvccBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn nal_unit_type_mismatch() {
+        let (vps, sps) = (test_hevc_vps(), test_hevc_sps(93, 1280, 720, 5));
+        let mut data = BytesMut::new();
+        write_test_hvcc_data(&mut data, &[(32, &[&vps, &sps])]);
+        let err = HvccBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
@@ -0,0 +1,99 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MdhdBox {
+    header: FullBoxHeader,
+    pub creation_time: u64,
+    pub modification_time: u64,
+    pub timescale: u32,
+    pub duration: u64,
+    pub language: u16,
+    pre_defined: u16,
+}
+
+const NAME: BoxType = BoxType::MDHD;
+
+impl ParseBox for MdhdBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        let creation_time = header
+            .parse_versioned_u64(&mut *buf)
+            .while_parsing_field(NAME, "creation_time")?;
+        let modification_time = header
+            .parse_versioned_u64(&mut *buf)
+            .while_parsing_field(NAME, "modification_time")?;
+        let timescale = u32::parse(&mut *buf).while_parsing_field(NAME, "timescale")?;
+        let duration = header
+            .parse_versioned_u64(&mut *buf)
+            .while_parsing_field(NAME, "duration")?;
+        let language = u16::parse(&mut *buf).while_parsing_field(NAME, "language")?;
+        let pre_defined = u16::parse(&mut *buf).while_parsing_field(NAME, "pre_defined")?;
+        Ok(Self { header, creation_time, modification_time, timescale, duration, language, pre_defined })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for MdhdBox {
+    fn encoded_len(&self) -> u64 {
+        FullBoxHeader::encoded_len() + 3 * self.header.versioned_u64_len() + u32::encoded_len() + 2 * u16::encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.header.put_versioned_u64(self.creation_time, &mut out);
+        self.header.put_versioned_u64(self.modification_time, &mut out);
+        self.timescale.put_buf(&mut out);
+        self.header.put_versioned_u64(self.duration, &mut out);
+        self.language.put_buf(&mut out);
+        self.pre_defined.put_buf(&mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::write_test_mdhd_data;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        write_test_mdhd_data(&mut data, 0);
+        let expected = data.clone();
+        let mdhd = MdhdBox::parse(&mut data).unwrap();
+        assert_eq!(mdhd.timescale, 1);
+        let mut encoded = BytesMut::new();
+        mdhd.put_buf(&mut encoded);
+        assert_eq!(encoded.len() as u64, mdhd.encoded_len());
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn version_1() {
+        let mut mdhd = MdhdBox { header: FullBoxHeader { version: 1, flags: 0 }, ..Default::default() };
+        mdhd.creation_time = u64::MAX;
+        let mut data = BytesMut::new();
+        mdhd.put_buf(&mut data);
+        assert_eq!(data.len() as u64, mdhd.encoded_len());
+        assert_eq!(MdhdBox::parse(&mut data).unwrap(), mdhd);
+    }
+
+    #[test]
+    fn invalid_version() {
+        let mut data = BytesMut::new();
+        FullBoxHeader { version: 2, flags: 0 }.put_buf(&mut data);
+        data.extend_from_slice(&[0; 32]);
+        let err = MdhdBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
@@ -4,7 +4,7 @@ use crate::error::Result;
 
 use super::error::ParseResultExt;
 use super::mp4box::Boxes;
-use super::{BoxType, MinfBox, ParseBox, ParseError, ParsedBox};
+use super::{BoxType, HdlrBox, MdhdBox, MinfBox, ParseBox, ParseError, ParsedBox, StrictValidationOptions};
 
 #[derive(Clone, Debug, ParseBox, ParsedBox)]
 #[box_type = "mdia"]
@@ -20,7 +20,29 @@ impl MdiaBox {
         Self { children: children.into() }
     }
 
+    pub fn hdlr_mut(&mut self) -> Result<&mut HdlrBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::HDLR)
+    }
+
+    pub fn mdhd_mut(&mut self) -> Result<&mut MdhdBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::MDHD)
+    }
+
     pub fn minf_mut(&mut self) -> Result<&mut MinfBox, ParseError> {
         self.children.get_one_mut().while_parsing_child(NAME, BoxType::MINF)
     }
+
+    /// Parses every descendant box, returning [`ParseError::UnsupportedBox`] for any box not known to the parser.
+    pub fn validate_strict(&mut self) -> Result<(), ParseError> {
+        self.validate_strict_with(Default::default())
+    }
+
+    /// Like [`validate_strict`](Self::validate_strict), but also accepts the boxes allowed by `options`.
+    pub fn validate_strict_with(&mut self, options: StrictValidationOptions) -> Result<(), ParseError> {
+        self.children
+            .ensure_only(NAME, &[BoxType::MDHD, BoxType::HDLR, BoxType::MINF])?;
+        self.mdhd_mut()?;
+        self.hdlr_mut()?;
+        self.minf_mut()?.validate_strict_with(options)
+    }
 }
@@ -0,0 +1,10 @@
+#![allow(missing_docs)]
+
+use super::{ConstFullBoxHeader, ParseBox, ParsedBox};
+
+#[derive(Clone, Debug, Default, ParseBox, ParsedBox)]
+#[box_type = "mfhd"]
+pub struct MfhdBox {
+    header: ConstFullBoxHeader,
+    pub sequence_number: u32,
+}
@@ -4,7 +4,10 @@ use crate::error::Result;
 
 use super::error::ParseResultExt;
 use super::mp4box::Boxes;
-use super::{BoxType, ParseBox, ParseError, ParsedBox, StblBox};
+use super::{
+    BoxType, DinfBox, HdlrBox, NmhdBox, ParseBox, ParseError, ParsedBox, SmhdBox, StblBox, StrictValidationOptions,
+    VmhdBox,
+};
 
 #[derive(Clone, Debug, ParseBox, ParsedBox)]
 #[box_type = "minf"]
@@ -20,7 +23,48 @@ impl MinfBox {
         Self { children: children.into() }
     }
 
+    pub fn dinf_mut(&mut self) -> Result<Option<&mut DinfBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::DINF)
+    }
+
     pub fn stbl_mut(&mut self) -> Result<&mut StblBox, ParseError> {
         self.children.get_one_mut().while_parsing_child(NAME, BoxType::STBL)
     }
+
+    /// Parses every descendant box, returning [`ParseError::UnsupportedBox`] for any box not known to the parser.
+    pub fn validate_strict(&mut self) -> Result<(), ParseError> {
+        self.validate_strict_with(Default::default())
+    }
+
+    /// Like [`validate_strict`](Self::validate_strict), but also accepts the boxes allowed by `options`.
+    pub fn validate_strict_with(&mut self, options: StrictValidationOptions) -> Result<(), ParseError> {
+        // NB: QuickTime files contain a data handler reference box (`hdlr`) here.
+        const CHILDREN: &[BoxType] = &[
+            BoxType::VMHD,
+            BoxType::SMHD,
+            BoxType::NMHD,
+            BoxType::HDLR,
+            BoxType::DINF,
+            BoxType::STBL,
+        ];
+        self.children.ensure_only(NAME, CHILDREN)?;
+        self.children
+            .get_optional_mut::<VmhdBox>()
+            .while_parsing_child(NAME, BoxType::VMHD)?;
+        self.children
+            .get_optional_mut::<SmhdBox>()
+            .while_parsing_child(NAME, BoxType::SMHD)?;
+        self.children
+            .get_optional_mut::<NmhdBox>()
+            .while_parsing_child(NAME, BoxType::NMHD)?;
+        self.children
+            .get_optional_mut::<HdlrBox>()
+            .while_parsing_child(NAME, BoxType::HDLR)?;
+        if let Some(dinf) = self.dinf_mut()? {
+            dinf.validate_strict()?;
+        }
+        self.stbl_mut()?.validate_strict_with(options)
+    }
 }
@@ -0,0 +1,207 @@
+#![allow(missing_docs)]
+
+use mediasan_common::util::checked_add_signed;
+
+use crate::error::Result;
+use crate::InputSpan;
+
+use super::error::{ParseResultExt, WhileParsingChild, WhileParsingField};
+use super::{BoxType, Boxes, BoxesValidator, MfhdBox, ParseBox, ParseError, ParsedBox, TrafBox, TrexBox};
+
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "moof"]
+pub struct MoofBox {
+    children: Boxes<MoofChildrenValidator>,
+}
+
+pub(crate) struct MoofChildrenValidator;
+
+const NAME: BoxType = BoxType::MOOF;
+
+impl MoofBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes<MoofChildrenValidator>>>(children: C) -> Self {
+        Self { children: children.into() }
+    }
+
+    pub fn mfhd_mut(&mut self) -> Result<&mut MfhdBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::MFHD)
+    }
+
+    pub fn trafs(&mut self) -> impl Iterator<Item = Result<&mut TrafBox, ParseError>> + '_ {
+        self.children
+            .get_mut()
+            .map(|result| result.while_parsing_child(NAME, BoxType::TRAF))
+    }
+
+    /// Returns whether any track fragment header in this movie fragment specifies an explicit base data offset.
+    ///
+    /// Such offsets are relative to the start of the input, rather than to the movie fragment.
+    pub fn has_base_data_offset(&mut self) -> Result<bool, ParseError> {
+        for traf in self.trafs() {
+            if traf?.tfhd_mut()?.base_data_offset().is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns the spans of the input containing the sample data referenced by this movie fragment.
+    ///
+    /// `moof_offset` is the offset of this box in the input, and `trexs` are the track extends boxes from the movie
+    /// extends box (`mvex`) of the movie, which provide defaults for each track fragment.
+    pub fn sample_data_spans(&mut self, moof_offset: u64, trexs: &[TrexBox]) -> Result<Vec<InputSpan>, ParseError> {
+        let mut spans = Vec::new();
+        let mut next_base_data_offset = moof_offset;
+        for traf in self.trafs() {
+            let traf = traf?;
+            let tfhd = *traf.tfhd_mut()?;
+            let Some(trex) = trexs.iter().find(|trex| trex.track_id == tfhd.track_id) else {
+                bail_attach!(
+                    ParseError::InvalidInput,
+                    format!("no `trex` box for track id {}", tfhd.track_id),
+                    WhileParsingChild(NAME, BoxType::TRAF),
+                );
+            };
+            let base_data_offset = match tfhd.base_data_offset() {
+                Some(base_data_offset) => base_data_offset,
+                None if tfhd.default_base_is_moof() => moof_offset,
+                None => next_base_data_offset,
+            };
+            let default_sample_size = tfhd.default_sample_size().unwrap_or(trex.default_sample_size);
+
+            let mut data_offset = base_data_offset;
+            for trun in traf.truns() {
+                let trun = trun?;
+                if let Some(trun_data_offset) = trun.data_offset() {
+                    data_offset = checked_add_signed(base_data_offset, trun_data_offset.into()).ok_or_else(|| {
+                        report_attach!(
+                            ParseError::InvalidInput,
+                            "data offset overflow",
+                            WhileParsingChild(BoxType::TRAF, BoxType::TRUN),
+                        )
+                    })?;
+                }
+                let data_len = trun.sample_data_len(default_sample_size);
+                let data_end = data_len.and_then(|data_len| data_offset.checked_add(data_len));
+                let (Some(data_len), Some(data_end)) = (data_len, data_end) else {
+                    bail_attach!(
+                        ParseError::InvalidInput,
+                        "sample data length overflow",
+                        WhileParsingChild(BoxType::TRAF, BoxType::TRUN),
+                    );
+                };
+                if data_len != 0 {
+                    spans.push(InputSpan { offset: data_offset, len: data_len });
+                }
+                data_offset = data_end;
+            }
+            next_base_data_offset = data_offset;
+        }
+        Ok(spans)
+    }
+}
+
+impl BoxesValidator for MoofChildrenValidator {
+    fn validate<V>(children: &Boxes<V>) -> Result<(), ParseError> {
+        ensure_attach!(
+            children.box_types().any(|box_type| box_type == BoxType::MFHD),
+            ParseError::MissingRequiredBox(BoxType::MFHD),
+            WhileParsingField(NAME, "children"),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::{Mp4Box, TfhdBox, TrunBox};
+
+    use super::*;
+
+    fn test_moof(tfhd: TfhdBox, truns: &[TrunBox]) -> MoofBox {
+        let mut traf = vec![Mp4Box::with_data(tfhd.into()).unwrap().into()];
+        for trun in truns {
+            traf.push(Mp4Box::with_data(trun.clone().into()).unwrap().into());
+        }
+        let traf = Mp4Box::with_data(TrafBox::with_children(traf).into()).unwrap();
+        let mfhd = Mp4Box::with_data(MfhdBox::default().into()).unwrap();
+        MoofBox::with_children(vec![mfhd.into(), traf.into()])
+    }
+
+    fn test_trex(track_id: u32, default_sample_size: u32) -> TrexBox {
+        let mut trex = TrexBox::default();
+        trex.track_id = track_id;
+        trex.default_sample_size = default_sample_size;
+        trex
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        test_moof(TfhdBox::new(1), &[TrunBox::with_sample_sizes(None, [1])]).put_buf(&mut data);
+        let mut moof = MoofBox::parse(&mut data).unwrap();
+        assert_eq!(moof.trafs().count(), 1);
+    }
+
+    #[test]
+    fn no_mfhd() {
+        let mut data = BytesMut::new();
+        MoofBox::with_children(vec![]).put_buf(&mut data);
+        let err = MoofBox::parse(&mut data).unwrap_err();
+        assert!(
+            matches!(err.get_ref(), ParseError::MissingRequiredBox(BoxType::MFHD)),
+            "{err}",
+        );
+    }
+
+    #[test]
+    fn sample_data_spans() {
+        let truns = [
+            TrunBox::with_sample_sizes(Some(100), [1, 2]),
+            TrunBox::with_sample_sizes(None, [3]),
+        ];
+        let mut moof = test_moof(TfhdBox::new(1), &truns);
+        let spans = moof.sample_data_spans(1000, &[test_trex(1, 0)]).unwrap();
+        assert_eq!(
+            spans,
+            [InputSpan { offset: 1100, len: 3 }, InputSpan { offset: 1103, len: 3 }]
+        );
+    }
+
+    #[test]
+    fn sample_data_spans_base_data_offset() {
+        let mut tfhd = TfhdBox::new(1);
+        tfhd.set_base_data_offset(Some(50));
+        tfhd.set_default_sample_size(Some(4));
+        let mut moof = test_moof(tfhd, &[TrunBox::new(Some(-10), 2)]);
+        let spans = moof.sample_data_spans(1000, &[test_trex(1, 0)]).unwrap();
+        assert_eq!(spans, [InputSpan { offset: 40, len: 8 }]);
+    }
+
+    #[test]
+    fn sample_data_spans_trex_default_sample_size() {
+        let mut moof = test_moof(TfhdBox::new(2), &[TrunBox::new(None, 3)]);
+        let spans = moof.sample_data_spans(1000, &[test_trex(2, 5)]).unwrap();
+        assert_eq!(spans, [InputSpan { offset: 1000, len: 15 }]);
+    }
+
+    #[test]
+    fn sample_data_spans_no_trex() {
+        let mut moof = test_moof(TfhdBox::new(1), &[]);
+        let err = moof.sample_data_spans(0, &[test_trex(2, 0)]).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn sample_data_spans_overflow() {
+        let truns = [TrunBox::with_sample_sizes(Some(i32::MAX), [u32::MAX])];
+        let mut moof = test_moof(TfhdBox::new(1), &truns);
+        let err = moof
+            .sample_data_spans(u64::MAX - u64::from(u32::MAX), &[test_trex(1, 0)])
+            .unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
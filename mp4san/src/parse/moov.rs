@@ -2,8 +2,11 @@
 
 use crate::error::Result;
 
-use super::error::{ParseResultExt, WhileParsingField};
-use super::{BoxType, Boxes, BoxesValidator, ParseBox, ParseError, ParsedBox, TrakBox};
+use super::error::{ParseResultExt, WhileParsingBox, WhileParsingField};
+use super::{
+    BoxType, BoxUuid, Boxes, BoxesValidator, MvexBox, MvhdBox, ParseBox, ParseError, ParsedBox, PsshBox, TrakBox,
+    UdtaBox,
+};
 
 #[derive(Clone, Debug, ParseBox, ParsedBox)]
 #[box_type = "moov"]
@@ -13,6 +16,21 @@ pub struct MoovBox {
 
 pub(crate) struct MoovChildrenValidator;
 
+/// Boxes accepted by [`MoovBox::validate_strict_with`] besides those describing the structure of the movie and its
+/// tracks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StrictValidationOptions {
+    /// Whether to accept user data boxes (`udta`) containing only valid 3GPP asset boxes.
+    pub allow_3gpp_assets: bool,
+
+    /// Whether to accept the boxes used by common encryption: protection system specific headers (`pssh`) in the
+    /// movie, and sample encryption (`senc`) and auxiliary information (`saiz`/`saio`) boxes in sample tables.
+    pub allow_common_encryption: bool,
+
+    /// Whether to accept `uuid` extension boxes in the movie and each of its tracks.
+    pub allow_uuid_boxes: bool,
+}
+
 const NAME: BoxType = BoxType::MOOV;
 
 impl MoovBox {
@@ -21,15 +39,205 @@ impl MoovBox {
         Self { children: children.into() }
     }
 
+    pub fn mvhd_mut(&mut self) -> Result<&mut MvhdBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::MVHD)
+    }
+
+    pub fn mvex_mut(&mut self) -> Result<Option<&mut MvexBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::MVEX)
+    }
+
+    /// Removes each track for which `f` returns `false`, along with its track extends box (`trex`), if any.
+    ///
+    /// References to the removed tracks are removed from the track reference box (`tref`) of each remaining track, and
+    /// the next track ID in the movie header (`mvhd`) is set to follow the highest remaining track ID, unless it
+    /// indicates that the next track ID is unknown.
+    ///
+    /// Returns the IDs of the removed tracks. If every track would be removed, or `f` returns an error, no tracks are
+    /// removed and an error is returned instead.
+    pub fn retain_traks<F>(&mut self, mut f: F) -> Result<Vec<u32>, ParseError>
+    where
+        F: FnMut(&mut TrakBox) -> Result<bool, ParseError>,
+    {
+        let mut retain = Vec::new();
+        let mut removed_track_ids = Vec::new();
+        for trak in self.traks() {
+            let trak = trak?;
+            let retain_trak = f(trak)?;
+            if !retain_trak {
+                removed_track_ids.push(trak.tkhd_mut()?.track_id);
+            }
+            retain.push(retain_trak);
+        }
+        if removed_track_ids.is_empty() {
+            return Ok(removed_track_ids);
+        }
+        ensure_attach!(
+            retain.contains(&true),
+            ParseError::MissingRequiredBox(BoxType::TRAK),
+            "every track removed",
+            WhileParsingBox(NAME),
+        );
+
+        let mut retain = retain.into_iter();
+        self.children
+            .try_retain(|mp4box| match mp4box.parse_data_as::<TrakBox>()? {
+                Some(_) => Ok(retain.next().unwrap_or(true)),
+                None => Ok(true),
+            })?;
+        if let Some(mvex) = self.mvex_mut()? {
+            if !mvex.remove_trexs(&removed_track_ids)? {
+                self.children.remove(BoxType::MVEX);
+            }
+        }
+
+        let mut max_track_id = 0;
+        for trak in self.traks() {
+            let trak = trak?;
+            trak.remove_track_references(&removed_track_ids)?;
+            max_track_id = max_track_id.max(trak.tkhd_mut()?.track_id);
+        }
+        let mvhd = self.mvhd_mut()?;
+        if mvhd.next_track_id != MvhdBox::UNKNOWN_NEXT_TRACK_ID {
+            mvhd.next_track_id = max_track_id.saturating_add(1);
+        }
+        Ok(removed_track_ids)
+    }
+
+    /// Removes all user data (`udta`) and metadata (`meta`) boxes from this movie and each of its tracks.
+    ///
+    /// Returns whether any boxes were removed.
+    pub fn strip_user_metadata(&mut self) -> Result<bool, ParseError> {
+        let mut stripped = self.children.remove(BoxType::UDTA) + self.children.remove(BoxType::META) != 0;
+        for trak in self.traks() {
+            stripped |= trak?.strip_user_metadata();
+        }
+        Ok(stripped)
+    }
+
+    /// Removes each `uuid` extension box from this movie and each of its tracks for which `f` returns `false`.
+    ///
+    /// Returns the number of boxes removed. If `f` returns an error, it is returned instead, and boxes may already have
+    /// been removed from the movie or some of its tracks.
+    pub fn retain_uuid_boxes<F>(&mut self, mut f: F) -> Result<usize, ParseError>
+    where
+        F: FnMut(BoxUuid) -> Result<bool, ParseError>,
+    {
+        let mut removed_count = self.children.try_retain_uuid(&mut f)?;
+        for trak in self.traks() {
+            removed_count += trak?.retain_uuid_boxes(&mut f)?;
+        }
+        Ok(removed_count)
+    }
+
+    pub fn udta_mut(&mut self) -> Result<Option<&mut UdtaBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::UDTA)
+    }
+
+    /// Parses the 3GPP asset boxes in the user data box (`udta`) of this movie and each of its tracks.
+    pub fn validate_3gpp_assets(&mut self) -> Result<(), ParseError> {
+        if let Some(udta) = self.udta_mut()? {
+            udta.validate_3gpp_assets()?;
+        }
+        for trak in self.traks() {
+            if let Some(udta) = trak?.udta_mut()? {
+                udta.validate_3gpp_assets()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the protection system specific header boxes (`pssh`) of this movie.
+    pub fn psshs(&mut self) -> impl Iterator<Item = Result<&mut PsshBox, ParseError>> + '_ {
+        self.children
+            .get_mut()
+            .map(|result| result.while_parsing_child(NAME, BoxType::PSSH))
+    }
+
+    /// Returns the type of the first box indicating that this movie is protected, if any: either a protection system
+    /// specific header (`pssh`), or a [protected sample entry](super::StsdBox::PROTECTED_ENTRY_TYPES) of a track.
+    pub fn protected_box_type(&mut self) -> Result<Option<BoxType>, ParseError> {
+        if self.children.box_types().any(|box_type| box_type == BoxType::PSSH) {
+            return Ok(Some(BoxType::PSSH));
+        }
+        for trak in self.traks() {
+            let stsd = trak?.mdia_mut()?.minf_mut()?.stbl_mut()?.stsd_mut()?;
+            if let Some(protected_entry_type) = stsd.protected_entry_type() {
+                return Ok(Some(protected_entry_type));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parses the protection system specific header boxes (`pssh`) of this movie, and validates the common encryption
+    /// boxes of each of its tracks.
+    pub fn validate_common_encryption(&mut self) -> Result<(), ParseError> {
+        for pssh in self.psshs() {
+            pssh?;
+        }
+        for trak in self.traks() {
+            trak?.validate_common_encryption()?;
+        }
+        Ok(())
+    }
+
     pub fn traks(&mut self) -> impl Iterator<Item = Result<&mut TrakBox, ParseError>> + '_ {
         self.children
             .get_mut()
             .map(|result| result.while_parsing_child(NAME, BoxType::TRAK))
     }
+
+    /// Parses every descendant box, returning [`ParseError::UnsupportedBox`] for any box not known to the parser.
+    pub fn validate_strict(&mut self) -> Result<(), ParseError> {
+        self.validate_strict_with(Default::default())
+    }
+
+    /// Like [`validate_strict`](Self::validate_strict), but also accepts the boxes allowed by `options`.
+    pub fn validate_strict_with(&mut self, options: StrictValidationOptions) -> Result<(), ParseError> {
+        let mut children = vec![BoxType::MVHD, BoxType::TRAK, BoxType::MVEX];
+        if options.allow_3gpp_assets {
+            children.push(BoxType::UDTA);
+        }
+        if options.allow_common_encryption {
+            children.push(BoxType::PSSH);
+        }
+        if options.allow_uuid_boxes {
+            children.extend(
+                self.children
+                    .box_types()
+                    .filter(|box_type| matches!(box_type, BoxType::Uuid(_))),
+            );
+        }
+        self.children.ensure_only(NAME, &children)?;
+        self.mvhd_mut()?;
+        if let Some(udta) = self.udta_mut()? {
+            udta.validate_strict_3gpp()?;
+        }
+        for pssh in self.psshs() {
+            pssh?;
+        }
+        for trak in self.traks() {
+            trak?.validate_strict_with(options)?;
+        }
+        if let Some(mvex) = self.mvex_mut()? {
+            mvex.validate_strict()?;
+        }
+        Ok(())
+    }
 }
 
 impl BoxesValidator for MoovChildrenValidator {
     fn validate<V>(children: &Boxes<V>) -> Result<(), ParseError> {
+        // A QuickTime compressed movie contains its tracks compressed inside a `cmov` box, which we don't support.
+        ensure_attach!(
+            !children.box_types().any(|box_type| box_type == BoxType::CMOV),
+            ParseError::UnsupportedBox(BoxType::CMOV),
+            WhileParsingField(NAME, "children"),
+        );
         ensure_attach!(
             children.box_types().any(|box_type| box_type == BoxType::TRAK),
             ParseError::MissingRequiredBox(BoxType::TRAK),
@@ -43,7 +251,8 @@ impl BoxesValidator for MoovChildrenValidator {
 mod test {
     use bytes::BytesMut;
 
-    use crate::parse::Mp4Box;
+    use crate::parse::{FourCC, Mp4Box, TrackReference, TrefBox};
+    use crate::util::test::{test_tkhd, write_test_mvhd_data};
 
     use super::*;
 
@@ -58,6 +267,18 @@ mod test {
         MoovBox::parse(&mut data).unwrap();
     }
 
+    #[test]
+    fn cmov() {
+        let mut data = BytesMut::new();
+        let cmov = Mp4Box::with_bytes(BoxType::CMOV, BytesMut::new());
+        MoovBox::with_children(vec![cmov]).put_buf(&mut data);
+        let err = MoovBox::parse(&mut data).unwrap_err();
+        assert!(
+            matches!(err.get_ref(), ParseError::UnsupportedBox(BoxType::CMOV)),
+            "{err}"
+        );
+    }
+
     #[test]
     fn no_traks() {
         let mut data = BytesMut::new();
@@ -68,4 +289,60 @@ mod test {
             "{err}",
         );
     }
+
+    #[test]
+    fn retain_uuid_boxes() {
+        let uuid = |value: &[u8; 16]| Mp4Box::with_bytes(BoxType::Uuid(BoxUuid { value: *value }), BytesMut::new());
+        let trak = TrakBox::with_children(vec![uuid(b"thisisatestuuid!"), uuid(b"thisisatestuuid2")]);
+        let mut moov = MoovBox::with_children(vec![
+            uuid(b"thisisatestuuid!"),
+            Mp4Box::with_data(trak.into()).unwrap().into(),
+        ]);
+
+        let removed_count = moov
+            .retain_uuid_boxes(|uuid| Ok(uuid.value == *b"thisisatestuuid2"))
+            .unwrap();
+        assert_eq!(removed_count, 2);
+        assert_eq!(moov.children.box_types().collect::<Vec<_>>(), [BoxType::TRAK]);
+        let trak = moov.traks().next().unwrap().unwrap();
+        assert_eq!(trak.retain_uuid_boxes(|_| Ok(false)).unwrap(), 1);
+    }
+
+    fn test_referencing_trak(track_id: u32, referenced_track_ids: Vec<u32>) -> Mp4Box<TrakBox> {
+        let reference_type = BoxType::FourCC(FourCC { value: *b"cdsc" });
+        let tref = TrefBox::new(vec![TrackReference { reference_type, track_ids: referenced_track_ids }]);
+        let tref = Mp4Box::with_data(tref.into()).unwrap();
+        Mp4Box::with_data(TrakBox::with_children(vec![test_tkhd(track_id), tref.into()]).into()).unwrap()
+    }
+
+    #[test]
+    fn retain_traks() {
+        let mut mvhd_data = BytesMut::new();
+        write_test_mvhd_data(&mut mvhd_data);
+        let mut mvhd = MvhdBox::parse(&mut mvhd_data).unwrap();
+        mvhd.next_track_id = 4;
+        let mut moov = MoovBox::with_children(vec![
+            Mp4Box::with_data(mvhd.into()).unwrap().into(),
+            test_referencing_trak(1, vec![2, 3]).into(),
+            test_referencing_trak(2, vec![1]).into(),
+            test_referencing_trak(3, vec![1, 2]).into(),
+        ]);
+
+        let removed_track_ids = moov.retain_traks(|trak| Ok(trak.tkhd_mut()?.track_id != 2)).unwrap();
+        assert_eq!(removed_track_ids, [2]);
+        assert_eq!(moov.mvhd_mut().unwrap().next_track_id, 4);
+        let mut traks = moov.traks().map(Result::unwrap);
+        let tref = traks.next().unwrap().tref_mut().unwrap().unwrap();
+        assert_eq!(tref.references()[0].track_ids, [3]);
+        let tref = traks.next().unwrap().tref_mut().unwrap().unwrap();
+        assert_eq!(tref.references()[0].track_ids, [1]);
+        assert!(traks.next().is_none());
+        drop(traks);
+
+        let removed_track_ids = moov.retain_traks(|trak| Ok(trak.tkhd_mut()?.track_id != 3)).unwrap();
+        assert_eq!(removed_track_ids, [3]);
+        assert_eq!(moov.mvhd_mut().unwrap().next_track_id, 2);
+        let trak = moov.traks().next().unwrap().unwrap();
+        assert!(trak.tref_mut().unwrap().is_none());
+    }
 }
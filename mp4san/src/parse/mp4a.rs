@@ -0,0 +1,129 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{AudioSampleEntry, BoxDepth, BoxType, Boxes, EsdsBox, Mp4Value, ParseBox, ParseError, ParsedBox};
+
+/// An MPEG-4 audio sample entry, as defined by ISO/IEC 14496-14 section 6.7.2.
+#[derive(Clone, Debug)]
+pub struct Mp4aBox {
+    pub entry: AudioSampleEntry,
+    quicktime_fields: BytesMut,
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::MP4A;
+
+/// The length of the additional fields of a version 1 QuickTime sound sample description.
+const QUICKTIME_V1_FIELDS_LEN: usize = 16;
+
+/// The length of the additional fields of a version 2 QuickTime sound sample description.
+const QUICKTIME_V2_FIELDS_LEN: usize = 36;
+
+impl Mp4aBox {
+    pub fn with_children<C: Into<Boxes>>(entry: AudioSampleEntry, children: C) -> Self {
+        Self { entry, quicktime_fields: BytesMut::new(), children: children.into() }
+    }
+
+    pub fn esds_mut(&mut self) -> Result<&mut EsdsBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::ESDS)
+    }
+}
+
+impl ParseBox for Mp4aBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        Self::parse_at_depth(buf, Default::default())
+    }
+
+    fn parse_at_depth(buf: &mut BytesMut, depth: BoxDepth) -> Result<Self, ParseError> {
+        let entry = AudioSampleEntry::parse(&mut *buf).while_parsing_field(NAME, "entry")?;
+        let quicktime_fields_len = match entry.version() {
+            0 => 0,
+            1 => QUICKTIME_V1_FIELDS_LEN,
+            2 => QUICKTIME_V2_FIELDS_LEN,
+            version => bail_attach!(
+                ParseError::InvalidInput,
+                format!("unsupported sound sample description version {version}"),
+                WhileParsingField(NAME, "entry"),
+            ),
+        };
+        ensure_attach!(
+            quicktime_fields_len <= buf.len(),
+            ParseError::TruncatedBox,
+            WhileParsingField(NAME, "entry"),
+        );
+        let quicktime_fields = buf.split_to(quicktime_fields_len);
+        let children = Boxes::parse_at_depth(&mut *buf, depth).while_parsing_field(NAME, "children")?;
+        Ok(Self { entry, quicktime_fields, children })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for Mp4aBox {
+    fn encoded_len(&self) -> u64 {
+        self.entry.encoded_len() + self.quicktime_fields.len() as u64 + self.children.encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.entry.put_buf(&mut out);
+        out.put_slice(&self.quicktime_fields);
+        self.children.put_buf(&mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::test_mp4a;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut mp4a_box = test_mp4a(&[0x12, 0x10]);
+        let mut expected = BytesMut::new();
+        mp4a_box.put_buf(&mut expected);
+        let mp4a = mp4a_box.parse_data_as::<Mp4aBox>().unwrap().unwrap();
+        assert_eq!((mp4a.entry.channelcount, mp4a.entry.samplerate >> 16), (2, 44100));
+        assert_eq!(
+            mp4a.esds_mut()
+                .unwrap()
+                .parse_audio_specific_config()
+                .unwrap()
+                .channel_configuration,
+            2
+        );
+        let mut encoded = BytesMut::new();
+        mp4a_box.put_buf(&mut encoded);
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn quicktime_v1() {
+        let mut data = BytesMut::new();
+        AudioSampleEntry::new(2, 44100).put_buf(&mut data);
+        data[9] = 1; // version
+        data.put_slice(&[0; QUICKTIME_V1_FIELDS_LEN]);
+        let expected = data.clone();
+        let mp4a = Mp4aBox::parse(&mut data).unwrap();
+        assert_eq!(mp4a.entry.version(), 1);
+        let mut encoded = BytesMut::new();
+        mp4a.put_buf(&mut encoded);
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn quicktime_v1_truncated() {
+        let mut data = BytesMut::new();
+        AudioSampleEntry::new(2, 44100).put_buf(&mut data);
+        data[9] = 1; // version
+        data.put_slice(&[0; QUICKTIME_V1_FIELDS_LEN - 1]);
+        let err = Mp4aBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
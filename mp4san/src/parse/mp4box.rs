@@ -11,22 +11,22 @@ use derive_more::From;
 use derive_where::derive_where;
 use downcast_rs::{impl_downcast, Downcast};
 use dyn_clonable::clonable;
-use futures_util::io::BufReader;
 use futures_util::{AsyncRead, AsyncReadExt};
 use mediasan_common::error::WhileParsingType;
 use mediasan_common::{AsyncSkipExt, ResultExt};
 
 use crate::error::Result;
 use crate::util::IoResultExt;
-use crate::{AsyncSkip, BoxDataTooLarge, Error};
+use crate::{AsyncSkip, BoxDataTooLarge, Error, LimitExceeded};
 
 use super::error::{MultipleBoxes, WhileParsingBox};
-use super::{BoxHeader, BoxType, Mp4Value, ParseError};
+use super::{BoxHeader, BoxType, BoxUuid, Mp4Value, ParseError};
 
 #[derive(Debug)]
 #[derive_where(Clone; BoxData<T>)]
 pub struct Mp4Box<T: ?Sized> {
     parsed_header: BoxHeader,
+    depth: BoxDepth,
     pub data: BoxData<T>,
 }
 
@@ -42,6 +42,13 @@ pub enum BoxData<T: ?Sized> {
 pub trait ParseBox: Sized {
     fn parse(buf: &mut BytesMut) -> Result<Self, ParseError>;
 
+    /// Parses the data of a box nested at `depth`, as [`parse`](Self::parse) does.
+    ///
+    /// The default implementation ignores the depth, and is only suitable for boxes which don't contain child boxes.
+    fn parse_at_depth(buf: &mut BytesMut, _depth: BoxDepth) -> Result<Self, ParseError> {
+        Self::parse(buf)
+    }
+
     fn box_type() -> BoxType;
 }
 
@@ -52,6 +59,17 @@ pub trait ParsedBox: Clone + Debug + Downcast {
     fn put_buf(&self, out: &mut dyn BufMut);
 }
 
+/// The depth at which a box is nested, counting top-level boxes as depth 1, along with the maximum depth allowed for
+/// boxes containing child boxes.
+///
+/// The depth is tracked as boxes are parsed from within other boxes, and exceeding the maximum depth causes parsing to
+/// fail with [`ParseError::InvalidInput`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoxDepth {
+    depth: u32,
+    max_depth: u32,
+}
+
 #[derive(From)]
 #[derive_where(Clone, Debug, Default)]
 pub struct Boxes<V = ()> {
@@ -71,15 +89,11 @@ impl<T: ParsedBox + ?Sized> Mp4Box<T> {
         T: ParseBox,
     {
         let parsed_header = BoxHeader::with_data_size(T::box_type(), data.encoded_len())?;
-        Ok(Self { parsed_header, data })
+        Ok(Self { parsed_header, depth: Default::default(), data })
     }
 
-    /// Read and parse a box's data assuming its header has already been read.
-    pub(crate) async fn read_data<R>(
-        mut reader: Pin<&mut BufReader<R>>,
-        header: BoxHeader,
-        max_size: u64,
-    ) -> StdResult<Self, Error>
+    /// Read a box's data assuming its header has already been read, failing if it is larger than `max_size`.
+    pub async fn read_data<R>(mut reader: Pin<&mut R>, header: BoxHeader, max_size: u64) -> StdResult<Self, Error>
     where
         R: AsyncRead + AsyncSkip,
         T: ParseBox,
@@ -103,7 +117,7 @@ impl<T: ParsedBox + ?Sized> Mp4Box<T> {
                 WhileParsingBox(header.box_type())
             ))
         })?;
-        Ok(Self { parsed_header: header, data: BoxData::Bytes(buf) })
+        Ok(Self { parsed_header: header, depth: Default::default(), data: BoxData::Bytes(buf) })
     }
 
     pub fn calculated_header(&self) -> BoxHeader {
@@ -121,15 +135,24 @@ impl<T: ParsedBox + ?Sized> Mp4Box<T> {
         if self.parsed_header.box_type() != U::box_type() {
             return Ok(None);
         }
-        self.data.parse_as()
+        self.data.parse_as(self.depth)
     }
-}
 
-impl<T: ParsedBox + ?Sized> Mp4Value for Mp4Box<T> {
-    fn parse(mut buf: &mut BytesMut) -> Result<Self, ParseError> {
+    fn parse_with_depth(mut buf: &mut BytesMut, depth: BoxDepth) -> Result<Self, ParseError> {
         let parsed_header = BoxHeader::parse(&mut buf).attach_printable(WhileParsingType::new::<Self>())?;
         let data = BoxData::get_from_bytes_mut(buf, &parsed_header).attach_printable(WhileParsingType::new::<Self>())?;
-        Ok(Self { parsed_header, data })
+        Ok(Self { parsed_header, depth, data })
+    }
+}
+
+impl<T: ParsedBox + ?Sized> Mp4Value for Mp4Box<T> {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        Self::parse_with_depth(buf, Default::default())
+    }
+
+    fn parse_at_depth(buf: &mut BytesMut, depth: BoxDepth) -> Result<Self, ParseError> {
+        let child_depth = depth.child().attach_printable(WhileParsingType::new::<Self>())?;
+        Self::parse_with_depth(buf, child_depth)
     }
 
     fn encoded_len(&self) -> u64 {
@@ -145,13 +168,13 @@ impl<T: ParsedBox + ?Sized> Mp4Value for Mp4Box<T> {
 impl AnyMp4Box {
     pub fn with_bytes(box_type: BoxType, bytes: BytesMut) -> Self {
         let parsed_header = BoxHeader::with_data_size(box_type, bytes.len() as u64).expect("box size overflow");
-        Self { parsed_header, data: BoxData::Bytes(bytes) }
+        Self { parsed_header, depth: Default::default(), data: BoxData::Bytes(bytes) }
     }
 }
 
 impl<T: ParsedBox> From<Mp4Box<T>> for AnyMp4Box {
     fn from(from: Mp4Box<T>) -> Self {
-        Self { parsed_header: from.parsed_header, data: from.data.into() }
+        Self { parsed_header: from.parsed_header, depth: from.depth, data: from.data.into() }
     }
 }
 
@@ -182,11 +205,19 @@ impl<T: ParsedBox + ?Sized> BoxData<T> {
     }
 
     pub fn parse(&mut self) -> Result<&mut T, ParseError>
+    where
+        T: ParseBox + Sized,
+    {
+        self.parse_at_depth(Default::default())
+    }
+
+    /// Parses the data of a box nested at `depth`, if it hasn't been parsed already.
+    pub fn parse_at_depth(&mut self, depth: BoxDepth) -> Result<&mut T, ParseError>
     where
         T: ParseBox + Sized,
     {
         if let BoxData::Bytes(data) = self {
-            let parsed = T::parse(data).while_parsing_type()?;
+            let parsed = T::parse_at_depth(data, depth).while_parsing_type()?;
             ensure_attach!(
                 data.is_empty(),
                 ParseError::InvalidInput,
@@ -201,9 +232,12 @@ impl<T: ParsedBox + ?Sized> BoxData<T> {
         }
     }
 
-    fn parse_as<U: ParseBox + ParsedBox + Into<Box<T>>>(&mut self) -> Result<Option<&mut U>, ParseError> {
+    fn parse_as<U: ParseBox + ParsedBox + Into<Box<T>>>(
+        &mut self,
+        depth: BoxDepth,
+    ) -> Result<Option<&mut U>, ParseError> {
         if let BoxData::Bytes(data) = self {
-            let parsed = U::parse(data).while_parsing_type()?;
+            let parsed = U::parse_at_depth(data, depth).while_parsing_type()?;
             ensure_attach!(
                 data.is_empty(),
                 ParseError::InvalidInput,
@@ -252,6 +286,44 @@ impl<T: ParsedBox + Sized> From<T> for BoxData<T> {
     }
 }
 
+//
+// BoxDepth impls
+//
+
+impl BoxDepth {
+    /// The depth of a top-level box, allowing boxes containing child boxes to be nested at most `max_depth` deep.
+    pub fn top_level(max_depth: u32) -> Self {
+        Self { depth: 1, max_depth }
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    /// Returns the depth of the child boxes of a box nested at this depth.
+    ///
+    /// Fails with [`ParseError::InvalidInput`] if a box nested at this depth may not contain child boxes.
+    pub fn child(self) -> Result<Self, ParseError> {
+        ensure_attach!(
+            self.depth <= self.max_depth,
+            ParseError::InvalidInput,
+            LimitExceeded("box depth", u64::from(self.depth), self.max_depth.into()),
+        );
+        Ok(Self { depth: self.depth.saturating_add(1), ..self })
+    }
+}
+
+impl Default for BoxDepth {
+    /// The depth of a top-level box, without any maximum depth.
+    fn default() -> Self {
+        Self::top_level(u32::MAX)
+    }
+}
+
 //
 // ParsedBox impls
 //
@@ -269,10 +341,60 @@ impl<'a, T: ParsedBox + 'a> From<T> for Box<dyn ParsedBox + 'a> {
 //
 
 impl<V> Boxes<V> {
-    pub fn box_types(&self) -> impl Iterator<Item = BoxType> + ExactSizeIterator + '_ {
+    pub fn box_types(&self) -> impl ExactSizeIterator<Item = BoxType> + '_ {
         self.boxes.iter().map(|mp4box| mp4box.parsed_header.box_type())
     }
 
+    /// Ensures that every box is one of `box_types`, returning [`ParseError::UnsupportedBox`] otherwise.
+    pub fn ensure_only(&self, parent: BoxType, box_types: &[BoxType]) -> Result<(), ParseError> {
+        for box_type in self.box_types() {
+            ensure_attach!(
+                box_types.contains(&box_type),
+                ParseError::UnsupportedBox(box_type),
+                WhileParsingBox(parent),
+            );
+        }
+        Ok(())
+    }
+
+    /// Removes all boxes of type `box_type`, returning the number of boxes removed.
+    pub fn remove(&mut self, box_type: BoxType) -> usize {
+        let len = self.boxes.len();
+        self.boxes.retain(|mp4box| mp4box.parsed_header.box_type() != box_type);
+        len - self.boxes.len()
+    }
+
+    /// Removes all boxes for which `f` returns `false`, returning the number of boxes removed.
+    ///
+    /// If `f` returns an error, no boxes are removed.
+    pub fn try_retain<F>(&mut self, mut f: F) -> Result<usize, ParseError>
+    where
+        F: FnMut(&mut AnyMp4Box) -> Result<bool, ParseError>,
+    {
+        let retain = self.boxes.iter_mut().map(&mut f).collect::<Result<Vec<_>, _>>()?;
+        let len = self.boxes.len();
+        let mut retain = retain.into_iter();
+        self.boxes.retain(|_| retain.next().unwrap_or(true));
+        Ok(len - self.boxes.len())
+    }
+
+    /// Removes all `uuid` extension boxes for which `f` returns `false`, returning the number of boxes removed.
+    ///
+    /// If `f` returns an error, no boxes are removed.
+    pub fn try_retain_uuid<F>(&mut self, mut f: F) -> Result<usize, ParseError>
+    where
+        F: FnMut(BoxUuid) -> Result<bool, ParseError>,
+    {
+        self.try_retain(|mp4box| match mp4box.parsed_header.box_type() {
+            BoxType::Uuid(uuid) => f(uuid),
+            BoxType::FourCC(_) => Ok(true),
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl ExactSizeIterator<Item = &mut AnyMp4Box> + '_ {
+        self.boxes.iter_mut()
+    }
+
     pub fn get_mut<T: ParseBox + ParsedBox>(&mut self) -> impl Iterator<Item = Result<&mut T, ParseError>> {
         self.boxes
             .iter_mut()
@@ -289,13 +411,26 @@ impl<V> Boxes<V> {
             .next()
             .ok_or_else(|| ParseError::MissingRequiredBox(T::box_type()))?
     }
+
+    pub fn get_optional_mut<T: ParseBox + ParsedBox>(&mut self) -> Result<Option<&mut T>, ParseError> {
+        ensure_attach!(
+            self.box_types().filter(|box_type| *box_type == T::box_type()).count() <= 1,
+            ParseError::InvalidBoxLayout,
+            MultipleBoxes(T::box_type()),
+        );
+        self.get_mut().next().transpose()
+    }
 }
 
 impl<V: BoxesValidator> Mp4Value for Boxes<V> {
     fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        Self::parse_at_depth(buf, Default::default())
+    }
+
+    fn parse_at_depth(buf: &mut BytesMut, depth: BoxDepth) -> Result<Self, ParseError> {
         let mut boxes = Vec::new();
         while buf.has_remaining() {
-            boxes.push(Mp4Box::parse(buf)?);
+            boxes.push(Mp4Box::parse_at_depth(buf, depth)?);
         }
         let boxes = Self { boxes, _validator: PhantomData };
         V::validate(&boxes)?;
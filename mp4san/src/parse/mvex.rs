@@ -0,0 +1,89 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, Boxes, BoxesValidator, ParseBox, ParseError, ParsedBox, TrexBox};
+
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "mvex"]
+pub struct MvexBox {
+    children: Boxes<MvexChildrenValidator>,
+}
+
+pub(crate) struct MvexChildrenValidator;
+
+const NAME: BoxType = BoxType::MVEX;
+
+impl MvexBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes<MvexChildrenValidator>>>(children: C) -> Self {
+        Self { children: children.into() }
+    }
+
+    /// Removes the track extends boxes (`trex`) for each of `track_ids`, returning whether any track extends boxes
+    /// remain.
+    pub fn remove_trexs(&mut self, track_ids: &[u32]) -> Result<bool, ParseError> {
+        self.children
+            .try_retain(|mp4box| match mp4box.parse_data_as::<TrexBox>()? {
+                Some(trex) => Ok(!track_ids.contains(&trex.track_id)),
+                None => Ok(true),
+            })?;
+        Ok(self.children.box_types().any(|box_type| box_type == BoxType::TREX))
+    }
+
+    pub fn trexs(&mut self) -> impl Iterator<Item = Result<&mut TrexBox, ParseError>> + '_ {
+        self.children
+            .get_mut()
+            .map(|result| result.while_parsing_child(NAME, BoxType::TREX))
+    }
+
+    /// Parses every descendant box, returning [`ParseError::UnsupportedBox`] for any box not known to the parser.
+    pub fn validate_strict(&mut self) -> Result<(), ParseError> {
+        self.children.ensure_only(NAME, &[BoxType::TREX])?;
+        for trex in self.trexs() {
+            trex?;
+        }
+        Ok(())
+    }
+}
+
+impl BoxesValidator for MvexChildrenValidator {
+    fn validate<V>(children: &Boxes<V>) -> Result<(), ParseError> {
+        ensure_attach!(
+            children.box_types().any(|box_type| box_type == BoxType::TREX),
+            ParseError::MissingRequiredBox(BoxType::TREX),
+            WhileParsingField(NAME, "children"),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::Mp4Box;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        let trex = Mp4Box::with_data(TrexBox::default().into()).unwrap();
+        MvexBox::with_children(vec![trex.into()]).put_buf(&mut data);
+        let mut mvex = MvexBox::parse(&mut data).unwrap();
+        assert_eq!(mvex.trexs().count(), 1);
+    }
+
+    #[test]
+    fn no_trexs() {
+        let mut data = BytesMut::new();
+        MvexBox::with_children(vec![]).put_buf(&mut data);
+        let err = MvexBox::parse(&mut data).unwrap_err();
+        assert!(
+            matches!(err.get_ref(), ParseError::MissingRequiredBox(BoxType::TREX)),
+            "{err}",
+        );
+    }
+}
@@ -0,0 +1,137 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MvhdBox {
+    header: FullBoxHeader,
+    pub creation_time: u64,
+    pub modification_time: u64,
+    pub timescale: u32,
+    pub duration: u64,
+    pub rate: i32,
+    pub volume: i16,
+    reserved: [u16; 5],
+    pub matrix: [i32; 9],
+    pre_defined: [u32; 6],
+    pub next_track_id: u32,
+}
+
+const NAME: BoxType = BoxType::MVHD;
+
+impl MvhdBox {
+    /// The value of [`next_track_id`](Self::next_track_id) indicating that an unused track ID must be searched for.
+    pub const UNKNOWN_NEXT_TRACK_ID: u32 = u32::MAX;
+}
+
+impl ParseBox for MvhdBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        let creation_time = header
+            .parse_versioned_u64(&mut *buf)
+            .while_parsing_field(NAME, "creation_time")?;
+        let modification_time = header
+            .parse_versioned_u64(&mut *buf)
+            .while_parsing_field(NAME, "modification_time")?;
+        let timescale = u32::parse(&mut *buf).while_parsing_field(NAME, "timescale")?;
+        let duration = header
+            .parse_versioned_u64(&mut *buf)
+            .while_parsing_field(NAME, "duration")?;
+        let rate = i32::parse(&mut *buf).while_parsing_field(NAME, "rate")?;
+        let volume = i16::parse(&mut *buf).while_parsing_field(NAME, "volume")?;
+        let reserved = <[u16; 5]>::parse(&mut *buf).while_parsing_field(NAME, "reserved")?;
+        let matrix = <[i32; 9]>::parse(&mut *buf).while_parsing_field(NAME, "matrix")?;
+        let pre_defined = <[u32; 6]>::parse(&mut *buf).while_parsing_field(NAME, "pre_defined")?;
+        let next_track_id = u32::parse(&mut *buf).while_parsing_field(NAME, "next_track_id")?;
+        Ok(Self {
+            header,
+            creation_time,
+            modification_time,
+            timescale,
+            duration,
+            rate,
+            volume,
+            reserved,
+            matrix,
+            pre_defined,
+            next_track_id,
+        })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for MvhdBox {
+    fn encoded_len(&self) -> u64 {
+        FullBoxHeader::encoded_len()
+            + 3 * self.header.versioned_u64_len()
+            + u32::encoded_len()
+            + i32::encoded_len()
+            + i16::encoded_len()
+            + <[u16; 5]>::encoded_len()
+            + <[i32; 9]>::encoded_len()
+            + <[u32; 6]>::encoded_len()
+            + u32::encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.header.put_versioned_u64(self.creation_time, &mut out);
+        self.header.put_versioned_u64(self.modification_time, &mut out);
+        self.timescale.put_buf(&mut out);
+        self.header.put_versioned_u64(self.duration, &mut out);
+        self.rate.put_buf(&mut out);
+        self.volume.put_buf(&mut out);
+        self.reserved.put_buf(&mut out);
+        self.matrix.put_buf(&mut out);
+        self.pre_defined.put_buf(&mut out);
+        self.next_track_id.put_buf(&mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::write_test_mvhd_data;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        write_test_mvhd_data(&mut data);
+        let expected = data.clone();
+        let mvhd = MvhdBox::parse(&mut data).unwrap();
+        assert_eq!(mvhd.timescale, 1);
+        assert_eq!(mvhd.next_track_id, u32::MAX);
+        let mut encoded = BytesMut::new();
+        mvhd.put_buf(&mut encoded);
+        assert_eq!(encoded.len() as u64, mvhd.encoded_len());
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn version_1() {
+        let mut mvhd = MvhdBox { header: FullBoxHeader { version: 1, flags: 0 }, ..Default::default() };
+        mvhd.duration = u64::MAX;
+        let mut data = BytesMut::new();
+        mvhd.put_buf(&mut data);
+        assert_eq!(data.len() as u64, mvhd.encoded_len());
+        assert_eq!(MvhdBox::parse(&mut data).unwrap(), mvhd);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut data = BytesMut::new();
+        write_test_mvhd_data(&mut data);
+        data.truncate(data.len() - 1);
+        let err = MvhdBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
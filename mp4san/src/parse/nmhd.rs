@@ -0,0 +1,9 @@
+#![allow(missing_docs)]
+
+use super::{ConstFullBoxHeader, ParseBox, ParsedBox};
+
+#[derive(Clone, Debug, Default, ParseBox, ParsedBox)]
+#[box_type = "nmhd"]
+pub struct NmhdBox {
+    header: ConstFullBoxHeader,
+}
@@ -0,0 +1,123 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoundedArray, BoxType, FullBoxHeader, Mp4Value, ParseBox, ParseError, ParsedBox};
+
+/// A protection system specific header box, as defined by ISO/IEC 23001-7 section 8.1.
+#[derive(Clone, Debug)]
+pub struct PsshBox {
+    header: FullBoxHeader,
+    pub system_id: [u8; 16],
+    kids: BoundedArray<u32, [u8; 16]>,
+    data: BoundedArray<u32, u8>,
+}
+
+const NAME: BoxType = BoxType::PSSH;
+
+impl PsshBox {
+    pub fn new(system_id: [u8; 16], kids: impl IntoIterator<Item = [u8; 16]>, data: &[u8]) -> Self {
+        let kids: BoundedArray<u32, [u8; 16]> = kids.into_iter().collect();
+        let version = if kids.entry_count() != 0 { 1 } else { 0 };
+        Self { header: FullBoxHeader { version, flags: 0 }, system_id, kids, data: data.iter().copied().collect() }
+    }
+
+    /// Returns the key IDs of the protected content, which are only present in version 1 boxes.
+    pub fn kids(&self) -> impl ExactSizeIterator<Item = [u8; 16]> + '_ {
+        self.kids
+            .entries()
+            .map(|kid| kid.get().unwrap_or_else(|_| unreachable!()))
+    }
+
+    pub fn data_len(&self) -> u32 {
+        self.data.entry_count()
+    }
+}
+
+impl ParseBox for PsshBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version <= 1,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingField(NAME, "header"),
+        );
+        let system_id = <[u8; 16]>::parse(&mut *buf).while_parsing_field(NAME, "SystemID")?;
+        let kids = match header.version {
+            0 => Default::default(),
+            _ => BoundedArray::parse(&mut *buf).while_parsing_field(NAME, "KIDs")?,
+        };
+        let data = BoundedArray::parse(&mut *buf).while_parsing_field(NAME, "Data")?;
+        Ok(Self { header, system_id, kids, data })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for PsshBox {
+    fn encoded_len(&self) -> u64 {
+        let mut len = self.header.encoded_len() + self.system_id.encoded_len() + self.data.encoded_len();
+        if self.header.version != 0 {
+            len += self.kids.encoded_len();
+        }
+        len
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.system_id.put_buf(&mut out);
+        if self.header.version != 0 {
+            self.kids.put_buf(&mut out);
+        }
+        self.data.put_buf(&mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_SYSTEM_ID: [u8; 16] = *b"testsystemid0123";
+    const TEST_KID: [u8; 16] = *b"thisisatestkeyid";
+
+    #[test]
+    fn roundtrip() {
+        for pssh in [
+            PsshBox::new(TEST_SYSTEM_ID, [], b"data"),
+            PsshBox::new(TEST_SYSTEM_ID, [TEST_KID], b""),
+        ] {
+            let mut data = BytesMut::new();
+            pssh.put_buf(&mut data);
+            assert_eq!(data.len() as u64, pssh.encoded_len());
+            let parsed = PsshBox::parse(&mut data).unwrap();
+            assert!(data.is_empty());
+            assert_eq!(parsed.system_id, TEST_SYSTEM_ID);
+            assert_eq!(parsed.kids().collect::<Vec<_>>(), pssh.kids().collect::<Vec<_>>());
+            assert_eq!(parsed.data_len(), pssh.data_len());
+        }
+    }
+
+    #[test]
+    fn truncated_data() {
+        let mut data = BytesMut::new();
+        PsshBox::new(TEST_SYSTEM_ID, [TEST_KID], b"data").put_buf(&mut data);
+        data.truncate(data.len() - 1);
+        let err = PsshBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+
+    #[test]
+    fn truncated_kids() {
+        let mut data = BytesMut::new();
+        PsshBox::new(TEST_SYSTEM_ID, [TEST_KID], b"").put_buf(&mut data);
+        data[23] = 2;
+        let err = PsshBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
@@ -0,0 +1,159 @@
+#![allow(missing_docs)]
+
+use std::io::Cursor;
+
+use bitstream_io::{BigEndian, BitRead, BitReader, Numeric};
+
+use crate::error::Result;
+
+use super::ParseError;
+
+/// A bit reader over the raw byte sequence payload (RBSP) of an H.264 or H.265 NAL unit.
+pub struct RbspReader {
+    reader: BitReader<Cursor<Vec<u8>>, BigEndian>,
+}
+
+/// The maximum number of leading zero bits in an Exp-Golomb code whose value fits in a `u32`.
+const MAX_EXP_GOLOMB_LEADING_ZEROS: u32 = 32;
+
+impl RbspReader {
+    /// Construct a reader over `nal_unit`, including its header, removing any emulation prevention bytes.
+    pub fn new(nal_unit: &[u8]) -> Self {
+        let mut rbsp = Vec::with_capacity(nal_unit.len());
+        let mut zero_count = 0;
+        for &byte in nal_unit {
+            if zero_count >= 2 && byte == 3 {
+                zero_count = 0;
+                continue;
+            }
+            zero_count = if byte == 0 { zero_count + 1 } else { 0 };
+            rbsp.push(byte);
+        }
+        Self { reader: BitReader::new(Cursor::new(rbsp)) }
+    }
+
+    pub fn read<T: Numeric>(&mut self, bits: u32) -> Result<T, ParseError> {
+        self.reader
+            .read(bits)
+            .map_err(|_| report_attach!(ParseError::InvalidInput, "truncated NAL unit"))
+    }
+
+    pub fn read_bit(&mut self) -> Result<bool, ParseError> {
+        self.reader
+            .read_bit()
+            .map_err(|_| report_attach!(ParseError::InvalidInput, "truncated NAL unit"))
+    }
+
+    pub fn skip(&mut self, bits: u32) -> Result<(), ParseError> {
+        self.reader
+            .skip(bits)
+            .map_err(|_| report_attach!(ParseError::InvalidInput, "truncated NAL unit"))
+    }
+
+    /// Read an unsigned Exp-Golomb code (`ue(v)`).
+    pub fn read_ue(&mut self) -> Result<u32, ParseError> {
+        let mut leading_zeros = 0;
+        while !self.read_bit()? {
+            leading_zeros += 1;
+            ensure_attach!(
+                leading_zeros <= MAX_EXP_GOLOMB_LEADING_ZEROS,
+                ParseError::InvalidInput,
+                "Exp-Golomb code too long",
+            );
+        }
+        let suffix = match leading_zeros {
+            0 => 0,
+            _ => self.read::<u64>(leading_zeros)?,
+        };
+        let value = (1 << leading_zeros) - 1 + suffix;
+        u32::try_from(value).map_err(|_| report_attach!(ParseError::InvalidInput, "Exp-Golomb code too long"))
+    }
+
+    /// Read an unsigned Exp-Golomb code (`ue(v)`), ensuring its value is at most `max`.
+    pub fn read_ue_max(&mut self, max: u32) -> Result<u32, ParseError> {
+        let value = self.read_ue()?;
+        ensure_attach!(
+            value <= max,
+            ParseError::InvalidInput,
+            format!("value {value} greater than maximum {max}"),
+        );
+        Ok(value)
+    }
+
+    /// Read a signed Exp-Golomb code (`se(v)`).
+    pub fn read_se(&mut self) -> Result<i32, ParseError> {
+        let code = i64::from(self.read_ue()?);
+        let value = match code % 2 {
+            0 => -(code / 2),
+            _ => (code + 1) / 2,
+        };
+        i32::try_from(value).map_err(|_| report_attach!(ParseError::InvalidInput, "Exp-Golomb code too long"))
+    }
+
+    /// Read a signed Exp-Golomb code (`se(v)`), ensuring its value lies within `min..=max`.
+    pub fn read_se_range(&mut self, min: i32, max: i32) -> Result<i32, ParseError> {
+        let value = self.read_se()?;
+        ensure_attach!(
+            (min..=max).contains(&value),
+            ParseError::InvalidInput,
+            format!("value {value} not within range {min}..={max}"),
+        );
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exp_golomb() {
+        // 1 010 011 00100 00101 0000001000000 (0 * 32)1(0 * 32)
+        let data = [
+            0b1010_0110,
+            0b0100_0010,
+            0b1000_0001,
+            0,
+            0,
+            0,
+            0,
+            0b0000_0010,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let mut reader = RbspReader::new(&data);
+        assert_eq!(reader.read_ue().unwrap(), 0);
+        assert_eq!(reader.read_ue().unwrap(), 1);
+        assert_eq!(reader.read_se().unwrap(), -1);
+        assert_eq!(reader.read_se().unwrap(), 2);
+        assert_eq!(reader.read_se().unwrap(), -2);
+        assert_eq!(reader.read_ue().unwrap(), 63);
+        assert_eq!(reader.read_ue().unwrap(), u32::MAX);
+    }
+
+    #[test]
+    fn exp_golomb_too_long() {
+        let mut reader = RbspReader::new(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x80]);
+        let err = reader.read_ue().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn emulation_prevention() {
+        let mut reader = RbspReader::new(&[0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03]);
+        assert_eq!(reader.read::<u32>(32).unwrap(), 0x00000100);
+        assert_eq!(reader.read::<u8>(8).unwrap(), 0x00);
+        let err = reader.read::<u8>(1).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn read_ue_max() {
+        let mut reader = RbspReader::new(&[0b0010_0001, 0b0100_0000]);
+        assert_eq!(reader.read_ue_max(3).unwrap(), 3);
+        let err = reader.read_ue_max(3).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
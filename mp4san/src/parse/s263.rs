@@ -0,0 +1,41 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{BoxType, Boxes, D263Box, ParseBox, ParseError, ParsedBox, VisualSampleEntry};
+
+/// An H.263 visual sample entry, as defined by 3GPP TS 26.244 section 6.8.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "s263"]
+pub struct S263Box {
+    pub entry: VisualSampleEntry,
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::S263;
+
+impl S263Box {
+    pub fn with_children<C: Into<Boxes>>(entry: VisualSampleEntry, children: C) -> Self {
+        Self { entry, children: children.into() }
+    }
+
+    pub fn d263_mut(&mut self) -> Result<&mut D263Box, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::D263)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::test_s263;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut s263_box = test_s263(10, 0);
+        let s263 = s263_box.parse_data_as::<S263Box>().unwrap().unwrap();
+        assert_eq!((s263.entry.width, s263.entry.height), (176, 144));
+        assert_eq!(s263.d263_mut().unwrap().h263_level, 10);
+    }
+}
@@ -0,0 +1,147 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, FourCC, FullBoxHeader, Mp4Value, ParseBox, ParseError, ParsedBox};
+
+/// A sample auxiliary information offsets box, as defined by ISO/IEC 14496-12 section 8.7.9.
+#[derive(Clone, Debug)]
+pub struct SaioBox {
+    header: FullBoxHeader,
+    pub aux_info_type: Option<(FourCC, u32)>,
+    entry_count: u32,
+    offsets: BytesMut,
+}
+
+const NAME: BoxType = BoxType::SAIO;
+
+impl SaioBox {
+    pub const AUX_INFO_TYPE_PRESENT: u32 = 0x00_0001;
+
+    pub fn with_offsets(offsets: impl IntoIterator<Item = u64>) -> Self {
+        let offsets: Vec<u64> = offsets.into_iter().collect();
+        let version = if offsets.iter().any(|&offset| offset > u32::MAX.into()) {
+            1
+        } else {
+            0
+        };
+        let mut offsets_bytes = BytesMut::new();
+        for offset in &offsets {
+            match version {
+                0 => offsets_bytes.put_u32(*offset as u32),
+                _ => offsets_bytes.put_u64(*offset),
+            }
+        }
+        Self {
+            header: FullBoxHeader { version, flags: 0 },
+            aux_info_type: None,
+            entry_count: offsets.len() as u32,
+            offsets: offsets_bytes,
+        }
+    }
+
+    pub fn entry_count(&self) -> u32 {
+        self.entry_count
+    }
+
+    /// Returns the offsets of the auxiliary information of each chunk, or of all chunks if there is only one.
+    pub fn offsets(&self) -> impl Iterator<Item = u64> + '_ {
+        let offset_len = Self::offset_len(self.header.version);
+        self.offsets
+            .chunks_exact(offset_len)
+            .map(move |mut offset| match offset_len {
+                4 => offset.get_u32().into(),
+                _ => offset.get_u64(),
+            })
+    }
+
+    fn offset_len(version: u8) -> usize {
+        match version {
+            0 => 4,
+            _ => 8,
+        }
+    }
+}
+
+impl ParseBox for SaioBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version <= 1,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingField(NAME, "header"),
+        );
+        let aux_info_type = match header.flags & Self::AUX_INFO_TYPE_PRESENT {
+            0 => None,
+            _ => Some((
+                <FourCC as Mp4Value>::parse(&mut *buf).while_parsing_field(NAME, "aux_info_type")?,
+                u32::parse(&mut *buf).while_parsing_field(NAME, "aux_info_type_parameter")?,
+            )),
+        };
+        let entry_count = u32::parse(&mut *buf).while_parsing_field(NAME, "entry_count")?;
+        let offsets_len = u64::from(entry_count) * Self::offset_len(header.version) as u64;
+        ensure_attach!(
+            buf.remaining() as u64 >= offsets_len,
+            ParseError::TruncatedBox,
+            WhileParsingField(NAME, "offset"),
+        );
+        let offsets = buf.split_to(offsets_len as usize);
+        Ok(Self { header, aux_info_type, entry_count, offsets })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for SaioBox {
+    fn encoded_len(&self) -> u64 {
+        let aux_info_type_len = match self.aux_info_type {
+            Some(_) => 8,
+            None => 0,
+        };
+        self.header.encoded_len() + aux_info_type_len + self.entry_count.encoded_len() + self.offsets.len() as u64
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        if let Some((aux_info_type, aux_info_type_parameter)) = self.aux_info_type {
+            aux_info_type.put_buf(&mut out);
+            aux_info_type_parameter.put_buf(&mut out);
+        }
+        self.entry_count.put_buf(&mut out);
+        out.put_slice(&self.offsets);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for offsets in [vec![0x1000], vec![0x1000, 0x1_0000_0000]] {
+            let saio = SaioBox::with_offsets(offsets.clone());
+            let mut data = BytesMut::new();
+            saio.put_buf(&mut data);
+            assert_eq!(data.len() as u64, saio.encoded_len());
+            let saio = SaioBox::parse(&mut data).unwrap();
+            assert!(data.is_empty());
+            assert_eq!(saio.entry_count() as usize, offsets.len());
+            assert_eq!(saio.offsets().collect::<Vec<_>>(), offsets);
+        }
+    }
+
+    #[test]
+    fn truncated_offsets() {
+        let mut data = BytesMut::new();
+        SaioBox::with_offsets([0x1000, 0x2000]).put_buf(&mut data);
+        data.truncate(data.len() - 1);
+        let err = SaioBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
@@ -0,0 +1,144 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, FourCC, FullBoxHeader, Mp4Value, ParseBox, ParseError, ParsedBox};
+
+/// A sample auxiliary information sizes box, as defined by ISO/IEC 14496-12 section 8.7.8.
+#[derive(Clone, Debug)]
+pub struct SaizBox {
+    header: FullBoxHeader,
+    pub aux_info_type: Option<(FourCC, u32)>,
+    pub default_sample_info_size: u8,
+    sample_count: u32,
+    sample_info_sizes: BytesMut,
+}
+
+const NAME: BoxType = BoxType::SAIZ;
+
+impl SaizBox {
+    pub const AUX_INFO_TYPE_PRESENT: u32 = 0x00_0001;
+
+    pub fn with_default_size(default_sample_info_size: u8, sample_count: u32) -> Self {
+        Self {
+            header: FullBoxHeader::default(),
+            aux_info_type: None,
+            default_sample_info_size,
+            sample_count,
+            sample_info_sizes: BytesMut::new(),
+        }
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Returns the size of the auxiliary information of each sample.
+    pub fn sample_info_sizes(&self) -> impl Iterator<Item = u8> + '_ {
+        let default_sizes = (self.default_sample_info_size != 0)
+            .then(|| (0..self.sample_count).map(|_| self.default_sample_info_size))
+            .into_iter()
+            .flatten();
+        default_sizes.chain(self.sample_info_sizes.iter().copied())
+    }
+}
+
+impl ParseBox for SaizBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version == 0,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingField(NAME, "header"),
+        );
+        let aux_info_type = match header.flags & Self::AUX_INFO_TYPE_PRESENT {
+            0 => None,
+            _ => Some((
+                <FourCC as Mp4Value>::parse(&mut *buf).while_parsing_field(NAME, "aux_info_type")?,
+                u32::parse(&mut *buf).while_parsing_field(NAME, "aux_info_type_parameter")?,
+            )),
+        };
+        let default_sample_info_size = u8::parse(&mut *buf).while_parsing_field(NAME, "default_sample_info_size")?;
+        let sample_count = u32::parse(&mut *buf).while_parsing_field(NAME, "sample_count")?;
+        let sample_info_sizes = if default_sample_info_size == 0 {
+            ensure_attach!(
+                buf.remaining() as u64 >= sample_count.into(),
+                ParseError::TruncatedBox,
+                WhileParsingField(NAME, "sample_info_size"),
+            );
+            buf.split_to(sample_count as usize)
+        } else {
+            BytesMut::new()
+        };
+        Ok(Self { header, aux_info_type, default_sample_info_size, sample_count, sample_info_sizes })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for SaizBox {
+    fn encoded_len(&self) -> u64 {
+        let aux_info_type_len = match self.aux_info_type {
+            Some(_) => 8,
+            None => 0,
+        };
+        self.header.encoded_len() + aux_info_type_len + 5 + self.sample_info_sizes.len() as u64
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        if let Some((aux_info_type, aux_info_type_parameter)) = self.aux_info_type {
+            aux_info_type.put_buf(&mut out);
+            aux_info_type_parameter.put_buf(&mut out);
+        }
+        self.default_sample_info_size.put_buf(&mut out);
+        self.sample_count.put_buf(&mut out);
+        out.put_slice(&self.sample_info_sizes);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        SaizBox::with_default_size(16, 3).put_buf(&mut data);
+        assert_eq!(data.len() as u64, SaizBox::with_default_size(16, 3).encoded_len());
+        let saiz = SaizBox::parse(&mut data).unwrap();
+        assert!(data.is_empty());
+        assert_eq!(saiz.sample_info_sizes().collect::<Vec<_>>(), [16, 16, 16]);
+    }
+
+    #[test]
+    fn sample_info_sizes() {
+        let mut saiz = SaizBox::with_default_size(0, 3);
+        saiz.header.flags = SaizBox::AUX_INFO_TYPE_PRESENT;
+        saiz.aux_info_type = Some((FourCC::from_str("cenc"), 0));
+        saiz.sample_info_sizes.put_slice(&[8, 14, 20]);
+        let mut data = BytesMut::new();
+        saiz.put_buf(&mut data);
+        assert_eq!(data.len() as u64, saiz.encoded_len());
+        let saiz = SaizBox::parse(&mut data).unwrap();
+        assert!(data.is_empty());
+        assert_eq!(saiz.aux_info_type, Some((FourCC::from_str("cenc"), 0)));
+        assert_eq!(saiz.sample_info_sizes().collect::<Vec<_>>(), [8, 14, 20]);
+    }
+
+    #[test]
+    fn truncated_sample_info_sizes() {
+        let mut saiz = SaizBox::with_default_size(0, 3);
+        saiz.sample_info_sizes.put_slice(&[8, 14]);
+        let mut data = BytesMut::new();
+        saiz.put_buf(&mut data);
+        let err = SaizBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
@@ -0,0 +1,41 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{AudioSampleEntry, BoxType, Boxes, DamrBox, ParseBox, ParseError, ParsedBox};
+
+/// An AMR-WB (wideband) audio sample entry, as defined by 3GPP TS 26.244 section 6.5.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "sawb"]
+pub struct SawbBox {
+    pub entry: AudioSampleEntry,
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::SAWB;
+
+impl SawbBox {
+    pub fn with_children<C: Into<Boxes>>(entry: AudioSampleEntry, children: C) -> Self {
+        Self { entry, children: children.into() }
+    }
+
+    pub fn damr_mut(&mut self) -> Result<&mut DamrBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::DAMR)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::test_amr_sample_entry;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut sawb_box = test_amr_sample_entry(NAME);
+        let sawb = sawb_box.parse_data_as::<SawbBox>().unwrap().unwrap();
+        assert_eq!(sawb.entry.channelcount, 1);
+        sawb.damr_mut().unwrap().validate(true).unwrap();
+    }
+}
@@ -0,0 +1,46 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{BoxType, Boxes, ParseBox, ParseError, ParsedBox, TencBox};
+
+/// A scheme information box, as defined by ISO/IEC 14496-12 section 8.12.6.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "schi"]
+pub struct SchiBox {
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::SCHI;
+
+impl SchiBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes>>(children: C) -> Self {
+        Self { children: children.into() }
+    }
+
+    pub fn tenc_mut(&mut self) -> Result<Option<&mut TencBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::TENC)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::Mp4Box;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let tenc = Mp4Box::with_data(TencBox::new(8, [0; 16]).into()).unwrap();
+        let mut data = BytesMut::new();
+        SchiBox::with_children(vec![tenc.into()]).put_buf(&mut data);
+        let mut schi = SchiBox::parse(&mut data).unwrap();
+        assert_eq!(schi.tenc_mut().unwrap().unwrap().default_per_sample_iv_size, 8);
+    }
+}
@@ -0,0 +1,132 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, FourCC, FullBoxHeader, Mp4Value, ParseBox, ParseError, ParsedBox};
+
+/// A scheme type box, as defined by ISO/IEC 14496-12 section 8.12.5.
+#[derive(Clone, Debug)]
+pub struct SchmBox {
+    header: FullBoxHeader,
+    pub scheme_type: FourCC,
+    pub scheme_version: u32,
+    scheme_uri: BytesMut,
+}
+
+const NAME: BoxType = BoxType::SCHM;
+
+impl SchmBox {
+    pub const SCHEME_URI_PRESENT: u32 = 0x00_0001;
+
+    /// The AES-CTR full sample and video NAL subsample encryption scheme, as defined by ISO/IEC 23001-7.
+    pub const CENC: FourCC = FourCC::from_str("cenc");
+
+    /// The AES-CBC full sample and video NAL subsample encryption scheme, as defined by ISO/IEC 23001-7.
+    pub const CBC1: FourCC = FourCC::from_str("cbc1");
+
+    /// The AES-CTR partial video NAL pattern encryption scheme, as defined by ISO/IEC 23001-7.
+    pub const CENS: FourCC = FourCC::from_str("cens");
+
+    /// The AES-CBC partial video NAL pattern encryption scheme, as defined by ISO/IEC 23001-7.
+    pub const CBCS: FourCC = FourCC::from_str("cbcs");
+
+    /// The common encryption schemes defined by ISO/IEC 23001-7.
+    pub const COMMON_ENCRYPTION_SCHEMES: [FourCC; 4] = [Self::CENC, Self::CBC1, Self::CENS, Self::CBCS];
+
+    pub fn new(scheme_type: FourCC, scheme_version: u32) -> Self {
+        Self { header: FullBoxHeader::default(), scheme_type, scheme_version, scheme_uri: BytesMut::new() }
+    }
+
+    /// Returns whether the scheme is one of the [common encryption schemes](Self::COMMON_ENCRYPTION_SCHEMES).
+    pub fn is_common_encryption(&self) -> bool {
+        Self::COMMON_ENCRYPTION_SCHEMES.contains(&self.scheme_type)
+    }
+
+    /// Returns whether the scheme uses AES-CBC mode, which requires 16-byte initialization vectors.
+    pub fn is_cbc_mode(&self) -> bool {
+        self.scheme_type == Self::CBC1 || self.scheme_type == Self::CBCS
+    }
+}
+
+impl ParseBox for SchmBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version == 0,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingField(NAME, "header"),
+        );
+        let scheme_type = <FourCC as Mp4Value>::parse(&mut *buf).while_parsing_field(NAME, "scheme_type")?;
+        let scheme_version = u32::parse(&mut *buf).while_parsing_field(NAME, "scheme_version")?;
+        let scheme_uri = if header.flags & Self::SCHEME_URI_PRESENT != 0 {
+            ensure_attach!(
+                buf.last() == Some(&0),
+                ParseError::InvalidInput,
+                "unterminated scheme URI",
+                WhileParsingField(NAME, "scheme_uri"),
+            );
+            buf.split()
+        } else {
+            BytesMut::new()
+        };
+        Ok(Self { header, scheme_type, scheme_version, scheme_uri })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for SchmBox {
+    fn encoded_len(&self) -> u64 {
+        self.header.encoded_len()
+            + self.scheme_type.encoded_len()
+            + self.scheme_version.encoded_len()
+            + self.scheme_uri.len() as u64
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.scheme_type.put_buf(&mut out);
+        self.scheme_version.put_buf(&mut out);
+        out.put_slice(&self.scheme_uri);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        SchmBox::new(SchmBox::CBCS, 0x10000).put_buf(&mut data);
+        assert_eq!(data.len() as u64, SchmBox::new(SchmBox::CBCS, 0x10000).encoded_len());
+        let schm = SchmBox::parse(&mut data).unwrap();
+        assert_eq!((schm.scheme_type, schm.scheme_version), (SchmBox::CBCS, 0x10000));
+        assert!(schm.is_common_encryption() && schm.is_cbc_mode());
+    }
+
+    #[test]
+    fn scheme_uri() {
+        let mut schm = SchmBox::new(FourCC::from_str("test"), 1);
+        schm.header.flags = SchmBox::SCHEME_URI_PRESENT;
+        schm.scheme_uri.put_slice(b"urn:test\0");
+        let mut data = BytesMut::new();
+        schm.put_buf(&mut data);
+        let schm = SchmBox::parse(&mut data).unwrap();
+        assert_eq!(&schm.scheme_uri[..], b"urn:test\0");
+        assert!(!schm.is_common_encryption());
+
+        let mut data = BytesMut::new();
+        let mut unterminated = schm.clone();
+        unterminated.scheme_uri.truncate(8);
+        unterminated.put_buf(&mut data);
+        let err = SchmBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
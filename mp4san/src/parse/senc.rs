@@ -0,0 +1,151 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingBox, WhileParsingField};
+use super::{BoxType, FullBoxHeader, Mp4Value, ParseBox, ParseError, ParsedBox};
+
+/// A sample encryption box, as defined by ISO/IEC 23001-7 section 7.2.
+///
+/// The sample entries can only be parsed given the size of their initialization vectors, which is specified by the
+/// track encryption box (`tenc`), so they are only [validated](Self::validate) on request.
+#[derive(Clone, Debug)]
+pub struct SencBox {
+    header: FullBoxHeader,
+    sample_count: u32,
+    samples: BytesMut,
+}
+
+const NAME: BoxType = BoxType::SENC;
+
+impl SencBox {
+    pub const USE_SUBSAMPLE_ENCRYPTION: u32 = 0x00_0002;
+
+    /// The encoded length of each subsample entry, consisting of the number of clear and protected bytes.
+    const SUBSAMPLE_LEN: usize = 6;
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn use_subsample_encryption(&self) -> bool {
+        self.header.flags & Self::USE_SUBSAMPLE_ENCRYPTION != 0
+    }
+
+    /// Validates that the box contains exactly [`sample_count`](Self::sample_count) sample entries, each with an
+    /// initialization vector of `per_sample_iv_size` bytes.
+    pub fn validate(&self, per_sample_iv_size: u8) -> Result<(), ParseError> {
+        let mut samples = &self.samples[..];
+        for _ in 0..self.sample_count {
+            ensure_attach!(
+                samples.remaining() >= per_sample_iv_size.into(),
+                ParseError::TruncatedBox,
+                WhileParsingField(NAME, "InitializationVector"),
+            );
+            samples.advance(per_sample_iv_size.into());
+            if self.use_subsample_encryption() {
+                ensure_attach!(
+                    samples.remaining() >= 2,
+                    ParseError::TruncatedBox,
+                    WhileParsingField(NAME, "subsample_count"),
+                );
+                let subsample_count = samples.get_u16();
+                let subsamples_len = usize::from(subsample_count) * Self::SUBSAMPLE_LEN;
+                ensure_attach!(
+                    samples.remaining() >= subsamples_len,
+                    ParseError::TruncatedBox,
+                    WhileParsingField(NAME, "subsamples"),
+                );
+                samples.advance(subsamples_len);
+            }
+        }
+        ensure_attach!(
+            samples.is_empty(),
+            ParseError::InvalidInput,
+            "extra unparsed data",
+            WhileParsingBox(NAME),
+        );
+        Ok(())
+    }
+}
+
+impl ParseBox for SencBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version == 0,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingField(NAME, "header"),
+        );
+        let sample_count = u32::parse(&mut *buf).while_parsing_field(NAME, "sample_count")?;
+        Ok(Self { header, sample_count, samples: buf.split() })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for SencBox {
+    fn encoded_len(&self) -> u64 {
+        self.header.encoded_len() + self.sample_count.encoded_len() + self.samples.len() as u64
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.sample_count.put_buf(&mut out);
+        out.put_slice(&self.samples);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::write_test_senc_data;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        write_test_senc_data(&mut data, 3, 8, true);
+        let expected = data.clone();
+        let senc = SencBox::parse(&mut data).unwrap();
+        assert_eq!(senc.sample_count(), 3);
+        assert!(senc.use_subsample_encryption());
+        senc.validate(8).unwrap();
+        let mut encoded = BytesMut::new();
+        senc.put_buf(&mut encoded);
+        assert_eq!(encoded.len() as u64, senc.encoded_len());
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn without_subsamples() {
+        let mut data = BytesMut::new();
+        write_test_senc_data(&mut data, 3, 16, false);
+        SencBox::parse(&mut data).unwrap().validate(16).unwrap();
+    }
+
+    #[test]
+    fn iv_size_mismatch() {
+        let mut data = BytesMut::new();
+        write_test_senc_data(&mut data, 3, 8, false);
+        let senc = SencBox::parse(&mut data).unwrap();
+        let err = senc.validate(16).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+        let err = senc.validate(0).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn truncated_subsamples() {
+        let mut data = BytesMut::new();
+        write_test_senc_data(&mut data, 3, 8, true);
+        data.truncate(data.len() - 1);
+        let err = SencBox::parse(&mut data).unwrap().validate(8).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
@@ -0,0 +1,169 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingBox, WhileParsingChild, WhileParsingField};
+use super::{BoxType, Boxes, BoxesValidator, FrmaBox, ParseBox, ParseError, ParsedBox, SchiBox, SchmBox, TencBox};
+
+/// A protection scheme information box, as defined by ISO/IEC 14496-12 section 8.12.1.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "sinf"]
+pub struct SinfBox {
+    children: Boxes<SinfChildrenValidator>,
+}
+
+pub(crate) struct SinfChildrenValidator;
+
+const NAME: BoxType = BoxType::SINF;
+
+impl SinfBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes<SinfChildrenValidator>>>(children: C) -> Self {
+        Self { children: children.into() }
+    }
+
+    pub fn frma_mut(&mut self) -> Result<&mut FrmaBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::FRMA)
+    }
+
+    pub fn schm_mut(&mut self) -> Result<Option<&mut SchmBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::SCHM)
+    }
+
+    pub fn schi_mut(&mut self) -> Result<Option<&mut SchiBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::SCHI)
+    }
+
+    /// Parses the original format, scheme type, and scheme information, returning the track encryption box (`tenc`)
+    /// for the [common encryption schemes](SchmBox::COMMON_ENCRYPTION_SCHEMES), or [`ParseError::UnsupportedBox`] for
+    /// any other scheme.
+    ///
+    /// The initialization vectors of the `cbc1` and `cbcs` schemes must be 16 bytes long.
+    pub fn validate_common_encryption(&mut self) -> Result<&mut TencBox, ParseError> {
+        self.children
+            .ensure_only(NAME, &[BoxType::FRMA, BoxType::SCHM, BoxType::SCHI])?;
+        self.frma_mut()?;
+        let Some(schm) = self.schm_mut()? else {
+            bail_attach!(ParseError::MissingRequiredBox(BoxType::SCHM), WhileParsingBox(NAME));
+        };
+        ensure_attach!(
+            schm.is_common_encryption(),
+            ParseError::UnsupportedBox(NAME),
+            format!("unsupported protection scheme {}", schm.scheme_type),
+            WhileParsingChild(NAME, BoxType::SCHM),
+        );
+        let is_cbc_mode = schm.is_cbc_mode();
+        let Some(schi) = self.schi_mut()? else {
+            bail_attach!(ParseError::MissingRequiredBox(BoxType::SCHI), WhileParsingBox(NAME));
+        };
+        let Some(tenc) = schi.tenc_mut()? else {
+            bail_attach!(
+                ParseError::MissingRequiredBox(BoxType::TENC),
+                WhileParsingBox(BoxType::SCHI)
+            );
+        };
+        if is_cbc_mode {
+            let iv_size = match tenc.default_constant_iv() {
+                Some(constant_iv) => constant_iv.len(),
+                None => tenc.default_per_sample_iv_size.into(),
+            };
+            ensure_attach!(
+                !tenc.default_is_protected || iv_size == 16,
+                ParseError::InvalidInput,
+                format!("invalid IV size {iv_size} for CBC mode"),
+                WhileParsingChild(NAME, BoxType::TENC),
+            );
+        }
+        Ok(tenc)
+    }
+}
+
+impl BoxesValidator for SinfChildrenValidator {
+    fn validate<V>(children: &Boxes<V>) -> Result<(), ParseError> {
+        ensure_attach!(
+            children.box_types().any(|box_type| box_type == BoxType::FRMA),
+            ParseError::MissingRequiredBox(BoxType::FRMA),
+            WhileParsingField(NAME, "children"),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::{fourcc, FourCC, Mp4Box};
+    use crate::util::test::test_sinf;
+
+    use super::*;
+
+    fn parse_sinf(sinf: SinfBox) -> SinfBox {
+        let mut data = BytesMut::new();
+        sinf.put_buf(&mut data);
+        SinfBox::parse(&mut data).unwrap()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut sinf_box = test_sinf(fourcc::AVC1, SchmBox::CENC, TencBox::new(8, [0; 16]));
+        let sinf = sinf_box.parse_data_as::<SinfBox>().unwrap().unwrap();
+        assert_eq!(sinf.frma_mut().unwrap().data_format, fourcc::AVC1);
+        assert_eq!(sinf.schm_mut().unwrap().unwrap().scheme_type, SchmBox::CENC);
+        let tenc = sinf.validate_common_encryption().unwrap();
+        assert_eq!(tenc.default_per_sample_iv_size, 8);
+    }
+
+    #[test]
+    fn cbcs() {
+        let tenc = TencBox::with_pattern(1, 9, &[0; 16], [0; 16]);
+        let mut sinf_box = test_sinf(fourcc::AVC1, SchmBox::CBCS, tenc);
+        let sinf = sinf_box.parse_data_as::<SinfBox>().unwrap().unwrap();
+        sinf.validate_common_encryption().unwrap();
+    }
+
+    #[test]
+    fn cbcs_short_iv() {
+        let mut sinf_box = test_sinf(fourcc::AVC1, SchmBox::CBCS, TencBox::new(8, [0; 16]));
+        let sinf = sinf_box.parse_data_as::<SinfBox>().unwrap().unwrap();
+        let err = sinf.validate_common_encryption().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn unsupported_scheme() {
+        let mut sinf_box = test_sinf(fourcc::AVC1, FourCC::from_str("test"), TencBox::new(8, [0; 16]));
+        let sinf = sinf_box.parse_data_as::<SinfBox>().unwrap().unwrap();
+        let err = sinf.validate_common_encryption().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::UnsupportedBox(NAME)), "{err}");
+    }
+
+    #[test]
+    fn missing_tenc() {
+        let frma = Mp4Box::with_data(FrmaBox::new(fourcc::AVC1).into()).unwrap();
+        let schm = Mp4Box::with_data(SchmBox::new(SchmBox::CENC, 0x10000).into()).unwrap();
+        let schi = Mp4Box::with_data(SchiBox::with_children(vec![]).into()).unwrap();
+        let mut sinf = parse_sinf(SinfBox::with_children(vec![frma.into(), schm.into(), schi.into()]));
+        let err = sinf.validate_common_encryption().unwrap_err();
+        assert!(
+            matches!(err.get_ref(), ParseError::MissingRequiredBox(BoxType::TENC)),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn missing_frma() {
+        let schm = Mp4Box::with_data(SchmBox::new(SchmBox::CENC, 0x10000).into()).unwrap();
+        let mut data = BytesMut::new();
+        SinfBox::with_children(vec![schm.into()]).put_buf(&mut data);
+        let err = SinfBox::parse(&mut data).unwrap_err();
+        assert!(
+            matches!(err.get_ref(), ParseError::MissingRequiredBox(BoxType::FRMA)),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,26 @@
+#![allow(missing_docs)]
+
+use super::{ConstFullBoxHeader, ParseBox, ParsedBox};
+
+#[derive(Clone, Debug, Default, ParseBox, ParsedBox)]
+#[box_type = "smhd"]
+pub struct SmhdBox {
+    header: ConstFullBoxHeader,
+    pub balance: i16,
+    reserved: u16,
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        SmhdBox::default().put_buf(&mut data);
+        assert_eq!(data.len() as u64, SmhdBox::default().encoded_len());
+        SmhdBox::parse(&mut data).unwrap();
+    }
+}
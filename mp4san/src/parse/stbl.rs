@@ -1,9 +1,13 @@
 #![allow(missing_docs)]
 
 use crate::error::Result;
+use crate::InputSpan;
 
-use super::error::{ParseResultExt, WhileParsingChild};
-use super::{BoxType, Boxes, Co64Box, ParseBox, ParseError, ParsedBox, StcoBox};
+use super::error::{ParseResultExt, WhileParsingBox, WhileParsingChild};
+use super::{
+    AnyMp4Box, BoxType, Boxes, Co64Box, CttsBox, Mp4Box, ParseBox, ParseError, ParsedBox, QuickTimeSoundV1Fields,
+    SaioBox, SaizBox, SencBox, StcoBox, StrictValidationOptions, StscBox, StsdBox, StssBox, StszBox, SttsBox,
+};
 
 #[derive(Clone, Debug, ParseBox, ParsedBox)]
 #[box_type = "stbl"]
@@ -48,6 +52,263 @@ impl StblBox {
                 .map(StblCoMut::Co64)
         }
     }
+
+    /// Replaces the stco box with a co64 box containing the same chunk offsets, so that they may be displaced past
+    /// [`u32::MAX`].
+    ///
+    /// Does nothing if the chunk offsets are already stored in a co64 box.
+    pub fn upgrade_stco_to_co64(&mut self) -> Result<(), ParseError> {
+        let StblCoMut::Stco(stco) = self.co_mut()? else {
+            return Ok(());
+        };
+        let co64: Co64Box = stco
+            .entries_mut()
+            .map(|entry| u64::from(entry.get().unwrap_or_else(|_| unreachable!())))
+            .collect();
+        let co64 = AnyMp4Box::from(Mp4Box::with_data(co64.into()).while_parsing_child(NAME, CO64)?);
+        if let Some(mp4box) = self
+            .children
+            .iter_mut()
+            .find(|mp4box| mp4box.calculated_header().box_type() == STCO)
+        {
+            *mp4box = co64;
+        }
+        Ok(())
+    }
+
+    /// Returns the spans of the input containing each chunk of samples, as computed from the sample tables.
+    ///
+    /// The spans are returned in chunk order, and include empty chunks.
+    pub fn chunk_spans(&mut self) -> Result<Vec<InputSpan>, ParseError> {
+        self.chunk_spans_with(&[])
+    }
+
+    /// Returns the spans of the input containing each chunk of samples, as [`chunk_spans`](Self::chunk_spans) does,
+    /// given the [additional fields](StsdBox::quicktime_sound_v1_fields) of each sample entry which is a version 1
+    /// QuickTime sound sample description.
+    ///
+    /// Chunks described by such a sample entry with a constant sample size of 1 hold uncompressed frames rather than
+    /// bytes, and their lengths are computed from the packet sizes given by the sample entry.
+    pub fn chunk_spans_with(
+        &mut self,
+        quicktime_sound_v1_fields: &[Option<QuickTimeSoundV1Fields>],
+    ) -> Result<Vec<InputSpan>, ParseError> {
+        let chunk_offsets = self.co_mut()?.chunk_offsets();
+        if chunk_offsets.is_empty() {
+            return Ok(Vec::new());
+        }
+        let stsc_entries: Vec<_> = self.stsc_mut()?.entries().collect();
+        let stsz = self.stsz_mut()?;
+
+        ensure_attach!(
+            stsc_entries.first().map(|entry| entry.first_chunk) == Some(1),
+            ParseError::InvalidInput,
+            "first sample-to-chunk entry does not start at chunk 1",
+            WhileParsingBox(BoxType::STSC),
+        );
+        ensure_attach!(
+            stsc_entries
+                .windows(2)
+                .all(|entries| entries[0].first_chunk < entries[1].first_chunk),
+            ParseError::InvalidInput,
+            "sample-to-chunk entries not in increasing chunk order",
+            WhileParsingBox(BoxType::STSC),
+        );
+
+        let sample_count_mismatch = || {
+            report_attach!(
+                ParseError::InvalidInput,
+                "sample count does not match sample-to-chunk table",
+                WhileParsingBox(BoxType::STSZ),
+            )
+        };
+        let mut spans = Vec::with_capacity(chunk_offsets.len());
+        let mut remaining_sample_count = stsz.sample_count();
+        let mut sample_sizes = stsz.sample_sizes();
+        let mut stsc_entries = stsc_entries.into_iter().peekable();
+        let mut samples_per_chunk = 0;
+        let mut sample_description_index = 0;
+        for (chunk_index, chunk_offset) in chunk_offsets.into_iter().enumerate() {
+            let chunk_number = chunk_index as u64 + 1;
+            while let Some(entry) = stsc_entries.next_if(|entry| u64::from(entry.first_chunk) <= chunk_number) {
+                samples_per_chunk = entry.samples_per_chunk;
+                sample_description_index = entry.sample_description_index;
+            }
+            remaining_sample_count = remaining_sample_count
+                .checked_sub(samples_per_chunk)
+                .ok_or_else(sample_count_mismatch)?;
+
+            // NB: Avoid iterating over each sample when they're all the same size, as the sample count is unbounded.
+            let quicktime_sound_v1_fields = (sample_description_index as usize)
+                .checked_sub(1)
+                .and_then(|index| quicktime_sound_v1_fields.get(index))
+                .copied()
+                .flatten();
+            let chunk_len = match (stsz.sample_size(), quicktime_sound_v1_fields) {
+                (Some(1), Some(fields)) => fields.samples_len(samples_per_chunk).ok_or_else(|| {
+                    report_attach!(
+                        ParseError::InvalidInput,
+                        "zero samples per packet in sound sample description",
+                        WhileParsingBox(BoxType::STSD),
+                    )
+                })?,
+                (Some(sample_size), _) => u64::from(samples_per_chunk) * u64::from(sample_size),
+                (None, _) => (&mut sample_sizes)
+                    .take(samples_per_chunk as usize)
+                    .map(u64::from)
+                    .sum(),
+            };
+            spans.push(InputSpan { offset: chunk_offset, len: chunk_len });
+        }
+        ensure_attach!(remaining_sample_count == 0, sample_count_mismatch());
+        Ok(spans)
+    }
+
+    pub fn ctts_mut(&mut self) -> Result<Option<&mut CttsBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::CTTS)
+    }
+
+    pub fn saio_mut(&mut self) -> Result<Option<&mut SaioBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::SAIO)
+    }
+
+    pub fn saiz_mut(&mut self) -> Result<Option<&mut SaizBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::SAIZ)
+    }
+
+    pub fn senc_mut(&mut self) -> Result<Option<&mut SencBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::SENC)
+    }
+
+    pub fn stsc_mut(&mut self) -> Result<&mut StscBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::STSC)
+    }
+
+    pub fn stsd_mut(&mut self) -> Result<&mut StsdBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::STSD)
+    }
+
+    pub fn stss_mut(&mut self) -> Result<Option<&mut StssBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::STSS)
+    }
+
+    pub fn stsz_mut(&mut self) -> Result<&mut StszBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::STSZ)
+    }
+
+    pub fn stts_mut(&mut self) -> Result<&mut SttsBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::STTS)
+    }
+
+    /// Validates the protection scheme information of each protected sample entry, along with any sample encryption
+    /// (`senc`) and auxiliary information (`saiz`/`saio`) boxes.
+    ///
+    /// The sample encryption box must describe every sample, using the initialization vector size given by the track
+    /// encryption box (`tenc`) of every protected sample entry. The auxiliary information sizes box (`saiz`) must
+    /// describe every sample, and its offsets box (`saio`) must contain a single offset or one for each chunk.
+    pub fn validate_common_encryption(&mut self) -> Result<(), ParseError> {
+        let tencs = self.stsd_mut()?.validate_common_encryption()?;
+        let sample_count = self.stsz_mut()?.sample_count();
+        if let Some(senc) = self.senc_mut()? {
+            let Some(tenc) = tencs.first() else {
+                bail_attach!(
+                    ParseError::MissingRequiredBox(BoxType::TENC),
+                    WhileParsingChild(NAME, BoxType::SENC)
+                );
+            };
+            let per_sample_iv_size = tenc.default_per_sample_iv_size;
+            ensure_attach!(
+                tencs
+                    .iter()
+                    .all(|tenc| tenc.default_per_sample_iv_size == per_sample_iv_size),
+                ParseError::InvalidInput,
+                "sample entries with differing IV sizes",
+                WhileParsingChild(NAME, BoxType::SENC),
+            );
+            ensure_attach!(
+                senc.sample_count() == sample_count,
+                ParseError::InvalidInput,
+                format!(
+                    "sample count {} does not match {sample_count} samples",
+                    senc.sample_count()
+                ),
+                WhileParsingChild(NAME, BoxType::SENC),
+            );
+            senc.validate(per_sample_iv_size)
+                .while_parsing_child(NAME, BoxType::SENC)?;
+        }
+
+        let saiz_sample_count = self.saiz_mut()?.map(|saiz| saiz.sample_count());
+        let saio_entry_count = self.saio_mut()?.map(|saio| saio.entry_count());
+        match (saiz_sample_count, saio_entry_count) {
+            (None, None) => {}
+            (Some(saiz_sample_count), Some(saio_entry_count)) => {
+                ensure_attach!(
+                    saiz_sample_count == sample_count,
+                    ParseError::InvalidInput,
+                    format!("sample count {saiz_sample_count} does not match {sample_count} samples"),
+                    WhileParsingChild(NAME, BoxType::SAIZ),
+                );
+                let chunk_count = self.co_mut()?.entry_count();
+                ensure_attach!(
+                    saio_entry_count == 1 || saio_entry_count == chunk_count,
+                    ParseError::InvalidInput,
+                    format!("entry count {saio_entry_count} does not match {chunk_count} chunks"),
+                    WhileParsingChild(NAME, BoxType::SAIO),
+                );
+            }
+            (None, Some(_)) => bail_attach!(ParseError::MissingRequiredBox(BoxType::SAIZ), WhileParsingBox(NAME)),
+            (Some(_), None) => bail_attach!(ParseError::MissingRequiredBox(BoxType::SAIO), WhileParsingBox(NAME)),
+        }
+        Ok(())
+    }
+
+    /// Parses every descendant box, returning [`ParseError::UnsupportedBox`] for any box not known to the parser.
+    pub fn validate_strict(&mut self) -> Result<(), ParseError> {
+        self.validate_strict_with(Default::default())
+    }
+
+    /// Like [`validate_strict`](Self::validate_strict), but also accepts the boxes allowed by `options`.
+    pub fn validate_strict_with(&mut self, options: StrictValidationOptions) -> Result<(), ParseError> {
+        const CHILDREN: &[BoxType] = &[
+            BoxType::STSD,
+            BoxType::STTS,
+            BoxType::CTTS,
+            BoxType::STSC,
+            BoxType::STSZ,
+            BoxType::STSS,
+            BoxType::STCO,
+            BoxType::CO64,
+        ];
+        const COMMON_ENCRYPTION_CHILDREN: &[BoxType] = &[BoxType::SENC, BoxType::SAIZ, BoxType::SAIO];
+        if options.allow_common_encryption {
+            self.children
+                .ensure_only(NAME, &[CHILDREN, COMMON_ENCRYPTION_CHILDREN].concat())?;
+            self.senc_mut()?;
+            self.saiz_mut()?;
+            self.saio_mut()?;
+        } else {
+            self.children.ensure_only(NAME, CHILDREN)?;
+        }
+        self.stsd_mut()?;
+        self.stts_mut()?;
+        self.ctts_mut()?;
+        self.stsc_mut()?;
+        self.stsz_mut()?;
+        self.stss_mut()?;
+        self.co_mut()?;
+        Ok(())
+    }
 }
 
 //
@@ -55,6 +316,19 @@ impl StblBox {
 //
 
 impl StblCoMut<'_> {
+    pub fn chunk_offsets(&mut self) -> Vec<u64> {
+        match self {
+            StblCoMut::Stco(stco) => stco
+                .entries_mut()
+                .map(|entry| entry.get().unwrap_or_else(|_| unreachable!()).into())
+                .collect(),
+            StblCoMut::Co64(co64) => co64
+                .entries_mut()
+                .map(|entry| entry.get().unwrap_or_else(|_| unreachable!()))
+                .collect(),
+        }
+    }
+
     pub fn entry_count(&self) -> u32 {
         match self {
             StblCoMut::Stco(stco) => stco.entry_count(),
@@ -62,3 +336,118 @@ impl StblCoMut<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::parse::{AnyMp4Box, Mp4Box, StscEntry};
+
+    use super::*;
+
+    fn test_stbl(stsc_entries: &[(u32, u32)], sample_sizes: &[u32], chunk_offsets: &[u32]) -> StblBox {
+        let stsc = stsc_entries
+            .iter()
+            .map(|&(first_chunk, samples_per_chunk)| StscEntry {
+                first_chunk,
+                samples_per_chunk,
+                sample_description_index: 1,
+            })
+            .collect::<StscBox>();
+        let children: Vec<AnyMp4Box> = vec![
+            Mp4Box::with_data(stsc.into()).unwrap().into(),
+            Mp4Box::with_data(StszBox::with_sample_sizes(sample_sizes.iter().copied()).into())
+                .unwrap()
+                .into(),
+            Mp4Box::with_data(StcoBox::from_iter(chunk_offsets.iter().copied()).into())
+                .unwrap()
+                .into(),
+        ];
+        StblBox::with_children(children)
+    }
+
+    #[test]
+    fn chunk_spans() {
+        let mut stbl = test_stbl(&[(1, 2), (2, 1)], &[3, 1, 2, 4], &[100, 200, 300]);
+        assert_eq!(
+            stbl.chunk_spans().unwrap(),
+            [
+                InputSpan { offset: 100, len: 4 },
+                InputSpan { offset: 200, len: 2 },
+                InputSpan { offset: 300, len: 4 },
+            ]
+        );
+    }
+
+    #[test]
+    fn chunk_spans_quicktime_sound_v1() {
+        let stsc = [(1, 640, 1), (2, 64, 2)]
+            .into_iter()
+            .map(|(first_chunk, samples_per_chunk, sample_description_index)| StscEntry {
+                first_chunk,
+                samples_per_chunk,
+                sample_description_index,
+            })
+            .collect::<StscBox>();
+        let children: Vec<AnyMp4Box> = vec![
+            Mp4Box::with_data(stsc.into()).unwrap().into(),
+            Mp4Box::with_data(StszBox::with_sample_size(1, 704).into())
+                .unwrap()
+                .into(),
+            Mp4Box::with_data(StcoBox::from_iter([100, 500]).into()).unwrap().into(),
+        ];
+        let mut stbl = StblBox::with_children(children);
+        let fields = QuickTimeSoundV1Fields {
+            samples_per_packet: 64,
+            bytes_per_packet: 34,
+            bytes_per_frame: 34,
+            bytes_per_sample: 2,
+        };
+        assert_eq!(
+            stbl.chunk_spans_with(&[Some(fields), None]).unwrap(),
+            [InputSpan { offset: 100, len: 340 }, InputSpan { offset: 500, len: 64 }]
+        );
+        assert_eq!(
+            stbl.chunk_spans().unwrap(),
+            [InputSpan { offset: 100, len: 640 }, InputSpan { offset: 500, len: 64 }]
+        );
+
+        let fields = QuickTimeSoundV1Fields { samples_per_packet: 0, ..fields };
+        let err = stbl.chunk_spans_with(&[Some(fields)]).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn upgrade_stco_to_co64() {
+        let mut stbl = test_stbl(&[(1, 1)], &[1, 1], &[100, u32::MAX]);
+        stbl.upgrade_stco_to_co64().unwrap();
+        assert!(matches!(stbl.co_mut().unwrap(), StblCoMut::Co64(_)));
+        assert_eq!(stbl.co_mut().unwrap().chunk_offsets(), [100, u32::MAX.into()]);
+        assert_eq!(
+            stbl.children.box_types().collect::<Vec<_>>(),
+            [BoxType::STSC, BoxType::STSZ, CO64]
+        );
+
+        stbl.upgrade_stco_to_co64().unwrap();
+        assert_eq!(stbl.co_mut().unwrap().chunk_offsets(), [100, u32::MAX.into()]);
+    }
+
+    #[test]
+    fn chunk_spans_too_few_samples() {
+        let mut stbl = test_stbl(&[(1, 2)], &[1, 1, 1], &[100, 200]);
+        let err = stbl.chunk_spans().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn chunk_spans_too_many_samples() {
+        let mut stbl = test_stbl(&[(1, 1)], &[1, 1, 1], &[100, 200]);
+        let err = stbl.chunk_spans().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn chunk_spans_invalid_first_chunk() {
+        let mut stbl = test_stbl(&[(2, 1)], &[1, 1], &[100, 200]);
+        let err = stbl.chunk_spans().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
@@ -10,7 +10,7 @@ pub struct StcoBox {
 }
 
 impl StcoBox {
-    pub fn entries_mut(&mut self) -> impl Iterator<Item = ArrayEntryMut<'_, u32>> + ExactSizeIterator + '_ {
+    pub fn entries_mut(&mut self) -> impl ExactSizeIterator<Item = ArrayEntryMut<'_, u32>> + '_ {
         self.entries.entries_mut()
     }
 
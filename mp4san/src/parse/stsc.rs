@@ -0,0 +1,83 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut};
+use mediasan_common::error::WhileParsingType;
+
+use crate::error::Result;
+
+use super::{BoundedArray, ConstFullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, Default, ParseBox, ParsedBox)]
+#[box_type = "stsc"]
+pub struct StscBox {
+    header: ConstFullBoxHeader,
+    entries: BoundedArray<u32, StscEntry>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StscEntry {
+    pub first_chunk: u32,
+    pub samples_per_chunk: u32,
+    pub sample_description_index: u32,
+}
+
+impl StscBox {
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = StscEntry> + '_ {
+        self.entries
+            .entries()
+            .map(|entry| entry.get().unwrap_or_else(|_| unreachable!()))
+    }
+
+    pub fn entry_count(&self) -> u32 {
+        self.entries.entry_count()
+    }
+}
+
+impl FromIterator<StscEntry> for StscBox {
+    fn from_iter<I: IntoIterator<Item = StscEntry>>(entries: I) -> Self {
+        Self { header: Default::default(), entries: entries.into_iter().collect() }
+    }
+}
+
+impl Mp4Prim for StscEntry {
+    fn parse<B: Buf>(mut buf: B) -> Result<Self, ParseError> {
+        ensure_attach!(
+            buf.remaining() >= Self::encoded_len() as usize,
+            ParseError::TruncatedBox,
+            WhileParsingType::new::<Self>(),
+        );
+        Ok(Self {
+            first_chunk: buf.get_u32(),
+            samples_per_chunk: buf.get_u32(),
+            sample_description_index: buf.get_u32(),
+        })
+    }
+
+    fn encoded_len() -> u64 {
+        12
+    }
+
+    fn put_buf<B: BufMut>(&self, mut buf: B) {
+        buf.put_u32(self.first_chunk);
+        buf.put_u32(self.samples_per_chunk);
+        buf.put_u32(self.sample_description_index);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::util::test::write_test_stsc_data;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        write_test_stsc_data(&mut data);
+        let stsc = StscBox::parse(&mut data).unwrap();
+        let expected = StscEntry { first_chunk: 1, samples_per_chunk: 1, sample_description_index: 1 };
+        assert_eq!(stsc.entries().collect::<Vec<_>>(), [expected]);
+    }
+}
@@ -0,0 +1,225 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{
+    AnyMp4Box, AudioSampleEntry, Avc1Box, BoxDepth, BoxType, Boxes, ConstFullBoxHeader, EncaBox, EncvBox, Hev1Box,
+    Hvc1Box, Mp4Value, Mp4aBox, ParseBox, ParseError, ParsedBox, QuickTimeSoundV1Fields, S263Box, SamrBox, SawbBox,
+    TencBox,
+};
+
+#[derive(Clone, Debug)]
+pub struct StsdBox {
+    header: ConstFullBoxHeader,
+    entries: Boxes,
+}
+
+const NAME: BoxType = BoxType::STSD;
+
+impl StsdBox {
+    /// The types of the sample entries whose samples are protected, as defined by ISO/IEC 14496-12 section 8.12.
+    pub const PROTECTED_ENTRY_TYPES: [BoxType; 4] = [BoxType::ENCV, BoxType::ENCA, BoxType::ENCT, BoxType::ENCS];
+
+    pub fn with_entries<C: Into<Boxes>>(entries: C) -> Self {
+        Self { header: Default::default(), entries: entries.into() }
+    }
+
+    pub fn entry_count(&self) -> u32 {
+        self.entries.box_types().len() as u32
+    }
+
+    /// Returns the box types of the sample entries, in order.
+    pub fn entry_types(&self) -> impl ExactSizeIterator<Item = BoxType> + '_ {
+        self.entries.box_types()
+    }
+
+    /// Returns whether any sample entry is one of the [protected entry types](Self::PROTECTED_ENTRY_TYPES).
+    pub fn is_protected(&self) -> bool {
+        self.protected_entry_type().is_some()
+    }
+
+    /// Returns the type of the first sample entry which is one of the [protected entry
+    /// types](Self::PROTECTED_ENTRY_TYPES), if any.
+    pub fn protected_entry_type(&self) -> Option<BoxType> {
+        self.entries
+            .box_types()
+            .find(|box_type| Self::PROTECTED_ENTRY_TYPES.contains(box_type))
+    }
+
+    /// Validates the protection scheme information (`sinf`) of each protected visual (`encv`) and audio (`enca`)
+    /// sample entry, returning the track encryption box (`tenc`) of each.
+    ///
+    /// Other protected sample entries are rejected with [`ParseError::UnsupportedBox`].
+    pub fn validate_common_encryption(&mut self) -> Result<Vec<TencBox>, ParseError> {
+        let mut tencs = Vec::new();
+        for entry in self.entries.iter_mut() {
+            let sinfs = if let Some(encv) = entry
+                .parse_data_as::<EncvBox>()
+                .while_parsing_child(NAME, BoxType::ENCV)?
+            {
+                encv.sinfs()?
+            } else if let Some(enca) = entry
+                .parse_data_as::<EncaBox>()
+                .while_parsing_child(NAME, BoxType::ENCA)?
+            {
+                enca.sinfs()?
+            } else {
+                let box_type = entry.calculated_header().box_type();
+                ensure_attach!(
+                    !Self::PROTECTED_ENTRY_TYPES.contains(&box_type),
+                    ParseError::UnsupportedBox(box_type),
+                    WhileParsingField(NAME, "entries"),
+                );
+                continue;
+            };
+            for sinf in sinfs {
+                tencs.push(sinf.validate_common_encryption()?.clone());
+            }
+        }
+        Ok(tencs)
+    }
+
+    /// Returns the additional fields of each sample entry which is a version 1 QuickTime sound sample description, in
+    /// sample entry order.
+    ///
+    /// Each sample entry is assumed to be an audio sample entry, as in a sound track; `None` is returned for entries
+    /// too short to be one, or of any other version.
+    pub fn quicktime_sound_v1_fields(&mut self) -> Result<Vec<Option<QuickTimeSoundV1Fields>>, ParseError> {
+        let mut fields = Vec::with_capacity(self.entries.box_types().len());
+        for entry in self.entries.iter_mut() {
+            let box_type = entry.calculated_header().box_type();
+            let mut data = BytesMut::with_capacity(entry.data.encoded_len() as usize);
+            entry.data.put_buf(&mut data);
+            if data.len() < <AudioSampleEntry as super::Mp4Prim>::encoded_len() as usize {
+                fields.push(None);
+                continue;
+            }
+            let audio_entry = AudioSampleEntry::parse(&mut data).while_parsing_child(NAME, box_type)?;
+            if audio_entry.version() != 1 {
+                fields.push(None);
+                continue;
+            }
+            fields.push(Some(
+                QuickTimeSoundV1Fields::parse(&mut data).while_parsing_child(NAME, box_type)?,
+            ));
+        }
+        Ok(fields)
+    }
+
+    pub fn entries_mut(&mut self) -> impl ExactSizeIterator<Item = &mut AnyMp4Box> + '_ {
+        self.entries.iter_mut()
+    }
+
+    /// Parses the decoder configuration of each H.264 (`avc1`), H.265 (`hvc1`/`hev1`), and MPEG-4 audio (`mp4a`)
+    /// sample entry, validating its parameter sets or `AudioSpecificConfig`, and of each 3GPP H.263 (`s263`) and AMR
+    /// (`samr`/`sawb`) sample entry.
+    pub fn validate_decoder_configurations(&mut self) -> Result<(), ParseError> {
+        for entry in self.entries.iter_mut() {
+            if let Some(avc1) = entry
+                .parse_data_as::<Avc1Box>()
+                .while_parsing_child(NAME, BoxType::AVC1)?
+            {
+                avc1.avcc_mut()?;
+            } else if let Some(hvc1) = entry
+                .parse_data_as::<Hvc1Box>()
+                .while_parsing_child(NAME, BoxType::HVC1)?
+            {
+                hvc1.hvcc_mut()?;
+            } else if let Some(hev1) = entry
+                .parse_data_as::<Hev1Box>()
+                .while_parsing_child(NAME, BoxType::HEV1)?
+            {
+                hev1.hvcc_mut()?;
+            } else if let Some(mp4a) = entry
+                .parse_data_as::<Mp4aBox>()
+                .while_parsing_child(NAME, BoxType::MP4A)?
+            {
+                mp4a.esds_mut()?.parse_audio_specific_config()?;
+            } else if let Some(s263) = entry
+                .parse_data_as::<S263Box>()
+                .while_parsing_child(NAME, BoxType::S263)?
+            {
+                s263.d263_mut()?.validate()?;
+            } else if let Some(samr) = entry
+                .parse_data_as::<SamrBox>()
+                .while_parsing_child(NAME, BoxType::SAMR)?
+            {
+                samr.damr_mut()?.validate(false)?;
+            } else if let Some(sawb) = entry
+                .parse_data_as::<SawbBox>()
+                .while_parsing_child(NAME, BoxType::SAWB)?
+            {
+                sawb.damr_mut()?.validate(true)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ParseBox for StsdBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        Self::parse_at_depth(buf, Default::default())
+    }
+
+    fn parse_at_depth(buf: &mut BytesMut, depth: BoxDepth) -> Result<Self, ParseError> {
+        let header = ConstFullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        let entry_count = u32::parse(&mut *buf).while_parsing_field(NAME, "entry_count")?;
+        let entries = Boxes::parse_at_depth(&mut *buf, depth).while_parsing_field(NAME, "entries")?;
+        ensure_attach!(
+            entries.box_types().len() == entry_count as usize,
+            ParseError::InvalidInput,
+            "entry count mismatch",
+            WhileParsingField(NAME, "entry_count"),
+        );
+        Ok(Self { header, entries })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for StsdBox {
+    fn encoded_len(&self) -> u64 {
+        self.header.encoded_len() + self.entry_count().encoded_len() + self.entries.encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.entry_count().put_buf(&mut out);
+        self.entries.put_buf(&mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::box_type::METT;
+    use crate::util::test::write_test_stsd_data;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        write_test_stsd_data(&mut data, METT);
+        let expected = data.clone();
+        let stsd = StsdBox::parse(&mut data).unwrap();
+        assert_eq!(stsd.entry_types().collect::<Vec<_>>(), [METT]);
+        let mut encoded = BytesMut::new();
+        stsd.put_buf(&mut encoded);
+        assert_eq!(encoded.len() as u64, stsd.encoded_len());
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn entry_count_mismatch() {
+        let mut data = BytesMut::new();
+        write_test_stsd_data(&mut data, METT);
+        data[7] = 2;
+        let err = StsdBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
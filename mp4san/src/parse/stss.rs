@@ -0,0 +1,44 @@
+#![allow(missing_docs)]
+
+use super::{BoundedArray, ConstFullBoxHeader, ParseBox, ParsedBox};
+
+#[derive(Clone, Debug, Default, ParseBox, ParsedBox)]
+#[box_type = "stss"]
+pub struct StssBox {
+    header: ConstFullBoxHeader,
+    entries: BoundedArray<u32, u32>,
+}
+
+impl StssBox {
+    /// Returns the (1-based) sample numbers of the sync samples.
+    pub fn sample_numbers(&self) -> impl ExactSizeIterator<Item = u32> + '_ {
+        self.entries
+            .entries()
+            .map(|entry| entry.get().unwrap_or_else(|_| unreachable!()))
+    }
+
+    pub fn entry_count(&self) -> u32 {
+        self.entries.entry_count()
+    }
+}
+
+impl FromIterator<u32> for StssBox {
+    fn from_iter<I: IntoIterator<Item = u32>>(entries: I) -> Self {
+        Self { header: Default::default(), entries: entries.into_iter().collect() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        StssBox::from_iter([1, 5, 9]).put_buf(&mut data);
+        let stss = StssBox::parse(&mut data).unwrap();
+        assert_eq!(stss.sample_numbers().collect::<Vec<_>>(), [1, 5, 9]);
+    }
+}
@@ -0,0 +1,153 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, ConstFullBoxHeader, Mp4Value, ParseBox, ParseError, ParsedBox, UnboundedArray};
+
+#[derive(Clone, Debug, Default)]
+pub struct StszBox {
+    header: ConstFullBoxHeader,
+    sample_size: u32,
+    sample_count: u32,
+    entries: UnboundedArray<u32>,
+}
+
+const NAME: BoxType = BoxType::STSZ;
+
+impl StszBox {
+    /// Returns a sample size box for `sample_count` samples all of size `sample_size`.
+    pub fn with_sample_size(sample_size: u32, sample_count: u32) -> Self {
+        Self { header: Default::default(), sample_size, sample_count, entries: Default::default() }
+    }
+
+    /// Returns a sample size box with the given sizes for each sample.
+    pub fn with_sample_sizes(sample_sizes: impl IntoIterator<Item = u32>) -> Self {
+        let entries: UnboundedArray<u32> = sample_sizes.into_iter().collect();
+        let sample_count = entries.entry_count() as u32;
+        Self { header: Default::default(), sample_size: 0, sample_count, entries }
+    }
+
+    /// Returns the size of every sample, if all samples are the same size.
+    pub fn sample_size(&self) -> Option<u32> {
+        (self.sample_size != 0).then_some(self.sample_size)
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Returns the size of each sample.
+    pub fn sample_sizes(&self) -> impl ExactSizeIterator<Item = u32> + '_ {
+        let sample_count = match self.sample_size() {
+            Some(_) => self.sample_count as usize,
+            None => 0,
+        };
+        let entries = self
+            .entries
+            .entries()
+            .map(|entry| entry.get().unwrap_or_else(|_| unreachable!()));
+        let sample_sizes = std::iter::repeat(self.sample_size).take(sample_count);
+        SampleSizes { entries, sample_sizes }
+    }
+}
+
+struct SampleSizes<E, S> {
+    entries: E,
+    sample_sizes: S,
+}
+
+impl<E: ExactSizeIterator<Item = u32>, S: ExactSizeIterator<Item = u32>> Iterator for SampleSizes<E, S> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        self.entries.next().or_else(|| self.sample_sizes.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.entries.len() + self.sample_sizes.len();
+        (len, Some(len))
+    }
+}
+
+impl<E: ExactSizeIterator<Item = u32>, S: ExactSizeIterator<Item = u32>> ExactSizeIterator for SampleSizes<E, S> {}
+
+impl ParseBox for StszBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = ConstFullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        let sample_size = u32::parse(&mut *buf).while_parsing_field(NAME, "sample_size")?;
+        let sample_count = u32::parse(&mut *buf).while_parsing_field(NAME, "sample_count")?;
+        let entries = if sample_size == 0 {
+            let entries_len = u64::from(sample_count) * 4;
+            ensure_attach!(
+                buf.remaining() as u64 >= entries_len,
+                ParseError::TruncatedBox,
+                WhileParsingField(NAME, "entries"),
+            );
+            UnboundedArray::parse(&mut buf.split_to(entries_len as usize)).while_parsing_field(NAME, "entries")?
+        } else {
+            Default::default()
+        };
+        Ok(Self { header, sample_size, sample_count, entries })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for StszBox {
+    fn encoded_len(&self) -> u64 {
+        self.header.encoded_len()
+            + self.sample_size.encoded_len()
+            + self.sample_count.encoded_len()
+            + self.entries.encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.sample_size.put_buf(&mut out);
+        self.sample_count.put_buf(&mut out);
+        self.entries.put_buf(&mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::write_test_stsz_data;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip_sample_size() {
+        let mut data = BytesMut::new();
+        write_test_stsz_data(&mut data, 3);
+        let stsz = StszBox::parse(&mut data).unwrap();
+        assert_eq!(stsz.sample_size(), Some(1));
+        assert_eq!(stsz.sample_sizes().collect::<Vec<_>>(), [1, 1, 1]);
+    }
+
+    #[test]
+    fn roundtrip_sample_sizes() {
+        let mut data = BytesMut::new();
+        let stsz = StszBox::with_sample_sizes([3, 1, 2]);
+        stsz.put_buf(&mut data);
+        assert_eq!(data.len() as u64, stsz.encoded_len());
+        let stsz = StszBox::parse(&mut data).unwrap();
+        assert_eq!(stsz.sample_size(), None);
+        assert_eq!(stsz.sample_count(), 3);
+        assert_eq!(stsz.sample_sizes().len(), 3);
+        assert_eq!(stsz.sample_sizes().collect::<Vec<_>>(), [3, 1, 2]);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut data = BytesMut::new();
+        StszBox::with_sample_sizes([3, 1, 2]).put_buf(&mut data);
+        data.truncate(data.len() - 1);
+        let err = StszBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
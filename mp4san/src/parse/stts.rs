@@ -0,0 +1,96 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut};
+use mediasan_common::error::WhileParsingType;
+
+use crate::error::Result;
+
+use super::{BoundedArray, ConstFullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, Default, ParseBox, ParsedBox)]
+#[box_type = "stts"]
+pub struct SttsBox {
+    header: ConstFullBoxHeader,
+    entries: BoundedArray<u32, SttsEntry>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SttsEntry {
+    pub sample_count: u32,
+    pub sample_delta: u32,
+}
+
+impl SttsBox {
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = SttsEntry> + '_ {
+        self.entries
+            .entries()
+            .map(|entry| entry.get().unwrap_or_else(|_| unreachable!()))
+    }
+
+    pub fn entry_count(&self) -> u32 {
+        self.entries.entry_count()
+    }
+
+    /// Returns the total number of samples described by this box, or [`None`] on overflow.
+    pub fn sample_count(&self) -> Option<u64> {
+        self.entries().try_fold(0u64, |sample_count, entry| {
+            sample_count.checked_add(entry.sample_count.into())
+        })
+    }
+}
+
+impl FromIterator<SttsEntry> for SttsBox {
+    fn from_iter<I: IntoIterator<Item = SttsEntry>>(entries: I) -> Self {
+        Self { header: Default::default(), entries: entries.into_iter().collect() }
+    }
+}
+
+impl Mp4Prim for SttsEntry {
+    fn parse<B: Buf>(mut buf: B) -> Result<Self, ParseError> {
+        ensure_attach!(
+            buf.remaining() >= Self::encoded_len() as usize,
+            ParseError::TruncatedBox,
+            WhileParsingType::new::<Self>(),
+        );
+        Ok(Self { sample_count: buf.get_u32(), sample_delta: buf.get_u32() })
+    }
+
+    fn encoded_len() -> u64 {
+        8
+    }
+
+    fn put_buf<B: BufMut>(&self, mut buf: B) {
+        buf.put_u32(self.sample_count);
+        buf.put_u32(self.sample_delta);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::util::test::write_test_stts_data;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        write_test_stts_data(&mut data, 3);
+        let stts = SttsBox::parse(&mut data).unwrap();
+        assert_eq!(
+            stts.entries().collect::<Vec<_>>(),
+            [SttsEntry { sample_count: 3, sample_delta: 1 }]
+        );
+        assert_eq!(stts.sample_count(), Some(3));
+    }
+
+    #[test]
+    fn truncated() {
+        let mut data = BytesMut::new();
+        write_test_stts_data(&mut data, 3);
+        data.truncate(data.len() - 1);
+        let err = SttsBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
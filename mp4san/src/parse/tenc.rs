@@ -0,0 +1,217 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, FullBoxHeader, Mp4Value, ParseBox, ParseError, ParsedBox};
+
+/// A track encryption box, as defined by ISO/IEC 23001-7 section 8.2.
+#[derive(Clone, Debug)]
+pub struct TencBox {
+    header: FullBoxHeader,
+    pub default_crypt_byte_block: u8,
+    pub default_skip_byte_block: u8,
+    pub default_is_protected: bool,
+    pub default_per_sample_iv_size: u8,
+    pub default_kid: [u8; 16],
+    default_constant_iv: BytesMut,
+}
+
+const NAME: BoxType = BoxType::TENC;
+
+/// The initialization vector sizes allowed by ISO/IEC 23001-7, in bytes.
+const IV_SIZES: [u8; 2] = [8, 16];
+
+impl TencBox {
+    pub fn new(default_per_sample_iv_size: u8, default_kid: [u8; 16]) -> Self {
+        Self {
+            header: FullBoxHeader::default(),
+            default_crypt_byte_block: 0,
+            default_skip_byte_block: 0,
+            default_is_protected: true,
+            default_per_sample_iv_size,
+            default_kid,
+            default_constant_iv: BytesMut::new(),
+        }
+    }
+
+    /// Returns a version 1 box using pattern encryption with a constant initialization vector, as used by the `cbcs`
+    /// scheme.
+    pub fn with_pattern(crypt_byte_block: u8, skip_byte_block: u8, constant_iv: &[u8], default_kid: [u8; 16]) -> Self {
+        Self {
+            header: FullBoxHeader { version: 1, flags: 0 },
+            default_crypt_byte_block: crypt_byte_block,
+            default_skip_byte_block: skip_byte_block,
+            default_constant_iv: constant_iv.into(),
+            ..Self::new(0, default_kid)
+        }
+    }
+
+    /// Returns the constant initialization vector used for all samples, if the samples are protected and have no
+    /// per-sample initialization vectors.
+    pub fn default_constant_iv(&self) -> Option<&[u8]> {
+        (!self.default_constant_iv.is_empty()).then_some(&self.default_constant_iv[..])
+    }
+
+    fn has_constant_iv(is_protected: bool, per_sample_iv_size: u8) -> bool {
+        is_protected && per_sample_iv_size == 0
+    }
+}
+
+impl ParseBox for TencBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version <= 1,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingField(NAME, "header"),
+        );
+        u8::parse(&mut *buf).while_parsing_field(NAME, "reserved")?;
+        let pattern = u8::parse(&mut *buf).while_parsing_field(NAME, "default_crypt_byte_block")?;
+        let (default_crypt_byte_block, default_skip_byte_block) = match header.version {
+            0 => (0, 0),
+            _ => (pattern >> 4, pattern & 0xf),
+        };
+        let default_is_protected = match u8::parse(&mut *buf).while_parsing_field(NAME, "default_isProtected")? {
+            0 => false,
+            1 => true,
+            is_protected => bail_attach!(
+                ParseError::InvalidInput,
+                format!("invalid protection flag {is_protected}"),
+                WhileParsingField(NAME, "default_isProtected"),
+            ),
+        };
+        let default_per_sample_iv_size =
+            u8::parse(&mut *buf).while_parsing_field(NAME, "default_Per_Sample_IV_Size")?;
+        ensure_attach!(
+            default_per_sample_iv_size == 0 || IV_SIZES.contains(&default_per_sample_iv_size),
+            ParseError::InvalidInput,
+            format!("invalid IV size {default_per_sample_iv_size}"),
+            WhileParsingField(NAME, "default_Per_Sample_IV_Size"),
+        );
+        let default_kid = <[u8; 16]>::parse(&mut *buf).while_parsing_field(NAME, "default_KID")?;
+        let default_constant_iv = if Self::has_constant_iv(default_is_protected, default_per_sample_iv_size) {
+            let iv_size = u8::parse(&mut *buf).while_parsing_field(NAME, "default_constant_IV_size")?;
+            ensure_attach!(
+                IV_SIZES.contains(&iv_size),
+                ParseError::InvalidInput,
+                format!("invalid constant IV size {iv_size}"),
+                WhileParsingField(NAME, "default_constant_IV_size"),
+            );
+            ensure_attach!(
+                buf.remaining() >= iv_size.into(),
+                ParseError::TruncatedBox,
+                WhileParsingField(NAME, "default_constant_IV"),
+            );
+            buf.split_to(iv_size.into())
+        } else {
+            BytesMut::new()
+        };
+        Ok(Self {
+            header,
+            default_crypt_byte_block,
+            default_skip_byte_block,
+            default_is_protected,
+            default_per_sample_iv_size,
+            default_kid,
+            default_constant_iv,
+        })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for TencBox {
+    fn encoded_len(&self) -> u64 {
+        let mut len = self.header.encoded_len() + 4 + self.default_kid.encoded_len();
+        if Self::has_constant_iv(self.default_is_protected, self.default_per_sample_iv_size) {
+            len += 1 + self.default_constant_iv.len() as u64;
+        }
+        len
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        out.put_u8(0); // reserved
+        match self.header.version {
+            0 => out.put_u8(0), // reserved
+            _ => out.put_u8(self.default_crypt_byte_block << 4 | self.default_skip_byte_block & 0xf),
+        }
+        out.put_u8(self.default_is_protected.into());
+        out.put_u8(self.default_per_sample_iv_size);
+        self.default_kid.put_buf(&mut out);
+        if Self::has_constant_iv(self.default_is_protected, self.default_per_sample_iv_size) {
+            out.put_u8(self.default_constant_iv.len() as u8);
+            out.put_slice(&self.default_constant_iv);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_KID: [u8; 16] = *b"thisisatestkeyid";
+
+    fn roundtrip(tenc: TencBox) -> TencBox {
+        let mut data = BytesMut::new();
+        tenc.put_buf(&mut data);
+        assert_eq!(data.len() as u64, tenc.encoded_len());
+        let parsed = TencBox::parse(&mut data).unwrap();
+        assert!(data.is_empty());
+        parsed
+    }
+
+    #[test]
+    fn per_sample_iv() {
+        let tenc = roundtrip(TencBox::new(8, TEST_KID));
+        assert_eq!((tenc.default_per_sample_iv_size, tenc.default_kid), (8, TEST_KID));
+        assert_eq!(tenc.default_constant_iv(), None);
+    }
+
+    #[test]
+    fn pattern_with_constant_iv() {
+        let tenc = roundtrip(TencBox::with_pattern(1, 9, &[0xa5; 16], TEST_KID));
+        assert_eq!((tenc.default_crypt_byte_block, tenc.default_skip_byte_block), (1, 9));
+        assert_eq!(tenc.default_constant_iv(), Some(&[0xa5; 16][..]));
+    }
+
+    #[test]
+    fn invalid_iv_size() {
+        let mut data = BytesMut::new();
+        TencBox::new(4, TEST_KID).put_buf(&mut data);
+        let err = TencBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn invalid_constant_iv_size() {
+        let mut data = BytesMut::new();
+        TencBox::with_pattern(1, 9, &[0xa5; 4], TEST_KID).put_buf(&mut data);
+        let err = TencBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn invalid_protection_flag() {
+        let mut data = BytesMut::new();
+        TencBox::new(16, TEST_KID).put_buf(&mut data);
+        data[6] = 2;
+        let err = TencBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn truncated_constant_iv() {
+        let mut data = BytesMut::new();
+        TencBox::with_pattern(1, 9, &[0xa5; 16], TEST_KID).put_buf(&mut data);
+        data.truncate(data.len() - 1);
+        let err = TencBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
@@ -0,0 +1,83 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TfdtBox {
+    header: FullBoxHeader,
+    pub base_media_decode_time: u64,
+}
+
+const NAME: BoxType = BoxType::TFDT;
+
+impl TfdtBox {
+    pub fn new(base_media_decode_time: u64) -> Self {
+        let version = if base_media_decode_time > u32::MAX.into() { 1 } else { 0 };
+        Self { header: FullBoxHeader { version, flags: 0 }, base_media_decode_time }
+    }
+}
+
+impl ParseBox for TfdtBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        let base_media_decode_time = match header.version {
+            0 => u32::parse(&mut *buf).map(u64::from),
+            1 => u64::parse(&mut *buf),
+            version => bail_attach!(ParseError::InvalidInput, format!("unsupported box version {version}")),
+        }
+        .while_parsing_field(NAME, "base_media_decode_time")?;
+        Ok(Self { header, base_media_decode_time })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for TfdtBox {
+    fn encoded_len(&self) -> u64 {
+        let time_len = match self.header.version {
+            0 => u32::encoded_len(),
+            _ => u64::encoded_len(),
+        };
+        FullBoxHeader::encoded_len() + time_len
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        match self.header.version {
+            0 => (self.base_media_decode_time as u32).put_buf(&mut out),
+            _ => self.base_media_decode_time.put_buf(&mut out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for base_media_decode_time in [0, u32::MAX.into(), u64::MAX] {
+            let mut buf = BytesMut::new();
+            let tfdt = TfdtBox::new(base_media_decode_time);
+            tfdt.put_buf(&mut buf);
+            assert_eq!(buf.len() as u64, tfdt.encoded_len());
+            assert_eq!(TfdtBox::parse(&mut buf).unwrap(), tfdt);
+        }
+    }
+
+    #[test]
+    fn invalid_version() {
+        let mut buf = BytesMut::new();
+        FullBoxHeader { version: 2, flags: 0 }.put_buf(&mut buf);
+        0u64.put_buf(&mut buf);
+        let err = TfdtBox::parse(&mut buf).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
@@ -0,0 +1,180 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TfhdBox {
+    header: FullBoxHeader,
+    pub track_id: u32,
+    base_data_offset: Option<u64>,
+    sample_description_index: Option<u32>,
+    default_sample_duration: Option<u32>,
+    default_sample_size: Option<u32>,
+    default_sample_flags: Option<u32>,
+}
+
+const NAME: BoxType = BoxType::TFHD;
+
+impl TfhdBox {
+    pub const BASE_DATA_OFFSET_PRESENT: u32 = 0x00_0001;
+    pub const SAMPLE_DESCRIPTION_INDEX_PRESENT: u32 = 0x00_0002;
+    pub const DEFAULT_SAMPLE_DURATION_PRESENT: u32 = 0x00_0008;
+    pub const DEFAULT_SAMPLE_SIZE_PRESENT: u32 = 0x00_0010;
+    pub const DEFAULT_SAMPLE_FLAGS_PRESENT: u32 = 0x00_0020;
+    pub const DURATION_IS_EMPTY: u32 = 0x01_0000;
+    pub const DEFAULT_BASE_IS_MOOF: u32 = 0x02_0000;
+
+    pub fn new(track_id: u32) -> Self {
+        Self { track_id, ..Default::default() }
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.header.flags
+    }
+
+    pub fn base_data_offset(&self) -> Option<u64> {
+        self.base_data_offset
+    }
+
+    pub fn default_base_is_moof(&self) -> bool {
+        self.header.flags & Self::DEFAULT_BASE_IS_MOOF != 0
+    }
+
+    pub fn default_sample_size(&self) -> Option<u32> {
+        self.default_sample_size
+    }
+
+    pub fn set_base_data_offset(&mut self, base_data_offset: Option<u64>) {
+        self.base_data_offset = base_data_offset;
+        self.set_flag(Self::BASE_DATA_OFFSET_PRESENT, base_data_offset.is_some());
+    }
+
+    pub fn set_default_base_is_moof(&mut self, default_base_is_moof: bool) {
+        self.set_flag(Self::DEFAULT_BASE_IS_MOOF, default_base_is_moof);
+    }
+
+    pub fn set_default_sample_size(&mut self, default_sample_size: Option<u32>) {
+        self.default_sample_size = default_sample_size;
+        self.set_flag(Self::DEFAULT_SAMPLE_SIZE_PRESENT, default_sample_size.is_some());
+    }
+
+    fn set_flag(&mut self, flag: u32, value: bool) {
+        if value {
+            self.header.flags |= flag;
+        } else {
+            self.header.flags &= !flag;
+        }
+    }
+}
+
+impl ParseBox for TfhdBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version == 0,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingField(NAME, "header"),
+        );
+        let flags = header.flags;
+        let track_id = u32::parse(&mut *buf).while_parsing_field(NAME, "track_id")?;
+        let base_data_offset = (flags & Self::BASE_DATA_OFFSET_PRESENT != 0)
+            .then(|| u64::parse(&mut *buf))
+            .transpose()
+            .while_parsing_field(NAME, "base_data_offset")?;
+        let sample_description_index = (flags & Self::SAMPLE_DESCRIPTION_INDEX_PRESENT != 0)
+            .then(|| u32::parse(&mut *buf))
+            .transpose()
+            .while_parsing_field(NAME, "sample_description_index")?;
+        let default_sample_duration = (flags & Self::DEFAULT_SAMPLE_DURATION_PRESENT != 0)
+            .then(|| u32::parse(&mut *buf))
+            .transpose()
+            .while_parsing_field(NAME, "default_sample_duration")?;
+        let default_sample_size = (flags & Self::DEFAULT_SAMPLE_SIZE_PRESENT != 0)
+            .then(|| u32::parse(&mut *buf))
+            .transpose()
+            .while_parsing_field(NAME, "default_sample_size")?;
+        let default_sample_flags = (flags & Self::DEFAULT_SAMPLE_FLAGS_PRESENT != 0)
+            .then(|| u32::parse(&mut *buf))
+            .transpose()
+            .while_parsing_field(NAME, "default_sample_flags")?;
+        Ok(Self {
+            header,
+            track_id,
+            base_data_offset,
+            sample_description_index,
+            default_sample_duration,
+            default_sample_size,
+            default_sample_flags,
+        })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for TfhdBox {
+    fn encoded_len(&self) -> u64 {
+        let optional_u32s = [
+            self.sample_description_index,
+            self.default_sample_duration,
+            self.default_sample_size,
+            self.default_sample_flags,
+        ];
+        let optional_u32s_len = optional_u32s.iter().flatten().count() as u64 * u32::encoded_len();
+        let base_data_offset_len = self.base_data_offset.map(|_| u64::encoded_len()).unwrap_or_default();
+        FullBoxHeader::encoded_len() + u32::encoded_len() + base_data_offset_len + optional_u32s_len
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.track_id.put_buf(&mut out);
+        if let Some(base_data_offset) = self.base_data_offset {
+            base_data_offset.put_buf(&mut out);
+        }
+        let optional_u32s = [
+            self.sample_description_index,
+            self.default_sample_duration,
+            self.default_sample_size,
+            self.default_sample_flags,
+        ];
+        for value in optional_u32s.iter().flatten() {
+            value.put_buf(&mut out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut tfhd = TfhdBox::new(1);
+        for _ in 0..2 {
+            let mut buf = BytesMut::new();
+            tfhd.put_buf(&mut buf);
+            assert_eq!(buf.len() as u64, tfhd.encoded_len());
+            assert_eq!(TfhdBox::parse(&mut buf).unwrap(), tfhd);
+
+            tfhd.set_base_data_offset(Some(u64::MAX));
+            tfhd.set_default_sample_size(Some(u32::MAX));
+            tfhd.set_default_base_is_moof(true);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let mut buf = BytesMut::new();
+        FullBoxHeader { version: 0, flags: TfhdBox::DEFAULT_SAMPLE_SIZE_PRESENT }.put_buf(&mut buf);
+        1u32.put_buf(&mut buf);
+        let err = TfhdBox::parse(&mut buf).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
@@ -0,0 +1,145 @@
+#![allow(missing_docs)]
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::ParseResultExt;
+use super::{BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TkhdBox {
+    header: FullBoxHeader,
+    pub creation_time: u64,
+    pub modification_time: u64,
+    pub track_id: u32,
+    reserved_1: u32,
+    pub duration: u64,
+    reserved_2: [u32; 2],
+    pub layer: i16,
+    pub alternate_group: i16,
+    pub volume: i16,
+    reserved_3: u16,
+    pub matrix: [i32; 9],
+    pub width: u32,
+    pub height: u32,
+}
+
+const NAME: BoxType = BoxType::TKHD;
+
+impl TkhdBox {
+    pub const TRACK_ENABLED: u32 = 0x000001;
+    pub const TRACK_IN_MOVIE: u32 = 0x000002;
+    pub const TRACK_IN_PREVIEW: u32 = 0x000004;
+    pub const TRACK_SIZE_IS_ASPECT_RATIO: u32 = 0x000008;
+
+    pub fn flags(&self) -> u32 {
+        self.header.flags
+    }
+}
+
+impl ParseBox for TkhdBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        let creation_time = header
+            .parse_versioned_u64(&mut *buf)
+            .while_parsing_field(NAME, "creation_time")?;
+        let modification_time = header
+            .parse_versioned_u64(&mut *buf)
+            .while_parsing_field(NAME, "modification_time")?;
+        let track_id = u32::parse(&mut *buf).while_parsing_field(NAME, "track_id")?;
+        let reserved_1 = u32::parse(&mut *buf).while_parsing_field(NAME, "reserved")?;
+        let duration = header
+            .parse_versioned_u64(&mut *buf)
+            .while_parsing_field(NAME, "duration")?;
+        let reserved_2 = <[u32; 2]>::parse(&mut *buf).while_parsing_field(NAME, "reserved")?;
+        let layer = i16::parse(&mut *buf).while_parsing_field(NAME, "layer")?;
+        let alternate_group = i16::parse(&mut *buf).while_parsing_field(NAME, "alternate_group")?;
+        let volume = i16::parse(&mut *buf).while_parsing_field(NAME, "volume")?;
+        let reserved_3 = u16::parse(&mut *buf).while_parsing_field(NAME, "reserved")?;
+        let matrix = <[i32; 9]>::parse(&mut *buf).while_parsing_field(NAME, "matrix")?;
+        let width = u32::parse(&mut *buf).while_parsing_field(NAME, "width")?;
+        let height = u32::parse(&mut *buf).while_parsing_field(NAME, "height")?;
+        Ok(Self {
+            header,
+            creation_time,
+            modification_time,
+            track_id,
+            reserved_1,
+            duration,
+            reserved_2,
+            layer,
+            alternate_group,
+            volume,
+            reserved_3,
+            matrix,
+            width,
+            height,
+        })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for TkhdBox {
+    fn encoded_len(&self) -> u64 {
+        FullBoxHeader::encoded_len()
+            + 3 * self.header.versioned_u64_len()
+            + 2 * u32::encoded_len()
+            + <[u32; 2]>::encoded_len()
+            + 3 * i16::encoded_len()
+            + u16::encoded_len()
+            + <[i32; 9]>::encoded_len()
+            + 2 * u32::encoded_len()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.header.put_versioned_u64(self.creation_time, &mut out);
+        self.header.put_versioned_u64(self.modification_time, &mut out);
+        self.track_id.put_buf(&mut out);
+        self.reserved_1.put_buf(&mut out);
+        self.header.put_versioned_u64(self.duration, &mut out);
+        self.reserved_2.put_buf(&mut out);
+        self.layer.put_buf(&mut out);
+        self.alternate_group.put_buf(&mut out);
+        self.volume.put_buf(&mut out);
+        self.reserved_3.put_buf(&mut out);
+        self.matrix.put_buf(&mut out);
+        self.width.put_buf(&mut out);
+        self.height.put_buf(&mut out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::test::write_test_tkhd_data;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        write_test_tkhd_data(&mut data, 3);
+        let expected = data.clone();
+        let tkhd = TkhdBox::parse(&mut data).unwrap();
+        assert_eq!(tkhd.track_id, 3);
+        let mut encoded = BytesMut::new();
+        tkhd.put_buf(&mut encoded);
+        assert_eq!(encoded.len() as u64, tkhd.encoded_len());
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn version_1() {
+        let mut tkhd =
+            TkhdBox { header: FullBoxHeader { version: 1, flags: TkhdBox::TRACK_ENABLED }, ..Default::default() };
+        tkhd.duration = u64::MAX;
+        let mut data = BytesMut::new();
+        tkhd.put_buf(&mut data);
+        assert_eq!(data.len() as u64, tkhd.encoded_len());
+        assert_eq!(TkhdBox::parse(&mut data).unwrap(), tkhd);
+    }
+}
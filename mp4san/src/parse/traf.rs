@@ -0,0 +1,50 @@
+#![allow(missing_docs)]
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, Boxes, BoxesValidator, ParseBox, ParseError, ParsedBox, TfdtBox, TfhdBox, TrunBox};
+
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "traf"]
+pub struct TrafBox {
+    children: Boxes<TrafChildrenValidator>,
+}
+
+pub(crate) struct TrafChildrenValidator;
+
+const NAME: BoxType = BoxType::TRAF;
+
+impl TrafBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes<TrafChildrenValidator>>>(children: C) -> Self {
+        Self { children: children.into() }
+    }
+
+    pub fn tfhd_mut(&mut self) -> Result<&mut TfhdBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::TFHD)
+    }
+
+    pub fn tfdt_mut(&mut self) -> Result<Option<&mut TfdtBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::TFDT)
+    }
+
+    pub fn truns(&mut self) -> impl Iterator<Item = Result<&mut TrunBox, ParseError>> + '_ {
+        self.children
+            .get_mut()
+            .map(|result| result.while_parsing_child(NAME, BoxType::TRUN))
+    }
+}
+
+impl BoxesValidator for TrafChildrenValidator {
+    fn validate<V>(children: &Boxes<V>) -> Result<(), ParseError> {
+        ensure_attach!(
+            children.box_types().any(|box_type| box_type == BoxType::TFHD),
+            ParseError::MissingRequiredBox(BoxType::TFHD),
+            WhileParsingField(NAME, "children"),
+        );
+        Ok(())
+    }
+}
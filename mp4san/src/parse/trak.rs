@@ -1,10 +1,14 @@
 #![allow(missing_docs)]
 
 use crate::error::Result;
+use crate::InputSpan;
 
 use super::error::ParseResultExt;
 use super::mp4box::Boxes;
-use super::{BoxType, MdiaBox, ParseBox, ParseError, ParsedBox, StblCoMut};
+use super::{
+    BoxType, BoxUuid, DrefBox, EdtsBox, ElstBox, HdlrBox, MdiaBox, ParseBox, ParseError, ParsedBox, StblCoMut,
+    StrictValidationOptions, TkhdBox, TrefBox, UdtaBox,
+};
 
 #[derive(Clone, Debug, ParseBox, ParsedBox)]
 #[box_type = "trak"]
@@ -20,11 +24,139 @@ impl TrakBox {
         Self { children: children.into() }
     }
 
+    /// Removes all user data (`udta`) and metadata (`meta`) boxes from this track.
+    ///
+    /// Returns whether any boxes were removed.
+    pub fn strip_user_metadata(&mut self) -> bool {
+        self.children.remove(BoxType::UDTA) + self.children.remove(BoxType::META) != 0
+    }
+
+    /// Removes each `uuid` extension box from this track for which `f` returns `false`.
+    ///
+    /// Returns the number of boxes removed. If `f` returns an error, no boxes are removed.
+    pub fn retain_uuid_boxes<F>(&mut self, f: F) -> Result<usize, ParseError>
+    where
+        F: FnMut(BoxUuid) -> Result<bool, ParseError>,
+    {
+        self.children.try_retain_uuid(f)
+    }
+
+    /// Returns the spans of the input containing each chunk of samples of this track, as computed from its sample
+    /// tables.
+    ///
+    /// For sound tracks, the lengths of chunks described by version 1 QuickTime sound sample descriptions are computed
+    /// as by [`StblBox::chunk_spans_with`](super::StblBox::chunk_spans_with).
+    pub fn chunk_spans(&mut self) -> Result<Vec<InputSpan>, ParseError> {
+        let mdia = self.mdia_mut()?;
+        // NB: A missing handler reference box (`hdlr`) is left to strict validation to reject.
+        let is_sound = mdia
+            .children
+            .get_optional_mut::<HdlrBox>()
+            .while_parsing_child(BoxType::MDIA, BoxType::HDLR)?
+            .is_some_and(|hdlr| hdlr.handler_type == HdlrBox::SOUND);
+        let stbl = mdia.minf_mut()?.stbl_mut()?;
+        let quicktime_sound_v1_fields = match is_sound {
+            true => stbl.stsd_mut()?.quicktime_sound_v1_fields()?,
+            false => Vec::new(),
+        };
+        stbl.chunk_spans_with(&quicktime_sound_v1_fields)
+    }
+
     pub fn co_mut(&mut self) -> Result<StblCoMut<'_>, ParseError> {
         self.mdia_mut()?.minf_mut()?.stbl_mut()?.co_mut()
     }
 
+    pub fn dref_mut(&mut self) -> Result<Option<&mut DrefBox>, ParseError> {
+        match self.mdia_mut()?.minf_mut()?.dinf_mut()? {
+            Some(dinf) => dinf.dref_mut().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn edts_mut(&mut self) -> Result<Option<&mut EdtsBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::EDTS)
+    }
+
+    /// Returns this track's edit list (`elst`), if it has one.
+    pub fn elst_mut(&mut self) -> Result<Option<&mut ElstBox>, ParseError> {
+        match self.edts_mut()? {
+            Some(edts) => edts.elst_mut(),
+            None => Ok(None),
+        }
+    }
+
     pub fn mdia_mut(&mut self) -> Result<&mut MdiaBox, ParseError> {
         self.children.get_one_mut().while_parsing_child(NAME, BoxType::MDIA)
     }
+
+    /// Returns the types of the sample entries in this track's sample description box (`stsd`).
+    pub fn sample_entry_types(&mut self) -> Result<Vec<BoxType>, ParseError> {
+        let stsd = self.mdia_mut()?.minf_mut()?.stbl_mut()?.stsd_mut()?;
+        Ok(stsd.entry_types().collect())
+    }
+
+    /// Validates the protection scheme information of each protected sample entry of this track, along with any
+    /// sample encryption (`senc`) and auxiliary information (`saiz`/`saio`) boxes in its sample table.
+    pub fn validate_common_encryption(&mut self) -> Result<(), ParseError> {
+        self.mdia_mut()?.minf_mut()?.stbl_mut()?.validate_common_encryption()
+    }
+
+    pub fn tkhd_mut(&mut self) -> Result<&mut TkhdBox, ParseError> {
+        self.children.get_one_mut().while_parsing_child(NAME, BoxType::TKHD)
+    }
+
+    pub fn tref_mut(&mut self) -> Result<Option<&mut TrefBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::TREF)
+    }
+
+    pub fn udta_mut(&mut self) -> Result<Option<&mut UdtaBox>, ParseError> {
+        self.children
+            .get_optional_mut()
+            .while_parsing_child(NAME, BoxType::UDTA)
+    }
+
+    /// Removes all references to each of `track_ids` from this track's track reference box (`tref`), removing the
+    /// box if no references remain.
+    pub fn remove_track_references(&mut self, track_ids: &[u32]) -> Result<(), ParseError> {
+        if let Some(tref) = self.tref_mut()? {
+            if !tref.remove_track_ids(track_ids) {
+                self.children.remove(BoxType::TREF);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses every descendant box, returning [`ParseError::UnsupportedBox`] for any box not known to the parser.
+    pub fn validate_strict(&mut self) -> Result<(), ParseError> {
+        self.validate_strict_with(Default::default())
+    }
+
+    /// Like [`validate_strict`](Self::validate_strict), but also accepts the boxes allowed by `options`.
+    pub fn validate_strict_with(&mut self, options: StrictValidationOptions) -> Result<(), ParseError> {
+        let mut children = vec![BoxType::TKHD, BoxType::TREF, BoxType::EDTS, BoxType::MDIA];
+        if options.allow_3gpp_assets {
+            children.push(BoxType::UDTA);
+        }
+        if options.allow_uuid_boxes {
+            children.extend(
+                self.children
+                    .box_types()
+                    .filter(|box_type| matches!(box_type, BoxType::Uuid(_))),
+            );
+        }
+        self.children.ensure_only(NAME, &children)?;
+        if let Some(udta) = self.udta_mut()? {
+            udta.validate_strict_3gpp()?;
+        }
+        self.tkhd_mut()?;
+        self.tref_mut()?;
+        if let Some(edts) = self.edts_mut()? {
+            edts.validate_strict()?;
+        }
+        self.mdia_mut()?.validate_strict_with(options)
+    }
 }
@@ -0,0 +1,162 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxHeader, BoxType, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrefBox {
+    references: Vec<TrackReference>,
+}
+
+/// A track reference type box within a [`TrefBox`], referencing other tracks by their track IDs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrackReference {
+    /// The type of the reference, such as `hint`, `cdsc`, or `chap`.
+    pub reference_type: BoxType,
+    pub track_ids: Vec<u32>,
+}
+
+const NAME: BoxType = BoxType::TREF;
+
+impl TrefBox {
+    pub fn new(references: Vec<TrackReference>) -> Self {
+        Self { references }
+    }
+
+    pub fn references(&self) -> &[TrackReference] {
+        &self.references
+    }
+
+    /// Removes all references to each of `track_ids`, along with any track reference type box left empty, returning
+    /// whether any references remain.
+    pub fn remove_track_ids(&mut self, track_ids: &[u32]) -> bool {
+        for reference in &mut self.references {
+            reference.track_ids.retain(|track_id| !track_ids.contains(track_id));
+        }
+        self.references.retain(|reference| !reference.track_ids.is_empty());
+        !self.references.is_empty()
+    }
+}
+
+impl TrackReference {
+    fn header(&self) -> BoxHeader {
+        BoxHeader::with_u32_data_size(
+            self.reference_type,
+            self.track_ids.len() as u32 * u32::encoded_len() as u32,
+        )
+    }
+}
+
+impl ParseBox for TrefBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let mut references = Vec::new();
+        while buf.has_remaining() {
+            let reference = parse_reference(&mut *buf).while_parsing_field(NAME, "references")?;
+            references.push(reference);
+        }
+        Ok(Self { references })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+fn parse_reference(buf: &mut BytesMut) -> Result<TrackReference, ParseError> {
+    let header = BoxHeader::parse(&mut *buf)?;
+    let data_size = header.box_data_size()?.unwrap_or(buf.remaining() as u64);
+    ensure_attach!(
+        data_size <= buf.remaining() as u64,
+        ParseError::TruncatedBox,
+        WhileParsingField(header.box_type(), "track_IDs"),
+    );
+    ensure_attach!(
+        data_size % u32::encoded_len() == 0,
+        ParseError::InvalidInput,
+        format!("track reference size {data_size} not a multiple of 4"),
+        WhileParsingField(header.box_type(), "track_IDs"),
+    );
+    let mut data = buf.split_to(data_size as usize);
+    let mut track_ids = Vec::with_capacity(data.len() / u32::encoded_len() as usize);
+    while data.has_remaining() {
+        track_ids.push(u32::parse(&mut data)?);
+    }
+    Ok(TrackReference { reference_type: header.box_type(), track_ids })
+}
+
+impl ParsedBox for TrefBox {
+    fn encoded_len(&self) -> u64 {
+        (self.references.iter())
+            .map(|reference| reference.header().encoded_len() + reference.track_ids.len() as u64 * u32::encoded_len())
+            .sum()
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        for reference in &self.references {
+            reference.header().put_buf(&mut out);
+            for track_id in &reference.track_ids {
+                track_id.put_buf(&mut out);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::FourCC;
+
+    use super::*;
+
+    const CDSC: BoxType = BoxType::FourCC(FourCC { value: *b"cdsc" });
+    const HINT: BoxType = BoxType::FourCC(FourCC { value: *b"hint" });
+
+    fn test_tref() -> TrefBox {
+        TrefBox::new(vec![
+            TrackReference { reference_type: CDSC, track_ids: vec![2, 3] },
+            TrackReference { reference_type: HINT, track_ids: vec![3] },
+        ])
+    }
+
+    #[test]
+    fn roundtrip() {
+        let tref = test_tref();
+        let mut data = BytesMut::new();
+        tref.put_buf(&mut data);
+        assert_eq!(data.len() as u64, tref.encoded_len());
+        assert_eq!(TrefBox::parse(&mut data).unwrap(), tref);
+    }
+
+    #[test]
+    fn remove_track_ids() {
+        let mut tref = test_tref();
+        assert!(tref.remove_track_ids(&[3]));
+        assert_eq!(
+            tref.references(),
+            [TrackReference { reference_type: CDSC, track_ids: vec![2] }]
+        );
+        assert!(!tref.remove_track_ids(&[2]));
+        assert!(tref.references().is_empty());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut data = BytesMut::new();
+        test_tref().put_buf(&mut data);
+        data.truncate(data.len() - 1);
+        let err = TrefBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+
+    #[test]
+    fn invalid_size() {
+        let mut data = BytesMut::new();
+        BoxHeader::with_u32_data_size(CDSC, 3).put_buf(&mut data);
+        data.extend_from_slice(&[0; 3]);
+        let err = TrefBox::parse(&mut data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+}
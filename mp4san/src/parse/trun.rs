@@ -0,0 +1,205 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingField};
+use super::{BoxType, FullBoxHeader, Mp4Prim, ParseBox, ParseError, ParsedBox};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrunBox {
+    header: FullBoxHeader,
+    sample_count: u32,
+    data_offset: Option<i32>,
+    first_sample_flags: Option<u32>,
+    samples: BytesMut,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrunSample {
+    pub duration: Option<u32>,
+    pub size: Option<u32>,
+    pub flags: Option<u32>,
+    pub composition_time_offset: Option<i64>,
+}
+
+const NAME: BoxType = BoxType::TRUN;
+
+impl TrunBox {
+    pub const DATA_OFFSET_PRESENT: u32 = 0x00_0001;
+    pub const FIRST_SAMPLE_FLAGS_PRESENT: u32 = 0x00_0004;
+    pub const SAMPLE_DURATION_PRESENT: u32 = 0x00_0100;
+    pub const SAMPLE_SIZE_PRESENT: u32 = 0x00_0200;
+    pub const SAMPLE_FLAGS_PRESENT: u32 = 0x00_0400;
+    pub const SAMPLE_COMPOSITION_TIME_OFFSETS_PRESENT: u32 = 0x00_0800;
+
+    const SAMPLE_FIELDS: [u32; 4] = [
+        Self::SAMPLE_DURATION_PRESENT,
+        Self::SAMPLE_SIZE_PRESENT,
+        Self::SAMPLE_FLAGS_PRESENT,
+        Self::SAMPLE_COMPOSITION_TIME_OFFSETS_PRESENT,
+    ];
+
+    pub fn new(data_offset: Option<i32>, sample_count: u32) -> Self {
+        let flags = if data_offset.is_some() {
+            Self::DATA_OFFSET_PRESENT
+        } else {
+            0
+        };
+        let header = FullBoxHeader { version: 0, flags };
+        Self { header, sample_count, data_offset, ..Default::default() }
+    }
+
+    pub fn with_sample_sizes<I: IntoIterator<Item = u32>>(data_offset: Option<i32>, sample_sizes: I) -> Self {
+        let mut trun = Self::new(data_offset, 0);
+        for sample_size in sample_sizes {
+            sample_size.put_buf(&mut trun.samples);
+            trun.sample_count += 1;
+        }
+        trun.header.flags |= Self::SAMPLE_SIZE_PRESENT;
+        trun
+    }
+
+    pub fn data_offset(&self) -> Option<i32> {
+        self.data_offset
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn samples(&self) -> impl ExactSizeIterator<Item = TrunSample> + '_ {
+        let FullBoxHeader { version, flags } = self.header;
+        let mut samples = &self.samples[..];
+        (0..self.sample_count as usize).map(move |_| {
+            let mut sample_field = |flag: u32| (flags & flag != 0).then(|| samples.get_u32());
+            let duration = sample_field(Self::SAMPLE_DURATION_PRESENT);
+            let size = sample_field(Self::SAMPLE_SIZE_PRESENT);
+            let sample_flags = sample_field(Self::SAMPLE_FLAGS_PRESENT);
+            let composition_time_offset =
+                sample_field(Self::SAMPLE_COMPOSITION_TIME_OFFSETS_PRESENT).map(|offset| match version {
+                    0 => offset.into(),
+                    _ => (offset as i32).into(),
+                });
+            TrunSample { duration, size, flags: sample_flags, composition_time_offset }
+        })
+    }
+
+    /// Returns the total size of the sample data described by this track run.
+    ///
+    /// `default_sample_size` is used for every sample if the track run does not specify individual sample sizes.
+    pub fn sample_data_len(&self, default_sample_size: u32) -> Option<u64> {
+        if self.header.flags & Self::SAMPLE_SIZE_PRESENT == 0 {
+            return u64::from(default_sample_size).checked_mul(self.sample_count.into());
+        }
+        self.samples()
+            .try_fold(0u64, |len, sample| len.checked_add(sample.size?.into()))
+    }
+
+    fn sample_len(flags: u32) -> u64 {
+        let field_count = Self::SAMPLE_FIELDS.iter().filter(|&&flag| flags & flag != 0).count() as u64;
+        field_count * u32::encoded_len()
+    }
+}
+
+impl ParseBox for TrunBox {
+    fn parse(buf: &mut BytesMut) -> Result<Self, ParseError> {
+        let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(NAME, "header")?;
+        ensure_attach!(
+            header.version <= 1,
+            ParseError::InvalidInput,
+            format!("unsupported box version {}", header.version),
+            WhileParsingField(NAME, "header"),
+        );
+        let flags = header.flags;
+        let sample_count = u32::parse(&mut *buf).while_parsing_field(NAME, "sample_count")?;
+        let data_offset = (flags & Self::DATA_OFFSET_PRESENT != 0)
+            .then(|| i32::parse(&mut *buf))
+            .transpose()
+            .while_parsing_field(NAME, "data_offset")?;
+        let first_sample_flags = (flags & Self::FIRST_SAMPLE_FLAGS_PRESENT != 0)
+            .then(|| u32::parse(&mut *buf))
+            .transpose()
+            .while_parsing_field(NAME, "first_sample_flags")?;
+
+        let samples_len = Self::sample_len(flags)
+            .checked_mul(sample_count.into())
+            .ok_or_else(|| report_attach!(ParseError::InvalidInput, "overflow", WhileParsingField(NAME, "samples")))?;
+        ensure_attach!(
+            buf.remaining() as u64 >= samples_len,
+            ParseError::TruncatedBox,
+            WhileParsingField(NAME, "samples"),
+        );
+        let samples = buf.split_to(samples_len as usize);
+
+        Ok(Self { header, sample_count, data_offset, first_sample_flags, samples })
+    }
+
+    fn box_type() -> BoxType {
+        NAME
+    }
+}
+
+impl ParsedBox for TrunBox {
+    fn encoded_len(&self) -> u64 {
+        let data_offset_len = self.data_offset.map(|_| i32::encoded_len()).unwrap_or_default();
+        let first_sample_flags_len = self.first_sample_flags.map(|_| u32::encoded_len()).unwrap_or_default();
+        FullBoxHeader::encoded_len()
+            + u32::encoded_len()
+            + data_offset_len
+            + first_sample_flags_len
+            + self.samples.len() as u64
+    }
+
+    fn put_buf(&self, mut out: &mut dyn BufMut) {
+        self.header.put_buf(&mut out);
+        self.sample_count.put_buf(&mut out);
+        if let Some(data_offset) = self.data_offset {
+            data_offset.put_buf(&mut out);
+        }
+        if let Some(first_sample_flags) = self.first_sample_flags {
+            first_sample_flags.put_buf(&mut out);
+        }
+        out.put_slice(&self.samples[..]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut buf = BytesMut::new();
+        let trun = TrunBox::with_sample_sizes(Some(-1), [1, 2, 3]);
+        trun.put_buf(&mut buf);
+        assert_eq!(buf.len() as u64, trun.encoded_len());
+        let trun = TrunBox::parse(&mut buf).unwrap();
+        assert_eq!(trun.data_offset(), Some(-1));
+        assert_eq!(
+            trun.samples().map(|sample| sample.size).collect::<Vec<_>>(),
+            [Some(1), Some(2), Some(3)]
+        );
+        assert_eq!(trun.sample_data_len(0), Some(6));
+    }
+
+    #[test]
+    fn default_sample_size() {
+        let mut buf = BytesMut::new();
+        TrunBox::new(None, 3).put_buf(&mut buf);
+        let trun = TrunBox::parse(&mut buf).unwrap();
+        assert_eq!(trun.samples().len(), 3);
+        assert_eq!(trun.sample_data_len(5), Some(15));
+    }
+
+    #[test]
+    fn truncated() {
+        let mut buf = BytesMut::new();
+        FullBoxHeader { version: 0, flags: TrunBox::SAMPLE_SIZE_PRESENT }.put_buf(&mut buf);
+        2u32.put_buf(&mut buf);
+        1u32.put_buf(&mut buf);
+        let err = TrunBox::parse(&mut buf).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+}
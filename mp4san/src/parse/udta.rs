@@ -0,0 +1,341 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BytesMut};
+
+use crate::error::Result;
+
+use super::error::{ParseResultExt, WhileParsingBox, WhileParsingField};
+use super::{BoxData, BoxType, Boxes, FullBoxHeader, Mp4Value, ParseBox, ParseError, ParsedBox};
+
+/// A user data box, as defined by ISO/IEC 14496-12 section 8.10.1.
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "udta"]
+pub struct UdtaBox {
+    children: Boxes,
+}
+
+const NAME: BoxType = BoxType::UDTA;
+
+/// The types of the asset boxes defined by 3GPP TS 26.244 section 8.
+pub const THREE_GPP_ASSETS: [BoxType; 12] = [
+    BoxType::TITL,
+    BoxType::DSCP,
+    BoxType::CPRT,
+    BoxType::PERF,
+    BoxType::AUTH,
+    BoxType::GNRE,
+    BoxType::RTNG,
+    BoxType::CLSF,
+    BoxType::KYWD,
+    BoxType::LOCI,
+    BoxType::ALBM,
+    BoxType::YRRC,
+];
+
+/// The pad bit preceding a packed ISO 639-2/T language code, which must be zero.
+const LANGUAGE_PAD_BIT: u16 = 0x8000;
+
+const UTF16_BE_BOM: [u8; 2] = [0xfe, 0xff];
+const UTF16_LE_BOM: [u8; 2] = [0xff, 0xfe];
+
+impl UdtaBox {
+    #[cfg(test)]
+    pub(crate) fn with_children<C: Into<Boxes>>(children: C) -> Self {
+        Self { children: children.into() }
+    }
+
+    /// Parses each 3GPP asset box, such as the title (`titl`) or location (`loci`), returning
+    /// [`ParseError::InvalidInput`] for any malformed asset. Other boxes are ignored.
+    pub fn validate_3gpp_assets(&mut self) -> Result<(), ParseError> {
+        for child in self.children.iter_mut() {
+            let box_type = child.calculated_header().box_type();
+            if !THREE_GPP_ASSETS.contains(&box_type) {
+                continue;
+            }
+            let BoxData::Bytes(data) = &child.data else {
+                continue;
+            };
+            parse_3gpp_asset(box_type, &mut data.clone()).while_parsing_child(NAME, box_type)?;
+        }
+        Ok(())
+    }
+
+    /// Parses each 3GPP asset box, returning [`ParseError::UnsupportedBox`] for any other box.
+    pub fn validate_strict_3gpp(&mut self) -> Result<(), ParseError> {
+        self.children.ensure_only(NAME, &THREE_GPP_ASSETS)?;
+        self.validate_3gpp_assets()
+    }
+}
+
+fn parse_3gpp_asset(box_type: BoxType, buf: &mut BytesMut) -> Result<(), ParseError> {
+    let header = FullBoxHeader::parse(&mut *buf).while_parsing_field(box_type, "header")?;
+    ensure_attach!(
+        header.version == 0,
+        ParseError::InvalidInput,
+        format!("unsupported box version {}", header.version),
+        WhileParsingBox(box_type),
+    );
+    match box_type {
+        BoxType::YRRC => {
+            u16::parse(&mut *buf).while_parsing_field(box_type, "recording_year")?;
+        }
+        BoxType::RTNG => {
+            u32::parse(&mut *buf).while_parsing_field(box_type, "rating_entity")?;
+            u32::parse(&mut *buf).while_parsing_field(box_type, "rating_criteria")?;
+            parse_language(buf, box_type)?;
+            parse_string(buf, box_type, "rating_info")?;
+        }
+        BoxType::CLSF => {
+            u32::parse(&mut *buf).while_parsing_field(box_type, "classification_entity")?;
+            u16::parse(&mut *buf).while_parsing_field(box_type, "classification_table")?;
+            parse_language(buf, box_type)?;
+            parse_string(buf, box_type, "classification_info")?;
+        }
+        BoxType::KYWD => {
+            parse_language(buf, box_type)?;
+            let keyword_count = u8::parse(&mut *buf).while_parsing_field(box_type, "keyword_cnt")?;
+            for _ in 0..keyword_count {
+                let keyword_size = u8::parse(&mut *buf).while_parsing_field(box_type, "keyword_size")?;
+                ensure_attach!(
+                    buf.remaining() >= keyword_size.into(),
+                    ParseError::TruncatedBox,
+                    WhileParsingField(box_type, "keyword"),
+                );
+                let mut keyword = buf.split_to(keyword_size.into());
+                parse_string(&mut keyword, box_type, "keyword")?;
+                ensure_attach!(
+                    keyword.is_empty(),
+                    ParseError::InvalidInput,
+                    "extra data after keyword",
+                    WhileParsingField(box_type, "keyword"),
+                );
+            }
+        }
+        BoxType::LOCI => {
+            parse_language(buf, box_type)?;
+            parse_string(buf, box_type, "name")?;
+            u8::parse(&mut *buf).while_parsing_field(box_type, "role")?;
+            u32::parse(&mut *buf).while_parsing_field(box_type, "longitude")?;
+            u32::parse(&mut *buf).while_parsing_field(box_type, "latitude")?;
+            u32::parse(&mut *buf).while_parsing_field(box_type, "altitude")?;
+            parse_string(buf, box_type, "astronomical_body")?;
+            parse_string(buf, box_type, "additional_notes")?;
+        }
+        BoxType::ALBM => {
+            parse_language(buf, box_type)?;
+            parse_string(buf, box_type, "albumtitle")?;
+            if !buf.is_empty() {
+                u8::parse(&mut *buf).while_parsing_field(box_type, "trackNumber")?;
+            }
+        }
+        _ => {
+            parse_language(buf, box_type)?;
+            parse_string(buf, box_type, "value")?;
+        }
+    }
+    ensure_attach!(
+        buf.is_empty(),
+        ParseError::InvalidInput,
+        "extra unparsed data",
+        WhileParsingBox(box_type),
+    );
+    Ok(())
+}
+
+fn parse_language(buf: &mut BytesMut, box_type: BoxType) -> Result<(), ParseError> {
+    let language = u16::parse(&mut *buf).while_parsing_field(box_type, "language")?;
+    ensure_attach!(
+        language & LANGUAGE_PAD_BIT == 0,
+        ParseError::InvalidInput,
+        "non-zero pad bit",
+        WhileParsingField(box_type, "language"),
+    );
+    Ok(())
+}
+
+/// Parses a NUL-terminated string, encoded as UTF-8, or as UTF-16 if it begins with a byte order mark.
+fn parse_string(buf: &mut BytesMut, box_type: BoxType, field_name: &'static str) -> Result<(), ParseError> {
+    let big_endian = match buf.get(..2) {
+        Some(bom) if bom == UTF16_BE_BOM => Some(true),
+        Some(bom) if bom == UTF16_LE_BOM => Some(false),
+        _ => None,
+    };
+    let valid = match big_endian {
+        Some(big_endian) => {
+            let code_units = buf[2..].chunks_exact(2).map(|code_unit| match big_endian {
+                true => u16::from_be_bytes([code_unit[0], code_unit[1]]),
+                false => u16::from_le_bytes([code_unit[0], code_unit[1]]),
+            });
+            let Some(len) = code_units.clone().position(|code_unit| code_unit == 0) else {
+                bail_attach!(ParseError::TruncatedBox, WhileParsingField(box_type, field_name));
+            };
+            let valid = char::decode_utf16(code_units.take(len)).all(|char| char.is_ok());
+            buf.advance(2 + 2 * (len + 1));
+            valid
+        }
+        None => {
+            let Some(len) = buf.iter().position(|&byte| byte == 0) else {
+                bail_attach!(ParseError::TruncatedBox, WhileParsingField(box_type, field_name));
+            };
+            let valid = std::str::from_utf8(&buf[..len]).is_ok();
+            buf.advance(len + 1);
+            valid
+        }
+    };
+    ensure_attach!(
+        valid,
+        ParseError::InvalidInput,
+        "invalid string encoding",
+        WhileParsingField(box_type, field_name),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BufMut;
+
+    use crate::parse::Mp4Box;
+    use crate::util::test::{test_3gpp_asset, test_udta};
+
+    use super::*;
+
+    fn validate(asset_type: BoxType, asset_data: &[u8]) -> Result<(), ParseError> {
+        let mut udta = UdtaBox::with_children(vec![test_3gpp_asset(asset_type, asset_data)]);
+        udta.validate_strict_3gpp()
+    }
+
+    fn string_asset(string: &[u8]) -> Vec<u8> {
+        let mut data = vec![];
+        data.put_u16(0x15c7); // language
+        data.put_slice(string);
+        data
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        UdtaBox::with_children(vec![test_3gpp_asset(BoxType::TITL, &string_asset(b"title\0"))]).put_buf(&mut data);
+        let mut udta = UdtaBox::parse(&mut data).unwrap();
+        udta.validate_strict_3gpp().unwrap();
+    }
+
+    #[test]
+    fn string_assets() {
+        for asset_type in [
+            BoxType::TITL,
+            BoxType::DSCP,
+            BoxType::CPRT,
+            BoxType::PERF,
+            BoxType::AUTH,
+            BoxType::GNRE,
+        ] {
+            validate(asset_type, &string_asset(b"\0")).unwrap();
+            validate(asset_type, &string_asset(b"caf\xc3\xa9\0")).unwrap();
+            validate(asset_type, &string_asset(b"\xfe\xff\x00c\x00a\x00f\x00\xe9\0\0")).unwrap();
+            validate(asset_type, &string_asset(b"\xff\xfec\x00a\x00f\x00\xe9\x00\0\0")).unwrap();
+        }
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let err = validate(BoxType::TITL, &string_asset(b"caf\xe9\0")).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn invalid_utf16() {
+        let err = validate(BoxType::TITL, &string_asset(b"\xfe\xff\xd8\x00\0\0")).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let err = validate(BoxType::TITL, &string_asset(b"title")).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+
+    #[test]
+    fn extra_data() {
+        let err = validate(BoxType::TITL, &string_asset(b"title\0\0")).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn language_pad_bit() {
+        let mut data = string_asset(b"title\0");
+        data[0] |= 0x80;
+        let err = validate(BoxType::TITL, &data).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn album() {
+        validate(BoxType::ALBM, &string_asset(b"album\0")).unwrap();
+        validate(BoxType::ALBM, &string_asset(b"album\0\x03")).unwrap();
+    }
+
+    #[test]
+    fn recording_year() {
+        validate(BoxType::YRRC, &2008u16.to_be_bytes()).unwrap();
+        let err = validate(BoxType::YRRC, &[0x07]).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+
+    #[test]
+    fn rating_and_classification() {
+        let mut rtng = b"MPAAPG13".to_vec();
+        rtng.extend(string_asset(b"rating\0"));
+        validate(BoxType::RTNG, &rtng).unwrap();
+
+        let mut clsf = b"TEST\x00\x01".to_vec();
+        clsf.extend(string_asset(b"classification\0"));
+        validate(BoxType::CLSF, &clsf).unwrap();
+    }
+
+    #[test]
+    fn keywords() {
+        let mut kywd = vec![0x15, 0xc7, 2];
+        kywd.extend([4, b'o', b'n', b'e', 0]);
+        kywd.extend([4, b't', b'w', b'o', 0]);
+        validate(BoxType::KYWD, &kywd).unwrap();
+
+        kywd[3] = 5;
+        validate(BoxType::KYWD, &kywd).unwrap_err();
+    }
+
+    #[test]
+    fn location() {
+        let mut loci = string_asset(b"home\0");
+        loci.put_u8(0); // role
+        loci.put_u32(0xff87_3cf8); // longitude
+        loci.put_u32(0x0025_bf6d); // latitude
+        loci.put_u32(0); // altitude
+        loci.put_slice(b"earth\0");
+        loci.put_slice(b"\0");
+        validate(BoxType::LOCI, &loci).unwrap();
+
+        loci.pop();
+        let err = validate(BoxType::LOCI, &loci).unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::TruncatedBox), "{err}");
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut data = BytesMut::new();
+        FullBoxHeader { version: 1, flags: 0 }.put_buf(&mut data);
+        data.put_slice(&string_asset(b"title\0"));
+        let mut udta = UdtaBox::with_children(vec![Mp4Box::with_bytes(BoxType::TITL, data)]);
+        let err = udta.validate_3gpp_assets().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::InvalidInput), "{err}");
+    }
+
+    #[test]
+    fn non_3gpp_children() {
+        let mut udta_box = test_udta();
+        let udta = udta_box.parse_data_as::<UdtaBox>().unwrap().unwrap();
+        udta.validate_3gpp_assets().unwrap();
+        let err = udta.validate_strict_3gpp().unwrap_err();
+        assert!(matches!(err.get_ref(), ParseError::UnsupportedBox(_)), "{err}");
+    }
+}
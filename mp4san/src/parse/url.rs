@@ -0,0 +1,70 @@
+#![allow(missing_docs)]
+
+use super::{FullBoxHeader, ParseBox, ParsedBox, UnboundedArray};
+
+#[derive(Clone, Debug, Default, ParseBox, ParsedBox)]
+#[box_type = "url "]
+pub struct UrlBox {
+    header: FullBoxHeader,
+    pub location: UnboundedArray<u8>,
+}
+
+impl UrlBox {
+    /// Flag indicating that the media data is in the same file as the box containing this data reference.
+    pub const SELF_CONTAINED_FLAG: u32 = 0x000001;
+
+    /// Returns a data reference to the same file as the box containing it.
+    pub fn self_contained() -> Self {
+        Self { header: FullBoxHeader { version: 0, flags: Self::SELF_CONTAINED_FLAG }, location: Default::default() }
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.header.flags
+    }
+
+    /// Returns whether this data reference refers to the same file as the box containing it.
+    ///
+    /// A data reference with the self-contained flag set must have no location, as downstream parsers may still follow
+    /// one.
+    pub fn is_self_contained(&self) -> bool {
+        self.header.flags & Self::SELF_CONTAINED_FLAG != 0 && self.location.entry_count() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::parse::Mp4Value;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        UrlBox::self_contained().put_buf(&mut data);
+        assert_eq!(data.len() as u64, UrlBox::self_contained().encoded_len());
+        let url = UrlBox::parse(&mut data).unwrap();
+        assert!(url.is_self_contained());
+        assert_eq!(url.location.entry_count(), 0);
+    }
+
+    #[test]
+    fn external() {
+        let mut data = BytesMut::new();
+        FullBoxHeader::default().put_buf(&mut data);
+        data.extend_from_slice(b"https://example.com/\0");
+        let url = UrlBox::parse(&mut data).unwrap();
+        assert!(!url.is_self_contained());
+        assert_eq!(url.location.entry_count(), 21);
+    }
+
+    #[test]
+    fn self_contained_with_location() {
+        let mut data = BytesMut::new();
+        FullBoxHeader { version: 0, flags: UrlBox::SELF_CONTAINED_FLAG }.put_buf(&mut data);
+        data.extend_from_slice(b"https://example.com/\0");
+        let url = UrlBox::parse(&mut data).unwrap();
+        assert!(!url.is_self_contained());
+    }
+}
@@ -4,10 +4,18 @@ use bytes::{BufMut, BytesMut};
 
 use crate::error::Result;
 
-use super::{Mp4Prim, ParseError};
+use super::{BoxDepth, Mp4Prim, ParseError};
 
 pub trait Mp4Value: Sized {
     fn parse(buf: &mut BytesMut) -> Result<Self, ParseError>;
+
+    /// Parses a value from the data of a box nested at `depth`, as [`parse`](Self::parse) does.
+    ///
+    /// The default implementation ignores the depth, and is only suitable for values which don't contain boxes.
+    fn parse_at_depth(buf: &mut BytesMut, _depth: BoxDepth) -> Result<Self, ParseError> {
+        Self::parse(buf)
+    }
+
     fn encoded_len(&self) -> u64;
     fn put_buf<B: BufMut>(&self, buf: B);
 }
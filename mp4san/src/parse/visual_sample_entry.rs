@@ -0,0 +1,98 @@
+#![allow(missing_docs)]
+
+use bytes::{Buf, BufMut};
+use mediasan_common::error::WhileParsingType;
+
+use crate::error::Result;
+
+use super::{Mp4Prim, ParseError};
+
+/// The fields common to all visual sample entries, as defined by ISO/IEC 14496-12 section 12.1.3.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VisualSampleEntry {
+    reserved_1: [u8; 6],
+    pub data_reference_index: u16,
+    pre_defined_1: u16,
+    reserved_2: u16,
+    pre_defined_2: [u32; 3],
+    pub width: u16,
+    pub height: u16,
+    pub horizresolution: u32,
+    pub vertresolution: u32,
+    reserved_3: u32,
+    pub frame_count: u16,
+    pub compressorname: [u8; 32],
+    pub depth: u16,
+    pre_defined_3: i16,
+}
+
+impl VisualSampleEntry {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            reserved_1: Default::default(),
+            data_reference_index: 1,
+            pre_defined_1: 0,
+            reserved_2: 0,
+            pre_defined_2: Default::default(),
+            width,
+            height,
+            horizresolution: 0x00480000,
+            vertresolution: 0x00480000,
+            reserved_3: 0,
+            frame_count: 1,
+            compressorname: [0; 32],
+            depth: 0x0018,
+            pre_defined_3: -1,
+        }
+    }
+}
+
+impl Mp4Prim for VisualSampleEntry {
+    fn parse<B: Buf>(mut buf: B) -> Result<Self, ParseError> {
+        ensure_attach!(
+            buf.remaining() >= Self::encoded_len() as usize,
+            ParseError::TruncatedBox,
+            WhileParsingType::new::<Self>(),
+        );
+        fn parse<T: Mp4Prim, B: Buf>(buf: B) -> T {
+            T::parse(buf).unwrap_or_else(|_| unreachable!())
+        }
+        Ok(Self {
+            reserved_1: parse(&mut buf),
+            data_reference_index: parse(&mut buf),
+            pre_defined_1: parse(&mut buf),
+            reserved_2: parse(&mut buf),
+            pre_defined_2: parse(&mut buf),
+            width: parse(&mut buf),
+            height: parse(&mut buf),
+            horizresolution: parse(&mut buf),
+            vertresolution: parse(&mut buf),
+            reserved_3: parse(&mut buf),
+            frame_count: parse(&mut buf),
+            compressorname: parse(&mut buf),
+            depth: parse(&mut buf),
+            pre_defined_3: parse(&mut buf),
+        })
+    }
+
+    fn encoded_len() -> u64 {
+        78
+    }
+
+    fn put_buf<B: BufMut>(&self, mut buf: B) {
+        self.reserved_1.put_buf(&mut buf);
+        self.data_reference_index.put_buf(&mut buf);
+        self.pre_defined_1.put_buf(&mut buf);
+        self.reserved_2.put_buf(&mut buf);
+        self.pre_defined_2.put_buf(&mut buf);
+        self.width.put_buf(&mut buf);
+        self.height.put_buf(&mut buf);
+        self.horizresolution.put_buf(&mut buf);
+        self.vertresolution.put_buf(&mut buf);
+        self.reserved_3.put_buf(&mut buf);
+        self.frame_count.put_buf(&mut buf);
+        self.compressorname.put_buf(&mut buf);
+        self.depth.put_buf(&mut buf);
+        self.pre_defined_3.put_buf(&mut buf);
+    }
+}
@@ -0,0 +1,32 @@
+#![allow(missing_docs)]
+
+use super::{FullBoxHeader, ParseBox, ParsedBox};
+
+#[derive(Clone, Debug, ParseBox, ParsedBox)]
+#[box_type = "vmhd"]
+pub struct VmhdBox {
+    header: FullBoxHeader,
+    pub graphics_mode: u16,
+    pub opcolor: [u16; 3],
+}
+
+impl Default for VmhdBox {
+    fn default() -> Self {
+        Self { header: FullBoxHeader { version: 0, flags: 1 }, graphics_mode: 0, opcolor: [0; 3] }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut data = BytesMut::new();
+        VmhdBox::default().put_buf(&mut data);
+        assert_eq!(data.len() as u64, VmhdBox::default().encoded_len());
+        VmhdBox::parse(&mut data).unwrap();
+    }
+}
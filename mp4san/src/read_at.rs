@@ -0,0 +1,203 @@
+use std::io;
+use std::io::Read;
+
+use bytes::{Bytes, BytesMut};
+use mediasan_common::sync;
+
+use crate::parse::{BoxHeader, BoxType};
+use crate::{
+    ensure_not_cancelled, sanitize_async_with_config, Config, Error, ReadAt, SanitizedMetadata, Skip, MAX_FTYP_SIZE,
+};
+
+//
+// public functions
+//
+
+/// Sanitize an input supporting positional reads.
+///
+/// The header of each top-level box is read at its offset, and then the data of each box parsed by the sanitizer, such
+/// as the movie box (`moov`), is read at its offset with a single read. The sanitizer then runs over the boxes read,
+/// skipping over the data of all other boxes, such as media data boxes (`mdat`), without reading it.
+pub fn sanitize_with_config<R: ReadAt + Unpin>(input: R, config: Config) -> Result<SanitizedMetadata, Error> {
+    let input = PrefetchedInput::read_top_level_boxes(input, &config)?;
+    sync::sanitize(input, |input| sanitize_async_with_config(input, config))
+}
+
+//
+// private types
+//
+
+/// An input whose top-level box headers, along with the data of the boxes parsed by the sanitizer, have been read
+/// ahead of sanitizing it.
+///
+/// Reads of the input outside of the prefetched boxes are passed through to the underlying [`ReadAt`] input.
+struct PrefetchedInput<R> {
+    input: R,
+    input_len: u64,
+    prefetched: Vec<(u64, Bytes)>,
+    position: u64,
+}
+
+//
+// PrefetchedInput impls
+//
+
+impl<R: ReadAt> PrefetchedInput<R> {
+    fn read_top_level_boxes(input: R, config: &Config) -> Result<Self, Error> {
+        let input_len = input.stream_len()?;
+        let mut prefetched = Vec::new();
+        let mut offset = 0;
+        while offset < input_len && (prefetched.len() as u64) < config.max_top_level_boxes {
+            ensure_not_cancelled(config)?;
+
+            let mut header_buf = [0; BoxHeader::MAX_SIZE as usize];
+            let header_read_len = read_at_most(&input, offset, &mut header_buf)?;
+            let header_bytes = &header_buf[..header_read_len];
+            let header = BoxHeader::parse(header_bytes).and_then(|header| Ok((header, header.box_data_size()?)));
+            let Ok((header, box_data_size)) = header else {
+                // Leave the invalid or truncated header to be rejected by the sanitizer.
+                prefetched.push((offset, Bytes::copy_from_slice(header_bytes)));
+                break;
+            };
+            let header_len = header.encoded_len();
+            let data_offset = offset.saturating_add(header_len);
+            let box_data_size = box_data_size.unwrap_or(input_len.saturating_sub(data_offset));
+
+            let mut box_bytes = BytesMut::from(&header_bytes[..header_len as usize]);
+            if box_data_size <= max_prefetched_data_size(header.box_type(), config) {
+                let data_start = box_bytes.len();
+                box_bytes.resize(data_start + box_data_size as usize, 0);
+                let data_len = read_at_most(&input, data_offset, &mut box_bytes[data_start..])?;
+                box_bytes.truncate(data_start + data_len);
+            }
+            prefetched.push((offset, box_bytes.freeze()));
+
+            let Some(next_offset) = data_offset.checked_add(box_data_size) else {
+                break;
+            };
+            offset = next_offset;
+        }
+        Ok(Self { input, input_len, prefetched, position: 0 })
+    }
+}
+
+impl<R: ReadAt> Read for PrefetchedInput<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let prefetched_index = self.prefetched.partition_point(|(offset, _)| *offset <= self.position);
+        let read_len = match prefetched_index.checked_sub(1).map(|index| &self.prefetched[index]) {
+            Some((offset, bytes)) if self.position - offset < bytes.len() as u64 => {
+                let bytes = &bytes[(self.position - offset) as usize..];
+                let read_len = bytes.len().min(buf.len());
+                buf[..read_len].copy_from_slice(&bytes[..read_len]);
+                read_len
+            }
+            _ => self.input.read_at(self.position, buf)?,
+        };
+        self.position += read_len as u64;
+        Ok(read_len)
+    }
+}
+
+impl<R> Skip for PrefetchedInput<R> {
+    fn skip(&mut self, amount: u64) -> io::Result<()> {
+        self.position = self
+            .position
+            .checked_add(amount)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "seek past u64::MAX"))?;
+        Ok(())
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+
+    fn stream_len(&mut self) -> io::Result<u64> {
+        Ok(self.input_len)
+    }
+}
+
+//
+// private functions
+//
+
+/// Returns the largest box data the sanitizer will read for a top-level box of type `box_type`.
+///
+/// The data of larger boxes is left unread, to be rejected by the sanitizer before reading it.
+fn max_prefetched_data_size(box_type: BoxType, config: &Config) -> u64 {
+    match box_type {
+        BoxType::FTYP => MAX_FTYP_SIZE,
+        BoxType::MOOV => config.max_metadata_size,
+        BoxType::MOOF if config.allow_fragmented => config.max_metadata_size,
+        _ => 0,
+    }
+}
+
+/// Read bytes starting at `offset` in `input` until `buf` is filled or the end of the input is reached, returning the
+/// number of bytes read.
+fn read_at_most<R: ReadAt>(input: &R, mut offset: u64, mut buf: &mut [u8]) -> io::Result<usize> {
+    let buf_len = buf.len();
+    while !buf.is_empty() {
+        match input.read_at(offset, buf) {
+            Ok(0) => break,
+            Ok(read_len) => {
+                offset += read_len as u64;
+                buf = &mut buf[read_len..];
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(buf_len - buf.len())
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+
+    use crate::util::test::test_mp4;
+
+    use super::*;
+
+    /// A [`ReadAt`] input recording the offset and length of each read.
+    struct RecordingReadAt {
+        data: Vec<u8>,
+        reads: RefCell<Vec<(u64, usize)>>,
+    }
+
+    impl ReadAt for RecordingReadAt {
+        fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+            let read_len = self.data.read_at(offset, buf)?;
+            self.reads.borrow_mut().push((offset, read_len));
+            Ok(read_len)
+        }
+
+        fn stream_len(&self) -> io::Result<u64> {
+            Ok(self.data.len() as u64)
+        }
+    }
+
+    #[test]
+    fn media_data_not_read() {
+        let test = test_mp4().mdat_data(vec![0xAB; 4096]).build();
+        let input = RecordingReadAt { data: test.data.to_vec(), reads: Default::default() };
+        let sanitized = sanitize_with_config(&input, Config::default()).unwrap();
+        assert_eq!(sanitized.data, test.mdat);
+
+        let mdat_data_offset = test.mdat.offset + 8;
+        let moov_offset = test.mdat.offset + test.mdat.len;
+        let moov_data_len = test.data.len() - moov_offset as usize - 8;
+        let reads = input.reads.into_inner();
+        assert!(
+            reads.contains(&(moov_offset, BoxHeader::MAX_SIZE as usize)),
+            "{reads:?}"
+        );
+        assert!(reads.contains(&(moov_offset + 8, moov_data_len)), "{reads:?}");
+        for (offset, len) in reads {
+            let read_end = offset + len as u64;
+            assert!(
+                read_end <= mdat_data_offset + BoxHeader::MAX_SIZE || offset >= moov_offset,
+                "media data read at {offset} of {len} bytes",
+            );
+        }
+    }
+}
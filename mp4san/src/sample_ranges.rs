@@ -0,0 +1,178 @@
+use crate::parse::error::WhileParsingBox;
+use crate::parse::{BoxType, MoovBox, ParseError};
+use crate::{Error, InputSpan};
+
+//
+// public functions
+//
+
+/// Validate that the sample data of each track, as computed from its sample tables, lies within the media `data`, and
+/// doesn't overlap the sample data of any other track.
+///
+/// Returns the spans of the non-empty chunks of each track, in order.
+pub fn validate_sample_ranges(moov: &mut MoovBox, data: &[InputSpan]) -> Result<Vec<InputSpan>, Error> {
+    let mut chunks = Vec::new();
+    for (trak_index, trak) in moov.traks().enumerate() {
+        let trak = trak?;
+        for chunk in trak.chunk_spans()? {
+            if chunk.len == 0 {
+                continue;
+            }
+            let chunk_end = chunk.offset.checked_add(chunk.len);
+            ensure_attach!(
+                data.iter().any(|span| span.offset <= chunk.offset
+                    && chunk_end.is_some_and(|chunk_end| chunk_end <= span.offset + span.len)),
+                ParseError::InvalidSampleRange,
+                format!(
+                    "chunk @ 0x{:08x} of 0x{:08x} bytes not within mdat",
+                    chunk.offset, chunk.len
+                ),
+                WhileParsingBox(BoxType::TRAK),
+            );
+            chunks.push((chunk, trak_index));
+        }
+    }
+
+    let trak_chunks = chunks.iter().map(|&(chunk, _)| chunk).collect();
+
+    // Check each chunk, in order of offset, against the furthest extent of the chunks of any other track so far. Only
+    // the furthest extent of any track, the track it belongs to, and the furthest extent of any other track are needed.
+    chunks.sort_unstable_by_key(|(chunk, _)| chunk.offset);
+    let mut max_data_end = 0;
+    let mut max_data_end_trak_index = None;
+    let mut other_max_data_end = 0;
+    for (chunk, trak_index) in chunks {
+        let other_trak_data_end = match max_data_end_trak_index {
+            Some(max_trak_index) if max_trak_index == trak_index => other_max_data_end,
+            _ => max_data_end,
+        };
+        ensure_attach!(
+            other_trak_data_end <= chunk.offset,
+            ParseError::InvalidSampleRange,
+            format!("chunk @ 0x{:08x} overlaps sample data of another track", chunk.offset),
+            WhileParsingBox(BoxType::TRAK),
+        );
+        let data_end = chunk.offset + chunk.len;
+        if max_data_end_trak_index == Some(trak_index) {
+            max_data_end = max_data_end.max(data_end);
+        } else if data_end > max_data_end {
+            other_max_data_end = max_data_end;
+            max_data_end = data_end;
+            max_data_end_trak_index = Some(trak_index);
+        } else {
+            other_max_data_end = other_max_data_end.max(data_end);
+        }
+    }
+    Ok(trak_chunks)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+    use std::time::Duration;
+
+    use assert_matches::assert_matches;
+
+    use crate::parse::box_type::MDAT;
+    use crate::parse::{BoxHeader, Mp4Value};
+    use crate::util::test::{
+        init_logger, test_ftyp, test_moov, test_trak, test_two_track_mp4, write_test_mdat, TestQuickTimeSoundBuilder,
+    };
+    use crate::{sanitize, sanitize_with_config, Config};
+
+    use super::*;
+
+    #[test]
+    fn sample_ranges_many_traks() {
+        init_logger();
+        const TRAK_COUNT: u64 = 1024;
+        const CHUNK_COUNT: u64 = 1024;
+
+        let mut data = vec![];
+        test_ftyp().build().put_buf(&mut data);
+        let mdat = write_test_mdat(&mut data, &vec![0; (TRAK_COUNT * CHUNK_COUNT) as usize]);
+        let mdat_data_offset = mdat.offset + BoxHeader::with_u32_data_size(MDAT, 0).encoded_len();
+        let co_entries = |trak_index: u64| -> Vec<u64> {
+            (0..CHUNK_COUNT)
+                .map(|chunk_index| mdat_data_offset + chunk_index * TRAK_COUNT + trak_index)
+                .collect()
+        };
+        test_moov()
+            .co_entries(co_entries(0))
+            .traks(
+                (1..TRAK_COUNT)
+                    .map(|trak_index| test_trak().co_entries(co_entries(trak_index)).clone())
+                    .collect::<Vec<_>>(),
+            )
+            .build()
+            .put_buf(&mut data);
+
+        // Each chunk is checked against every other track in constant time, rather than in time linear in the number
+        // of tracks.
+        let start = std::time::Instant::now();
+        let sanitized = sanitize(io::Cursor::new(&data)).unwrap();
+        assert_eq!(sanitized.media_info.unwrap().tracks.len(), TRAK_COUNT as usize);
+        let elapsed = start.elapsed();
+        assert!(elapsed < Duration::from_secs(10), "sanitizing took {elapsed:?}");
+    }
+
+    #[test]
+    fn sample_range_before_mdat() {
+        init_logger();
+        let mut data = vec![];
+        test_ftyp().build().put_buf(&mut data);
+        write_test_mdat(&mut data, b"abc");
+        test_moov().co_entries(vec![0]).build().put_buf(&mut data);
+        assert_matches!(sanitize(io::Cursor::new(&data)).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidSampleRange);
+        });
+    }
+
+    #[test]
+    fn sample_range_past_mdat_end() {
+        init_logger();
+        let mut data = vec![];
+        test_ftyp().build().put_buf(&mut data);
+        let mdat = write_test_mdat(&mut data, b"abc");
+        test_moov()
+            .co_entries(vec![mdat.offset + mdat.len])
+            .build()
+            .put_buf(&mut data);
+        assert_matches!(sanitize(io::Cursor::new(&data)).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidSampleRange);
+        });
+    }
+
+    #[test]
+    fn sample_ranges_interleaved() {
+        init_logger();
+        let data = test_two_track_mp4(&mut test_moov(), &mut test_trak(), vec![0, 2], vec![1, 3]);
+        sanitize(io::Cursor::new(&data)).unwrap();
+    }
+
+    #[test]
+    fn sample_ranges_overlapping() {
+        init_logger();
+        let data = test_two_track_mp4(&mut test_moov(), &mut test_trak(), vec![0, 1], vec![1, 2]);
+        assert_matches!(sanitize(io::Cursor::new(&data)).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidSampleRange);
+        });
+    }
+
+    #[test]
+    fn quicktime_sound_v1() {
+        let config = Config::builder().allow_quicktime(true).build();
+        let test = TestQuickTimeSoundBuilder::default().build();
+        let sanitized = sanitize_with_config(io::Cursor::new(&test.data), config).unwrap();
+        assert_eq!(sanitized.data, test.mdat);
+    }
+
+    #[test]
+    fn quicktime_sound_v1_chunk_past_mdat() {
+        let config = Config::builder().allow_quicktime(true).build();
+        let test = TestQuickTimeSoundBuilder::default().mdat_data_len(339).build();
+        assert_matches!(sanitize_with_config(io::Cursor::new(&test.data), config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidSampleRange);
+        });
+    }
+}
@@ -0,0 +1,154 @@
+use crate::parse::error::WhileParsingBox;
+use crate::parse::{BoxType, FourCC, MoovBox, ParseError};
+use crate::Error;
+
+//
+// public functions
+//
+
+/// Remove each track in `moov` whose handler type is not in `keep_handler_types`.
+///
+/// Returns the track IDs of the removed tracks.
+pub fn retain_handler_types(moov: &mut MoovBox, keep_handler_types: &[FourCC]) -> Result<Vec<u32>, Error> {
+    let removed_track_ids = moov.retain_traks(|trak| {
+        let handler_type = trak.mdia_mut()?.hdlr_mut()?.handler_type;
+        Ok(keep_handler_types.contains(&handler_type))
+    })?;
+    Ok(removed_track_ids)
+}
+
+/// Remove each track in `moov` using a sample entry type not in `allowed_sample_entries`, if `drop_disallowed_tracks`
+/// is set, or otherwise reject it.
+///
+/// Returns the track IDs of the removed tracks.
+pub fn retain_allowed_sample_entries(
+    moov: &mut MoovBox,
+    allowed_sample_entries: &[BoxType],
+    drop_disallowed_tracks: bool,
+) -> Result<Vec<u32>, Error> {
+    let removed_track_ids = moov.retain_traks(|trak| {
+        let disallowed_sample_entry = (trak.sample_entry_types()?.into_iter())
+            .find(|sample_entry_type| !allowed_sample_entries.contains(sample_entry_type));
+        match disallowed_sample_entry {
+            None => Ok(true),
+            Some(_) if drop_disallowed_tracks => Ok(false),
+            Some(sample_entry_type) => bail_attach!(
+                ParseError::UnsupportedBox(sample_entry_type),
+                "sample entry type not allowed",
+                WhileParsingBox(BoxType::STSD),
+            ),
+        }
+    })?;
+    Ok(removed_track_ids)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use assert_matches::assert_matches;
+
+    use crate::parse::box_type::{AVC1, METT, MP4A, TRAK};
+    use crate::parse::HdlrBox;
+    use crate::util::test::{
+        init_logger, sanitized_chunk_offsets, sanitized_data, test_moov, test_mp4, test_trak, test_two_track_mp4,
+    };
+    use crate::{sanitize_with_config, Config};
+
+    use super::*;
+
+    #[test]
+    fn allowed_sample_entries() {
+        init_logger();
+        let test = test_mp4().build();
+        let config = Config::builder().allowed_sample_entries(vec![METT]).build();
+        test.sanitize_ok_with_config(config);
+    }
+
+    #[test]
+    fn disallowed_sample_entry() {
+        init_logger();
+        let test = test_mp4().build();
+        let config = Config::builder().allowed_sample_entries(vec![AVC1, MP4A]).build();
+        let err = sanitize_with_config(test.clone(), config).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBox(METT));
+        });
+    }
+
+    #[test]
+    fn disallowed_sample_entry_track_dropped() {
+        init_logger();
+        let data = test_two_track_mp4(&mut test_moov(), test_trak().sample_entry(AVC1), vec![0, 1], vec![2, 3]);
+        let config = Config::builder()
+            .allowed_sample_entries(vec![METT])
+            .drop_disallowed_tracks(true)
+            .build();
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        let chunk_offsets = sanitized_chunk_offsets(sanitized.metadata.as_ref().unwrap());
+        let sanitized_data = sanitized_data(sanitized, &data);
+        let chunks: Vec<_> = chunk_offsets
+            .into_iter()
+            .map(|chunk_offset| sanitized_data[chunk_offset as usize])
+            .collect();
+        assert_eq!(chunks, b"ab");
+    }
+
+    #[test]
+    fn disallowed_sample_entry_every_track_dropped() {
+        init_logger();
+        let test = test_mp4().build();
+        let config = Config::builder()
+            .allowed_sample_entries(vec![AVC1])
+            .drop_disallowed_tracks(true)
+            .build();
+        let err = sanitize_with_config(test.clone(), config).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::MissingRequiredBox(TRAK));
+        });
+    }
+
+    #[test]
+    fn keep_handler_types() {
+        init_logger();
+        let mut moov = test_moov();
+        moov.first_trak(test_trak().handler_type(HdlrBox::VIDEO).clone());
+        // The second track's sample data lies outside the mdat, but is dropped along with it.
+        let data = test_two_track_mp4(&mut moov, &mut test_trak(), vec![0, 1], vec![100]);
+        let config = Config::builder().keep_handler_types(vec![HdlrBox::VIDEO]).build();
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        let chunk_offsets = sanitized_chunk_offsets(sanitized.metadata.as_ref().unwrap());
+        let sanitized_data = sanitized_data(sanitized, &data);
+        let chunks: Vec<_> = chunk_offsets
+            .into_iter()
+            .map(|chunk_offset| sanitized_data[chunk_offset as usize])
+            .collect();
+        assert_eq!(chunks, b"ab");
+    }
+
+    #[test]
+    fn keep_handler_types_media_info() {
+        init_logger();
+        let mut moov = test_moov();
+        moov.first_trak(test_trak().handler_type(HdlrBox::VIDEO).clone());
+        let data = test_two_track_mp4(&mut moov, &mut test_trak(), vec![0, 1], vec![2]);
+        let config = Config::builder().keep_handler_types(vec![HdlrBox::VIDEO]).build();
+        let sanitized = sanitize_with_config(io::Cursor::new(&data), config).unwrap();
+        let tracks = sanitized.media_info.unwrap().tracks;
+        assert_eq!(tracks.len(), 1);
+        assert_eq!((tracks[0].track_id, tracks[0].handler_type), (1, HdlrBox::VIDEO));
+    }
+
+    #[test]
+    fn keep_handler_types_every_track_dropped() {
+        init_logger();
+        let test = test_mp4().build();
+        let config = Config::builder()
+            .keep_handler_types(vec![HdlrBox::VIDEO, HdlrBox::SOUND])
+            .build();
+        let err = sanitize_with_config(test, config).unwrap_err();
+        assert_matches!(err, Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::MissingRequiredBox(TRAK));
+        });
+    }
+}
@@ -1,4 +1,4 @@
 #[cfg(test)]
 pub mod test;
 
-pub use mediasan_common::util::{checked_add_signed, CheckedAddSigned, IoResultExt};
+pub use mediasan_common::util::IoResultExt;
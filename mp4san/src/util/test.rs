@@ -1,42 +1,190 @@
 pub mod ftyp;
 pub mod moov;
 pub mod mp4;
+pub mod nal;
+pub mod quicktime;
+pub mod sparse;
+pub mod trak;
 
 use std::iter;
 
+use assert_matches::assert_matches;
 use bytes::{BufMut, BytesMut};
 
-use crate::parse::box_type::{DINF, DREF, HDLR, MDAT, MDHD, MECO, META, METT, MVHD, STSC, STSD, STSZ, STTS, TKHD, URL};
-use crate::parse::{fourcc, AnyMp4Box, BoxHeader, BoxType, BoxUuid, FourCC, FullBoxHeader, Mp4Box, Mp4Value};
-use crate::{InputSpan, SanitizedMetadata};
-
-pub const TEST_UUID: BoxType = BoxType::Uuid(BoxUuid { value: *b"thisisatestuuid!" });
+use crate::parse::box_type::{
+    DINF, DREF, ESDS, HDLR, LOCI, MDAT, MDHD, MECO, META, MP4A, MVEX, MVHD, S263, SAWB, STSC, STSD, STSZ, STTS, TITL,
+    TKHD, TREX, UDTA, URL,
+};
+use crate::parse::{
+    fourcc, AnyMp4Box, AudioSampleEntry, BoxHeader, BoxType, BoxUuid, D263Box, DamrBox, EncaBox, EncvBox, FourCC,
+    FrmaBox, FullBoxHeader, Mp4Box, Mp4Value, ParseError, PsshBox, SchiBox, SchmBox, SinfBox, TencBox,
+    VisualSampleEntry,
+};
+use crate::parse::{
+    EdtsBox, ElstBox, ElstEntry, MfhdBox, MoofBox, MoovBox, StblCoMut, TfdtBox, TfhdBox, TrafBox, TrunBox,
+};
+use crate::{Config, Error, InputSpan, SanitizedMetadata};
+
+pub const TEST_BOX_UUID: BoxUuid = BoxUuid { value: *b"thisisatestuuid!" };
+pub const TEST_UUID: BoxType = BoxType::Uuid(TEST_BOX_UUID);
 pub const MP42: FourCC = FourCC { value: *b"mp42" };
 pub const MP41: FourCC = FourCC { value: *b"mp41" };
 pub const ISOM: FourCC = FourCC { value: *b"isom" };
+pub const TEST_KID: [u8; 16] = *b"thisisatestkeyid";
+pub const QT: FourCC = FourCC { value: *b"qt  " };
+pub const THREE_GP4: FourCC = FourCC { value: *b"3gp4" };
+pub const THREE_GP6: FourCC = FourCC { value: *b"3gp6" };
 
 pub use ftyp::TestFtypBuilder;
 pub use moov::TestMoovBuilder;
 pub use mp4::TestMp4Builder;
+pub use quicktime::TestQuickTimeSoundBuilder;
+pub use trak::TestTrakBuilder;
 
 pub use mediasan_common_test::init_logger;
 
+/// Assert that the sanitizer failed because a limit in its [`Config`](crate::Config) was exceeded.
+pub fn assert_limit_exceeded(result: Result<SanitizedMetadata, Error>) {
+    assert_matches!(result.unwrap_err(), Error::Parse(err) => {
+        assert_matches!(err.get_ref(), ParseError::InvalidInput);
+        assert!(format!("{err:?}").contains("limit exceeded"), "{err:?}");
+    });
+}
+
 pub fn sanitized_data(sanitized: SanitizedMetadata, data: &[u8]) -> Vec<u8> {
-    match sanitized.metadata {
+    match &sanitized.metadata {
         Some(metadata) => {
-            let mdat = &data[sanitized.data.offset as usize..][..sanitized.data.len as usize];
-            [&metadata[..], mdat].concat()
+            let mut sanitized_data = metadata.clone();
+            for span in sanitized.data_spans() {
+                sanitized_data.extend_from_slice(&data[span.offset as usize..][..span.len as usize]);
+            }
+            sanitized_data
         }
         None => data.to_vec(),
     }
 }
 
-pub fn test_dinf() -> AnyMp4Box {
+/// Read the chunk offsets of all tracks from sanitized `metadata` consisting of an ftyp followed by a moov.
+pub fn sanitized_chunk_offsets(metadata: &[u8]) -> Vec<u64> {
+    let mut metadata = BytesMut::from(metadata);
+    AnyMp4Box::parse(&mut metadata).unwrap();
+    let mut moov = Mp4Box::<MoovBox>::parse(&mut metadata).unwrap();
+    let mut chunk_offsets = vec![];
+    for trak in moov.data.parse().unwrap().traks() {
+        match trak.unwrap().co_mut().unwrap() {
+            StblCoMut::Stco(stco) => {
+                chunk_offsets.extend(stco.entries_mut().map(|entry| u64::from(entry.get().unwrap())));
+            }
+            StblCoMut::Co64(co64) => chunk_offsets.extend(co64.entries_mut().map(|entry| entry.get().unwrap())),
+        }
+    }
+    chunk_offsets
+}
+
+pub fn test_3gpp_asset(asset_type: BoxType, asset_data: &[u8]) -> AnyMp4Box {
+    let mut data = BytesMut::new();
+    FullBoxHeader::default().put_buf(&mut data);
+    data.put_slice(asset_data);
+    Mp4Box::with_bytes(asset_type, data)
+}
+
+/// A user data box (`udta`) containing a 3GPP title (`titl`) and location (`loci`) asset.
+pub fn test_3gpp_udta() -> AnyMp4Box {
+    let mut titl = vec![];
+    titl.put_u16(0x15c7); // language
+    titl.put_slice(b"title\0");
+    let mut loci = titl.clone();
+    loci.put_u8(0); // role
+    loci.put_u32(0xff87_3cf8); // longitude
+    loci.put_u32(0x0025_bf6d); // latitude
+    loci.put_u32(0); // altitude
+    loci.put_slice(b"earth\0"); // astronomical body
+    loci.put_u8(0); // additional notes
     let mut data = BytesMut::new();
-    write_test_dinf_data(&mut data);
+    test_3gpp_asset(TITL, &titl).put_buf(&mut data);
+    test_3gpp_asset(LOCI, &loci).put_buf(&mut data);
+    Mp4Box::with_bytes(UDTA, data)
+}
+
+/// An AMR (`samr`) or AMR-WB (`sawb`) audio sample entry.
+pub fn test_amr_sample_entry(sample_entry_type: BoxType) -> AnyMp4Box {
+    let (mode_set, samplerate) = if sample_entry_type == SAWB {
+        (0x83ff, 16000)
+    } else {
+        (0x81ff, 8000)
+    };
+    let mut data = BytesMut::new();
+    AudioSampleEntry::new(1, samplerate).put_buf(&mut data);
+    Mp4Box::with_data(DamrBox::new(mode_set, 1).into())
+        .unwrap()
+        .put_buf(&mut data);
+    Mp4Box::with_bytes(sample_entry_type, data)
+}
+
+pub fn test_dinf(external: bool) -> AnyMp4Box {
+    let mut data = BytesMut::new();
+    write_test_dinf_data(&mut data, external);
     Mp4Box::with_bytes(DINF, data)
 }
 
+/// An mp4 with a single track whose two chunks lie in two mdat boxes, one on either side of the moov.
+pub fn test_discontiguous_mdat_mp4() -> (Vec<u8>, [InputSpan; 2]) {
+    let mut data = vec![];
+    test_ftyp().build().put_buf(&mut data);
+    let first_mdat = write_test_mdat(&mut data, b"abc");
+    let moov_len = test_moov().co_entries(vec![0; 2]).build().encoded_len();
+    let mdat_header_len = BoxHeader::with_u32_data_size(MDAT, 0).encoded_len();
+    let second_mdat_offset = data.len() as u64 + moov_len;
+    let co_entries = vec![
+        first_mdat.offset + mdat_header_len,
+        second_mdat_offset + mdat_header_len,
+    ];
+    test_moov().co_entries(co_entries).build().put_buf(&mut data);
+    let second_mdat = write_test_mdat(&mut data, b"defg");
+    assert_eq!(second_mdat.offset, second_mdat_offset);
+    (data, [first_mdat, second_mdat])
+}
+
+/// A [`Config`] allowing fragmented mp4s.
+pub fn fragmented_config() -> Config {
+    Config::builder().allow_fragmented(true).build()
+}
+
+/// A fragmented mp4 with two movie fragments, the first of whose track run data offset is displaced by
+/// `data_offset_displacement`.
+///
+/// Returns the span of the movie fragments.
+pub fn test_fragmented_mp4(data_offset_displacement: i32) -> (Vec<u8>, InputSpan) {
+    let mut data = vec![];
+    test_ftyp().build().put_buf(&mut data);
+    test_moov().mvex(true).build().put_buf(&mut data);
+    let first_fragment = write_test_fragment(&mut data, 1, b"abcdefg", data_offset_displacement);
+    let second_fragment = write_test_fragment(&mut data, 1, b"hijk", 0);
+    let fragments = InputSpan { offset: first_fragment.offset, len: first_fragment.len + second_fragment.len };
+    (data, fragments)
+}
+
+pub fn test_edts(entries: Vec<ElstEntry>) -> AnyMp4Box {
+    let elst = Mp4Box::with_data(ElstBox::with_entries(entries).into()).unwrap();
+    Mp4Box::with_data(EdtsBox::with_children(vec![elst.into()]).into())
+        .unwrap()
+        .into()
+}
+
+/// An encrypted audio sample entry (`enca`) protecting `mp4a` with the `cenc` scheme.
+pub fn test_enca(per_sample_iv_size: u8) -> AnyMp4Box {
+    let sinf = test_sinf(fourcc::MP4A, SchmBox::CENC, TencBox::new(per_sample_iv_size, TEST_KID));
+    let enca = EncaBox::with_children(AudioSampleEntry::new(2, 44100), vec![sinf]);
+    Mp4Box::with_data(enca.into()).unwrap().into()
+}
+
+/// An encrypted visual sample entry (`encv`) protecting `avc1` with the `cenc` scheme.
+pub fn test_encv(per_sample_iv_size: u8) -> AnyMp4Box {
+    let sinf = test_sinf(fourcc::AVC1, SchmBox::CENC, TencBox::new(per_sample_iv_size, TEST_KID));
+    let encv = EncvBox::with_children(VisualSampleEntry::new(320, 240), vec![sinf]);
+    Mp4Box::with_data(encv.into()).unwrap().into()
+}
+
 pub fn test_free(name: BoxType, len: u32) -> AnyMp4Box {
     let header_size = BoxHeader::with_u32_data_size(name, 0).encoded_len() as u32;
     let data = iter::repeat(0).take((len - header_size) as usize).collect();
@@ -53,9 +201,9 @@ pub fn test_hdlr(handler_type: FourCC) -> AnyMp4Box {
     Mp4Box::with_bytes(HDLR, data)
 }
 
-pub fn test_mdhd() -> AnyMp4Box {
+pub fn test_mdhd(duration: u32) -> AnyMp4Box {
     let mut data = BytesMut::new();
-    write_test_mdhd_data(&mut data);
+    write_test_mdhd_data(&mut data, duration);
     Mp4Box::with_bytes(MDHD, data)
 }
 
@@ -79,21 +227,67 @@ pub fn test_mp4() -> TestMp4Builder {
     Default::default()
 }
 
+pub fn test_mp4a(audio_specific_config: &[u8]) -> AnyMp4Box {
+    let mut data = BytesMut::new();
+    AudioSampleEntry::new(2, 44100).put_buf(&mut data);
+    let mut esds = BytesMut::new();
+    write_test_esds_data(&mut esds, audio_specific_config);
+    Mp4Box::with_bytes(ESDS, esds).put_buf(&mut data);
+    Mp4Box::with_bytes(MP4A, data)
+}
+
+pub fn test_mvex(track_ids: &[u32]) -> AnyMp4Box {
+    let mut data = BytesMut::new();
+    write_test_mvex_data(&mut data, track_ids);
+    Mp4Box::with_bytes(MVEX, data)
+}
+
 pub fn test_mvhd() -> AnyMp4Box {
     let mut data = BytesMut::new();
     write_test_mvhd_data(&mut data);
     Mp4Box::with_bytes(MVHD, data)
 }
 
+pub fn test_pssh() -> AnyMp4Box {
+    let pssh = PsshBox::new(*b"testsystemid0123", [TEST_KID], b"pssh data");
+    Mp4Box::with_data(pssh.into()).unwrap().into()
+}
+
+pub fn test_s263(h263_level: u8, h263_profile: u8) -> AnyMp4Box {
+    let mut data = BytesMut::new();
+    VisualSampleEntry::new(176, 144).put_buf(&mut data);
+    Mp4Box::with_data(D263Box::new(h263_level, h263_profile).into())
+        .unwrap()
+        .put_buf(&mut data);
+    Mp4Box::with_bytes(S263, data)
+}
+
+pub fn test_sinf(data_format: FourCC, scheme_type: FourCC, tenc: TencBox) -> AnyMp4Box {
+    let frma = Mp4Box::with_data(FrmaBox::new(data_format).into()).unwrap();
+    let schm = Mp4Box::with_data(SchmBox::new(scheme_type, 0x10000).into()).unwrap();
+    let tenc = Mp4Box::with_data(tenc.into()).unwrap();
+    let schi = Mp4Box::with_data(SchiBox::with_children(vec![tenc.into()]).into()).unwrap();
+    let sinf = SinfBox::with_children(vec![frma.into(), schm.into(), schi.into()]);
+    Mp4Box::with_data(sinf.into()).unwrap().into()
+}
+
 pub fn test_stsc() -> AnyMp4Box {
     let mut data = BytesMut::new();
     write_test_stsc_data(&mut data);
     Mp4Box::with_bytes(STSC, data)
 }
 
-pub fn test_stsd() -> AnyMp4Box {
+pub fn test_stsd(sample_entry_type: BoxType) -> AnyMp4Box {
+    let mut data = BytesMut::new();
+    write_test_stsd_data(&mut data, sample_entry_type);
+    Mp4Box::with_bytes(STSD, data)
+}
+
+pub fn test_stsd_with_entry(sample_entry: &AnyMp4Box) -> AnyMp4Box {
     let mut data = BytesMut::new();
-    write_test_stsd_data(&mut data);
+    FullBoxHeader::default().put_buf(&mut data);
+    data.put_u32(1); // entry count
+    sample_entry.put_buf(&mut data);
     Mp4Box::with_bytes(STSD, data)
 }
 
@@ -115,6 +309,37 @@ pub fn test_tkhd(track_id: u32) -> AnyMp4Box {
     Mp4Box::with_bytes(TKHD, data)
 }
 
+pub fn test_trak() -> TestTrakBuilder {
+    Default::default()
+}
+
+/// An mp4 with two tracks, described by `moov` and `second_trak`, whose chunk offsets are relative to the start of the
+/// data of a single mdat containing `abcd`.
+pub fn test_two_track_mp4(
+    moov: &mut TestMoovBuilder,
+    second_trak: &mut TestTrakBuilder,
+    co_entries: Vec<u64>,
+    second_trak_co_entries: Vec<u64>,
+) -> Vec<u8> {
+    let mut data = vec![];
+    test_ftyp().build().put_buf(&mut data);
+    let mdat = write_test_mdat(&mut data, b"abcd");
+    let mdat_data_offset = mdat.offset + BoxHeader::with_u32_data_size(MDAT, 0).encoded_len();
+    let offset_entries =
+        |entries: Vec<u64>| -> Vec<u64> { entries.into_iter().map(|entry| mdat_data_offset + entry).collect() };
+    moov.co_entries(offset_entries(co_entries))
+        .add_trak(second_trak.co_entries(offset_entries(second_trak_co_entries)).clone())
+        .build()
+        .put_buf(&mut data);
+    data
+}
+
+pub fn test_udta() -> AnyMp4Box {
+    let mut data = BytesMut::new();
+    write_test_udta_data(&mut data);
+    Mp4Box::with_bytes(UDTA, data)
+}
+
 pub fn write_hdlr_data<B: BufMut>(mut out: B, handler_type: FourCC) {
     FullBoxHeader::default().put_buf(&mut out);
     out.put_u32(0); // pre-defined
@@ -135,12 +360,71 @@ pub fn write_mdat_header(out: &mut Vec<u8>, data_len: Option<u64>) -> InputSpan
     InputSpan { offset, len: out.len() as u64 - offset }
 }
 
-pub fn write_test_dinf_data<B: BufMut>(mut out: B) {
-    BoxHeader::with_u32_data_size(DREF, 20).put_buf(&mut out); // dref header
+pub fn write_test_dinf_data<B: BufMut>(mut out: B, external: bool) {
+    const EXTERNAL_URL: &[u8] = b"https://example.com/\0";
+    let url_data_len = if external { 4 + EXTERNAL_URL.len() as u32 } else { 4 };
+    BoxHeader::with_u32_data_size(DREF, 16 + url_data_len).put_buf(&mut out); // dref header
     FullBoxHeader::default().put_buf(&mut out);
     out.put_u32(1); // entry count
-    BoxHeader::with_u32_data_size(URL, 4).put_buf(&mut out); // url header
-    FullBoxHeader { version: 0, flags: 1 }.put_buf(&mut out);
+    BoxHeader::with_u32_data_size(URL, url_data_len).put_buf(&mut out); // url header
+    if external {
+        FullBoxHeader { version: 0, flags: 0 }.put_buf(&mut out);
+        out.put_slice(EXTERNAL_URL);
+    } else {
+        FullBoxHeader { version: 0, flags: 1 }.put_buf(&mut out);
+    }
+}
+
+/// Write an esds box containing `audio_specific_config`, with descriptor sizes padded to four bytes as many muxers do.
+pub fn write_test_esds_data<B: BufMut>(mut out: B, audio_specific_config: &[u8]) {
+    fn put_descriptor_header<B: BufMut>(mut out: B, tag: u8, size: usize) {
+        out.put_u8(tag);
+        out.put_slice(&[0x80, 0x80, 0x80, size as u8]);
+    }
+    let decoder_config_len = 13 + 5 + audio_specific_config.len();
+    FullBoxHeader::default().put_buf(&mut out);
+    put_descriptor_header(&mut out, 0x03, 3 + 5 + decoder_config_len + 5 + 1); // ES_Descriptor
+    out.put_u16(1); // ES_ID
+    out.put_u8(0); // flags
+    put_descriptor_header(&mut out, 0x04, decoder_config_len); // DecoderConfigDescriptor
+    out.put_u8(0x40); // objectTypeIndication
+    out.put_u8(0x15); // streamType, upStream, reserved
+    out.put_slice(&[0, 0, 0]); // bufferSizeDB
+    out.put_u32(128000); // maxBitrate
+    out.put_u32(128000); // avgBitrate
+    put_descriptor_header(&mut out, 0x05, audio_specific_config.len()); // DecoderSpecificInfo
+    out.put_slice(audio_specific_config);
+    put_descriptor_header(&mut out, 0x06, 1); // SLConfigDescriptor
+    out.put_u8(2); // predefined
+}
+
+/// Write a movie fragment for `track_id` followed by an mdat containing `data` as a single sample.
+///
+/// The sample's data offset is displaced from its correct value by `data_offset_displacement`.
+pub fn write_test_fragment(out: &mut Vec<u8>, track_id: u32, data: &[u8], data_offset_displacement: i32) -> InputSpan {
+    let offset = out.len() as u64;
+    let moof = |data_offset| {
+        let mut tfhd = TfhdBox::new(track_id);
+        tfhd.set_default_base_is_moof(true);
+        let trun = TrunBox::with_sample_sizes(Some(data_offset), [data.len() as u32]);
+        let traf: Vec<AnyMp4Box> = vec![
+            Mp4Box::with_data(tfhd.into()).unwrap().into(),
+            Mp4Box::with_data(TfdtBox::new(0).into()).unwrap().into(),
+            Mp4Box::with_data(trun.into()).unwrap().into(),
+        ];
+        let moof: Vec<AnyMp4Box> = vec![
+            Mp4Box::with_data(MfhdBox::default().into()).unwrap().into(),
+            Mp4Box::with_data(TrafBox::with_children(traf).into()).unwrap().into(),
+        ];
+        Mp4Box::with_data(MoofBox::with_children(moof).into()).unwrap()
+    };
+    let mdat_header_len = BoxHeader::with_data_size(MDAT, data.len() as u64)
+        .unwrap()
+        .encoded_len();
+    let data_offset = moof(0).encoded_len() + mdat_header_len;
+    moof(data_offset as i32 + data_offset_displacement).put_buf(&mut *out);
+    let mdat = write_test_mdat(out, data);
+    InputSpan { offset, len: mdat.offset + mdat.len - offset }
 }
 
 pub fn write_test_mdat(out: &mut Vec<u8>, data: &[u8]) -> InputSpan {
@@ -159,16 +443,28 @@ pub fn write_test_meta_data<B: BufMut>(mut out: B) {
     test_hdlr(fourcc::META).put_buf(&mut out);
 }
 
-pub fn write_test_mdhd_data<B: BufMut>(mut out: B) {
+pub fn write_test_mdhd_data<B: BufMut>(mut out: B, duration: u32) {
     FullBoxHeader::default().put_buf(&mut out);
     out.put_u32(0); // creation time
     out.put_u32(0); // modification time
     out.put_u32(1); // timescale
-    out.put_u32(0); // duration
+    out.put_u32(duration); // duration
     out.put_u16(u16::from_be_bytes(*b"US")); // language
     out.put_u16(0); // pre-defined
 }
 
+pub fn write_test_mvex_data<B: BufMut>(mut out: B, track_ids: &[u32]) {
+    for &track_id in track_ids {
+        BoxHeader::with_u32_data_size(TREX, 24).put_buf(&mut out); // trex header
+        FullBoxHeader::default().put_buf(&mut out);
+        out.put_u32(track_id); // track id
+        out.put_u32(1); // default sample description index
+        out.put_u32(1); // default sample duration
+        out.put_u32(0); // default sample size
+        out.put_u32(0); // default sample flags
+    }
+}
+
 pub fn write_test_mvhd_data<B: BufMut>(mut out: B) {
     FullBoxHeader::default().put_buf(&mut out);
     out.put_u32(0); // creation time
@@ -190,6 +486,20 @@ pub fn write_test_mvhd_data<B: BufMut>(mut out: B) {
     out.put_u32(u32::MAX); // next track id
 }
 
+pub fn write_test_senc_data<B: BufMut>(mut out: B, sample_count: u32, per_sample_iv_size: u8, subsamples: bool) {
+    let flags = if subsamples { 0x2 } else { 0 };
+    FullBoxHeader { version: 0, flags }.put_buf(&mut out);
+    out.put_u32(sample_count);
+    for sample in 0..sample_count {
+        out.put_bytes(sample as u8, per_sample_iv_size.into()); // initialization vector
+        if subsamples {
+            out.put_u16(1); // subsample count
+            out.put_u16(1); // bytes of clear data
+            out.put_u32(0); // bytes of protected data
+        }
+    }
+}
+
 pub fn write_test_stsc_data<B: BufMut>(mut out: B) {
     FullBoxHeader::default().put_buf(&mut out);
     out.put_u32(1); // entry count
@@ -198,10 +508,10 @@ pub fn write_test_stsc_data<B: BufMut>(mut out: B) {
     out.put_u32(1); // sample description index
 }
 
-pub fn write_test_stsd_data<B: BufMut>(mut out: B) {
+pub fn write_test_stsd_data<B: BufMut>(mut out: B, sample_entry_type: BoxType) {
     FullBoxHeader::default().put_buf(&mut out);
     out.put_u32(1); // entry count
-    BoxHeader::with_u32_data_size(METT, 9).put_buf(&mut out); // mett header
+    BoxHeader::with_u32_data_size(sample_entry_type, 9).put_buf(&mut out); // sample entry header
     for _ in 0..6 {
         out.put_u8(0); // reserved
     }
@@ -243,6 +553,15 @@ pub fn write_test_tkhd_data<B: BufMut>(mut out: B, track_id: u32) {
     out.put_u32(0); // height
 }
 
+pub fn write_test_udta_data<B: BufMut>(mut out: B) {
+    const XYZ: &[u8] = b"+37.7749-122.4194/";
+    BoxHeader::with_u32_data_size(BoxType::FourCC(FourCC { value: *b"\xa9xyz" }), 4 + XYZ.len() as u32)
+        .put_buf(&mut out);
+    out.put_u16(XYZ.len() as u16); // string length
+    out.put_u16(0x15c7); // language code
+    out.put_slice(XYZ);
+}
+
 pub fn write_test_uuid(out: &mut Vec<u8>) {
     BoxHeader::with_u32_data_size(TEST_UUID, 0).put_buf(out);
 }
@@ -1,8 +1,10 @@
+use bytes::BytesMut;
 use derive_builder::Builder;
 
-use crate::parse::{fourcc, Co64Box, MdiaBox, MinfBox, MoovBox, Mp4Box, StblBox, StcoBox, TrakBox};
+use crate::parse::box_type::CMOV;
+use crate::parse::{AnyMp4Box, MoovBox, Mp4Box};
 
-use super::{test_dinf, test_hdlr, test_mdhd, test_mvhd, test_stsc, test_stsd, test_stsz, test_stts, test_tkhd};
+use super::{test_meta, test_mvex, test_mvhd, test_udta, TestTrakBuilder};
 
 #[derive(Builder)]
 #[builder(name = "TestMoovBuilder", build_fn(name = "build_spec"))]
@@ -20,6 +22,12 @@ pub struct TestMoovSpec {
     #[builder(default = "true")]
     pub stbl: bool,
 
+    #[builder(default = "true")]
+    pub dinf: bool,
+
+    #[builder(default)]
+    pub external_data_reference: bool,
+
     #[builder(default = "true")]
     pub minf: bool,
 
@@ -28,41 +36,63 @@ pub struct TestMoovSpec {
 
     #[builder(default = "true")]
     pub trak: bool,
+
+    /// The first track, whose chunk offsets are replaced by `co_entries`.
+    #[builder(default)]
+    pub first_trak: TestTrakBuilder,
+
+    /// Additional tracks following the first.
+    #[builder(default, setter(into, each(name = "add_trak")))]
+    pub traks: Vec<TestTrakBuilder>,
+
+    #[builder(default)]
+    pub mvex: bool,
+
+    #[builder(default)]
+    pub cmov: bool,
+
+    #[builder(default)]
+    pub user_metadata: bool,
+
+    /// Additional boxes for the movie.
+    #[builder(default, setter(into, each(name = "add_moov_box")))]
+    pub moov_boxes: Vec<AnyMp4Box>,
+
+    /// A user data box (`udta`) for the movie.
+    #[builder(default, setter(strip_option))]
+    pub udta: Option<AnyMp4Box>,
 }
 
 impl TestMoovBuilder {
     pub fn build(&self) -> Mp4Box<MoovBox> {
         let spec = self.build_spec().unwrap();
-        let chunk_count = spec.co_entries.len() as u32;
 
-        let mut stbl = vec![test_stsd(), test_stts(chunk_count), test_stsc(), test_stsz(chunk_count)];
-        if spec.co64 {
-            let entries = spec.co_entries.iter().cloned();
-            stbl.push(Mp4Box::with_data(Co64Box::from_iter(entries).into()).unwrap().into());
+        let mut moov = vec![test_mvhd()];
+        if spec.trak {
+            let first_trak = spec
+                .first_trak
+                .clone()
+                .co_entries(spec.co_entries.clone())
+                .build(1, &spec);
+            moov.push(first_trak);
         }
-        if spec.stco {
-            let entries = spec.co_entries.into_iter().map(|entry| entry as u32);
-            stbl.push(Mp4Box::with_data(StcoBox::from_iter(entries).into()).unwrap().into());
+        for (trak, track_id) in spec.traks.iter().zip(2..) {
+            moov.push(trak.build(track_id, &spec));
         }
-
-        let mut minf = vec![test_dinf()];
-        if spec.stbl {
-            minf.push(Mp4Box::with_data(StblBox::with_children(stbl).into()).unwrap().into());
+        if spec.mvex {
+            let track_ids: Vec<u32> = (1..).take(1 + spec.traks.len()).collect();
+            moov.push(test_mvex(&track_ids));
         }
-
-        let mut mdia = vec![test_mdhd(), test_hdlr(fourcc::META)];
-        if spec.minf {
-            mdia.push(Mp4Box::with_data(MinfBox::with_children(minf).into()).unwrap().into());
+        if spec.user_metadata {
+            moov.push(test_udta());
+            moov.push(test_meta());
         }
-
-        let mut trak = vec![test_tkhd(1)];
-        if spec.mdia {
-            trak.push(Mp4Box::with_data(MdiaBox::with_children(mdia).into()).unwrap().into());
+        if let Some(udta) = &spec.udta {
+            moov.push(udta.clone());
         }
-
-        let mut moov = vec![test_mvhd()];
-        if spec.trak {
-            moov.push(Mp4Box::with_data(TrakBox::with_children(trak).into()).unwrap().into());
+        moov.extend(spec.moov_boxes.iter().cloned());
+        if spec.cmov {
+            moov.push(Mp4Box::with_bytes(CMOV, BytesMut::new()));
         }
         Mp4Box::with_data(MoovBox::with_children(moov).into()).unwrap()
     }
@@ -6,7 +6,7 @@ use bytes::{Buf, Bytes};
 use derive_builder::Builder;
 use mp4san_test::{ffmpeg_assert_eq, gpac_assert_eq};
 
-use crate::parse::box_type::{FREE, FTYP, MDAT, MECO, META, MOOV, SKIP};
+use crate::parse::box_type::{FREE, FTYP, MDAT, MECO, META, MOOV, SKIP, WIDE};
 use crate::parse::{BoxType, Mp4Value};
 use crate::{sanitize, sanitize_with_config, Config, InputSpan, SanitizedMetadata, Skip};
 
@@ -93,9 +93,10 @@ impl TestMp4Spec {
                         None => mdat = Some(InputSpan { len: mdat_len, ..written_mdat }),
                     }
                 }
-                name @ (FREE | META | MECO | SKIP) => {
+                name @ (FREE | META | MECO | SKIP | WIDE) => {
                     let mp4_box = match name {
                         FREE | SKIP => test_free(name, 13),
+                        WIDE => test_free(name, 8),
                         META => test_meta(),
                         MECO => test_meco(),
                         _ => unreachable!(),
@@ -173,13 +174,15 @@ impl TestMp4 {
     }
 
     pub fn sanitize_ok_with_config(&self, config: Config) -> SanitizedMetadata {
-        let sanitized = sanitize_with_config(self.clone(), config).unwrap();
+        let sanitized = sanitize_with_config(self.clone(), config.clone()).unwrap();
         assert_eq!(sanitized.data, self.mdat);
         assert_matches!(sanitized.metadata.as_deref(), Some(metadata) => {
             assert_eq!(metadata, self.expected_metadata(metadata.len()));
         });
         let sanitized_data = sanitized_data(sanitized.clone(), &self.data);
-        sanitize(io::Cursor::new(&sanitized_data)).unwrap();
+        sanitize_with_config(io::Cursor::new(&sanitized_data), config.clone()).unwrap();
+        let verify_config = Config { verify_output: true, ..config };
+        assert_eq!(sanitize_with_config(self.clone(), verify_config).unwrap(), sanitized);
         ffmpeg_assert_eq(&sanitized_data, &self.mdat_data);
         gpac_assert_eq(&sanitized_data, &self.mdat_data);
         sanitized
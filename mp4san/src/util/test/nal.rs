@@ -0,0 +1,200 @@
+use bitstream_io::{BigEndian, BitWrite, BitWriter};
+use bytes::{BufMut, BytesMut};
+
+use crate::parse::box_type::{AVC1, AVCC, HVC1, HVCC};
+use crate::parse::{AnyMp4Box, BoxHeader, BoxType, Mp4Box, Mp4Value, VisualSampleEntry};
+
+/// A writer of test NAL units, supporting Exp-Golomb codes.
+pub struct TestNalUnitWriter {
+    writer: BitWriter<Vec<u8>, BigEndian>,
+}
+
+impl TestNalUnitWriter {
+    pub fn new() -> Self {
+        Self { writer: BitWriter::new(Vec::new()) }
+    }
+
+    pub fn bits(&mut self, bits: u32, value: u32) -> &mut Self {
+        self.writer.write(bits, value).unwrap();
+        self
+    }
+
+    pub fn flag(&mut self, value: bool) -> &mut Self {
+        self.writer.write_bit(value).unwrap();
+        self
+    }
+
+    pub fn ue(&mut self, value: u32) -> &mut Self {
+        let code = u64::from(value) + 1;
+        let code_len = u64::BITS - code.leading_zeros();
+        self.writer.write(code_len - 1, 0u64).unwrap();
+        self.writer.write(code_len, code).unwrap();
+        self
+    }
+
+    pub fn se(&mut self, value: i32) -> &mut Self {
+        let code = if value > 0 {
+            2 * value as u32 - 1
+        } else {
+            2 * value.unsigned_abs()
+        };
+        self.ue(code)
+    }
+
+    /// Writes the RBSP trailing bits, returning the NAL unit with emulation prevention bytes inserted.
+    pub fn finish(&mut self) -> Vec<u8> {
+        self.writer.write_bit(true).unwrap();
+        self.writer.byte_align().unwrap();
+        let rbsp = std::mem::replace(&mut self.writer, BitWriter::new(Vec::new())).into_writer();
+        let mut nal_unit = Vec::with_capacity(rbsp.len());
+        let mut zero_count = 0;
+        for byte in rbsp {
+            if zero_count >= 2 && byte <= 3 {
+                nal_unit.push(3);
+                zero_count = 0;
+            }
+            zero_count = if byte == 0 { zero_count + 1 } else { 0 };
+            nal_unit.push(byte);
+        }
+        nal_unit
+    }
+}
+
+pub const TEST_AVC_PROFILE_BASELINE: u8 = 66;
+pub const TEST_AVC_PROFILE_HIGH: u8 = 100;
+
+pub fn test_avc_sps(profile_idc: u8, max_num_ref_frames: u32, width_in_mbs: u32, height_in_mbs: u32) -> Vec<u8> {
+    let mut writer = TestNalUnitWriter::new();
+    writer.bits(1, 0).bits(2, 3).bits(5, 7); // nal unit header
+    writer.bits(8, profile_idc.into()).bits(8, 0).bits(8, 31); // profile, constraint set flags, level
+    writer.ue(0); // seq_parameter_set_id
+    if profile_idc == TEST_AVC_PROFILE_HIGH {
+        writer.ue(1).ue(0).ue(0); // chroma_format_idc, bit_depth_luma_minus8, bit_depth_chroma_minus8
+        writer.flag(false).flag(false); // qpprime_y_zero_transform_bypass_flag, seq_scaling_matrix_present_flag
+    }
+    writer.ue(0); // log2_max_frame_num_minus4
+    writer.ue(0).ue(0); // pic_order_cnt_type, log2_max_pic_order_cnt_lsb_minus4
+    writer.ue(max_num_ref_frames).flag(false); // max_num_ref_frames, gaps_in_frame_num_value_allowed_flag
+    writer.ue(width_in_mbs - 1).ue(height_in_mbs - 1);
+    writer.flag(true).flag(true); // frame_mbs_only_flag, direct_8x8_inference_flag
+    writer.flag(false).flag(false); // frame_cropping_flag, vui_parameters_present_flag
+    writer.finish()
+}
+
+pub fn test_avc_pps(seq_parameter_set_id: u32) -> Vec<u8> {
+    let mut writer = TestNalUnitWriter::new();
+    writer.bits(1, 0).bits(2, 3).bits(5, 8); // nal unit header
+    writer.ue(0).ue(seq_parameter_set_id); // pic_parameter_set_id, seq_parameter_set_id
+    writer.flag(false).flag(false); // entropy_coding_mode_flag, bottom_field_pic_order_in_frame_present_flag
+    writer.ue(0); // num_slice_groups_minus1
+    writer.ue(0).ue(0); // num_ref_idx_l0_default_active_minus1, num_ref_idx_l1_default_active_minus1
+    writer.flag(false).bits(2, 0); // weighted_pred_flag, weighted_bipred_idc
+    writer.se(0).se(0).se(0); // pic_init_qp_minus26, pic_init_qs_minus26, chroma_qp_index_offset
+    writer.flag(true).flag(false).flag(false); // deblocking, constrained intra, redundant_pic_cnt present flags
+    writer.finish()
+}
+
+pub fn test_hevc_vps() -> Vec<u8> {
+    let mut writer = TestNalUnitWriter::new();
+    write_test_hevc_nal_unit_header(&mut writer, 32);
+    writer.bits(4, 0).flag(true).flag(true); // vps_video_parameter_set_id, vps_base_layer_*_flag
+    writer.bits(6, 0).bits(3, 0).flag(true); // vps_max_layers_minus1, vps_max_sub_layers_minus1, nesting flag
+    writer.bits(16, 0xffff); // vps_reserved_0xffff_16bits
+    write_test_hevc_profile_tier_level(&mut writer, 93);
+    writer.flag(true).ue(3).ue(0).ue(0); // sub-layer ordering info
+    writer.bits(6, 0).ue(0).flag(false).flag(false); // vps_max_layer_id, num_layer_sets, timing, extension
+    writer.finish()
+}
+
+pub fn test_hevc_sps(level_idc: u8, width: u32, height: u32, max_dec_pic_buffering: u32) -> Vec<u8> {
+    let mut writer = TestNalUnitWriter::new();
+    write_test_hevc_nal_unit_header(&mut writer, 33);
+    writer.bits(4, 0).bits(3, 0).flag(true); // sps_video_parameter_set_id, sps_max_sub_layers_minus1, nesting flag
+    write_test_hevc_profile_tier_level(&mut writer, level_idc);
+    writer.ue(0).ue(1); // sps_seq_parameter_set_id, chroma_format_idc
+    writer.ue(width).ue(height).flag(false); // pic_width/height_in_luma_samples, conformance_window_flag
+    writer.ue(0).ue(0).ue(4); // bit_depth_luma_minus8, bit_depth_chroma_minus8, log2_max_pic_order_cnt_lsb_minus4
+    writer.flag(true).ue(max_dec_pic_buffering - 1).ue(0).ue(0); // sub-layer ordering info
+    writer.finish()
+}
+
+pub fn test_hevc_pps(seq_parameter_set_id: u32) -> Vec<u8> {
+    let mut writer = TestNalUnitWriter::new();
+    write_test_hevc_nal_unit_header(&mut writer, 34);
+    writer.ue(0).ue(seq_parameter_set_id); // pps_pic_parameter_set_id, pps_seq_parameter_set_id
+    writer.flag(false).flag(false).bits(3, 0); // dependent slices, output flag, num_extra_slice_header_bits
+    writer.flag(false).flag(false); // sign_data_hiding_enabled_flag, cabac_init_present_flag
+    writer.ue(0).ue(0).se(0); // num_ref_idx_l0/l1_default_active_minus1, init_qp_minus26
+    writer.finish()
+}
+
+pub fn test_avc1(avcc_data: &[u8]) -> AnyMp4Box {
+    test_visual_sample_entry(AVC1, AVCC, avcc_data)
+}
+
+pub fn test_hvc1(hvcc_data: &[u8]) -> AnyMp4Box {
+    test_visual_sample_entry(HVC1, HVCC, hvcc_data)
+}
+
+pub fn test_visual_sample_entry(sample_entry_type: BoxType, config_type: BoxType, config_data: &[u8]) -> AnyMp4Box {
+    let mut data = BytesMut::new();
+    VisualSampleEntry::new(320, 240).put_buf(&mut data);
+    BoxHeader::with_u32_data_size(config_type, config_data.len() as u32).put_buf(&mut data);
+    data.put_slice(config_data);
+    Mp4Box::with_bytes(sample_entry_type, data)
+}
+
+pub fn write_test_avcc_data<B: BufMut>(mut out: B, profile_idc: u8, sps: &[&[u8]], pps: &[&[u8]]) {
+    out.put_u8(1); // configuration version
+    out.put_u8(profile_idc);
+    out.put_u8(0); // profile compatibility
+    out.put_u8(31); // level
+    out.put_u8(0xff); // length size minus one
+    out.put_u8(0xe0 | sps.len() as u8);
+    for nal_unit in sps {
+        out.put_u16(nal_unit.len() as u16);
+        out.put_slice(nal_unit);
+    }
+    out.put_u8(pps.len() as u8);
+    for nal_unit in pps {
+        out.put_u16(nal_unit.len() as u16);
+        out.put_slice(nal_unit);
+    }
+}
+
+pub fn write_test_hvcc_data<B: BufMut>(mut out: B, arrays: &[(u8, &[&[u8]])]) {
+    out.put_u8(1); // configuration version
+    out.put_u8(1); // general profile space, tier flag, profile idc
+    out.put_u32(0x6000_0000); // general profile compatibility flags
+    out.put_slice(&[0x90, 0, 0, 0, 0, 0]); // general constraint indicator flags
+    out.put_u8(93); // general level idc
+    out.put_slice(&[0xf0, 0x00, 0xfc, 0xfd, 0xf8, 0xf8, 0x00, 0x00]); // format
+    out.put_u8(0x0f); // frame rate, temporal layers, length size minus one
+    out.put_u8(arrays.len() as u8);
+    for (nal_unit_type, nal_units) in arrays {
+        out.put_u8(0x80 | nal_unit_type);
+        out.put_u16(nal_units.len() as u16);
+        for nal_unit in *nal_units {
+            out.put_u16(nal_unit.len() as u16);
+            out.put_slice(nal_unit);
+        }
+    }
+}
+
+pub fn test_hvcc_data(sps: &[u8]) -> Vec<u8> {
+    let mut data = vec![];
+    let (vps, pps) = (test_hevc_vps(), test_hevc_pps(0));
+    write_test_hvcc_data(&mut data, &[(32, &[&vps]), (33, &[sps]), (34, &[&pps])]);
+    data
+}
+
+fn write_test_hevc_nal_unit_header(writer: &mut TestNalUnitWriter, nal_unit_type: u32) {
+    writer.flag(false).bits(6, nal_unit_type).bits(6, 0).bits(3, 1);
+}
+
+fn write_test_hevc_profile_tier_level(writer: &mut TestNalUnitWriter, level_idc: u8) {
+    writer.bits(2, 0).flag(false).bits(5, 1); // general_profile_space, general_tier_flag, general_profile_idc
+    writer.bits(32, 0x6000_0000); // general_profile_compatibility_flag
+    writer.bits(4, 0b1001).bits(32, 0).bits(12, 0); // general source and constraint flags
+    writer.bits(8, level_idc.into());
+}
@@ -0,0 +1,114 @@
+use bytes::BytesMut;
+use derive_builder::Builder;
+
+use crate::parse::{
+    AnyMp4Box, AudioSampleEntry, BoxType, FourCC, HdlrBox, MdiaBox, MinfBox, MoovBox, Mp4Box, Mp4Value,
+    QuickTimeSoundV1Fields, StblBox, StcoBox, StscBox, StscEntry, StszBox, TrakBox,
+};
+use crate::InputSpan;
+
+use super::{
+    test_dinf, test_hdlr, test_mdhd, test_mvhd, test_stsd_with_entry, test_stts, test_tkhd, write_mdat_header,
+    TestFtypBuilder, QT,
+};
+
+/// The type of the IMA 4:1 ADPCM sound sample entry, as used by QuickTime.
+pub const IMA4: BoxType = BoxType::FourCC(FourCC { value: *b"ima4" });
+
+/// The additional sound sample description fields of IMA 4:1 ADPCM, which stores 64 frames in 34 byte packets.
+pub const IMA4_FIELDS: QuickTimeSoundV1Fields =
+    QuickTimeSoundV1Fields { samples_per_packet: 64, bytes_per_packet: 34, bytes_per_frame: 34, bytes_per_sample: 2 };
+
+/// A QuickTime movie with a single sound track, described by a version 1 sound sample description, whose media data
+/// is a single chunk of uncompressed frames counted with a constant sample size of 1.
+#[derive(Builder)]
+#[builder(name = "TestQuickTimeSoundBuilder", build_fn(name = "build_spec"))]
+pub struct TestQuickTimeSoundSpec {
+    /// The additional fields of the version 1 sound sample description.
+    #[builder(default = "IMA4_FIELDS")]
+    fields: QuickTimeSoundV1Fields,
+
+    /// The number of frames in the chunk.
+    #[builder(default = "640")]
+    sample_count: u32,
+
+    /// The length of the media data, which defaults to the length of the chunk.
+    #[builder(default, setter(strip_option))]
+    mdat_data_len: Option<usize>,
+}
+
+pub struct TestQuickTimeSound {
+    pub data: Vec<u8>,
+    pub mdat: InputSpan,
+}
+
+impl TestQuickTimeSoundBuilder {
+    pub fn build(&self) -> TestQuickTimeSound {
+        self.build_spec().unwrap().build()
+    }
+}
+
+impl TestQuickTimeSoundSpec {
+    fn build(&self) -> TestQuickTimeSound {
+        let mdat_data_len = self.mdat_data_len.unwrap_or_else(|| {
+            let chunk_len = self.fields.samples_len(self.sample_count).unwrap();
+            chunk_len.try_into().unwrap()
+        });
+
+        let mut data = vec![];
+        TestFtypBuilder::default()
+            .major_brand(QT)
+            .compatible_brands(vec![QT])
+            .build()
+            .put_buf(&mut data);
+        let mdat_header = write_mdat_header(&mut data, Some(mdat_data_len as u64));
+        data.resize(data.len() + mdat_data_len, 0);
+        self.moov(data.len() as u32 - mdat_data_len as u32).put_buf(&mut data);
+
+        let mdat = InputSpan { len: mdat_header.len + mdat_data_len as u64, ..mdat_header };
+        TestQuickTimeSound { data, mdat }
+    }
+
+    fn moov(&self, chunk_offset: u32) -> Mp4Box<MoovBox> {
+        let stsc = [StscEntry { first_chunk: 1, samples_per_chunk: self.sample_count, sample_description_index: 1 }]
+            .into_iter()
+            .collect::<StscBox>();
+        let stbl: Vec<AnyMp4Box> = vec![
+            test_stsd_with_entry(&self.sample_entry()),
+            test_stts(self.sample_count),
+            Mp4Box::with_data(stsc.into()).unwrap().into(),
+            Mp4Box::with_data(StszBox::with_sample_size(1, self.sample_count).into())
+                .unwrap()
+                .into(),
+            Mp4Box::with_data(StcoBox::from_iter([chunk_offset]).into())
+                .unwrap()
+                .into(),
+        ];
+        let minf: Vec<AnyMp4Box> = vec![
+            test_dinf(false),
+            Mp4Box::with_data(StblBox::with_children(stbl).into()).unwrap().into(),
+        ];
+        let mdia: Vec<AnyMp4Box> = vec![
+            test_mdhd(0),
+            test_hdlr(HdlrBox::SOUND),
+            Mp4Box::with_data(MinfBox::with_children(minf).into()).unwrap().into(),
+        ];
+        let trak: Vec<AnyMp4Box> = vec![
+            test_tkhd(1),
+            Mp4Box::with_data(MdiaBox::with_children(mdia).into()).unwrap().into(),
+        ];
+        let moov: Vec<AnyMp4Box> = vec![
+            test_mvhd(),
+            Mp4Box::with_data(TrakBox::with_children(trak).into()).unwrap().into(),
+        ];
+        Mp4Box::with_data(MoovBox::with_children(moov).into()).unwrap()
+    }
+
+    fn sample_entry(&self) -> AnyMp4Box {
+        let mut data = BytesMut::new();
+        AudioSampleEntry::new(1, 22050).put_buf(&mut data);
+        data[9] = 1; // version
+        self.fields.put_buf(&mut data);
+        Mp4Box::with_bytes(IMA4, data)
+    }
+}
@@ -0,0 +1,72 @@
+use std::io;
+
+use bytes::Bytes;
+
+use crate::Skip;
+
+/// An input consisting of parts of data at given offsets and zeros elsewhere, for testing inputs too large to allocate.
+pub struct SparseInput {
+    parts: Vec<(u64, Bytes)>,
+    position: u64,
+    len: u64,
+}
+
+impl SparseInput {
+    pub fn new(len: u64) -> Self {
+        Self { parts: vec![], position: 0, len }
+    }
+
+    pub fn with_part(mut self, offset: u64, data: impl Into<Bytes>) -> Self {
+        let data = data.into();
+        assert!(offset + data.len() as u64 <= self.len);
+        self.parts.push((offset, data));
+        self.parts.sort_by_key(|&(offset, _)| offset);
+        self
+    }
+}
+
+impl io::Read for SparseInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let position = self.position;
+        let remaining = usize::try_from(self.len - position).unwrap_or(usize::MAX);
+        let mut read_len = buf.len().min(remaining);
+        let part = self
+            .parts
+            .iter()
+            .find(|(offset, data)| position < offset + data.len() as u64);
+        match part {
+            Some((offset, data)) if *offset <= position => {
+                let data = &data[(position - offset) as usize..];
+                read_len = read_len.min(data.len());
+                buf[..read_len].copy_from_slice(&data[..read_len]);
+            }
+            Some((offset, _)) => {
+                read_len = read_len.min(usize::try_from(offset - position).unwrap_or(usize::MAX));
+                buf[..read_len].fill(0);
+            }
+            None => buf[..read_len].fill(0),
+        }
+        self.position += read_len as u64;
+        Ok(read_len)
+    }
+}
+
+impl Skip for SparseInput {
+    fn skip(&mut self, amount: u64) -> io::Result<()> {
+        match self.position.checked_add(amount) {
+            Some(position) if position <= self.len => {
+                self.position = position;
+                Ok(())
+            }
+            _ => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+
+    fn stream_len(&mut self) -> io::Result<u64> {
+        Ok(self.len)
+    }
+}
@@ -0,0 +1,102 @@
+use derive_builder::Builder;
+
+use crate::parse::box_type::METT;
+use crate::parse::{
+    fourcc, AnyMp4Box, BoxType, Co64Box, ElstEntry, FourCC, MdiaBox, MinfBox, Mp4Box, StblBox, StcoBox, TrakBox,
+};
+
+use super::moov::TestMoovSpec;
+use super::{
+    test_dinf, test_edts, test_hdlr, test_mdhd, test_stsc, test_stsd, test_stsd_with_entry, test_stsz, test_stts,
+    test_tkhd, test_udta,
+};
+
+/// A track of a [test movie](super::TestMoovBuilder).
+#[derive(Builder)]
+#[builder(name = "TestTrakBuilder", build_fn(name = "build_spec"))]
+pub struct TestTrakSpec {
+    /// The chunk offsets of the track.
+    #[builder(default, setter(into))]
+    pub co_entries: Vec<u64>,
+
+    #[builder(default = "METT")]
+    pub sample_entry: BoxType,
+
+    /// A complete sample entry, overriding `sample_entry`.
+    #[builder(default, setter(strip_option))]
+    pub sample_entry_box: Option<AnyMp4Box>,
+
+    #[builder(default = "fourcc::META")]
+    pub handler_type: FourCC,
+
+    /// The media duration of the track, in units of the media timescale of 1.
+    #[builder(default)]
+    pub media_duration: u32,
+
+    /// The entries of an edit list for the track.
+    #[builder(default, setter(into, strip_option))]
+    pub edit_list: Option<Vec<ElstEntry>>,
+
+    /// Additional boxes for the sample table (`stbl`).
+    #[builder(default, setter(into, each(name = "add_stbl_box")))]
+    pub stbl_boxes: Vec<AnyMp4Box>,
+
+    /// A user data box (`udta`) for the track.
+    #[builder(default, setter(strip_option))]
+    pub udta: Option<AnyMp4Box>,
+}
+
+impl TestTrakBuilder {
+    pub fn build(&self, track_id: u32, moov: &TestMoovSpec) -> AnyMp4Box {
+        self.build_spec().unwrap().build(track_id, moov)
+    }
+}
+
+impl TestTrakSpec {
+    fn build(&self, track_id: u32, moov: &TestMoovSpec) -> AnyMp4Box {
+        let chunk_count = self.co_entries.len() as u32;
+
+        let stsd = match &self.sample_entry_box {
+            Some(sample_entry) => test_stsd_with_entry(sample_entry),
+            None => test_stsd(self.sample_entry),
+        };
+        let mut stbl = vec![stsd, test_stts(chunk_count), test_stsc(), test_stsz(chunk_count)];
+        if moov.co64 {
+            let entries = self.co_entries.iter().cloned();
+            stbl.push(Mp4Box::with_data(Co64Box::from_iter(entries).into()).unwrap().into());
+        }
+        if moov.stco {
+            let entries = self.co_entries.iter().map(|&entry| entry as u32);
+            stbl.push(Mp4Box::with_data(StcoBox::from_iter(entries).into()).unwrap().into());
+        }
+        stbl.extend(self.stbl_boxes.iter().cloned());
+
+        let mut minf = vec![];
+        if moov.dinf {
+            minf.push(test_dinf(moov.external_data_reference));
+        }
+        if moov.stbl {
+            minf.push(Mp4Box::with_data(StblBox::with_children(stbl).into()).unwrap().into());
+        }
+
+        let mut mdia = vec![test_mdhd(self.media_duration), test_hdlr(self.handler_type)];
+        if moov.minf {
+            mdia.push(Mp4Box::with_data(MinfBox::with_children(minf).into()).unwrap().into());
+        }
+
+        let mut trak = vec![test_tkhd(track_id)];
+        if let Some(edit_list) = &self.edit_list {
+            trak.push(test_edts(edit_list.clone()));
+        }
+        if moov.mdia {
+            trak.push(Mp4Box::with_data(MdiaBox::with_children(mdia).into()).unwrap().into());
+        }
+        if moov.user_metadata {
+            trak.push(test_udta());
+        }
+        if let Some(udta) = &self.udta {
+            trak.push(udta.clone());
+        }
+        Mp4Box::with_data(TrakBox::with_children(trak).into()).unwrap().into()
+    }
+}
@@ -0,0 +1,131 @@
+use mediasan_common::error;
+
+use crate::parse::{BoxType, BoxUuid, ParseError};
+use crate::UuidBoxAction;
+
+//
+// public functions
+//
+
+/// Returns whether to keep a `uuid` extension box with the given UUID, according to `uuid_boxes`.
+///
+/// Boxes with a UUID not in `uuid_boxes` are rejected.
+pub fn retain_uuid_box(uuid_boxes: &[(BoxUuid, UuidBoxAction)], uuid: BoxUuid) -> error::Result<bool, ParseError> {
+    let action = uuid_boxes
+        .iter()
+        .find(|&&(allowed_uuid, _)| allowed_uuid == uuid)
+        .map(|&(_, action)| action);
+    match action {
+        Some(UuidBoxAction::Keep) => Ok(true),
+        Some(UuidBoxAction::Drop) => Ok(false),
+        Some(UuidBoxAction::Reject) | None => {
+            bail_attach!(ParseError::UnsupportedBox(BoxType::Uuid(uuid)), "uuid box not allowed")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+    use bytes::BytesMut;
+
+    use crate::parse::box_type::{FTYP, MDAT, MOOV};
+    use crate::parse::{AnyMp4Box, BoxHeader, Mp4Box};
+    use crate::util::test::{sanitized_chunk_offsets, sanitized_data, test_moov, test_mp4, TEST_BOX_UUID, TEST_UUID};
+    use crate::{sanitize_with_config, Config, Error};
+
+    use super::*;
+
+    fn uuid_boxes_config(action: UuidBoxAction) -> Config {
+        Config::builder().uuid_boxes(vec![(TEST_BOX_UUID, action)]).build()
+    }
+
+    fn test_uuid_box() -> AnyMp4Box {
+        Mp4Box::with_bytes(TEST_UUID, BytesMut::new())
+    }
+
+    #[test]
+    fn uuid_boxes_keep() {
+        let config = uuid_boxes_config(UuidBoxAction::Keep);
+        let test = test_mp4().boxes(&[FTYP, MOOV, TEST_UUID, MDAT][..]).build();
+        let sanitized = sanitize_with_config(test.clone(), config.clone()).unwrap();
+        assert_eq!(sanitized.metadata, None);
+        assert_eq!(sanitized.data, test.mdat);
+
+        let test = test_mp4().boxes(&[FTYP, MDAT, TEST_UUID, MOOV][..]).build();
+        let sanitized = sanitize_with_config(test.clone(), config).unwrap();
+        assert_eq!(sanitized.data.offset, test.mdat.offset);
+        let uuid_box_size = BoxHeader::with_u32_data_size(TEST_UUID, 0).encoded_len();
+        assert_eq!(sanitized.data.len, test.mdat.len + uuid_box_size);
+    }
+
+    #[test]
+    fn uuid_boxes_drop() {
+        let config = uuid_boxes_config(UuidBoxAction::Drop);
+        for boxes in [&[FTYP, MOOV, TEST_UUID, MDAT][..], &[FTYP, MDAT, TEST_UUID, MOOV][..]] {
+            let test = test_mp4().boxes(boxes).build();
+            let sanitized = sanitize_with_config(test.clone(), config.clone()).unwrap();
+            assert_eq!(sanitized.data, test.mdat);
+            let metadata = sanitized.metadata.clone().unwrap();
+            assert!(!metadata.windows(16).any(|uuid| uuid == TEST_BOX_UUID.value));
+
+            let chunk_offsets = sanitized_chunk_offsets(&metadata);
+            let sanitized_data = sanitized_data(sanitized, &test.data);
+            let chunks: Vec<_> = chunk_offsets
+                .into_iter()
+                .map(|chunk_offset| sanitized_data[chunk_offset as usize])
+                .collect();
+            assert_eq!(chunks, test.mdat_data);
+        }
+    }
+
+    #[test]
+    fn uuid_boxes_reject() {
+        let configs = [
+            uuid_boxes_config(UuidBoxAction::Reject),
+            Config::builder().uuid_boxes(vec![]).build(),
+        ];
+        for config in configs {
+            let test = test_mp4().boxes(&[FTYP, MOOV, TEST_UUID, MDAT][..]).build();
+            assert_matches!(sanitize_with_config(test, config.clone()).unwrap_err(), Error::Parse(err) => {
+                assert_matches!(err.into_inner(), ParseError::UnsupportedBox(TEST_UUID));
+            });
+
+            let test = test_mp4()
+                .moov(test_moov().add_moov_box(test_uuid_box()).clone())
+                .build();
+            assert_matches!(sanitize_with_config(test, config).unwrap_err(), Error::Parse(err) => {
+                assert_matches!(err.into_inner(), ParseError::UnsupportedBox(TEST_UUID));
+            });
+        }
+    }
+
+    #[test]
+    fn uuid_boxes_in_moov_keep() {
+        let test = test_mp4()
+            .moov(test_moov().add_moov_box(test_uuid_box()).clone())
+            .build();
+        test.sanitize_ok();
+
+        let config = Config::builder().strict_validation(true).build();
+        assert_matches!(sanitize_with_config(test.clone(), config).unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::UnsupportedBox(TEST_UUID));
+        });
+
+        let config = Config { strict_validation: true, ..uuid_boxes_config(UuidBoxAction::Keep) };
+        test.sanitize_ok_with_config(config);
+    }
+
+    #[test]
+    fn uuid_boxes_in_moov_drop() {
+        for boxes in [&[FTYP, MDAT, MOOV][..], &[FTYP, MOOV, MDAT][..]] {
+            let test = test_mp4()
+                .boxes(boxes)
+                .moov(test_moov().add_moov_box(test_uuid_box()).clone())
+                .build();
+            let sanitized = sanitize_with_config(test, uuid_boxes_config(UuidBoxAction::Drop)).unwrap();
+            let metadata = sanitized.metadata.unwrap();
+            assert!(!metadata.windows(16).any(|uuid| uuid == TEST_BOX_UUID.value));
+        }
+    }
+}
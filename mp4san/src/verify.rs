@@ -0,0 +1,213 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_util::AsyncRead;
+
+use crate::error::Report;
+use crate::parse::ParseError;
+use crate::{sanitize_input, AsyncSkip, Config, Error, InputSpan, SanitizedInput};
+
+//
+// public functions
+//
+
+/// Verify the output of sanitizing an input by sanitizing it again.
+///
+/// The output is reconstructed from the sanitized metadata and the parts of the input recorded while sanitizing it. It
+/// must be sanitized without error, must not be modified, and must resolve each chunk to the same media data as the
+/// input.
+pub async fn verify_output(input: &SanitizedInput, config: &Config) -> Result<(), Error> {
+    let Some(metadata) = &input.sanitized.metadata else {
+        // The output is identical to the input, which has already been sanitized.
+        return Ok(());
+    };
+
+    let config = Config { verify_output: false, compact_media_data: false, progress_callback: None, ..config.clone() };
+    let output = ReplayOutput::new(input, metadata);
+    let expected_chunks = output_chunks(input, metadata.len() as u64)?;
+
+    let output = match sanitize_input(output, &config).await {
+        Ok(output) => output,
+        Err(Error::Io(err)) => bail_attach!(ParseError::InvalidOutput, err, "while sanitizing output"),
+        Err(Error::Parse(err)) => bail_attach!(ParseError::InvalidOutput, err, "while sanitizing output"),
+        Err(Error::Cancelled) => return Err(Error::Cancelled),
+    };
+
+    ensure_attach!(
+        output.sanitized.metadata.is_none(),
+        ParseError::InvalidOutput,
+        "output modified when sanitized",
+    );
+    ensure_attach!(
+        output.chunks == expected_chunks,
+        ParseError::InvalidOutput,
+        "output chunks differ from input chunks",
+    );
+    Ok(())
+}
+
+//
+// private types
+//
+
+/// A reader over the sanitized output, serving only the sanitized metadata and the parts of the input which were read
+/// while sanitizing it.
+struct ReplayOutput {
+    /// The readable parts of the output, by offset in the output, sorted by offset.
+    parts: Vec<(u64, Bytes)>,
+    position: u64,
+    len: u64,
+}
+
+//
+// private functions
+//
+
+/// Returns the spans of the chunks of the input in the sanitized output.
+fn output_chunks(input: &SanitizedInput, metadata_len: u64) -> Result<Vec<InputSpan>, Report<ParseError>> {
+    let spans = output_data_spans(input, metadata_len);
+    input
+        .chunks
+        .iter()
+        .map(|chunk| {
+            let (span, output_offset) = spans
+                .iter()
+                .find(|(span, _)| chunk.offset >= span.offset && chunk.offset + chunk.len <= span.offset + span.len)
+                .ok_or_else(|| report_attach!(ParseError::InvalidOutput, "chunk not within output data"))?;
+            let offset = output_offset + (chunk.offset - span.offset);
+            Ok(InputSpan { offset, len: chunk.len })
+        })
+        .collect()
+}
+
+/// Returns each span of the input's media data along with its offset in the sanitized output.
+fn output_data_spans(input: &SanitizedInput, metadata_len: u64) -> Vec<(InputSpan, u64)> {
+    let mut output_offset = metadata_len;
+    input
+        .sanitized
+        .data_spans()
+        .map(|span| {
+            let span_output_offset = output_offset;
+            output_offset += span.len;
+            (span, span_output_offset)
+        })
+        .collect()
+}
+
+//
+// ReplayOutput impls
+//
+
+impl ReplayOutput {
+    fn new(input: &SanitizedInput, metadata: &[u8]) -> Self {
+        let metadata_len = metadata.len() as u64;
+        let spans = output_data_spans(input, metadata_len);
+        let mut parts = vec![(0, Bytes::copy_from_slice(metadata))];
+        for (input_offset, data) in &input.read_boxes {
+            let input_end = input_offset + data.len() as u64;
+            let found_span = spans
+                .iter()
+                .find(|(span, _)| *input_offset >= span.offset && input_end <= span.offset + span.len);
+            if let Some((span, output_offset)) = found_span {
+                parts.push((output_offset + (input_offset - span.offset), data.clone()));
+            }
+        }
+        parts.sort_by_key(|&(offset, _)| offset);
+        let len = metadata_len + spans.iter().map(|(span, _)| span.len).sum::<u64>();
+        Self { parts, position: 0, len }
+    }
+}
+
+impl AsyncRead for ReplayOutput {
+    fn poll_read(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let position = self.position;
+        if position >= self.len || buf.is_empty() {
+            return Ok(0).into();
+        }
+        let part = self.parts.iter().find_map(|(offset, data)| {
+            let part_offset = usize::try_from(position.checked_sub(*offset)?).ok()?;
+            data.get(part_offset..).filter(|part| !part.is_empty())
+        });
+        let Some(part) = part else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "read of unrecorded output data",
+            ))
+            .into();
+        };
+        let read_len = part.len().min(buf.len());
+        buf[..read_len].copy_from_slice(&part[..read_len]);
+        self.position += read_len as u64;
+        Ok(read_len).into()
+    }
+}
+
+impl AsyncSkip for ReplayOutput {
+    fn poll_skip(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, amount: u64) -> Poll<io::Result<()>> {
+        match self.position.checked_add(amount) {
+            Some(position) if position <= self.len => {
+                self.position = position;
+                Ok(()).into()
+            }
+            _ => Err(io::ErrorKind::UnexpectedEof.into()).into(),
+        }
+    }
+
+    fn poll_stream_position(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Ok(self.position).into()
+    }
+
+    fn poll_stream_len(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Ok(self.len).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+    use futures_util::io::Cursor;
+    use futures_util::FutureExt;
+
+    use crate::util::test::test_mp4;
+
+    use super::*;
+
+    fn test_sanitized_input() -> (SanitizedInput, Config) {
+        let config = Config::builder().verify_output(true).build();
+        let test = test_mp4().build();
+        let input = sanitize_input(Cursor::new(&test.data), &config)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert!(input.sanitized.metadata.is_some());
+        (input, config)
+    }
+
+    fn assert_invalid_output(result: Result<(), Error>) {
+        assert_matches!(result.unwrap_err(), Error::Parse(err) => {
+            assert_matches!(err.into_inner(), ParseError::InvalidOutput);
+        });
+    }
+
+    #[test]
+    fn verified() {
+        let (input, config) = test_sanitized_input();
+        verify_output(&input, &config).now_or_never().unwrap().unwrap();
+    }
+
+    #[test]
+    fn truncated_metadata() {
+        let (mut input, config) = test_sanitized_input();
+        input.sanitized.metadata.as_mut().unwrap().pop();
+        assert_invalid_output(verify_output(&input, &config).now_or_never().unwrap());
+    }
+
+    #[test]
+    fn different_chunks() {
+        let (mut input, config) = test_sanitized_input();
+        input.chunks[0].len -= 1;
+        assert_invalid_output(verify_output(&input, &config).now_or_never().unwrap());
+    }
+}
@@ -0,0 +1,161 @@
+use std::io;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use futures_util::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{sanitize_async_with_config, sanitize_with_config, Config, Error, SanitizedMetadata, SeekSkipAdapter};
+
+//
+// public functions
+//
+
+/// Sanitize an input, then write the sanitized metadata to `output` followed by each span of media data, seeking
+/// `input` to the start of the span and copying it.
+///
+/// Nothing is written if the metadata did not need to be modified.
+pub fn sanitize_to_writer_with_config<R: Read + Seek, W: Write>(
+    mut input: R,
+    mut output: W,
+    config: Config,
+) -> Result<SanitizedMetadata, Error> {
+    let sanitized = sanitize_with_config(SeekSkipAdapter(&mut input), config)?;
+
+    let Some(metadata) = &sanitized.metadata else {
+        return Ok(sanitized);
+    };
+    output.write_all(metadata)?;
+    for span in sanitized.data_spans() {
+        input.seek(SeekFrom::Start(span.offset))?;
+        copy_exact(&mut input, &mut output, span.len)?;
+    }
+    output.flush()?;
+    Ok(sanitized)
+}
+
+/// Sanitize an input asynchronously, then write its sanitized output as [`sanitize_to_writer_with_config`] does.
+pub async fn sanitize_async_to_writer_with_config<R, W>(
+    mut input: R,
+    mut output: W,
+    config: Config,
+) -> Result<SanitizedMetadata, Error>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let sanitized = sanitize_async_with_config(SeekSkipAdapter(&mut input), config).await?;
+
+    let Some(metadata) = &sanitized.metadata else {
+        return Ok(sanitized);
+    };
+    output.write_all(metadata).await?;
+    for span in sanitized.data_spans() {
+        input.seek(SeekFrom::Start(span.offset)).await?;
+        copy_exact_async(&mut input, &mut output, span.len).await?;
+    }
+    output.flush().await?;
+    Ok(sanitized)
+}
+
+//
+// private functions
+//
+
+/// Copy exactly `len` bytes from `input` to `output`, returning an error if `input` ends first.
+fn copy_exact<R: Read, W: Write>(input: R, mut output: W, len: u64) -> io::Result<()> {
+    let copied = io::copy(&mut input.take(len), &mut output)?;
+    if copied != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+/// Copy exactly `len` bytes from `input` to `output`, returning an error if `input` ends first.
+async fn copy_exact_async<R, W>(input: R, mut output: W, len: u64) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let copied = futures_util::io::copy(input.take(len), &mut output).await?;
+    if copied != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::box_type::{FTYP, MDAT, MOOV};
+    use crate::parse::Mp4Value;
+    use crate::util::test::{init_logger, sanitized_data, test_ftyp, test_moov, test_mp4, write_test_mdat};
+    use crate::{sanitize_async_to_writer, sanitize_to_writer, sanitize_to_writer_with_config};
+
+    use super::*;
+
+    #[test]
+    fn sanitize_to_writer_metadata_modified() {
+        init_logger();
+        let test = test_mp4().build();
+        let mut output = vec![];
+        let sanitized = sanitize_to_writer(io::Cursor::new(&test.data[..]), &mut output).unwrap();
+        assert!(sanitized.metadata.is_some());
+        assert_eq!(output, sanitized_data(sanitized, &test.data));
+    }
+
+    #[test]
+    fn sanitize_to_writer_metadata_unmodified() {
+        init_logger();
+        let test = test_mp4().boxes(&[FTYP, MOOV, MDAT][..]).build();
+        let mut output = vec![];
+        let sanitized = sanitize_to_writer(io::Cursor::new(&test.data[..]), &mut output).unwrap();
+        assert_eq!(sanitized.metadata, None);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn sanitize_to_writer_discontiguous() {
+        init_logger();
+        let mut data = vec![];
+        test_ftyp().build().put_buf(&mut data);
+        write_test_mdat(&mut data, b"abc");
+        test_moov().build().put_buf(&mut data);
+        write_test_mdat(&mut data, b"def");
+        let config = Config::builder().allow_discontiguous_mdat(true).build();
+        let mut output = vec![];
+        let sanitized = sanitize_to_writer_with_config(io::Cursor::new(&data), &mut output, config).unwrap();
+        assert_eq!(sanitized.extra_data.len(), 1);
+        assert_eq!(output, sanitized_data(sanitized, &data));
+    }
+
+    #[test]
+    fn sanitize_to_writer_input_offset() {
+        init_logger();
+        let test = test_mp4().build();
+        let mut input = io::Cursor::new([&b"prefix"[..], &test.data[..]].concat());
+        input.set_position(6);
+        let mut output = vec![];
+        let sanitized = sanitize_to_writer(input, &mut output).unwrap();
+        let expected = sanitized_data(sanitized, &[&b"prefix"[..], &test.data[..]].concat());
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn sanitize_async_to_writer_metadata_modified() {
+        init_logger();
+        let test = test_mp4().build();
+        let mut output = vec![];
+        let sanitize = sanitize_async_to_writer(futures_util::io::Cursor::new(&test.data[..]), &mut output);
+        let sanitized = futures_util::FutureExt::now_or_never(sanitize).unwrap().unwrap();
+        assert_eq!(output, sanitized_data(sanitized, &test.data));
+    }
+
+    #[test]
+    fn sanitize_async_to_writer_metadata_unmodified() {
+        init_logger();
+        let test = test_mp4().boxes(&[FTYP, MOOV, MDAT][..]).build();
+        let mut output = vec![];
+        let sanitize = sanitize_async_to_writer(futures_util::io::Cursor::new(&test.data[..]), &mut output);
+        let sanitized = futures_util::FutureExt::now_or_never(sanitize).unwrap().unwrap();
+        assert_eq!(sanitized.metadata, None);
+        assert!(output.is_empty());
+    }
+}
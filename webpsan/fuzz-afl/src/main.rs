@@ -17,6 +17,7 @@ fn main() {
                     }
                     _ => panic!(),
                 },
+                webpsan::Error::Cancelled => panic!(),
                 webpsan::Error::Parse(error) => {
                     eprintln!("webpsan returned a parse error: {error}\n{error:?}");
                 }
@@ -19,6 +19,7 @@ fuzz_target!(|data: &[u8]| {
                 }
                 _ => panic!(),
             },
+            webpsan::Error::Cancelled => panic!(),
             webpsan::Error::Parse(error) => {
                 #[cfg(fuzzing_repro)]
                 eprintln!("webpsan returned a parse error: {error}\n{error:?}");